@@ -0,0 +1,45 @@
+-- Uploaded files are content-addressed blobs (see `src/storage`), shared by the rows storing the
+-- same content. Rows don't own their blobs: when a row is hard-deleted its blobs are released, and
+-- the `storage` worker deletes the ones no row references anymore. The deletion is delayed so that
+-- uploads in flight (blobs stored, rows not inserted yet) don't lose their blobs.
+
+ALTER TABLE "user_profile_images" ADD COLUMN thumbnail_urls JSONB NOT NULL DEFAULT '{}'::JSONB;
+
+-- The profile images uploaded before kept their thumbnails next to the original
+UPDATE user_profile_images SET thumbnail_urls = (
+    SELECT jsonb_object_agg(size::TEXT, 'profile-images/' || id || '/' || size)
+    FROM unnest(ARRAY[48, 96, 200, 400]) size
+)
+WHERE image_url LIKE 'profile-images/%';
+
+CREATE INDEX user_profile_images_image_url_index ON user_profile_images (image_url);
+CREATE INDEX user_profile_images_thumbnail_urls_index ON user_profile_images
+USING GIN (jsonb_path_query_array(thumbnail_urls, '$.*'));
+CREATE INDEX hess_media_media_url_index ON hess_media (media_url);
+
+CREATE OR REPLACE FUNCTION release_blobs(keys JSONB) RETURNS VOID AS $$
+    INSERT INTO jobs (queue, kind, payload, run_at)
+    VALUES ('storage', 'BLOBS_RELEASED', jsonb_build_object('keys', keys), NOW() + INTERVAL '1 hour');
+$$ LANGUAGE sql;
+
+CREATE OR REPLACE FUNCTION release_user_profile_image_blobs() RETURNS TRIGGER AS $$
+BEGIN
+    PERFORM release_blobs(
+        jsonb_build_array(OLD.image_url) || jsonb_path_query_array(OLD.thumbnail_urls, '$.*')
+    );
+    RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER trigger_release_user_profile_image_blobs AFTER
+DELETE ON user_profile_images FOR EACH ROW EXECUTE FUNCTION release_user_profile_image_blobs();
+
+CREATE OR REPLACE FUNCTION release_hess_media_blobs() RETURNS TRIGGER AS $$
+BEGIN
+    PERFORM release_blobs(jsonb_build_array(OLD.media_url));
+    RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER trigger_release_hess_media_blobs AFTER
+DELETE ON hess_media FOR EACH ROW EXECUTE FUNCTION release_hess_media_blobs();
@@ -0,0 +1,9 @@
+-- Media of hesses which don't exist can't be reached anyway
+DELETE FROM hess_media m WHERE NOT EXISTS (SELECT 1 FROM hesses h WHERE h.id = m.hess_id);
+
+ALTER TABLE "hess_media" ADD FOREIGN KEY (hess_id) REFERENCES hesses(id) ON DELETE RESTRICT;
+
+-- The content type is sniffed from the uploaded file, never taken from the client
+ALTER TABLE "hess_media"
+    ADD COLUMN content_type VARCHAR(100) NOT NULL DEFAULT 'application/octet-stream',
+    ADD COLUMN size_bytes BIGINT NOT NULL DEFAULT 0;
@@ -0,0 +1,42 @@
+-- Uploaded hess media are processed in the background by the `media` worker, which extracts their
+-- metadata and generates their derived assets. Media stay hidden from everyone but the author (and
+-- the admins) until they're READY.
+
+CREATE TYPE MEDIA_PROCESSING_STATUS AS ENUM ('PENDING', 'READY', 'FAILED');
+
+ALTER TABLE "hess_media"
+    ADD COLUMN processing_status MEDIA_PROCESSING_STATUS NOT NULL DEFAULT 'PENDING',
+    ADD COLUMN width INT DEFAULT NULL,
+    ADD COLUMN height INT DEFAULT NULL,
+    ADD COLUMN duration_ms BIGINT DEFAULT NULL,
+    ADD COLUMN blurhash VARCHAR(100) DEFAULT NULL,
+    -- The keys of the thumbnails of photos, by size (they all share the same content type)
+    ADD COLUMN thumbnail_urls JSONB NOT NULL DEFAULT '{}'::JSONB,
+    ADD COLUMN thumbnail_content_type VARCHAR(100) DEFAULT NULL,
+    ADD COLUMN processed_at TIMESTAMP WITH TIME ZONE DEFAULT NULL;
+
+CREATE INDEX hess_media_thumbnail_urls_index ON hess_media
+USING GIN (jsonb_path_query_array(thumbnail_urls, '$.*'));
+
+CREATE OR REPLACE FUNCTION release_hess_media_blobs() RETURNS TRIGGER AS $$
+BEGIN
+    PERFORM release_blobs(
+        jsonb_build_array(OLD.media_url) || jsonb_path_query_array(OLD.thumbnail_urls, '$.*')
+    );
+    RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE OR REPLACE FUNCTION enqueue_hess_media_jobs() RETURNS TRIGGER AS $$
+BEGIN
+    PERFORM enqueue_job('media', 'HESS_MEDIA_UPLOADED', jsonb_build_object('hessMediaId', NEW.id));
+    RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER trigger_enqueue_hess_media_jobs AFTER
+INSERT ON hess_media FOR EACH ROW EXECUTE FUNCTION enqueue_hess_media_jobs();
+
+-- The media uploaded before are processed as well
+SELECT enqueue_job('media', 'HESS_MEDIA_UPLOADED', jsonb_build_object('hessMediaId', id))
+FROM hess_media WHERE deleted_at IS NULL;
@@ -0,0 +1,17 @@
+-- Accessibility metadata of the hess media: an alternative text, and captions (or transcripts)
+-- of videos and audios as WebVTT, one per language.
+
+ALTER TABLE "hess_media" ADD COLUMN alt_text VARCHAR(1000) DEFAULT NULL;
+
+CREATE TABLE "hess_media_captions"
+(
+    hess_media_id UUID NOT NULL,
+    -- A BCP 47 language tag, lowercased (e.g. en, pt-br)
+    language VARCHAR(35) NOT NULL,
+    content TEXT NOT NULL,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (hess_media_id, language),
+    -- Captions have no meaning without their media
+    FOREIGN KEY (hess_media_id) REFERENCES hess_media(id) ON DELETE CASCADE
+);
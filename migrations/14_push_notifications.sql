@@ -0,0 +1,110 @@
+-- Push notifications: the FCM registration tokens of the devices of the users, and the events
+-- enqueued in the `push` queue (follows, follow requests and their approvals, likes, replies and
+-- mentions), along with who they're pushed to.
+
+CREATE TABLE "device_tokens"
+(
+    id UUID DEFAULT uuid_generate_v4() PRIMARY KEY,
+    user_id UUID NOT NULL,
+    -- Chosen by the client, stable across token refreshes (a device keeps a single token)
+    device_id VARCHAR(255) NOT NULL,
+    token VARCHAR(4096) NOT NULL,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+    UNIQUE (user_id, device_id),
+    -- A token belongs to the last user who signed in on the device
+    UNIQUE (token),
+    FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE
+);
+
+CREATE OR REPLACE FUNCTION enqueue_follower_push_jobs() RETURNS TRIGGER AS $$
+BEGIN
+    -- Approved follow requests are pushed to the requester instead (see below), the requested
+    -- user approved them
+    IF NEW.deleted_at IS NULL AND NOT EXISTS (
+        SELECT 1 FROM follow_requests r
+        WHERE r.requester_id = NEW.follower_id
+            AND r.requested_id = NEW.followed_id
+            AND r.status = 'APPROVED'
+            AND r.updated_at = NOW()
+    ) THEN
+        PERFORM enqueue_job('push', 'FOLLOW_CREATED', jsonb_build_object(
+            'recipientId', NEW.followed_id,
+            'actorId', NEW.follower_id
+        ));
+    END IF;
+    RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER trigger_enqueue_follower_push_jobs AFTER
+INSERT ON followers FOR EACH ROW EXECUTE FUNCTION enqueue_follower_push_jobs();
+
+CREATE OR REPLACE FUNCTION enqueue_follow_request_push_jobs() RETURNS TRIGGER AS $$
+BEGIN
+    IF TG_OP = 'INSERT' AND NEW.deleted_at IS NULL AND NEW.status = 'PENDING' THEN
+        PERFORM enqueue_job('push', 'FOLLOW_REQUEST_CREATED', jsonb_build_object(
+            'recipientId', NEW.requested_id,
+            'actorId', NEW.requester_id
+        ));
+    ELSIF TG_OP = 'UPDATE' AND NEW.deleted_at IS NULL
+        AND OLD.status <> 'APPROVED' AND NEW.status = 'APPROVED' THEN
+        PERFORM enqueue_job('push', 'FOLLOW_REQUEST_APPROVED', jsonb_build_object(
+            'recipientId', NEW.requester_id,
+            'actorId', NEW.requested_id
+        ));
+    END IF;
+    RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER trigger_enqueue_follow_request_push_jobs AFTER
+INSERT OR UPDATE OF status ON follow_requests FOR EACH ROW EXECUTE FUNCTION enqueue_follow_request_push_jobs();
+
+CREATE OR REPLACE FUNCTION enqueue_like_push_jobs() RETURNS TRIGGER AS $$
+BEGIN
+    IF NEW.deleted_at IS NULL THEN
+        PERFORM enqueue_job('push', 'LIKE_CREATED', jsonb_build_object(
+            'recipientId', (SELECT h.user_id FROM hesses h WHERE h.id = NEW.hess_id),
+            'actorId', NEW.user_id,
+            'hessId', NEW.hess_id
+        ));
+    END IF;
+    RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER trigger_enqueue_like_push_jobs AFTER
+INSERT ON likes FOR EACH ROW EXECUTE FUNCTION enqueue_like_push_jobs();
+
+CREATE OR REPLACE FUNCTION enqueue_reply_push_jobs() RETURNS TRIGGER AS $$
+BEGIN
+    IF NEW.deleted_at IS NULL AND NEW.parent_hess_id IS NOT NULL THEN
+        PERFORM enqueue_job('push', 'REPLY_CREATED', jsonb_build_object(
+            'recipientId', (SELECT h.user_id FROM hesses h WHERE h.id = NEW.parent_hess_id),
+            'actorId', NEW.user_id,
+            'hessId', NEW.id
+        ));
+    END IF;
+    RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER trigger_enqueue_reply_push_jobs AFTER
+INSERT ON hesses FOR EACH ROW EXECUTE FUNCTION enqueue_reply_push_jobs();
+
+CREATE OR REPLACE FUNCTION enqueue_mention_push_jobs() RETURNS TRIGGER AS $$
+BEGIN
+    IF NEW.deleted_at IS NULL THEN
+        PERFORM enqueue_job('push', 'MENTION_CREATED', jsonb_build_object(
+            'recipientId', NEW.user_id,
+            'actorId', (SELECT h.user_id FROM hesses h WHERE h.id = NEW.hess_id),
+            'hessId', NEW.hess_id
+        ));
+    END IF;
+    RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER trigger_enqueue_mention_push_jobs AFTER
+INSERT ON hess_mentions FOR EACH ROW EXECUTE FUNCTION enqueue_mention_push_jobs();
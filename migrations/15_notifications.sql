@@ -0,0 +1,152 @@
+-- In-app notifications: the events pushed so far (see 14_push_notifications.sql) are kept in a
+-- notification center. They're now enqueued in the `notifications` queue, whose worker stores
+-- the notification then pushes it.
+
+CREATE TABLE "notifications"
+(
+    id UUID DEFAULT uuid_generate_v4() PRIMARY KEY,
+    recipient_id UUID NOT NULL,
+    notification_type TEXT NOT NULL CHECK (notification_type IN (
+        'FOLLOW', 'FOLLOW_REQUEST', 'FOLLOW_REQUEST_APPROVED', 'LIKE', 'REPLY', 'MENTION'
+    )),
+    -- The hess the notification is about (the liked hess, the reply, the hess mentioning)
+    hess_id UUID DEFAULT NULL,
+    -- Unread notifications of the same group are merged ("X and 12 others liked your hess"),
+    -- e.g. the likes of a hess or the new followers. Other events are their own group.
+    group_key TEXT NOT NULL,
+    -- The users behind the merged events, latest first
+    actor_ids UUID[] NOT NULL,
+    -- The merged events (likes, follower relationships...), so retried jobs aren't merged twice
+    event_ids UUID[] NOT NULL,
+    occurred_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+    read_at TIMESTAMP WITH TIME ZONE DEFAULT NULL,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+    FOREIGN KEY (recipient_id) REFERENCES users(id) ON DELETE CASCADE,
+    FOREIGN KEY (hess_id) REFERENCES hesses(id) ON DELETE CASCADE
+);
+
+CREATE UNIQUE INDEX notifications_unread_group_idx ON notifications (recipient_id, group_key) WHERE read_at IS NULL;
+CREATE INDEX notifications_recipient_id_occurred_at_idx ON notifications (recipient_id, occurred_at DESC, id DESC);
+
+DROP TRIGGER trigger_enqueue_follower_push_jobs ON followers;
+DROP FUNCTION enqueue_follower_push_jobs();
+DROP TRIGGER trigger_enqueue_follow_request_push_jobs ON follow_requests;
+DROP FUNCTION enqueue_follow_request_push_jobs();
+DROP TRIGGER trigger_enqueue_like_push_jobs ON likes;
+DROP FUNCTION enqueue_like_push_jobs();
+DROP TRIGGER trigger_enqueue_reply_push_jobs ON hesses;
+DROP FUNCTION enqueue_reply_push_jobs();
+DROP TRIGGER trigger_enqueue_mention_push_jobs ON hess_mentions;
+DROP FUNCTION enqueue_mention_push_jobs();
+
+CREATE OR REPLACE FUNCTION enqueue_follower_notification_jobs() RETURNS TRIGGER AS $$
+BEGIN
+    -- Approved follow requests are notified to the requester instead (see below), the requested
+    -- user approved them
+    IF NEW.deleted_at IS NULL AND NOT EXISTS (
+        SELECT 1 FROM follow_requests r
+        WHERE r.requester_id = NEW.follower_id
+            AND r.requested_id = NEW.followed_id
+            AND r.status = 'APPROVED'
+            AND r.updated_at = NOW()
+    ) THEN
+        PERFORM enqueue_job('notifications', 'FOLLOW', jsonb_build_object(
+            'recipientId', NEW.followed_id,
+            'actorId', NEW.follower_id,
+            'eventId', NEW.id
+        ));
+    END IF;
+    RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER trigger_enqueue_follower_notification_jobs AFTER
+INSERT ON followers FOR EACH ROW EXECUTE FUNCTION enqueue_follower_notification_jobs();
+
+CREATE OR REPLACE FUNCTION enqueue_follow_request_notification_jobs() RETURNS TRIGGER AS $$
+BEGIN
+    IF TG_OP = 'INSERT' AND NEW.deleted_at IS NULL AND NEW.status = 'PENDING' THEN
+        PERFORM enqueue_job('notifications', 'FOLLOW_REQUEST', jsonb_build_object(
+            'recipientId', NEW.requested_id,
+            'actorId', NEW.requester_id,
+            'eventId', NEW.id
+        ));
+    ELSIF TG_OP = 'UPDATE' AND NEW.deleted_at IS NULL
+        AND OLD.status <> 'APPROVED' AND NEW.status = 'APPROVED' THEN
+        PERFORM enqueue_job('notifications', 'FOLLOW_REQUEST_APPROVED', jsonb_build_object(
+            'recipientId', NEW.requester_id,
+            'actorId', NEW.requested_id,
+            'eventId', NEW.id
+        ));
+    END IF;
+    RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER trigger_enqueue_follow_request_notification_jobs AFTER
+INSERT OR UPDATE OF status ON follow_requests FOR EACH ROW EXECUTE FUNCTION enqueue_follow_request_notification_jobs();
+
+CREATE OR REPLACE FUNCTION enqueue_like_notification_jobs() RETURNS TRIGGER AS $$
+BEGIN
+    IF NEW.deleted_at IS NULL THEN
+        PERFORM enqueue_job('notifications', 'LIKE', jsonb_build_object(
+            'recipientId', (SELECT h.user_id FROM hesses h WHERE h.id = NEW.hess_id),
+            'actorId', NEW.user_id,
+            'eventId', NEW.id,
+            'hessId', NEW.hess_id
+        ));
+    END IF;
+    RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER trigger_enqueue_like_notification_jobs AFTER
+INSERT ON likes FOR EACH ROW EXECUTE FUNCTION enqueue_like_notification_jobs();
+
+CREATE OR REPLACE FUNCTION enqueue_reply_notification_jobs() RETURNS TRIGGER AS $$
+BEGIN
+    IF NEW.deleted_at IS NULL AND NEW.parent_hess_id IS NOT NULL THEN
+        PERFORM enqueue_job('notifications', 'REPLY', jsonb_build_object(
+            'recipientId', (SELECT h.user_id FROM hesses h WHERE h.id = NEW.parent_hess_id),
+            'actorId', NEW.user_id,
+            'eventId', NEW.id,
+            'hessId', NEW.id
+        ));
+    END IF;
+    RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER trigger_enqueue_reply_notification_jobs AFTER
+INSERT ON hesses FOR EACH ROW EXECUTE FUNCTION enqueue_reply_notification_jobs();
+
+CREATE OR REPLACE FUNCTION enqueue_mention_notification_jobs() RETURNS TRIGGER AS $$
+BEGIN
+    IF NEW.deleted_at IS NULL THEN
+        PERFORM enqueue_job('notifications', 'MENTION', jsonb_build_object(
+            'recipientId', NEW.user_id,
+            'actorId', (SELECT h.user_id FROM hesses h WHERE h.id = NEW.hess_id),
+            'eventId', NEW.id,
+            'hessId', NEW.hess_id
+        ));
+    END IF;
+    RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER trigger_enqueue_mention_notification_jobs AFTER
+INSERT ON hess_mentions FOR EACH ROW EXECUTE FUNCTION enqueue_mention_notification_jobs();
+
+-- The pending pushes are moved to the new queue (their events are unknown, each one is its own)
+UPDATE jobs SET
+    queue = 'notifications',
+    kind = CASE kind
+        WHEN 'FOLLOW_CREATED' THEN 'FOLLOW'
+        WHEN 'FOLLOW_REQUEST_CREATED' THEN 'FOLLOW_REQUEST'
+        WHEN 'LIKE_CREATED' THEN 'LIKE'
+        WHEN 'REPLY_CREATED' THEN 'REPLY'
+        WHEN 'MENTION_CREATED' THEN 'MENTION'
+        ELSE kind
+    END,
+    payload = payload || jsonb_build_object('eventId', uuid_generate_v4())
+WHERE queue = 'push';
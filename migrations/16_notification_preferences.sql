@@ -0,0 +1,62 @@
+-- Notification preferences: for every notified event (see 15_notifications.sql), whether it's
+-- notified in-app, pushed and emailed, and the quiet hours during which nothing is pushed.
+
+CREATE TABLE "user_notification_preferences"
+(
+    id UUID DEFAULT uuid_generate_v4() PRIMARY KEY,
+    user_id UUID NOT NULL UNIQUE,
+
+    like_in_app BOOLEAN NOT NULL DEFAULT TRUE,
+    like_push BOOLEAN NOT NULL DEFAULT TRUE,
+    like_email BOOLEAN NOT NULL DEFAULT FALSE,
+
+    reply_in_app BOOLEAN NOT NULL DEFAULT TRUE,
+    reply_push BOOLEAN NOT NULL DEFAULT TRUE,
+    reply_email BOOLEAN NOT NULL DEFAULT FALSE,
+
+    mention_in_app BOOLEAN NOT NULL DEFAULT TRUE,
+    mention_push BOOLEAN NOT NULL DEFAULT TRUE,
+    mention_email BOOLEAN NOT NULL DEFAULT FALSE,
+
+    follow_in_app BOOLEAN NOT NULL DEFAULT TRUE,
+    follow_push BOOLEAN NOT NULL DEFAULT TRUE,
+    follow_email BOOLEAN NOT NULL DEFAULT FALSE,
+
+    -- Also covers the approvals of the user's follow requests
+    follow_request_in_app BOOLEAN NOT NULL DEFAULT TRUE,
+    follow_request_push BOOLEAN NOT NULL DEFAULT TRUE,
+    follow_request_email BOOLEAN NOT NULL DEFAULT FALSE,
+
+    -- In the user's timezone, the end is excluded and may be before the start (e.g. 22:00 to 07:00)
+    quiet_hours_start TIME DEFAULT NULL,
+    quiet_hours_end TIME DEFAULT NULL,
+    -- An IANA timezone name, as listed in pg_timezone_names
+    timezone VARCHAR(64) NOT NULL DEFAULT 'UTC',
+
+    updated_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+
+    CHECK ((quiet_hours_start IS NULL) = (quiet_hours_end IS NULL)),
+    FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE
+);
+
+CREATE OR REPLACE FUNCTION update_user_notification_preferences_updated_at() RETURNS TRIGGER AS $$
+BEGIN
+    NEW.updated_at = NOW();
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER trigger_update_user_notification_preferences_updated_at BEFORE
+UPDATE ON user_notification_preferences FOR EACH ROW EXECUTE FUNCTION update_user_notification_preferences_updated_at();
+
+CREATE OR REPLACE FUNCTION insert_default_notification_preferences() RETURNS TRIGGER AS $$
+BEGIN
+    INSERT INTO user_notification_preferences (user_id) VALUES (NEW.id);
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER after_insert_users_notification_preferences AFTER
+INSERT ON users FOR EACH ROW EXECUTE FUNCTION insert_default_notification_preferences();
+
+INSERT INTO user_notification_preferences (user_id) SELECT id FROM users;
@@ -0,0 +1,80 @@
+-- Real-time events streamed to the connected clients (see `src/stream`). Every event is stored,
+-- so that clients can resume from the last event they got, then announced on the
+-- `stream_events` channel so that every API instance delivers it to its own connections.
+
+CREATE TABLE "stream_events"
+(
+    id BIGSERIAL PRIMARY KEY,
+    user_id UUID NOT NULL,
+    event_type TEXT NOT NULL CHECK (event_type IN ('NOTIFICATION', 'FEED_ITEM', 'FOLLOW_REQUEST')),
+    payload JSONB NOT NULL DEFAULT '{}'::JSONB,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+    FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE
+);
+
+CREATE INDEX stream_events_user_id_id_idx ON stream_events (user_id, id);
+CREATE INDEX stream_events_created_at_idx ON stream_events (created_at);
+
+-- Notifications are only delivered once the transaction commits, the event is visible by then
+CREATE OR REPLACE FUNCTION publish_stream_event(recipient_id UUID, event_type TEXT, payload JSONB) RETURNS VOID AS $$
+DECLARE
+    event stream_events%ROWTYPE;
+BEGIN
+    INSERT INTO stream_events (user_id, event_type, payload)
+    VALUES (recipient_id, event_type, payload)
+    RETURNING * INTO event;
+
+    PERFORM pg_notify('stream_events', jsonb_build_object(
+        'id', event.id,
+        'userId', event.user_id,
+        'eventType', event.event_type,
+        'payload', event.payload,
+        'createdAt', event.created_at
+    )::TEXT);
+END;
+$$ LANGUAGE plpgsql;
+
+-- New notifications, and grouped ones getting a new actor
+CREATE OR REPLACE FUNCTION publish_notification_stream_events() RETURNS TRIGGER AS $$
+BEGIN
+    IF TG_OP = 'INSERT' OR NEW.actor_ids IS DISTINCT FROM OLD.actor_ids THEN
+        PERFORM publish_stream_event(NEW.recipient_id, 'NOTIFICATION', jsonb_build_object(
+            'notificationId', NEW.id,
+            'notificationType', NEW.notification_type,
+            'hessId', NEW.hess_id
+        ));
+    END IF;
+    RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER trigger_publish_notification_stream_events AFTER
+INSERT OR UPDATE OF actor_ids ON notifications FOR EACH ROW EXECUTE FUNCTION publish_notification_stream_events();
+
+-- Follow requests changes, sent to both the requester and the requested user
+CREATE OR REPLACE FUNCTION publish_follow_request_stream_events() RETURNS TRIGGER AS $$
+DECLARE
+    payload JSONB;
+BEGIN
+    IF TG_OP = 'UPDATE'
+        AND NEW.status = OLD.status
+        AND NEW.deleted_at IS NOT DISTINCT FROM OLD.deleted_at THEN
+        RETURN NULL;
+    END IF;
+
+    payload := jsonb_build_object(
+        'followRequestId', NEW.id,
+        'requesterId', NEW.requester_id,
+        'requestedId', NEW.requested_id,
+        'status', NEW.status,
+        'deleted', NEW.deleted_at IS NOT NULL
+    );
+
+    PERFORM publish_stream_event(NEW.requester_id, 'FOLLOW_REQUEST', payload);
+    PERFORM publish_stream_event(NEW.requested_id, 'FOLLOW_REQUEST', payload);
+    RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER trigger_publish_follow_request_stream_events AFTER
+INSERT OR UPDATE ON follow_requests FOR EACH ROW EXECUTE FUNCTION publish_follow_request_stream_events();
@@ -0,0 +1,25 @@
+-- Email digests: a periodic summary of the activity the users missed (new followers, top hesses
+-- of the followed users and unread mentions), sent to the users who didn't use the app since the
+-- last period. The digests due are enqueued in the `digests` queue by the digest scheduler.
+
+CREATE TYPE DIGEST_FREQUENCY AS ENUM ('NEVER', 'DAILY', 'WEEKLY');
+
+ALTER TABLE user_notification_preferences
+    ADD COLUMN digest_frequency DIGEST_FREQUENCY NOT NULL DEFAULT 'WEEKLY',
+    -- Sent in the one-click unsubscribe links of the digests
+    ADD COLUMN digest_unsubscribe_token UUID NOT NULL UNIQUE DEFAULT uuid_generate_v4();
+
+-- The digests now summarize new followers and mentions by default (the email channel wasn't used
+-- until now, the existing preferences are left alone)
+ALTER TABLE user_notification_preferences
+    ALTER COLUMN follow_email SET DEFAULT TRUE,
+    ALTER COLUMN mention_email SET DEFAULT TRUE;
+
+CREATE TABLE "user_activity"
+(
+    user_id UUID PRIMARY KEY,
+    -- Updated (at most every few minutes) on the authenticated requests of the user
+    last_active_at TIMESTAMP WITH TIME ZONE DEFAULT NULL,
+    last_digest_at TIMESTAMP WITH TIME ZONE DEFAULT NULL,
+    FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE
+);
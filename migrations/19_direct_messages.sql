@@ -0,0 +1,213 @@
+-- Direct messages: conversations between two users (or small groups), whose messages may
+-- reference hess media. Starting a conversation with someone is subject to the new
+-- `who_can_message_me` privacy preference (the `MESSAGE` action of the visibility policy), and
+-- lands in their message requests unless they follow the sender.
+
+ALTER TABLE "user_privacy_preferences"
+    -- If null means public, if empty array means no one
+    ADD COLUMN who_can_message_me TEXT[] DEFAULT NULL CHECK (is_valid_who_can_array(who_can_message_me));
+
+-- Replaces the `viewer_can` of 3_visibility_policy.sql (see its rules there), adding the
+-- 'MESSAGE' action.
+--
+-- Actions: 'VIEW', 'REPLY', 'LIKE', 'MENTION', 'WATCH_NEW_HESSES', 'WATCH_REPLIES', 'WATCH_LIKES', 'WATCH_FOLLOWS', 'MESSAGE'
+CREATE OR REPLACE FUNCTION viewer_can(action TEXT, viewer UUID, author UUID, target_hess UUID DEFAULT NULL) RETURNS BOOLEAN AS $$
+DECLARE
+    preferences user_privacy_preferences%ROWTYPE;
+    hess hesses%ROWTYPE;
+    is_mentioned BOOLEAN := FALSE;
+    who_can TEXT[];
+BEGIN
+    IF viewer = author THEN
+        RETURN TRUE;
+    END IF;
+
+    IF EXISTS (
+        SELECT 1 FROM blocked_users b
+        WHERE b.deleted_at IS NULL AND (
+            (b.blocker_id = author AND b.blocked_id = viewer)
+            OR (b.blocker_id = viewer AND b.blocked_id = author)
+        )
+    ) THEN
+        RETURN FALSE;
+    END IF;
+
+    SELECT * INTO preferences FROM user_privacy_preferences p WHERE p.user_id = author;
+
+    IF target_hess IS NOT NULL THEN
+        SELECT * INTO hess FROM hesses h WHERE h.id = target_hess;
+
+        is_mentioned := EXISTS (
+            SELECT 1 FROM hess_mentions m
+            WHERE m.hess_id = target_hess AND m.user_id = viewer AND m.deleted_at IS NULL
+        );
+    END IF;
+
+    IF COALESCE(preferences.is_private_profile, FALSE)
+        AND NOT is_mentioned
+        AND NOT EXISTS (
+            SELECT 1 FROM followers f
+            WHERE f.follower_id = viewer AND f.followed_id = author AND f.deleted_at IS NULL
+        )
+    THEN
+        RETURN FALSE;
+    END IF;
+
+    IF action = 'VIEW' OR is_mentioned THEN
+        RETURN TRUE;
+    END IF;
+
+    who_can := CASE action
+        WHEN 'REPLY' THEN COALESCE(hess.who_can_reply, preferences.who_can_reply)
+        WHEN 'LIKE' THEN COALESCE(hess.who_can_like, preferences.who_can_like)
+        WHEN 'MENTION' THEN preferences.who_can_mention_me
+        WHEN 'WATCH_NEW_HESSES' THEN preferences.who_can_watch_new_hesses
+        WHEN 'WATCH_REPLIES' THEN COALESCE(hess.who_can_watch_replies, preferences.who_can_watch_replies)
+        WHEN 'WATCH_LIKES' THEN COALESCE(hess.who_can_watch_likes, preferences.who_can_watch_likes)
+        WHEN 'WATCH_FOLLOWS' THEN preferences.who_can_watch_follows
+        WHEN 'MESSAGE' THEN preferences.who_can_message_me
+    END;
+
+    RETURN who_can_matches(who_can, viewer, author);
+END;
+$$ LANGUAGE plpgsql STABLE;
+
+-- 'ACCEPTED': the conversation is in the inbox of the participant.
+-- 'PENDING': a message request, the participant doesn't follow whoever added them.
+-- 'DECLINED': the message request was declined, the conversation is hidden from the participant.
+CREATE TYPE CONVERSATION_PARTICIPANT_STATUS AS ENUM ('ACCEPTED', 'PENDING', 'DECLINED');
+
+CREATE TABLE "conversations"
+(
+    id UUID DEFAULT uuid_generate_v4() PRIMARY KEY,
+    creator_id UUID NOT NULL,
+    -- Groups only, shown instead of the participants' names when set
+    title VARCHAR(100) DEFAULT NULL,
+    is_group BOOLEAN NOT NULL,
+    -- The two participants of a 1:1 conversation ('<smallest id>:<biggest id>'), there's only one
+    -- conversation per pair
+    direct_key TEXT UNIQUE DEFAULT NULL,
+    last_message_at TIMESTAMP WITH TIME ZONE DEFAULT NULL,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+    CHECK (is_group = (direct_key IS NULL)),
+    FOREIGN KEY (creator_id) REFERENCES users(id) ON DELETE RESTRICT
+);
+
+-- Create a trigger function to update conversations updated_at on every update
+CREATE OR REPLACE FUNCTION update_conversations_updated_at() RETURNS TRIGGER AS $$ BEGIN NEW.updated_at = NOW();
+RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+-- Create a trigger to call the update_conversations_updated_at function on every conversations update
+CREATE TRIGGER trigger_update_conversations_updated_at BEFORE
+UPDATE ON conversations FOR EACH ROW EXECUTE FUNCTION update_conversations_updated_at();
+
+CREATE TABLE "conversation_participants"
+(
+    conversation_id UUID NOT NULL,
+    user_id UUID NOT NULL,
+    status CONVERSATION_PARTICIPANT_STATUS NOT NULL,
+    -- Read receipts: the latest message the participant read
+    last_read_message_id UUID DEFAULT NULL,
+    last_read_at TIMESTAMP WITH TIME ZONE DEFAULT NULL,
+    joined_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+    -- Groups only, the participant left the group
+    left_at TIMESTAMP WITH TIME ZONE DEFAULT NULL,
+    PRIMARY KEY (conversation_id, user_id),
+    FOREIGN KEY (conversation_id) REFERENCES conversations(id) ON DELETE CASCADE,
+    FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE
+);
+
+CREATE INDEX conversation_participants_user_id_idx ON conversation_participants (user_id) WHERE left_at IS NULL;
+
+CREATE TABLE "messages"
+(
+    id UUID DEFAULT uuid_generate_v4() PRIMARY KEY,
+    conversation_id UUID NOT NULL,
+    sender_id UUID NOT NULL,
+    content TEXT DEFAULT NULL,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+    deleted_at TIMESTAMP WITH TIME ZONE DEFAULT NULL,
+    FOREIGN KEY (conversation_id) REFERENCES conversations(id) ON DELETE CASCADE,
+    FOREIGN KEY (sender_id) REFERENCES users(id) ON DELETE RESTRICT
+);
+
+CREATE INDEX messages_conversation_id_created_at_idx ON messages (conversation_id, created_at DESC, id DESC);
+
+ALTER TABLE "conversation_participants"
+    ADD FOREIGN KEY (last_read_message_id) REFERENCES messages(id) ON DELETE SET NULL;
+
+-- The hess media a message references, in order
+CREATE TABLE "message_media"
+(
+    message_id UUID NOT NULL,
+    hess_media_id UUID NOT NULL,
+    position SMALLINT NOT NULL,
+    PRIMARY KEY (message_id, hess_media_id),
+    FOREIGN KEY (message_id) REFERENCES messages(id) ON DELETE CASCADE,
+    FOREIGN KEY (hess_media_id) REFERENCES hess_media(id) ON DELETE CASCADE
+);
+
+-- Messages (new and deleted ones) are streamed to the participants who didn't leave or decline
+-- the conversation, the sender included (for their other devices). Read receipts are streamed
+-- to the other participants, unless the reader didn't accept the conversation.
+ALTER TABLE "stream_events" DROP CONSTRAINT stream_events_event_type_check;
+ALTER TABLE "stream_events" ADD CONSTRAINT stream_events_event_type_check
+    CHECK (event_type IN ('NOTIFICATION', 'FEED_ITEM', 'FOLLOW_REQUEST', 'MESSAGE', 'MESSAGE_READ'));
+
+CREATE OR REPLACE FUNCTION publish_message_stream_events() RETURNS TRIGGER AS $$
+DECLARE
+    participant conversation_participants%ROWTYPE;
+BEGIN
+    IF TG_OP = 'UPDATE' AND NEW.deleted_at IS NOT DISTINCT FROM OLD.deleted_at THEN
+        RETURN NULL;
+    END IF;
+
+    FOR participant IN
+        SELECT * FROM conversation_participants p
+        WHERE p.conversation_id = NEW.conversation_id
+            AND p.left_at IS NULL
+            AND p.status <> 'DECLINED'
+    LOOP
+        PERFORM publish_stream_event(participant.user_id, 'MESSAGE', jsonb_build_object(
+            'conversationId', NEW.conversation_id,
+            'messageId', NEW.id,
+            'senderId', NEW.sender_id,
+            'isRequest', participant.status = 'PENDING',
+            'deleted', NEW.deleted_at IS NOT NULL
+        ));
+    END LOOP;
+    RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER trigger_publish_message_stream_events AFTER
+INSERT OR UPDATE OF deleted_at ON messages FOR EACH ROW EXECUTE FUNCTION publish_message_stream_events();
+
+CREATE OR REPLACE FUNCTION publish_message_read_stream_events() RETURNS TRIGGER AS $$
+BEGIN
+    IF NEW.last_read_message_id IS NOT DISTINCT FROM OLD.last_read_message_id
+        OR NEW.last_read_message_id IS NULL
+        OR NEW.status <> 'ACCEPTED' THEN
+        RETURN NULL;
+    END IF;
+
+    PERFORM publish_stream_event(p.user_id, 'MESSAGE_READ', jsonb_build_object(
+        'conversationId', NEW.conversation_id,
+        'userId', NEW.user_id,
+        'lastReadMessageId', NEW.last_read_message_id,
+        'lastReadAt', NEW.last_read_at
+    ))
+    FROM conversation_participants p
+    WHERE p.conversation_id = NEW.conversation_id
+        AND p.user_id <> NEW.user_id
+        AND p.left_at IS NULL
+        AND p.status <> 'DECLINED';
+    RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER trigger_publish_message_read_stream_events AFTER
+UPDATE OF last_read_message_id ON conversation_participants FOR EACH ROW EXECUTE FUNCTION publish_message_read_stream_events();
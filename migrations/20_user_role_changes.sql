@@ -0,0 +1,16 @@
+-- Role changes: every change of the role of a user, with who made it (see role_policy.rs).
+
+CREATE TABLE "user_role_changes"
+(
+    id UUID DEFAULT uuid_generate_v4() PRIMARY KEY,
+    user_id UUID NOT NULL,
+    changed_by UUID NOT NULL,
+    old_role USER_ROLE NOT NULL,
+    new_role USER_ROLE NOT NULL,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW()
+);
+
+ALTER TABLE "user_role_changes" ADD FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE;
+ALTER TABLE "user_role_changes" ADD FOREIGN KEY (changed_by) REFERENCES users(id) ON DELETE RESTRICT;
+
+CREATE INDEX user_role_changes_user_id_idx ON "user_role_changes" (user_id, created_at DESC);
@@ -0,0 +1,39 @@
+-- Account verification: users request to be verified with some evidence, admins review the
+-- requests and fill the verified, verified_at and verified_by columns of users (see 1_init.sql).
+
+CREATE TYPE VERIFICATION_REQUEST_STATUS AS ENUM ('PENDING', 'APPROVED', 'REJECTED', 'REVOKED');
+
+CREATE TABLE "verification_requests"
+(
+    id UUID DEFAULT uuid_generate_v4() PRIMARY KEY,
+    user_id UUID NOT NULL,
+    evidence TEXT NOT NULL,
+    status VERIFICATION_REQUEST_STATUS NOT NULL DEFAULT 'PENDING',
+    reviewer_id UUID DEFAULT NULL,
+    review_notes TEXT DEFAULT NULL,
+    reviewed_at TIMESTAMP WITH TIME ZONE DEFAULT NULL,
+    -- An approved request is revoked along with the verification of the user
+    revoked_by UUID DEFAULT NULL,
+    revocation_notes TEXT DEFAULT NULL,
+    revoked_at TIMESTAMP WITH TIME ZONE DEFAULT NULL,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW()
+);
+
+ALTER TABLE "verification_requests" ADD FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE;
+ALTER TABLE "verification_requests" ADD FOREIGN KEY (reviewer_id) REFERENCES users(id) ON DELETE RESTRICT;
+ALTER TABLE "verification_requests" ADD FOREIGN KEY (revoked_by) REFERENCES users(id) ON DELETE RESTRICT;
+
+-- A single pending request per user
+CREATE UNIQUE INDEX verification_requests_pending_user_id_idx ON "verification_requests" (user_id) WHERE status = 'PENDING';
+CREATE INDEX verification_requests_user_id_idx ON "verification_requests" (user_id, created_at DESC);
+
+-- Create a trigger function to update verification_requests updated_at on every update
+CREATE OR REPLACE FUNCTION update_verification_requests_updated_at() RETURNS TRIGGER AS $$ BEGIN NEW.updated_at = NOW();
+RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+-- Create a trigger to call the update_verification_requests_updated_at function on every verification_requests update
+CREATE TRIGGER trigger_update_verification_requests_updated_at BEFORE
+UPDATE ON verification_requests FOR EACH ROW EXECUTE FUNCTION update_verification_requests_updated_at();
@@ -0,0 +1,73 @@
+CREATE TABLE "audience_lists"
+(
+    id UUID DEFAULT uuid_generate_v4() PRIMARY KEY,
+    owner_id UUID NOT NULL,
+    name VARCHAR(100) NOT NULL,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+    deleted_at TIMESTAMP WITH TIME ZONE DEFAULT NULL,
+    FOREIGN KEY (owner_id) REFERENCES users(id) ON DELETE RESTRICT
+);
+
+-- Create a trigger function to update audience lists updated_at on every update
+CREATE OR REPLACE FUNCTION update_audience_lists_updated_at() RETURNS TRIGGER AS $$ BEGIN NEW.updated_at = NOW();
+RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+-- Create a trigger to call the update_audience_lists_updated_at function on every audience lists update
+CREATE TRIGGER trigger_update_audience_lists_updated_at BEFORE
+UPDATE ON audience_lists FOR EACH ROW EXECUTE FUNCTION update_audience_lists_updated_at();
+
+CREATE TABLE "audience_list_members"
+(
+    id UUID DEFAULT uuid_generate_v4() PRIMARY KEY,
+    list_id UUID NOT NULL,
+    user_id UUID NOT NULL,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+    deleted_at TIMESTAMP WITH TIME ZONE DEFAULT NULL,
+    FOREIGN KEY (list_id) REFERENCES audience_lists(id) ON DELETE RESTRICT,
+    FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE RESTRICT
+);
+
+-- A user can only be an active member of a list once
+CREATE UNIQUE INDEX audience_list_members_unique_active
+ON audience_list_members (list_id, user_id) WHERE deleted_at IS NULL;
+
+-- `WHO_CAN` values are now stored as text so that they can reference audience lists:
+-- 'FOLLOWED', 'FOLLOWERS' or 'LIST:<audience list id>'
+CREATE OR REPLACE FUNCTION is_valid_who_can_array(who_can TEXT[]) RETURNS BOOLEAN AS $$
+    SELECT COALESCE(
+        bool_and(value ~ '^(FOLLOWED|FOLLOWERS|LIST:[0-9a-f]{8}-[0-9a-f]{4}-[0-9a-f]{4}-[0-9a-f]{4}-[0-9a-f]{12})$'),
+        TRUE
+    )
+    FROM unnest(who_can) AS value;
+$$ LANGUAGE sql IMMUTABLE;
+
+ALTER TABLE "hesses"
+    ALTER COLUMN who_can_reply TYPE TEXT[] USING who_can_reply::TEXT[],
+    ALTER COLUMN who_can_like TYPE TEXT[] USING who_can_like::TEXT[],
+    ALTER COLUMN who_can_watch_replies TYPE TEXT[] USING who_can_watch_replies::TEXT[],
+    ALTER COLUMN who_can_watch_likes TYPE TEXT[] USING who_can_watch_likes::TEXT[],
+    ADD CHECK (is_valid_who_can_array(who_can_reply)),
+    ADD CHECK (is_valid_who_can_array(who_can_like)),
+    ADD CHECK (is_valid_who_can_array(who_can_watch_replies)),
+    ADD CHECK (is_valid_who_can_array(who_can_watch_likes));
+
+ALTER TABLE "user_privacy_preferences"
+    ALTER COLUMN who_can_reply TYPE TEXT[] USING who_can_reply::TEXT[],
+    ALTER COLUMN who_can_like TYPE TEXT[] USING who_can_like::TEXT[],
+    ALTER COLUMN who_can_mention_me TYPE TEXT[] USING who_can_mention_me::TEXT[],
+    ALTER COLUMN who_can_watch_new_hesses TYPE TEXT[] USING who_can_watch_new_hesses::TEXT[],
+    ALTER COLUMN who_can_watch_replies TYPE TEXT[] USING who_can_watch_replies::TEXT[],
+    ALTER COLUMN who_can_watch_follows TYPE TEXT[] USING who_can_watch_follows::TEXT[],
+    ALTER COLUMN who_can_watch_likes TYPE TEXT[] USING who_can_watch_likes::TEXT[],
+    ADD CHECK (is_valid_who_can_array(who_can_reply)),
+    ADD CHECK (is_valid_who_can_array(who_can_like)),
+    ADD CHECK (is_valid_who_can_array(who_can_mention_me)),
+    ADD CHECK (is_valid_who_can_array(who_can_watch_new_hesses)),
+    ADD CHECK (is_valid_who_can_array(who_can_watch_replies)),
+    ADD CHECK (is_valid_who_can_array(who_can_watch_follows)),
+    ADD CHECK (is_valid_who_can_array(who_can_watch_likes));
+
+DROP TYPE WHO_CAN;
@@ -0,0 +1,100 @@
+-- SQL counterpart of the Rust visibility policy (`src/policies/visibility_policy.rs`),
+-- both implementations must be kept in sync.
+
+-- Checks whether the viewer is part of at least one of the `WHO_CAN` audiences of the author.
+-- A NULL `who_can` means public, an empty one means no one.
+CREATE OR REPLACE FUNCTION who_can_matches(who_can TEXT[], viewer UUID, author UUID) RETURNS BOOLEAN AS $$
+    SELECT who_can IS NULL OR EXISTS (
+        SELECT 1 FROM unnest(who_can) AS audience
+        WHERE (
+            audience = 'FOLLOWED' AND EXISTS (
+                SELECT 1 FROM followers f
+                WHERE f.follower_id = author AND f.followed_id = viewer AND f.deleted_at IS NULL
+            )
+        ) OR (
+            audience = 'FOLLOWERS' AND EXISTS (
+                SELECT 1 FROM followers f
+                WHERE f.follower_id = viewer AND f.followed_id = author AND f.deleted_at IS NULL
+            )
+        ) OR (
+            audience LIKE 'LIST:%' AND EXISTS (
+                SELECT 1 FROM audience_lists l
+                JOIN audience_list_members m ON m.list_id = l.id
+                WHERE l.id = substring(audience FROM 6)::UUID
+                    AND l.owner_id = author
+                    AND l.deleted_at IS NULL
+                    AND m.user_id = viewer
+                    AND m.deleted_at IS NULL
+            )
+        )
+    );
+$$ LANGUAGE sql STABLE;
+
+-- Checks whether the viewer can perform an action on the author (or on one of their hesses).
+--
+-- Actions: 'VIEW', 'REPLY', 'LIKE', 'MENTION', 'WATCH_NEW_HESSES', 'WATCH_REPLIES', 'WATCH_LIKES', 'WATCH_FOLLOWS'
+--
+-- 1. The author can always do everything.
+-- 2. Blocks (in either direction) deny everything.
+-- 3. Private profiles deny everything to whoever isn't a follower (or mentioned in the hess).
+-- 4. Hess level `who_can_*` overrides win over the author's privacy preferences, when not NULL.
+-- 5. Users mentioned in the hess are always part of the audience.
+CREATE OR REPLACE FUNCTION viewer_can(action TEXT, viewer UUID, author UUID, target_hess UUID DEFAULT NULL) RETURNS BOOLEAN AS $$
+DECLARE
+    preferences user_privacy_preferences%ROWTYPE;
+    hess hesses%ROWTYPE;
+    is_mentioned BOOLEAN := FALSE;
+    who_can TEXT[];
+BEGIN
+    IF viewer = author THEN
+        RETURN TRUE;
+    END IF;
+
+    IF EXISTS (
+        SELECT 1 FROM blocked_users b
+        WHERE b.deleted_at IS NULL AND (
+            (b.blocker_id = author AND b.blocked_id = viewer)
+            OR (b.blocker_id = viewer AND b.blocked_id = author)
+        )
+    ) THEN
+        RETURN FALSE;
+    END IF;
+
+    SELECT * INTO preferences FROM user_privacy_preferences p WHERE p.user_id = author;
+
+    IF target_hess IS NOT NULL THEN
+        SELECT * INTO hess FROM hesses h WHERE h.id = target_hess;
+
+        is_mentioned := EXISTS (
+            SELECT 1 FROM hess_mentions m
+            WHERE m.hess_id = target_hess AND m.user_id = viewer AND m.deleted_at IS NULL
+        );
+    END IF;
+
+    IF COALESCE(preferences.is_private_profile, FALSE)
+        AND NOT is_mentioned
+        AND NOT EXISTS (
+            SELECT 1 FROM followers f
+            WHERE f.follower_id = viewer AND f.followed_id = author AND f.deleted_at IS NULL
+        )
+    THEN
+        RETURN FALSE;
+    END IF;
+
+    IF action = 'VIEW' OR is_mentioned THEN
+        RETURN TRUE;
+    END IF;
+
+    who_can := CASE action
+        WHEN 'REPLY' THEN COALESCE(hess.who_can_reply, preferences.who_can_reply)
+        WHEN 'LIKE' THEN COALESCE(hess.who_can_like, preferences.who_can_like)
+        WHEN 'MENTION' THEN preferences.who_can_mention_me
+        WHEN 'WATCH_NEW_HESSES' THEN preferences.who_can_watch_new_hesses
+        WHEN 'WATCH_REPLIES' THEN COALESCE(hess.who_can_watch_replies, preferences.who_can_watch_replies)
+        WHEN 'WATCH_LIKES' THEN COALESCE(hess.who_can_watch_likes, preferences.who_can_watch_likes)
+        WHEN 'WATCH_FOLLOWS' THEN preferences.who_can_watch_follows
+    END;
+
+    RETURN who_can_matches(who_can, viewer, author);
+END;
+$$ LANGUAGE plpgsql STABLE;
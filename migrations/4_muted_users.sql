@@ -0,0 +1,19 @@
+CREATE TABLE "muted_users"
+(
+    id UUID DEFAULT uuid_generate_v4() PRIMARY KEY,
+    muter_id UUID NOT NULL,
+    muted_id UUID NOT NULL,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+    deleted_at TIMESTAMP WITH TIME ZONE DEFAULT NULL,
+    FOREIGN KEY (muter_id) REFERENCES users(id) ON DELETE RESTRICT,
+    FOREIGN KEY (muted_id) REFERENCES users(id) ON DELETE RESTRICT
+);
+
+-- A user can only mute another user once at a time
+CREATE UNIQUE INDEX muted_users_unique_active
+ON muted_users (muter_id, muted_id) WHERE deleted_at IS NULL;
+
+-- Indexes used to assemble the home feed
+CREATE INDEX followers_follower_id_idx ON followers (follower_id) WHERE deleted_at IS NULL;
+CREATE INDEX hesses_user_id_created_at_idx ON hesses (user_id, created_at DESC) WHERE deleted_at IS NULL;
+CREATE INDEX likes_user_id_created_at_idx ON likes (user_id, created_at DESC) WHERE deleted_at IS NULL;
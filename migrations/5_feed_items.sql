@@ -0,0 +1,119 @@
+-- Generic background jobs queue, consumed by the workers in `src/jobs`.
+CREATE TABLE "jobs"
+(
+    id UUID DEFAULT uuid_generate_v4() PRIMARY KEY,
+    queue TEXT NOT NULL,
+    kind TEXT NOT NULL,
+    payload JSONB NOT NULL DEFAULT '{}'::JSONB,
+    attempts INT NOT NULL DEFAULT 0,
+    last_error TEXT DEFAULT NULL,
+    run_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+    locked_at TIMESTAMP WITH TIME ZONE DEFAULT NULL,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX jobs_queue_run_at_idx ON jobs (queue, run_at);
+
+CREATE OR REPLACE FUNCTION enqueue_job(queue TEXT, kind TEXT, payload JSONB) RETURNS VOID AS $$
+    INSERT INTO jobs (queue, kind, payload) VALUES (queue, kind, payload);
+$$ LANGUAGE sql;
+
+-- Materialized home feeds (fan-out on write), one row per feed owner and event.
+-- Only the candidates are stored, the visibility policy and mutes are still applied when reading.
+CREATE TABLE "feed_items"
+(
+    id UUID DEFAULT uuid_generate_v4() PRIMARY KEY,
+    owner_id UUID NOT NULL,
+    item_type TEXT NOT NULL CHECK (item_type IN ('NEW_HESS', 'REPLY', 'LIKE', 'FOLLOW')),
+    event_id UUID NOT NULL,
+    occurred_at TIMESTAMP WITH TIME ZONE NOT NULL,
+    actor_id UUID NOT NULL,
+    hess_id UUID DEFAULT NULL,
+    followed_user_id UUID DEFAULT NULL,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+    FOREIGN KEY (owner_id) REFERENCES users(id) ON DELETE CASCADE,
+    FOREIGN KEY (actor_id) REFERENCES users(id) ON DELETE CASCADE,
+    FOREIGN KEY (hess_id) REFERENCES hesses(id) ON DELETE CASCADE,
+    FOREIGN KEY (followed_user_id) REFERENCES users(id) ON DELETE CASCADE,
+    UNIQUE (owner_id, event_id)
+);
+
+CREATE INDEX feed_items_owner_id_occurred_at_idx ON feed_items (owner_id, occurred_at DESC, event_id DESC);
+CREATE INDEX feed_items_event_id_idx ON feed_items (event_id);
+CREATE INDEX feed_items_hess_id_idx ON feed_items (hess_id);
+
+-- Used to count followers when deciding which authors are fanned out
+CREATE INDEX followers_followed_id_idx ON followers (followed_id) WHERE deleted_at IS NULL;
+
+-- Feed events, enqueued in the `feed` queue whatever the feed strategy is
+-- (the worker drops them when the feed is assembled on read).
+
+CREATE OR REPLACE FUNCTION enqueue_hess_feed_jobs() RETURNS TRIGGER AS $$
+BEGIN
+    IF TG_OP = 'INSERT' AND NEW.deleted_at IS NULL THEN
+        PERFORM enqueue_job('feed', 'HESS_CREATED', jsonb_build_object('hessId', NEW.id));
+    ELSIF TG_OP = 'UPDATE' AND OLD.deleted_at IS NULL AND NEW.deleted_at IS NOT NULL THEN
+        PERFORM enqueue_job('feed', 'HESS_DELETED', jsonb_build_object('hessId', NEW.id));
+    END IF;
+    RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER trigger_enqueue_hess_feed_jobs AFTER
+INSERT OR UPDATE OF deleted_at ON hesses FOR EACH ROW EXECUTE FUNCTION enqueue_hess_feed_jobs();
+
+CREATE OR REPLACE FUNCTION enqueue_like_feed_jobs() RETURNS TRIGGER AS $$
+BEGIN
+    IF TG_OP = 'INSERT' AND NEW.deleted_at IS NULL THEN
+        PERFORM enqueue_job('feed', 'LIKE_CREATED', jsonb_build_object('likeId', NEW.id));
+    ELSIF TG_OP = 'UPDATE' AND OLD.deleted_at IS NULL AND NEW.deleted_at IS NOT NULL THEN
+        PERFORM enqueue_job('feed', 'LIKE_DELETED', jsonb_build_object('likeId', NEW.id));
+    END IF;
+    RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER trigger_enqueue_like_feed_jobs AFTER
+INSERT OR UPDATE OF deleted_at ON likes FOR EACH ROW EXECUTE FUNCTION enqueue_like_feed_jobs();
+
+CREATE OR REPLACE FUNCTION enqueue_follower_feed_jobs() RETURNS TRIGGER AS $$
+DECLARE
+    payload JSONB := jsonb_build_object(
+        'followerRowId', NEW.id,
+        'followerId', NEW.follower_id,
+        'followedId', NEW.followed_id
+    );
+BEGIN
+    IF TG_OP = 'INSERT' AND NEW.deleted_at IS NULL THEN
+        PERFORM enqueue_job('feed', 'FOLLOW_CREATED', payload);
+    ELSIF TG_OP = 'UPDATE' AND OLD.deleted_at IS NULL AND NEW.deleted_at IS NOT NULL THEN
+        PERFORM enqueue_job('feed', 'FOLLOW_DELETED', payload);
+    ELSIF TG_OP = 'UPDATE' AND NEW.deleted_at IS NULL AND (
+        OLD.watch_new_hesses IS DISTINCT FROM NEW.watch_new_hesses
+        OR OLD.watch_replies IS DISTINCT FROM NEW.watch_replies
+        OR OLD.watch_likes IS DISTINCT FROM NEW.watch_likes
+        OR OLD.watch_follows IS DISTINCT FROM NEW.watch_follows
+    ) THEN
+        PERFORM enqueue_job('feed', 'FOLLOW_UPDATED', payload);
+    END IF;
+    RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER trigger_enqueue_follower_feed_jobs AFTER
+INSERT OR UPDATE ON followers FOR EACH ROW EXECUTE FUNCTION enqueue_follower_feed_jobs();
+
+CREATE OR REPLACE FUNCTION enqueue_blocked_user_feed_jobs() RETURNS TRIGGER AS $$
+BEGIN
+    IF NEW.deleted_at IS NULL THEN
+        PERFORM enqueue_job('feed', 'USER_BLOCKED', jsonb_build_object(
+            'blockerId', NEW.blocker_id,
+            'blockedId', NEW.blocked_id
+        ));
+    END IF;
+    RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER trigger_enqueue_blocked_user_feed_jobs AFTER
+INSERT ON blocked_users FOR EACH ROW EXECUTE FUNCTION enqueue_blocked_user_feed_jobs();
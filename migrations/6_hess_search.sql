@@ -0,0 +1,8 @@
+-- Full-text search over the hesses content
+ALTER TABLE hesses ADD COLUMN search_vector TSVECTOR
+GENERATED ALWAYS AS (to_tsvector('english', COALESCE(content, ''))) STORED;
+
+CREATE INDEX hesses_search_vector_idx ON hesses USING GIN (search_vector);
+
+-- Used by the `has:media` search filter
+CREATE INDEX hess_media_hess_id_idx ON hess_media (hess_id) WHERE deleted_at IS NULL;
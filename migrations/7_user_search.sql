@@ -0,0 +1,5 @@
+-- Typo tolerant (trigram) and prefix user search
+CREATE EXTENSION IF NOT EXISTS pg_trgm;
+
+CREATE INDEX users_username_trgm_idx ON users USING GIN (LOWER(username) gin_trgm_ops);
+CREATE INDEX users_name_trgm_idx ON users USING GIN (LOWER(name) gin_trgm_ops);
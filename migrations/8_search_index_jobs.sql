@@ -0,0 +1,34 @@
+-- Search index events, enqueued in the `search` queue whatever the search backend is
+-- (the worker drops them when Postgres is the backend, its indexes are always up to date).
+
+CREATE OR REPLACE FUNCTION enqueue_hess_search_jobs() RETURNS TRIGGER AS $$
+BEGIN
+    IF TG_OP = 'INSERT'
+        OR OLD.content IS DISTINCT FROM NEW.content
+        OR OLD.deleted_at IS DISTINCT FROM NEW.deleted_at
+    THEN
+        PERFORM enqueue_job('search', 'HESS_CHANGED', jsonb_build_object('hessId', NEW.id));
+    END IF;
+    RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER trigger_enqueue_hess_search_jobs AFTER
+INSERT OR UPDATE OF content, deleted_at ON hesses FOR EACH ROW EXECUTE FUNCTION enqueue_hess_search_jobs();
+
+CREATE OR REPLACE FUNCTION enqueue_user_search_jobs() RETURNS TRIGGER AS $$
+BEGIN
+    IF TG_OP = 'INSERT'
+        OR OLD.username IS DISTINCT FROM NEW.username
+        OR OLD.name IS DISTINCT FROM NEW.name
+        OR OLD.activated IS DISTINCT FROM NEW.activated
+        OR OLD.deleted_at IS DISTINCT FROM NEW.deleted_at
+    THEN
+        PERFORM enqueue_job('search', 'USER_CHANGED', jsonb_build_object('userId', NEW.id));
+    END IF;
+    RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER trigger_enqueue_user_search_jobs AFTER
+INSERT OR UPDATE OF username, name, activated, deleted_at ON users FOR EACH ROW EXECUTE FUNCTION enqueue_user_search_jobs();
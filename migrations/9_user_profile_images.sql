@@ -0,0 +1,9 @@
+-- Processed profile images: the original and the thumbnails share the same format,
+-- `image_url` being the storage key of the (re-encoded) original.
+
+ALTER TABLE "user_profile_images"
+    ADD COLUMN content_type VARCHAR(100) NOT NULL DEFAULT 'image/jpeg',
+    ADD COLUMN width INT DEFAULT NULL,
+    ADD COLUMN height INT DEFAULT NULL;
+
+CREATE INDEX user_profile_images_user_id_created_at_index ON user_profile_images (user_id, created_at);
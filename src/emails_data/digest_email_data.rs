@@ -0,0 +1,25 @@
+use serde_derive::Serialize;
+
+use crate::models::{DigestHess, DigestUser};
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DigestEmailData {
+    pub facebook_link: Option<String>,
+    pub twitter_link: Option<String>,
+    pub instagram_link: Option<String>,
+    pub linked_in_link: Option<String>,
+    #[serde(rename = "firstContactLine")]
+    pub first_contatct_line: Option<String>,
+    #[serde(rename = "secondContactLine")]
+    pub second_contatct_line: Option<String>,
+    pub name: String,
+    /// "daily" or "weekly".
+    pub period: String,
+    pub new_followers: Vec<DigestUser>,
+    /// The new followers not listed.
+    pub other_new_followers_count: i64,
+    pub top_hesses: Vec<DigestHess>,
+    pub unread_mentions: Vec<DigestHess>,
+    pub unsubscribe_link: String,
+}
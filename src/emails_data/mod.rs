@@ -1,5 +1,7 @@
 mod common_email_details;
+mod digest_email_data;
 mod user_activation_email_data;
 
 pub use common_email_details::CommonEmailDetails;
+pub use digest_email_data::DigestEmailData;
 pub use user_activation_email_data::UserActivationEmailData;
@@ -1,11 +1,11 @@
-use std::{str::FromStr, sync::Arc};
+use std::{collections::HashMap, str::FromStr, sync::Arc};
 
 use uuid::Uuid;
 use warp::Filter;
 
 use crate::{
     models::User,
-    services::users_service::get_user_by_id,
+    services::{digests_service::touch_user_activity, users_service::get_user_by_id},
     utils::{
         jwt::{get_claims_from_jwt_token, JwtConfig},
         response::ApiErrorType,
@@ -46,20 +46,79 @@ pub fn authentication_filter(
                     _ => return Err(warp::reject::custom(ApiErrorType::NotLoggedIn)),
                 };
 
-                // Retrieve claims from the JWT token
-                let claims = get_claims_from_jwt_token(&token, &jwt_config)
-                    .ok_or_else(|| warp::reject::custom(ApiErrorType::InvalidJwtToken))?;
+                authenticate(token, &jwt_config, pool).await
+            }
+        })
+}
 
-                // Parse the user ID from the JWT claims
-                let id = Uuid::from_str(&claims.sub)
-                    .map_err(|_| warp::reject::custom(ApiErrorType::InternalServerError))?;
+/// Creates a Warp filter for JWT-based authentication, taking the JWT from the "Authorization"
+/// header or else from the `accessToken` query field.
+///
+/// Meant for the connections browsers can't set headers on (WebSockets and server-sent events),
+/// see `authentication_filter` otherwise.
+///
+/// # Arguments
+///
+/// * `jwt_config` - An `Arc` reference to the JWT configuration settings.
+/// * `pool` - A PostgreSQL database connection pool (`PgPool`) used for querying user data.
+///
+/// # Returns
+///
+/// A Warp filter that extracts the authenticated `User` from the request if successful,
+/// or rejects the request with appropriate error types.
+///
+pub fn header_or_query_authentication_filter(
+    jwt_config: Arc<JwtConfig>,
+    pool: sqlx::postgres::PgPool,
+) -> impl Filter<Extract = (User,), Error = warp::Rejection> + Clone {
+    warp::any()
+        .and(warp::filters::header::optional("Authorization"))
+        .and(warp::query::<HashMap<String, String>>())
+        .and_then(
+            move |authorization_header: Option<String>, query: HashMap<String, String>| {
+                let jwt_config = jwt_config.clone();
+                let pool = pool.clone();
 
-                // Retrieve the user from the database using the user ID
-                let user = get_user_by_id(pool, id)
-                    .await
-                    .map_err(|_| warp::reject::custom(ApiErrorType::InvalidJwtToken))?;
+                async move {
+                    let token = match (authorization_header, query.get("accessToken")) {
+                        (Some(header), _) if header.starts_with("Bearer ") => {
+                            header[7..].to_string()
+                        }
+                        (_, Some(token)) => token.to_string(),
+                        _ => return Err(warp::reject::custom(ApiErrorType::NotLoggedIn)),
+                    };
 
-                Ok(user)
-            }
-        })
+                    authenticate(token, &jwt_config, pool).await
+                }
+            },
+        )
+}
+
+/// Retrieves the user a JWT token was issued to.
+async fn authenticate(
+    token: String,
+    jwt_config: &JwtConfig,
+    pool: sqlx::postgres::PgPool,
+) -> Result<User, warp::Rejection> {
+    // Retrieve claims from the JWT token
+    let claims = get_claims_from_jwt_token(&token, jwt_config)
+        .ok_or_else(|| warp::reject::custom(ApiErrorType::InvalidJwtToken))?;
+
+    // Parse the user ID from the JWT claims
+    let id = Uuid::from_str(&claims.sub)
+        .map_err(|_| warp::reject::custom(ApiErrorType::InternalServerError))?;
+
+    // Retrieve the user from the database using the user ID
+    let user = get_user_by_id(pool.clone(), id)
+        .await
+        .map_err(|_| warp::reject::custom(ApiErrorType::InvalidJwtToken))?;
+
+    // Active users don't get digests, recorded in the background to not slow the request down
+    tokio::spawn(async move {
+        if touch_user_activity(pool, id).await.is_err() {
+            log::warn!("Couldn't record the activity of the user {}", id);
+        }
+    });
+
+    Ok(user)
 }
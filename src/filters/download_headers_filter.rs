@@ -0,0 +1,24 @@
+use warp::Filter;
+
+use crate::media::DownloadHeaders;
+
+/// Create a Warp filter for extracting the headers of a download request.
+///
+/// The `Range`, `If-None-Match` and `If-Range` headers are optional, they're honored by
+/// `download_reply`.
+///
+/// # Returns
+///
+/// A Warp filter that extracts the headers into `DownloadHeaders`.
+///
+pub fn download_headers_filter(
+) -> impl Filter<Extract = (DownloadHeaders,), Error = warp::Rejection> + Clone {
+    warp::header::optional::<String>("range")
+        .and(warp::header::optional::<String>("if-none-match"))
+        .and(warp::header::optional::<String>("if-range"))
+        .map(|range, if_none_match, if_range| DownloadHeaders {
+            range,
+            if_none_match,
+            if_range,
+        })
+}
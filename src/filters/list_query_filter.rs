@@ -0,0 +1,30 @@
+use std::collections::HashMap;
+
+use warp::Filter;
+
+use crate::utils::list_query::{ListQuery, ListSpec};
+
+/// Create a Warp filter for extracting and validating the sorting and pagination query fields
+/// of a list endpoint.
+///
+/// The `sort`, `page`, `size` and `cursor` query fields are parsed against the sortable fields
+/// of the given `ListSpec`, see `ListQuery` for the syntax. If they're invalid, an appropriate
+/// rejection is generated.
+///
+/// # Parameters
+///
+/// - `spec`: The `ListSpec` of the listed resource.
+///
+/// # Returns
+///
+/// A Warp filter that extracts and validates the query into a `ListQuery`.
+///
+pub fn list_query_filter(
+    spec: &'static ListSpec,
+) -> impl Filter<Extract = (ListQuery,), Error = warp::Rejection> + Clone {
+    warp::any()
+        .and(warp::query::<HashMap<String, String>>())
+        .and_then(move |query: HashMap<String, String>| async move {
+            ListQuery::parse(spec, &query).map_err(warp::reject::custom)
+        })
+}
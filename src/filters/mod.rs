@@ -1,7 +1,17 @@
 mod authentication_filter;
 mod body_validation_filter;
+mod download_headers_filter;
+mod list_query_filter;
+mod multipart_form_filter;
+mod permission_filter;
+mod raw_body_filter;
 mod required_uuid_param_filter;
 
-pub use authentication_filter::authentication_filter;
+pub use authentication_filter::{authentication_filter, header_or_query_authentication_filter};
 pub use body_validation_filter::body_validation_filter;
+pub use download_headers_filter::download_headers_filter;
+pub use list_query_filter::list_query_filter;
+pub use multipart_form_filter::multipart_form_filter;
+pub use permission_filter::require_permission;
+pub use raw_body_filter::raw_body_filter;
 pub use required_uuid_param_filter::required_uuid_param_filter;
@@ -0,0 +1,34 @@
+use warp::{multipart::FormData, reject::PayloadTooLarge, Filter};
+
+use crate::utils::response::ApiErrorType;
+
+/// Create a Warp filter for extracting a multipart form body.
+///
+/// This function returns a filter wrapping `warp::multipart::form`, turning its rejections into
+/// API errors: an `ApiErrorType::FileTooLarge` error when the body is bigger than `max_length`,
+/// and an `ApiErrorType::InvalidMultipartBody` error when it isn't a multipart form
+/// (or has no `Content-Length`).
+///
+/// # Parameters
+///
+/// - `max_length`: The maximum size of the whole body, in bytes.
+///
+/// # Returns
+///
+/// A Warp filter that extracts the body as `FormData`, to be streamed.
+///
+pub fn multipart_form_filter(
+    max_length: u64,
+) -> impl Filter<Extract = (FormData,), Error = warp::Rejection> + Clone {
+    warp::multipart::form().max_length(max_length).or_else(
+        |rejection: warp::Rejection| async move {
+            let error = if rejection.find::<PayloadTooLarge>().is_some() {
+                ApiErrorType::FileTooLarge
+            } else {
+                ApiErrorType::InvalidMultipartBody
+            };
+
+            Err::<(FormData,), _>(warp::reject::custom(error))
+        },
+    )
+}
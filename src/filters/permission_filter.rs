@@ -0,0 +1,41 @@
+use std::sync::Arc;
+
+use warp::Filter;
+
+use crate::{
+    models::User,
+    policies::{has_permission, Permission},
+    utils::{jwt::JwtConfig, response::ApiErrorType},
+};
+
+use super::authentication_filter;
+
+/// Creates a Warp filter letting only the users having a permission through.
+///
+/// The user is authenticated with `authentication_filter`, then the request is rejected with
+/// `ApiErrorType::Unauthorized` unless their role has the permission (see `has_permission`).
+///
+/// # Arguments
+///
+/// * `jwt_config` - An `Arc` reference to the JWT configuration settings.
+/// * `pool` - A PostgreSQL database connection pool (`PgPool`) used for querying user data.
+/// * `permission` - The permission required.
+///
+/// # Returns
+///
+/// A Warp filter that extracts the authenticated `User` from the request if they have the
+/// permission, or rejects the request with appropriate error types.
+///
+pub fn require_permission(
+    jwt_config: Arc<JwtConfig>,
+    pool: sqlx::postgres::PgPool,
+    permission: Permission,
+) -> impl Filter<Extract = (User,), Error = warp::Rejection> + Clone {
+    authentication_filter(jwt_config, pool).and_then(move |user: User| async move {
+        if has_permission(&user.role, permission) {
+            Ok(user)
+        } else {
+            Err(warp::reject::custom(ApiErrorType::Unauthorized))
+        }
+    })
+}
@@ -0,0 +1,33 @@
+use warp::{hyper::body::Bytes, reject::PayloadTooLarge, Filter};
+
+use crate::utils::response::ApiErrorType;
+
+/// Create a Warp filter for extracting a raw (non JSON) body, e.g. an uploaded text file.
+///
+/// This function returns a filter wrapping `warp::body::bytes`, turning its rejections into
+/// API errors: an `ApiErrorType::FileTooLarge` error when the body is bigger than `max_length`,
+/// and an `ApiErrorType::EmptyFile` error when it has no `Content-Length`.
+///
+/// # Parameters
+///
+/// - `max_length`: The maximum size of the body, in bytes.
+///
+/// # Returns
+///
+/// A Warp filter that extracts the whole body.
+///
+pub fn raw_body_filter(
+    max_length: u64,
+) -> impl Filter<Extract = (Bytes,), Error = warp::Rejection> + Clone {
+    warp::body::content_length_limit(max_length)
+        .and(warp::body::bytes())
+        .or_else(|rejection: warp::Rejection| async move {
+            let error = if rejection.find::<PayloadTooLarge>().is_some() {
+                ApiErrorType::FileTooLarge
+            } else {
+                ApiErrorType::EmptyFile
+            };
+
+            Err::<(Bytes,), _>(warp::reject::custom(error))
+        })
+}
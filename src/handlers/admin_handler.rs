@@ -0,0 +1,164 @@
+use std::sync::Arc;
+
+use sqlx::{Pool, Postgres};
+use uuid::Uuid;
+use warp::{hyper::StatusCode, Filter};
+
+use crate::{
+    filters::{
+        body_validation_filter, list_query_filter, require_permission, required_uuid_param_filter,
+    },
+    models::User,
+    models_validators::user_validator::UpdateUserRoleData,
+    policies::{ensure_can_change_role, Permission},
+    services::{
+        hess_media_service::attach_hess_media,
+        hesses_service::{get_hesses, HESSES_LIST_SPEC},
+        user_role_changes_service::{change_user_role, get_user_role_changes},
+        users_service::{get_user_by_id, get_users, USERS_LIST_SPEC},
+    },
+    utils::{
+        jwt::JwtConfig,
+        list_query::ListQuery,
+        response::{ApiErrorType, ApiResource},
+    },
+};
+
+async fn get_users_handler(
+    list_query: ListQuery,
+    _: User,
+    pool: Pool<Postgres>,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    let users = get_users(pool, list_query)
+        .await
+        .map_err(warp::reject::custom)?;
+
+    Ok(warp::reply::json(&users))
+}
+
+async fn get_hesses_handler(
+    list_query: ListQuery,
+    _: User,
+    pool: Pool<Postgres>,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    let mut hesses = get_hesses(pool.clone(), list_query)
+        .await
+        .map_err(warp::reject::custom)?;
+
+    attach_hess_media(pool, hesses.items.iter_mut())
+        .await
+        .map_err(warp::reject::custom)?;
+
+    Ok(warp::reply::json(&hesses))
+}
+
+async fn change_user_role_handler(
+    id: Uuid,
+    user: User,
+    pool: Pool<Postgres>,
+    data: UpdateUserRoleData,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    let target = get_user_by_id(pool.clone(), id)
+        .await
+        .map_err(warp::reject::custom)?;
+
+    if target.deleted_at.is_some() {
+        return Err(warp::reject::custom(ApiErrorType::ResourceNotFound(
+            ApiResource::Users,
+        )));
+    }
+
+    ensure_can_change_role(&user, &target, &data.role).map_err(warp::reject::custom)?;
+
+    change_user_role(pool, id, data.role, user.id)
+        .await
+        .map_err(warp::reject::custom)?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+async fn get_user_role_changes_handler(
+    id: Uuid,
+    _: User,
+    pool: Pool<Postgres>,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    let changes = get_user_role_changes(pool, id)
+        .await
+        .map_err(warp::reject::custom)?;
+
+    Ok(warp::reply::json(&changes))
+}
+
+/// Creates the admin tooling routes.
+///
+/// - **GET** `/admin/users?filter=&sort=&page=&size=&cursor=`: List the users.
+/// - **GET** `/admin/hesses?filter=&sort=&page=&size=&cursor=`: List the hesses.
+/// - **PUT** `/admin/users/:id/role`: Change the role of a user (roots only, see `ensure_can_change_role`).
+/// - **GET** `/admin/users/:id/role-changes`: Get the role changes of a user, latest first.
+///
+/// The routes are restricted to admins, the lists include the soft-deleted rows and are
+/// paginated and filtered as described by `ListQuery`:
+///
+/// - Users can be filtered by `role`, `activated`, `verified`, `deleted` and `createdAt`,
+///   and sorted by `createdAt` (the default, newest first), `username` and `name`.
+/// - Hesses can be filtered by `userId`, `createdAt`, `hasMedia` and `deleted`,
+///   and sorted by `createdAt` (the default, newest first).
+///
+pub fn admin_routes(
+    jwt_config: Arc<JwtConfig>,
+    pool: Pool<Postgres>,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Clone {
+    let admin_user = require_permission(
+        jwt_config.clone(),
+        pool.clone(),
+        Permission::AccessAdminTools,
+    );
+    let root_user = require_permission(jwt_config, pool.clone(), Permission::ChangeUserRoles);
+    let include_pool = warp::any().map(move || pool.clone());
+
+    let admin = warp::path("admin");
+    let user = admin
+        .and(warp::path("users"))
+        .and(required_uuid_param_filter(ApiResource::Users));
+
+    let get_users = admin
+        .and(warp::path("users"))
+        .and(warp::path::end())
+        .and(warp::get())
+        .and(list_query_filter(&USERS_LIST_SPEC))
+        .and(admin_user.clone())
+        .and(include_pool.clone())
+        .and_then(get_users_handler);
+
+    let get_hesses = admin
+        .and(warp::path("hesses"))
+        .and(warp::path::end())
+        .and(warp::get())
+        .and(list_query_filter(&HESSES_LIST_SPEC))
+        .and(admin_user.clone())
+        .and(include_pool.clone())
+        .and_then(get_hesses_handler);
+
+    let change_user_role = user
+        .clone()
+        .and(warp::path("role"))
+        .and(warp::path::end())
+        .and(warp::put())
+        .and(root_user)
+        .and(include_pool.clone())
+        .and(body_validation_filter::<UpdateUserRoleData>())
+        .and_then(change_user_role_handler);
+
+    let get_user_role_changes = user
+        .and(warp::path("role-changes"))
+        .and(warp::path::end())
+        .and(warp::get())
+        .and(admin_user)
+        .and(include_pool)
+        .and_then(get_user_role_changes_handler);
+
+    get_users
+        .or(get_hesses)
+        .or(change_user_role)
+        .or(get_user_role_changes)
+}
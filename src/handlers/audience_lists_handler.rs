@@ -0,0 +1,273 @@
+use std::sync::Arc;
+
+use sqlx::{Pool, Postgres};
+use uuid::Uuid;
+use warp::{hyper::StatusCode, Filter};
+
+use crate::{
+    filters::{
+        authentication_filter, body_validation_filter, list_query_filter,
+        required_uuid_param_filter,
+    },
+    models::{AudienceList, User},
+    models_validators::audience_list_validator::InsertOrUpdateAudienceListData,
+    services::audience_lists_service::{
+        delete_audience_list, delete_audience_list_member, get_audience_list_by_id,
+        get_audience_list_members, get_audience_lists_by_owner_id, insert_audience_list,
+        insert_audience_list_member, update_audience_list, AUDIENCE_LISTS_LIST_SPEC,
+        AUDIENCE_LIST_MEMBERS_LIST_SPEC,
+    },
+    utils::{
+        jwt::JwtConfig,
+        list_query::ListQuery,
+        response::{ApiErrorType, ApiResource},
+    },
+};
+
+/// Retrieves an audience list, making sure it's owned by the given user.
+///
+/// Audience lists are private to their owners, so a list owned by someone else is
+/// reported as not found instead of unauthorized to avoid leaking its existence.
+///
+async fn get_owned_audience_list(
+    pool: Pool<Postgres>,
+    id: Uuid,
+    user: &User,
+) -> Result<AudienceList, ApiErrorType> {
+    let audience_list = get_audience_list_by_id(pool, id).await?;
+
+    if audience_list.owner_id == user.id {
+        Ok(audience_list)
+    } else {
+        Err(ApiErrorType::ResourceNotFound(ApiResource::AudienceLists))
+    }
+}
+
+async fn get_my_audience_lists_handler(
+    list_query: ListQuery,
+    user: User,
+    pool: Pool<Postgres>,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    let audience_lists = get_audience_lists_by_owner_id(pool, user.id, list_query)
+        .await
+        .map_err(warp::reject::custom)?;
+
+    Ok(warp::reply::json(&audience_lists))
+}
+
+async fn create_audience_list_handler(
+    user: User,
+    pool: Pool<Postgres>,
+    data: InsertOrUpdateAudienceListData,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    let audience_list = insert_audience_list(pool, user.id, data)
+        .await
+        .map_err(warp::reject::custom)?;
+
+    Ok(warp::reply::with_status(
+        warp::reply::json(&audience_list),
+        StatusCode::CREATED,
+    ))
+}
+
+async fn get_audience_list_handler(
+    id: Uuid,
+    user: User,
+    pool: Pool<Postgres>,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    let audience_list = get_owned_audience_list(pool, id, &user)
+        .await
+        .map_err(warp::reject::custom)?;
+
+    Ok(warp::reply::json(&audience_list))
+}
+
+async fn update_audience_list_handler(
+    id: Uuid,
+    user: User,
+    pool: Pool<Postgres>,
+    data: InsertOrUpdateAudienceListData,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    get_owned_audience_list(pool.clone(), id, &user)
+        .await
+        .map_err(warp::reject::custom)?;
+
+    let audience_list = update_audience_list(pool, id, data)
+        .await
+        .map_err(warp::reject::custom)?;
+
+    Ok(warp::reply::json(&audience_list))
+}
+
+async fn delete_audience_list_handler(
+    id: Uuid,
+    user: User,
+    pool: Pool<Postgres>,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    get_owned_audience_list(pool.clone(), id, &user)
+        .await
+        .map_err(warp::reject::custom)?;
+
+    delete_audience_list(pool, id)
+        .await
+        .map_err(warp::reject::custom)?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+async fn get_audience_list_members_handler(
+    id: Uuid,
+    list_query: ListQuery,
+    user: User,
+    pool: Pool<Postgres>,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    get_owned_audience_list(pool.clone(), id, &user)
+        .await
+        .map_err(warp::reject::custom)?;
+
+    let members = get_audience_list_members(pool, id, list_query)
+        .await
+        .map_err(warp::reject::custom)?;
+
+    Ok(warp::reply::json(&members))
+}
+
+async fn add_audience_list_member_handler(
+    id: Uuid,
+    member_id: Uuid,
+    user: User,
+    pool: Pool<Postgres>,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    get_owned_audience_list(pool.clone(), id, &user)
+        .await
+        .map_err(warp::reject::custom)?;
+
+    let member = insert_audience_list_member(pool, id, member_id)
+        .await
+        .map_err(warp::reject::custom)?;
+
+    Ok(warp::reply::with_status(
+        warp::reply::json(&member),
+        StatusCode::CREATED,
+    ))
+}
+
+async fn remove_audience_list_member_handler(
+    id: Uuid,
+    member_id: Uuid,
+    user: User,
+    pool: Pool<Postgres>,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    get_owned_audience_list(pool.clone(), id, &user)
+        .await
+        .map_err(warp::reject::custom)?;
+
+    delete_audience_list_member(pool, id, member_id)
+        .await
+        .map_err(warp::reject::custom)?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Creates the audience lists (e.g. close friends) routes.
+///
+/// - **GET** `/lists?sort=&page=&size=&cursor=`: Get the lists of the logged in user.
+/// - **POST** `/lists`: Create a new list.
+/// - **GET** `/lists/:id`: Get a list by ID.
+/// - **PUT** `/lists/:id`: Rename a list.
+/// - **DELETE** `/lists/:id`: Delete a list.
+/// - **GET** `/lists/:id/members?sort=&page=&size=&cursor=`: Get the members of a list.
+/// - **PUT** `/lists/:id/members/:userId`: Add a user to a list.
+/// - **DELETE** `/lists/:id/members/:userId`: Remove a user from a list.
+///
+/// Every route is restricted to the owner of the list. The lists are paginated (see `ListQuery`),
+/// they can be sorted by `name`, `createdAt` and `updatedAt`, the members by `createdAt`.
+///
+pub fn audience_lists_routes(
+    jwt_config: Arc<JwtConfig>,
+    pool: Pool<Postgres>,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Clone {
+    let authenticated = authentication_filter(jwt_config, pool.clone());
+    let include_pool = warp::any().map(move || pool.clone());
+
+    let lists = warp::path("lists");
+    let list = lists.and(required_uuid_param_filter(ApiResource::AudienceLists));
+    let member = list
+        .clone()
+        .and(warp::path("members"))
+        .and(required_uuid_param_filter(ApiResource::Users));
+
+    let get_my_audience_lists = lists
+        .and(warp::path::end())
+        .and(warp::get())
+        .and(list_query_filter(&AUDIENCE_LISTS_LIST_SPEC))
+        .and(authenticated.clone())
+        .and(include_pool.clone())
+        .and_then(get_my_audience_lists_handler);
+
+    let create_audience_list = lists
+        .and(warp::path::end())
+        .and(warp::post())
+        .and(authenticated.clone())
+        .and(include_pool.clone())
+        .and(body_validation_filter::<InsertOrUpdateAudienceListData>())
+        .and_then(create_audience_list_handler);
+
+    let get_audience_list = list
+        .clone()
+        .and(warp::path::end())
+        .and(warp::get())
+        .and(authenticated.clone())
+        .and(include_pool.clone())
+        .and_then(get_audience_list_handler);
+
+    let update_audience_list = list
+        .clone()
+        .and(warp::path::end())
+        .and(warp::put())
+        .and(authenticated.clone())
+        .and(include_pool.clone())
+        .and(body_validation_filter::<InsertOrUpdateAudienceListData>())
+        .and_then(update_audience_list_handler);
+
+    let delete_audience_list = list
+        .clone()
+        .and(warp::path::end())
+        .and(warp::delete())
+        .and(authenticated.clone())
+        .and(include_pool.clone())
+        .and_then(delete_audience_list_handler);
+
+    let get_audience_list_members = list
+        .and(warp::path("members"))
+        .and(warp::path::end())
+        .and(warp::get())
+        .and(list_query_filter(&AUDIENCE_LIST_MEMBERS_LIST_SPEC))
+        .and(authenticated.clone())
+        .and(include_pool.clone())
+        .and_then(get_audience_list_members_handler);
+
+    let add_audience_list_member = member
+        .clone()
+        .and(warp::path::end())
+        .and(warp::put())
+        .and(authenticated.clone())
+        .and(include_pool.clone())
+        .and_then(add_audience_list_member_handler);
+
+    let remove_audience_list_member = member
+        .and(warp::path::end())
+        .and(warp::delete())
+        .and(authenticated)
+        .and(include_pool)
+        .and_then(remove_audience_list_member_handler);
+
+    get_my_audience_lists
+        .or(create_audience_list)
+        .or(get_audience_list)
+        .or(update_audience_list)
+        .or(delete_audience_list)
+        .or(get_audience_list_members)
+        .or(add_audience_list_member)
+        .or(remove_audience_list_member)
+}
@@ -0,0 +1,73 @@
+use std::{collections::HashMap, sync::Arc};
+
+use chrono::Utc;
+use warp::{path::Tail, Filter};
+
+use crate::{
+    filters::download_headers_filter,
+    media::{download_reply, CacheControl, ContentDisposition, Download, DownloadHeaders},
+    storage::{BlobStore, BlobUrlSigner},
+    utils::response::ApiErrorType,
+};
+
+async fn get_signed_blob_handler(
+    tail: Tail,
+    query: HashMap<String, String>,
+    headers: DownloadHeaders,
+    blob_store: Arc<dyn BlobStore>,
+    url_signer: Arc<BlobUrlSigner>,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    let key = tail.as_str();
+
+    let (content_type, expires, signature) = match (
+        query.get("contentType"),
+        query
+            .get("expires")
+            .and_then(|expires| expires.parse::<i64>().ok()),
+        query.get("signature"),
+    ) {
+        (Some(content_type), Some(expires), Some(signature)) => (content_type, expires, signature),
+        _ => return Err(warp::reject::custom(ApiErrorType::InvalidSignedUrl)),
+    };
+
+    if !url_signer.verify(key, content_type, expires, signature) {
+        return Err(warp::reject::custom(ApiErrorType::InvalidSignedUrl));
+    }
+
+    let download = Download {
+        key,
+        content_type,
+        file_name: key.rsplit('/').next().unwrap_or(key).to_string(),
+        disposition: ContentDisposition::from_query(&query).map_err(warp::reject::custom)?,
+        // Browsers can keep it as long as the URL is valid
+        cache_control: CacheControl::PrivateFor((expires - Utc::now().timestamp()).max(0) as u64),
+    };
+
+    download_reply(blob_store, download, &headers)
+        .await
+        .map_err(warp::reject::custom)
+}
+
+/// Creates the signed blobs routes.
+///
+/// - **GET** `/blobs/:key?contentType=&expires=&signature=&download=`: Download a blob through a signed URL.
+///
+/// The signed URLs are handed out by the endpoints of private media when the local storage
+/// backend is used (see `BlobUrlSigner`), they don't require to be logged in but expire.
+///
+pub fn blobs_routes(
+    blob_store: Arc<dyn BlobStore>,
+    url_signer: Arc<BlobUrlSigner>,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Clone {
+    let include_blob_store = warp::any().map(move || blob_store.clone());
+    let include_url_signer = warp::any().map(move || url_signer.clone());
+
+    warp::path("blobs")
+        .and(warp::path::tail())
+        .and(warp::get())
+        .and(warp::query::<HashMap<String, String>>())
+        .and(download_headers_filter())
+        .and(include_blob_store)
+        .and(include_url_signer)
+        .and_then(get_signed_blob_handler)
+}
@@ -0,0 +1,474 @@
+use std::{collections::HashMap, num::NonZeroU64, sync::Arc};
+
+use serde::de::DeserializeOwned;
+use sqlx::{Pool, Postgres};
+use uuid::Uuid;
+use warp::{hyper::StatusCode, Filter};
+
+use crate::{
+    filters::{authentication_filter, body_validation_filter, required_uuid_param_filter},
+    models::{Conversation, ConversationParticipantStatus, User},
+    models_validators::{
+        conversation_validator::{InsertConversationData, CONVERSATION_MAX_PARTICIPANTS},
+        message_validator::InsertMessageData,
+    },
+    policies::{authorize, PolicyAction},
+    services::{
+        conversations_service::{
+            answer_message_request, get_conversation_by_id, get_conversations, insert_conversation,
+            leave_conversation, mark_conversation_as_read,
+        },
+        messages_service::{
+            count_viewable_hess_media, delete_message, get_messages, insert_message,
+        },
+        users_service::get_user_by_id,
+        viewer_relationships_service::get_viewer_relationship,
+    },
+    utils::{
+        cursor::decode_cursor,
+        jwt::JwtConfig,
+        response::{ApiErrorType, ApiResource, ValidationError},
+        validator::FieldLength,
+    },
+};
+
+const DEFAULT_CONVERSATIONS_PAGE_SIZE: u64 = 20;
+const DEFAULT_MESSAGES_PAGE_SIZE: u64 = 50;
+const MAX_PAGE_SIZE: u64 = 100;
+
+/// Parses the `cursor` and `size` query fields of the conversations and of the messages.
+fn parse_page_query<T: DeserializeOwned>(
+    query: &HashMap<String, String>,
+    default_size: u64,
+) -> Result<(Option<T>, i64), ApiErrorType> {
+    let cursor = match query.get("cursor") {
+        Some(cursor) => Some(
+            decode_cursor::<T>(cursor).ok_or(ApiErrorType::InvalidPaginationCursorQueryField)?,
+        ),
+        None => None,
+    };
+
+    let size = match query.get("size") {
+        Some(size) => size
+            .parse::<NonZeroU64>()
+            .map_err(|err| ApiErrorType::InvalidPaginationSizeQueryField(*err.kind()))?
+            .get()
+            .min(MAX_PAGE_SIZE),
+        None => default_size,
+    };
+
+    Ok((cursor, size as i64))
+}
+
+/// Makes sure a user can be added to a conversation started by the logged in user: they must
+/// be an active user whose `whoCanMessageMe` preference lets the logged in user message them
+/// (which blocks rule out).
+async fn ensure_can_message(
+    pool: Pool<Postgres>,
+    user: &User,
+    participant_id: Uuid,
+) -> Result<(), ApiErrorType> {
+    let participant = get_user_by_id(pool.clone(), participant_id).await?;
+
+    if !participant.activated || participant.deleted_at.is_some() {
+        return Err(ApiErrorType::ResourceNotFound(ApiResource::Users));
+    }
+
+    authorize(pool, user.id, PolicyAction::Message, participant.id, None).await
+}
+
+/// Makes sure the logged in user can send a message in a conversation. Blocks are enforced in
+/// 1:1 conversations; in groups, the messages of blocked users are hidden instead.
+async fn ensure_can_send_message(
+    pool: Pool<Postgres>,
+    user: &User,
+    conversation: &Conversation,
+) -> Result<(), ApiErrorType> {
+    if conversation.is_group {
+        return Ok(());
+    }
+
+    for participant in &conversation.participants {
+        if participant.user_id == user.id {
+            continue;
+        }
+
+        let relationship =
+            get_viewer_relationship(pool.clone(), user.id, participant.user_id, None).await?;
+
+        if relationship.is_blocked {
+            return Err(ApiErrorType::Unauthorized);
+        }
+    }
+
+    Ok(())
+}
+
+async fn create_conversation_handler(
+    user: User,
+    pool: Pool<Postgres>,
+    data: InsertConversationData,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    let participant_ids = data
+        .participant_ids
+        .into_iter()
+        .filter(|id| *id != user.id)
+        .collect::<Vec<_>>();
+
+    if participant_ids.is_empty() {
+        return Err(warp::reject::custom(ApiErrorType::BodyValidationErrors(
+            vec![ValidationError::InvalidFieldContentLength {
+                field_name: "participantIds".to_string(),
+                passed_length: 0,
+                expected_length: FieldLength {
+                    min: Some(1),
+                    max: Some(CONVERSATION_MAX_PARTICIPANTS - 1),
+                },
+            }],
+        )));
+    }
+
+    for participant_id in &participant_ids {
+        ensure_can_message(pool.clone(), &user, *participant_id)
+            .await
+            .map_err(warp::reject::custom)?;
+    }
+
+    let id = insert_conversation(pool.clone(), user.id, &participant_ids, data.title)
+        .await
+        .map_err(warp::reject::custom)?;
+
+    let conversation = get_conversation_by_id(pool, user.id, id)
+        .await
+        .map_err(warp::reject::custom)?;
+
+    Ok(warp::reply::with_status(
+        warp::reply::json(&conversation),
+        StatusCode::CREATED,
+    ))
+}
+
+async fn get_conversations_handler(
+    query: HashMap<String, String>,
+    user: User,
+    pool: Pool<Postgres>,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    let (cursor, size) =
+        parse_page_query(&query, DEFAULT_CONVERSATIONS_PAGE_SIZE).map_err(warp::reject::custom)?;
+
+    let conversations = get_conversations(
+        pool,
+        user.id,
+        ConversationParticipantStatus::Accepted,
+        cursor,
+        size,
+    )
+    .await
+    .map_err(warp::reject::custom)?;
+
+    Ok(warp::reply::json(&conversations))
+}
+
+async fn get_message_requests_handler(
+    query: HashMap<String, String>,
+    user: User,
+    pool: Pool<Postgres>,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    let (cursor, size) =
+        parse_page_query(&query, DEFAULT_CONVERSATIONS_PAGE_SIZE).map_err(warp::reject::custom)?;
+
+    let conversations = get_conversations(
+        pool,
+        user.id,
+        ConversationParticipantStatus::Pending,
+        cursor,
+        size,
+    )
+    .await
+    .map_err(warp::reject::custom)?;
+
+    Ok(warp::reply::json(&conversations))
+}
+
+async fn get_conversation_handler(
+    id: Uuid,
+    user: User,
+    pool: Pool<Postgres>,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    let conversation = get_conversation_by_id(pool, user.id, id)
+        .await
+        .map_err(warp::reject::custom)?;
+
+    Ok(warp::reply::json(&conversation))
+}
+
+async fn accept_conversation_handler(
+    id: Uuid,
+    user: User,
+    pool: Pool<Postgres>,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    answer_message_request(pool, id, user.id, ConversationParticipantStatus::Accepted)
+        .await
+        .map_err(warp::reject::custom)?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+async fn decline_conversation_handler(
+    id: Uuid,
+    user: User,
+    pool: Pool<Postgres>,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    answer_message_request(pool, id, user.id, ConversationParticipantStatus::Declined)
+        .await
+        .map_err(warp::reject::custom)?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+async fn leave_conversation_handler(
+    id: Uuid,
+    user: User,
+    pool: Pool<Postgres>,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    let conversation = get_conversation_by_id(pool.clone(), user.id, id)
+        .await
+        .map_err(warp::reject::custom)?;
+
+    if !conversation.is_group {
+        return Err(warp::reject::custom(ApiErrorType::NotAGroupConversation));
+    }
+
+    leave_conversation(pool, id, user.id)
+        .await
+        .map_err(warp::reject::custom)?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+async fn mark_conversation_as_read_handler(
+    id: Uuid,
+    user: User,
+    pool: Pool<Postgres>,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    get_conversation_by_id(pool.clone(), user.id, id)
+        .await
+        .map_err(warp::reject::custom)?;
+
+    mark_conversation_as_read(pool, id, user.id)
+        .await
+        .map_err(warp::reject::custom)?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+async fn get_messages_handler(
+    id: Uuid,
+    query: HashMap<String, String>,
+    user: User,
+    pool: Pool<Postgres>,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    let (cursor, size) =
+        parse_page_query(&query, DEFAULT_MESSAGES_PAGE_SIZE).map_err(warp::reject::custom)?;
+
+    get_conversation_by_id(pool.clone(), user.id, id)
+        .await
+        .map_err(warp::reject::custom)?;
+
+    let messages = get_messages(pool, user.id, id, cursor, size)
+        .await
+        .map_err(warp::reject::custom)?;
+
+    Ok(warp::reply::json(&messages))
+}
+
+async fn send_message_handler(
+    id: Uuid,
+    user: User,
+    pool: Pool<Postgres>,
+    data: InsertMessageData,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    let conversation = get_conversation_by_id(pool.clone(), user.id, id)
+        .await
+        .map_err(warp::reject::custom)?;
+
+    ensure_can_send_message(pool.clone(), &user, &conversation)
+        .await
+        .map_err(warp::reject::custom)?;
+
+    if !data.media_ids.is_empty() {
+        let viewable_media_count =
+            count_viewable_hess_media(pool.clone(), user.id, &data.media_ids)
+                .await
+                .map_err(warp::reject::custom)?;
+
+        if viewable_media_count != data.media_ids.len() as i64 {
+            return Err(warp::reject::custom(ApiErrorType::ResourceNotFound(
+                ApiResource::HessMedia,
+            )));
+        }
+    }
+
+    let message = insert_message(pool, id, user.id, data)
+        .await
+        .map_err(warp::reject::custom)?;
+
+    Ok(warp::reply::with_status(
+        warp::reply::json(&message),
+        StatusCode::CREATED,
+    ))
+}
+
+async fn delete_message_handler(
+    id: Uuid,
+    message_id: Uuid,
+    user: User,
+    pool: Pool<Postgres>,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    get_conversation_by_id(pool.clone(), user.id, id)
+        .await
+        .map_err(warp::reject::custom)?;
+
+    delete_message(pool, id, message_id, user.id)
+        .await
+        .map_err(warp::reject::custom)?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Creates the direct messages routes.
+///
+/// - **GET** `/conversations?cursor=&size=`: Get the logged in user's conversations, latest active first.
+/// - **POST** `/conversations`: Start a conversation (or get the existing 1:1 one).
+/// - **GET** `/me/message-requests?cursor=&size=`: Get the logged in user's message requests.
+/// - **GET** `/conversations/:id`: Get a conversation.
+/// - **POST** `/conversations/:id/accept`: Accept a message request.
+/// - **POST** `/conversations/:id/decline`: Decline a message request.
+/// - **POST** `/conversations/:id/leave`: Leave a group.
+/// - **POST** `/conversations/:id/read`: Mark the messages of a conversation as read.
+/// - **GET** `/conversations/:id/messages?cursor=&size=`: Get the messages of a conversation, latest first.
+/// - **POST** `/conversations/:id/messages`: Send a message.
+/// - **DELETE** `/conversations/:id/messages/:messageId`: Delete a sent message.
+///
+/// `size` defaults to 20 conversations or 50 messages and is capped at 100, `cursor` is the
+/// `nextCursor` of the previous page.
+///
+pub fn conversations_routes(
+    jwt_config: Arc<JwtConfig>,
+    pool: Pool<Postgres>,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Clone {
+    let authenticated = authentication_filter(jwt_config, pool.clone());
+    let include_pool = warp::any().map(move || pool.clone());
+
+    let conversations = warp::path("conversations");
+    let conversation = conversations.and(required_uuid_param_filter(ApiResource::Conversations));
+
+    let get_conversations = conversations
+        .and(warp::path::end())
+        .and(warp::get())
+        .and(warp::query::<HashMap<String, String>>())
+        .and(authenticated.clone())
+        .and(include_pool.clone())
+        .and_then(get_conversations_handler);
+
+    let create_conversation = conversations
+        .and(warp::path::end())
+        .and(warp::post())
+        .and(authenticated.clone())
+        .and(include_pool.clone())
+        .and(body_validation_filter::<InsertConversationData>())
+        .and_then(create_conversation_handler);
+
+    let get_message_requests = warp::path("me")
+        .and(warp::path("message-requests"))
+        .and(warp::path::end())
+        .and(warp::get())
+        .and(warp::query::<HashMap<String, String>>())
+        .and(authenticated.clone())
+        .and(include_pool.clone())
+        .and_then(get_message_requests_handler);
+
+    let get_conversation = conversation
+        .clone()
+        .and(warp::path::end())
+        .and(warp::get())
+        .and(authenticated.clone())
+        .and(include_pool.clone())
+        .and_then(get_conversation_handler);
+
+    let accept_conversation = conversation
+        .clone()
+        .and(warp::path("accept"))
+        .and(warp::path::end())
+        .and(warp::post())
+        .and(authenticated.clone())
+        .and(include_pool.clone())
+        .and_then(accept_conversation_handler);
+
+    let decline_conversation = conversation
+        .clone()
+        .and(warp::path("decline"))
+        .and(warp::path::end())
+        .and(warp::post())
+        .and(authenticated.clone())
+        .and(include_pool.clone())
+        .and_then(decline_conversation_handler);
+
+    let leave_conversation = conversation
+        .clone()
+        .and(warp::path("leave"))
+        .and(warp::path::end())
+        .and(warp::post())
+        .and(authenticated.clone())
+        .and(include_pool.clone())
+        .and_then(leave_conversation_handler);
+
+    let mark_conversation_as_read = conversation
+        .clone()
+        .and(warp::path("read"))
+        .and(warp::path::end())
+        .and(warp::post())
+        .and(authenticated.clone())
+        .and(include_pool.clone())
+        .and_then(mark_conversation_as_read_handler);
+
+    let messages = conversation.and(warp::path("messages"));
+
+    let get_messages = messages
+        .clone()
+        .and(warp::path::end())
+        .and(warp::get())
+        .and(warp::query::<HashMap<String, String>>())
+        .and(authenticated.clone())
+        .and(include_pool.clone())
+        .and_then(get_messages_handler);
+
+    let send_message = messages
+        .clone()
+        .and(warp::path::end())
+        .and(warp::post())
+        .and(authenticated.clone())
+        .and(include_pool.clone())
+        .and(body_validation_filter::<InsertMessageData>())
+        .and_then(send_message_handler);
+
+    let delete_message = messages
+        .and(required_uuid_param_filter(ApiResource::Messages))
+        .and(warp::path::end())
+        .and(warp::delete())
+        .and(authenticated)
+        .and(include_pool)
+        .and_then(delete_message_handler);
+
+    get_conversations
+        .or(create_conversation)
+        .or(get_message_requests)
+        .or(get_conversation)
+        .or(accept_conversation)
+        .or(decline_conversation)
+        .or(leave_conversation)
+        .or(mark_conversation_as_read)
+        .or(get_messages)
+        .or(send_message)
+        .or(delete_message)
+}
@@ -0,0 +1,69 @@
+use std::sync::Arc;
+
+use sqlx::{Pool, Postgres};
+use warp::{hyper::StatusCode, Filter};
+
+use crate::{
+    filters::{authentication_filter, body_validation_filter},
+    models::User,
+    models_validators::device_token_validator::UpsertDeviceTokenData,
+    services::device_tokens_service::{delete_device_token, upsert_device_token},
+    utils::jwt::JwtConfig,
+};
+
+async fn register_device_handler(
+    user: User,
+    pool: Pool<Postgres>,
+    data: UpsertDeviceTokenData,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    let device_token = upsert_device_token(pool, user.id, data)
+        .await
+        .map_err(warp::reject::custom)?;
+
+    Ok(warp::reply::json(&device_token))
+}
+
+async fn unregister_device_handler(
+    device_id: String,
+    user: User,
+    pool: Pool<Postgres>,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    delete_device_token(pool, user.id, &device_id)
+        .await
+        .map_err(warp::reject::custom)?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Creates the devices routes, registering where the push notifications are sent.
+///
+/// - **POST** `/me/devices`: Register (or refresh) the FCM token of a device.
+/// - **DELETE** `/me/devices/:deviceId`: Unregister a device (e.g. when signing out).
+///
+pub fn devices_routes(
+    jwt_config: Arc<JwtConfig>,
+    pool: Pool<Postgres>,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Clone {
+    let authenticated = authentication_filter(jwt_config, pool.clone());
+    let include_pool = warp::any().map(move || pool.clone());
+
+    let devices = warp::path("me").and(warp::path("devices"));
+
+    let register_device = devices
+        .and(warp::path::end())
+        .and(warp::post())
+        .and(authenticated.clone())
+        .and(include_pool.clone())
+        .and(body_validation_filter::<UpsertDeviceTokenData>())
+        .and_then(register_device_handler);
+
+    let unregister_device = devices
+        .and(warp::path::param::<String>())
+        .and(warp::path::end())
+        .and(warp::delete())
+        .and(authenticated)
+        .and(include_pool)
+        .and_then(unregister_device_handler);
+
+    register_device.or(unregister_device)
+}
@@ -0,0 +1,108 @@
+use std::collections::HashMap;
+
+use sqlx::{Pool, Postgres};
+use uuid::Uuid;
+use warp::{hyper::StatusCode, reply::Response, Filter, Reply};
+
+use crate::{
+    services::digests_service::unsubscribe_from_digests,
+    utils::response::{ApiErrorType, ApiResource},
+};
+
+/// The page behind the unsubscribe link, `{token}` is replaced by the (parsed) token.
+///
+/// Following the link doesn't unsubscribe by itself, as link scanners and prefetchers would.
+const UNSUBSCRIBE_PAGE: &str = r#"<!DOCTYPE html>
+<html lang="en">
+<head><meta charset="UTF-8"><title>HessHub</title></head>
+<body style="font-family: 'Lato', sans-serif; text-align: center; padding: 40px; color: #666666">
+  <h1 style="color: #b98c3f">Unsubscribe from the digests?</h1>
+  <p>You won't get the HessHub digests anymore, you can turn them back on in your notification preferences.</p>
+  <form method="post" action="?token={token}">
+    <button type="submit" style="background: #b98c3f; color: #ffffff; border: none; padding: 12px 24px; font-size: 16px">Unsubscribe</button>
+  </form>
+</body>
+</html>"#;
+
+const UNSUBSCRIBED_PAGE: &str = r#"<!DOCTYPE html>
+<html lang="en">
+<head><meta charset="UTF-8"><title>HessHub</title></head>
+<body style="font-family: 'Lato', sans-serif; text-align: center; padding: 40px; color: #666666">
+  <h1 style="color: #b98c3f">You're unsubscribed</h1>
+  <p>You won't get the HessHub digests anymore, you can turn them back on in your notification preferences.</p>
+</body>
+</html>"#;
+
+/// Parses the `token` query field, malformed tokens are reported like unknown ones.
+fn parse_token(query: &HashMap<String, String>) -> Result<Uuid, ApiErrorType> {
+    query
+        .get("token")
+        .and_then(|token| Uuid::parse_str(token).ok())
+        .ok_or(ApiErrorType::ResourceNotFound(
+            ApiResource::UserNotificationPreferences,
+        ))
+}
+
+async fn unsubscribe_page_handler(
+    query: HashMap<String, String>,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    let token = parse_token(&query).map_err(warp::reject::custom)?;
+
+    Ok(warp::reply::html(
+        UNSUBSCRIBE_PAGE.replace("{token}", &token.to_string()),
+    ))
+}
+
+/// Turns the digests off for the owner of the token, unknown tokens aren't told apart.
+///
+/// Email clients (RFC 8058) send `List-Unsubscribe=One-Click` and get no content, the
+/// confirmation form gets a page.
+async fn unsubscribe_handler(
+    pool: Pool<Postgres>,
+    query: HashMap<String, String>,
+    form: HashMap<String, String>,
+) -> Result<Response, warp::Rejection> {
+    let token = parse_token(&query).map_err(warp::reject::custom)?;
+
+    unsubscribe_from_digests(pool, token)
+        .await
+        .map_err(warp::reject::custom)?;
+
+    if form.get("List-Unsubscribe").map(String::as_str) == Some("One-Click") {
+        Ok(StatusCode::NO_CONTENT.into_response())
+    } else {
+        Ok(warp::reply::html(UNSUBSCRIBED_PAGE).into_response())
+    }
+}
+
+/// Creates the digests routes, behind the unsubscribe links of the digest emails.
+///
+/// - **GET** `/digests/unsubscribe?token=`: The page behind the link in the email, asking to confirm unsubscribing.
+/// - **POST** `/digests/unsubscribe?token=`: Unsubscribe from the digests, from that page or in one click, as sent by email clients (RFC 8058).
+///
+/// They don't require to be logged in, the token is only known to the recipient of the digests.
+///
+pub fn digests_routes(
+    pool: Pool<Postgres>,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Clone {
+    let include_pool = warp::any().map(move || pool.clone());
+
+    let unsubscribe = warp::path("digests")
+        .and(warp::path("unsubscribe"))
+        .and(warp::path::end());
+
+    let unsubscribe_page = unsubscribe
+        .and(warp::get())
+        .and(warp::query::<HashMap<String, String>>())
+        .and_then(unsubscribe_page_handler);
+
+    let unsubscribe = unsubscribe
+        .and(warp::post())
+        .and(include_pool)
+        .and(warp::query::<HashMap<String, String>>())
+        .and(warp::body::content_length_limit(1024))
+        .and(warp::body::form::<HashMap<String, String>>())
+        .and_then(unsubscribe_handler);
+
+    unsubscribe_page.or(unsubscribe)
+}
@@ -0,0 +1,106 @@
+use std::{collections::HashMap, num::NonZeroU64, sync::Arc};
+
+use serde::de::DeserializeOwned;
+use sqlx::{Pool, Postgres};
+use warp::Filter;
+
+use crate::{
+    filters::authentication_filter,
+    models::User,
+    rankers::FeedRanker,
+    services::{
+        feed_service::{get_home_feed, get_ranked_feed, FeedCursor, RankedFeedCursor},
+        hess_media_service::attach_hess_media,
+    },
+    utils::{cursor::decode_cursor, jwt::JwtConfig, response::ApiErrorType, FeedConfig},
+};
+
+const DEFAULT_FEED_PAGE_SIZE: u64 = 20;
+const MAX_FEED_PAGE_SIZE: u64 = 100;
+
+/// Parses the `cursor` and `size` query fields of the feed.
+fn parse_feed_query<C: DeserializeOwned>(
+    query: &HashMap<String, String>,
+) -> Result<(Option<C>, i64), ApiErrorType> {
+    let cursor = match query.get("cursor") {
+        Some(cursor) => Some(
+            decode_cursor::<C>(cursor).ok_or(ApiErrorType::InvalidPaginationCursorQueryField)?,
+        ),
+        None => None,
+    };
+
+    let size = match query.get("size") {
+        Some(size) => size
+            .parse::<NonZeroU64>()
+            .map_err(|err| ApiErrorType::InvalidPaginationSizeQueryField(*err.kind()))?
+            .get()
+            .min(MAX_FEED_PAGE_SIZE),
+        None => DEFAULT_FEED_PAGE_SIZE,
+    };
+
+    Ok((cursor, size as i64))
+}
+
+async fn get_feed_handler(
+    query: HashMap<String, String>,
+    user: User,
+    pool: Pool<Postgres>,
+    feed_config: Arc<FeedConfig>,
+    feed_ranker: Arc<dyn FeedRanker>,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    let mut feed = match query.get("mode").map(String::as_str) {
+        None | Some("chronological") => {
+            let (cursor, size) =
+                parse_feed_query::<FeedCursor>(&query).map_err(warp::reject::custom)?;
+
+            get_home_feed(pool.clone(), &feed_config, user.id, cursor, size).await
+        }
+        Some("ranked") => {
+            let (cursor, size) =
+                parse_feed_query::<RankedFeedCursor>(&query).map_err(warp::reject::custom)?;
+
+            get_ranked_feed(pool.clone(), feed_ranker.as_ref(), user.id, cursor, size).await
+        }
+        Some(mode) => Err(ApiErrorType::InvalidFeedModeQueryField(mode.to_string())),
+    }
+    .map_err(warp::reject::custom)?;
+
+    attach_hess_media(
+        pool,
+        feed.items.iter_mut().filter_map(|item| item.hess.as_mut()),
+    )
+    .await
+    .map_err(warp::reject::custom)?;
+
+    Ok(warp::reply::json(&feed))
+}
+
+/// Creates the feed routes.
+///
+/// - **GET** `/feed?mode=&cursor=&size=`: Get the logged in user's home feed.
+///
+/// `mode` is either `chronological` (the default, newest first) or `ranked` (see `FeedRanker`).
+/// `size` defaults to 20 and is capped at 100, `cursor` is the `nextCursor` of the previous page
+/// (of the same mode).
+///
+pub fn feed_routes(
+    jwt_config: Arc<JwtConfig>,
+    pool: Pool<Postgres>,
+    feed_config: Arc<FeedConfig>,
+    feed_ranker: Arc<dyn FeedRanker>,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Clone {
+    let authenticated = authentication_filter(jwt_config, pool.clone());
+    let include_pool = warp::any().map(move || pool.clone());
+    let include_feed_config = warp::any().map(move || feed_config.clone());
+    let include_feed_ranker = warp::any().map(move || feed_ranker.clone());
+
+    warp::path("feed")
+        .and(warp::path::end())
+        .and(warp::get())
+        .and(warp::query::<HashMap<String, String>>())
+        .and(authenticated)
+        .and(include_pool)
+        .and(include_feed_config)
+        .and(include_feed_ranker)
+        .and_then(get_feed_handler)
+}
@@ -0,0 +1,666 @@
+use std::{
+    collections::{HashMap, HashSet},
+    sync::Arc,
+};
+
+use chrono::{Duration, Utc};
+use futures_util::TryStreamExt;
+use sqlx::{Pool, Postgres};
+use uuid::Uuid;
+use warp::{
+    http::{header::CONTENT_TYPE, Response},
+    hyper::{body::Bytes, StatusCode},
+    multipart::FormData,
+    Filter,
+};
+
+use crate::{
+    filters::{
+        authentication_filter, body_validation_filter, download_headers_filter,
+        multipart_form_filter, raw_body_filter, required_uuid_param_filter,
+    },
+    media::{
+        download_reply, parse_language_tag, parse_webvtt, sniff_media, spool_multipart_part,
+        CacheControl, ContentDisposition, Download, DownloadHeaders, SniffedMedia,
+        CAPTIONS_CONTENT_TYPE, HESS_MEDIA_THUMBNAIL_SIZES, MULTIPART_OVERHEAD_BYTES,
+    },
+    models::{Hess, HessMedia, MediaProcessingStatus, MediaType, SignedUrl, User},
+    models_validators::{
+        enums::media_type_validator::validate_media_type_enum_field,
+        hess_media_validator::UpdateHessMediaData,
+    },
+    policies::{authorize, has_permission, Permission, PolicyAction},
+    services::{
+        blobs_service::release_blobs,
+        hess_media_service::{
+            count_hess_media_by_type, delete_hess_media, delete_hess_media_captions,
+            get_hess_media_by_hess_id, get_hess_media_by_id, get_hess_media_captions,
+            insert_hess_media, update_hess_media, upsert_hess_media_captions, InsertHessMediaData,
+        },
+        hesses_service::get_hess_by_id,
+    },
+    storage::BlobStore,
+    utils::{
+        jwt::JwtConfig,
+        response::{ApiErrorType, ApiResource, ValidationError},
+        MediaConfig, StorageConfig,
+    },
+};
+
+/// The configurations and the blob store the hess media handlers share.
+#[derive(Clone)]
+struct HessMediaContext {
+    media_config: Arc<MediaConfig>,
+    storage_config: Arc<StorageConfig>,
+    blob_store: Arc<dyn BlobStore>,
+}
+
+/// Makes sure the logged in user can see the media of a hess, which is the case of the
+/// admins and of whoever the visibility policy lets see the hess.
+async fn ensure_can_view_hess(
+    pool: Pool<Postgres>,
+    user: &User,
+    hess: &Hess,
+) -> Result<(), ApiErrorType> {
+    if has_permission(&user.role, Permission::ModerateContent) {
+        return Ok(());
+    }
+
+    authorize(pool, user.id, PolicyAction::View, hess.user_id, Some(hess)).await
+}
+
+/// Makes sure the logged in user is the author of a (non deleted) hess, who alone can attach
+/// media to it and edit them.
+async fn get_own_hess(pool: Pool<Postgres>, user: &User, id: Uuid) -> Result<Hess, ApiErrorType> {
+    let hess = get_hess_by_id(pool, id).await?;
+
+    if hess.user_id != user.id {
+        return Err(ApiErrorType::Unauthorized);
+    }
+
+    Ok(hess)
+}
+
+/// Makes sure the logged in user can remove the media of a (non deleted) hess, which is the
+/// case of the author and of the admins.
+async fn ensure_can_remove_hess_media(
+    pool: Pool<Postgres>,
+    user: &User,
+    id: Uuid,
+) -> Result<(), ApiErrorType> {
+    let hess = get_hess_by_id(pool, id).await?;
+
+    if hess.user_id != user.id && !has_permission(&user.role, Permission::ModerateContent) {
+        return Err(ApiErrorType::Unauthorized);
+    }
+
+    Ok(())
+}
+
+/// Retrieves a (non deleted) hess media, making sure it's attached to the given hess.
+async fn get_attached_hess_media(
+    pool: Pool<Postgres>,
+    hess_id: Uuid,
+    id: Uuid,
+) -> Result<HessMedia, ApiErrorType> {
+    let hess_media = get_hess_media_by_id(pool, id).await?;
+
+    if hess_media.hess_id != hess_id {
+        return Err(ApiErrorType::ResourceNotFound(ApiResource::HessMedia));
+    }
+
+    Ok(hess_media)
+}
+
+/// Whether the logged in user can see the media of a hess which aren't processed (or failed to
+/// be), which is the case of the author and of the admins.
+fn can_view_unprocessed_media(user: &User, hess: &Hess) -> bool {
+    hess.user_id == user.id || has_permission(&user.role, Permission::ModerateContent)
+}
+
+/// Retrieves a (non deleted) hess media the logged in user can see, along with its hess,
+/// making sure it's attached to the given hess.
+///
+/// Media which aren't processed are reported as not found to other viewers than the author
+/// (and the admins).
+///
+async fn get_visible_hess_media(
+    pool: Pool<Postgres>,
+    user: &User,
+    hess_id: Uuid,
+    id: Uuid,
+) -> Result<(Hess, HessMedia), ApiErrorType> {
+    let hess = get_hess_by_id(pool.clone(), hess_id).await?;
+
+    ensure_can_view_hess(pool.clone(), user, &hess).await?;
+
+    let hess_media = get_hess_media_by_id(pool, id).await?;
+
+    if hess_media.hess_id != hess_id
+        || (hess_media.processing_status != MediaProcessingStatus::Ready
+            && !can_view_unprocessed_media(user, &hess))
+    {
+        return Err(ApiErrorType::ResourceNotFound(ApiResource::HessMedia));
+    }
+
+    Ok((hess, hess_media))
+}
+
+/// Parses the `size` query field, one of the thumbnail sizes (the original when it's missing).
+fn parse_size_query(query: &HashMap<String, String>) -> Result<Option<u32>, ApiErrorType> {
+    let size = match query.get("size") {
+        Some(size) => size,
+        None => return Ok(None),
+    };
+
+    match size.parse::<u32>() {
+        Ok(size) if HESS_MEDIA_THUMBNAIL_SIZES.contains(&size) => Ok(Some(size)),
+        _ => Err(ApiErrorType::QueryValidationErrors(vec![
+            ValidationError::IncorrectEnumValue {
+                field_name: "size".to_string(),
+                passed_value: size.to_string(),
+                expected_values: HESS_MEDIA_THUMBNAIL_SIZES
+                    .iter()
+                    .map(|size| size.to_string())
+                    .collect(),
+            },
+        ])),
+    }
+}
+
+/// The key and the content type of the original or of a thumbnail of a hess media.
+///
+/// Media without such thumbnail (which aren't photos, or photos smaller than the size)
+/// fall back to the original.
+///
+fn hess_media_blob(hess_media: &HessMedia, size: Option<u32>) -> (&str, &str) {
+    let thumbnail = size.and_then(|size| hess_media.thumbnail_urls.get(&size));
+
+    match (thumbnail, &hess_media.thumbnail_content_type) {
+        (Some(key), Some(content_type)) => (key, content_type),
+        _ => (&hess_media.media_url, &hess_media.content_type),
+    }
+}
+
+/// Streams the `media` parts of a multipart form into the blob store, one at a time, through a
+/// temporary file (see `spool_multipart_part`) so they're never held in memory.
+///
+/// Every file is sniffed from its first bytes, and refused as soon as it's bigger than the
+/// maximum size of its media type or when the hess would have too many media of its type.
+/// The media stored before a failure are pushed to `stored_media` all the same, for the caller
+/// to release them.
+///
+async fn stream_hess_media(
+    mut form: FormData,
+    mut counts: HashMap<MediaType, i64>,
+    media_config: &MediaConfig,
+    blob_store: &dyn BlobStore,
+    stored_media: &mut Vec<InsertHessMediaData>,
+) -> Result<(), ApiErrorType> {
+    while let Some(part) = form
+        .try_next()
+        .await
+        .map_err(|_| ApiErrorType::InvalidMultipartBody)?
+    {
+        if part.name() != "media" {
+            continue;
+        }
+
+        let mut sniffed_media = None;
+
+        let file = spool_multipart_part(part, |content| {
+            let media = sniff_media(content).ok_or(ApiErrorType::UnsupportedMediaType)?;
+
+            let count = counts.entry(media.media_type.clone()).or_insert(0);
+            *count += 1;
+
+            if *count > media_config.hess_max_media(&media.media_type) {
+                return Err(ApiErrorType::TooManyMedia(media.media_type));
+            }
+
+            let max_size = media_config.hess_media_max_size_bytes(&media.media_type);
+            sniffed_media = Some(media);
+
+            Ok(max_size)
+        })
+        .await?;
+
+        let SniffedMedia {
+            media_type,
+            content_type,
+        } = sniffed_media.ok_or(ApiErrorType::InternalServerError)?;
+
+        blob_store
+            .put_file(&file.key, &file.path, content_type)
+            .await?;
+
+        stored_media.push(InsertHessMediaData {
+            id: Uuid::new_v4(),
+            media_type,
+            media_url: file.key.clone(),
+            content_type: content_type.to_string(),
+            size_bytes: file.size as i64,
+        });
+    }
+
+    if stored_media.is_empty() {
+        return Err(ApiErrorType::NoMedia);
+    }
+
+    Ok(())
+}
+
+/// The (deduplicated) keys of the blobs of stored media, to release them when they couldn't be
+/// attached to their hess.
+fn hess_media_keys(media: &[InsertHessMediaData]) -> Vec<String> {
+    media
+        .iter()
+        .map(|data| data.media_url.clone())
+        .collect::<HashSet<_>>()
+        .into_iter()
+        .collect()
+}
+
+async fn upload_hess_media_handler(
+    id: Uuid,
+    user: User,
+    pool: Pool<Postgres>,
+    context: HessMediaContext,
+    form: FormData,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    get_own_hess(pool.clone(), &user, id)
+        .await
+        .map_err(warp::reject::custom)?;
+
+    let counts = count_hess_media_by_type(&pool, id)
+        .await
+        .map_err(warp::reject::custom)?;
+
+    let mut stored_media = Vec::new();
+
+    if let Err(err) = stream_hess_media(
+        form,
+        counts,
+        &context.media_config,
+        context.blob_store.as_ref(),
+        &mut stored_media,
+    )
+    .await
+    {
+        let _ = release_blobs(pool, &hess_media_keys(&stored_media)).await;
+
+        return Err(warp::reject::custom(err));
+    }
+
+    let stored_keys = hess_media_keys(&stored_media);
+
+    match insert_hess_media(pool.clone(), id, stored_media, &context.media_config).await {
+        Ok(hess_media) => Ok(warp::reply::with_status(
+            warp::reply::json(&hess_media),
+            StatusCode::CREATED,
+        )),
+        Err(err) => {
+            let _ = release_blobs(pool, &stored_keys).await;
+
+            Err(warp::reject::custom(err))
+        }
+    }
+}
+
+async fn get_hess_media_list_handler(
+    id: Uuid,
+    query: HashMap<String, String>,
+    user: User,
+    pool: Pool<Postgres>,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    let mut errors = Vec::new();
+    let media_type_value = query.get("type").cloned().map(serde_json::Value::String);
+    let media_type =
+        validate_media_type_enum_field(&media_type_value.as_ref(), "type", &mut errors, true);
+
+    if !errors.is_empty() {
+        return Err(warp::reject::custom(ApiErrorType::QueryValidationErrors(
+            errors,
+        )));
+    }
+
+    let hess = get_hess_by_id(pool.clone(), id)
+        .await
+        .map_err(warp::reject::custom)?;
+
+    ensure_can_view_hess(pool.clone(), &user, &hess)
+        .await
+        .map_err(warp::reject::custom)?;
+
+    let ready_only = !can_view_unprocessed_media(&user, &hess);
+
+    let hess_media = get_hess_media_by_hess_id(pool, id, media_type, ready_only)
+        .await
+        .map_err(warp::reject::custom)?;
+
+    Ok(warp::reply::json(&hess_media))
+}
+
+async fn get_hess_media_handler(
+    id: Uuid,
+    media_id: Uuid,
+    query: HashMap<String, String>,
+    headers: DownloadHeaders,
+    user: User,
+    pool: Pool<Postgres>,
+    context: HessMediaContext,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    let size = parse_size_query(&query).map_err(warp::reject::custom)?;
+    let disposition = ContentDisposition::from_query(&query).map_err(warp::reject::custom)?;
+
+    let (_, hess_media) = get_visible_hess_media(pool, &user, id, media_id)
+        .await
+        .map_err(warp::reject::custom)?;
+
+    let (key, content_type) = hess_media_blob(&hess_media, size);
+    let file_name = match size {
+        Some(size) if key != hess_media.media_url => format!("{}-{}", hess_media.id, size),
+        _ => hess_media.id.to_string(),
+    };
+
+    let download = Download {
+        key,
+        content_type,
+        file_name,
+        disposition,
+        // Never reused without asking (a cheap `304` thanks to the `ETag`), so a hess made
+        // private, a block or a deletion applies to the copies cached along the way too
+        cache_control: CacheControl::Private,
+    };
+
+    download_reply(context.blob_store, download, &headers)
+        .await
+        .map_err(warp::reject::custom)
+}
+
+async fn get_hess_media_url_handler(
+    id: Uuid,
+    media_id: Uuid,
+    query: HashMap<String, String>,
+    user: User,
+    pool: Pool<Postgres>,
+    context: HessMediaContext,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    let size = parse_size_query(&query).map_err(warp::reject::custom)?;
+
+    let (_, hess_media) = get_visible_hess_media(pool, &user, id, media_id)
+        .await
+        .map_err(warp::reject::custom)?;
+
+    let (key, content_type) = hess_media_blob(&hess_media, size);
+    let expires_at =
+        Utc::now() + Duration::minutes(context.storage_config.signed_url_expire_in_minutes);
+
+    Ok(warp::reply::json(&SignedUrl {
+        url: context.blob_store.signed_url(key, content_type, expires_at),
+        expires_at,
+    }))
+}
+
+async fn delete_hess_media_handler(
+    id: Uuid,
+    media_id: Uuid,
+    user: User,
+    pool: Pool<Postgres>,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    ensure_can_remove_hess_media(pool.clone(), &user, id)
+        .await
+        .map_err(warp::reject::custom)?;
+
+    delete_hess_media(pool, id, media_id)
+        .await
+        .map_err(warp::reject::custom)?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+async fn update_hess_media_handler(
+    id: Uuid,
+    media_id: Uuid,
+    user: User,
+    pool: Pool<Postgres>,
+    data: UpdateHessMediaData,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    get_own_hess(pool.clone(), &user, id)
+        .await
+        .map_err(warp::reject::custom)?;
+
+    let hess_media = update_hess_media(pool, id, media_id, data)
+        .await
+        .map_err(warp::reject::custom)?;
+
+    Ok(warp::reply::json(&hess_media))
+}
+
+async fn get_hess_media_captions_handler(
+    id: Uuid,
+    media_id: Uuid,
+    language: String,
+    user: User,
+    pool: Pool<Postgres>,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    let language = parse_language_tag(&language).map_err(warp::reject::custom)?;
+
+    let (_, hess_media) = get_visible_hess_media(pool.clone(), &user, id, media_id)
+        .await
+        .map_err(warp::reject::custom)?;
+
+    let captions = get_hess_media_captions(pool, hess_media.id, &language)
+        .await
+        .map_err(warp::reject::custom)?;
+
+    Response::builder()
+        .header(CONTENT_TYPE, CAPTIONS_CONTENT_TYPE)
+        .body(captions)
+        .map_err(|_| warp::reject::custom(ApiErrorType::InternalServerError))
+}
+
+async fn upload_hess_media_captions_handler(
+    id: Uuid,
+    media_id: Uuid,
+    language: String,
+    user: User,
+    pool: Pool<Postgres>,
+    content: Bytes,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    let language = parse_language_tag(&language).map_err(warp::reject::custom)?;
+
+    get_own_hess(pool.clone(), &user, id)
+        .await
+        .map_err(warp::reject::custom)?;
+
+    let hess_media = get_attached_hess_media(pool.clone(), id, media_id)
+        .await
+        .map_err(warp::reject::custom)?;
+
+    if hess_media.media_type == MediaType::Photo {
+        return Err(warp::reject::custom(ApiErrorType::CaptionsNotSupported(
+            hess_media.media_type,
+        )));
+    }
+
+    let captions = parse_webvtt(&content).map_err(warp::reject::custom)?;
+
+    upsert_hess_media_captions(pool.clone(), hess_media.id, &language, captions)
+        .await
+        .map_err(warp::reject::custom)?;
+
+    let hess_media = get_hess_media_by_id(pool, hess_media.id)
+        .await
+        .map_err(warp::reject::custom)?;
+
+    Ok(warp::reply::json(&hess_media))
+}
+
+async fn delete_hess_media_captions_handler(
+    id: Uuid,
+    media_id: Uuid,
+    language: String,
+    user: User,
+    pool: Pool<Postgres>,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    let language = parse_language_tag(&language).map_err(warp::reject::custom)?;
+
+    ensure_can_remove_hess_media(pool.clone(), &user, id)
+        .await
+        .map_err(warp::reject::custom)?;
+
+    let hess_media = get_attached_hess_media(pool.clone(), id, media_id)
+        .await
+        .map_err(warp::reject::custom)?;
+
+    delete_hess_media_captions(pool, hess_media.id, &language)
+        .await
+        .map_err(warp::reject::custom)?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Creates the hess media routes.
+///
+/// - **POST** `/hesses/:id/media`: Attach media to a hess (the `media` parts of a multipart form).
+/// - **GET** `/hesses/:id/media?type=`: List the media attached to a hess.
+/// - **GET** `/hesses/:id/media/:mediaId?size=&download=`: Get a media attached to a hess, or one of its thumbnails.
+/// - **GET** `/hesses/:id/media/:mediaId/url?size=`: Get a signed URL of a media attached to a hess.
+/// - **PATCH** `/hesses/:id/media/:mediaId`: Edit the alternative text of a media attached to a hess.
+/// - **DELETE** `/hesses/:id/media/:mediaId`: Remove a media from a hess.
+/// - **GET** `/hesses/:id/media/:mediaId/captions/:language`: Get the WebVTT captions of a video or an audio.
+/// - **PUT** `/hesses/:id/media/:mediaId/captions/:language`: Add (or replace) the captions of a video or an audio (a `text/vtt` body).
+/// - **DELETE** `/hesses/:id/media/:mediaId/captions/:language`: Remove the captions of a video or an audio.
+///
+/// Uploads are streamed into the `BlobStore` one file at a time, through a temporary file. Every file is sniffed from its
+/// magic bytes into a `MediaType` (see `sniff_media`), and the size and the number of the media
+/// of each type are limited (see `MediaConfig`).
+///
+/// Uploaded media are processed in the background (see `MediaJobHandler`), until they're READY
+/// only the author and the admins can see them.
+///
+/// Media are served with byte ranges, ETags and caching headers (see `download_reply`), the
+/// media of public hesses being cacheable by anyone.
+///
+/// Only the author can attach media (and edit their alternative text and captions), the author
+/// and the admins can remove them, and whoever can see the hess can see its media.
+///
+pub fn hess_media_routes(
+    jwt_config: Arc<JwtConfig>,
+    pool: Pool<Postgres>,
+    media_config: Arc<MediaConfig>,
+    storage_config: Arc<StorageConfig>,
+    blob_store: Arc<dyn BlobStore>,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Clone {
+    let authenticated = authentication_filter(jwt_config, pool.clone());
+    let include_pool = warp::any().map(move || pool.clone());
+    let form_max_length = media_config.hess_media_form_max_size_bytes() + MULTIPART_OVERHEAD_BYTES;
+    let captions_max_length = media_config.hess_media_captions_max_size_bytes;
+    let context = HessMediaContext {
+        media_config,
+        storage_config,
+        blob_store,
+    };
+    let include_context = warp::any().map(move || context.clone());
+
+    let hess_media_list = warp::path("hesses")
+        .and(required_uuid_param_filter(ApiResource::Hesses))
+        .and(warp::path("media"));
+
+    let upload_hess_media = hess_media_list
+        .clone()
+        .and(warp::path::end())
+        .and(warp::post())
+        .and(authenticated.clone())
+        .and(include_pool.clone())
+        .and(include_context.clone())
+        .and(multipart_form_filter(form_max_length))
+        .and_then(upload_hess_media_handler);
+
+    let get_hess_media_list = hess_media_list
+        .clone()
+        .and(warp::path::end())
+        .and(warp::get())
+        .and(warp::query::<HashMap<String, String>>())
+        .and(authenticated.clone())
+        .and(include_pool.clone())
+        .and_then(get_hess_media_list_handler);
+
+    let hess_media = hess_media_list.and(required_uuid_param_filter(ApiResource::HessMedia));
+
+    let get_hess_media = hess_media
+        .clone()
+        .and(warp::path::end())
+        .and(warp::get())
+        .and(warp::query::<HashMap<String, String>>())
+        .and(download_headers_filter())
+        .and(authenticated.clone())
+        .and(include_pool.clone())
+        .and(include_context.clone())
+        .and_then(get_hess_media_handler);
+
+    let get_hess_media_url = hess_media
+        .clone()
+        .and(warp::path("url"))
+        .and(warp::path::end())
+        .and(warp::get())
+        .and(warp::query::<HashMap<String, String>>())
+        .and(authenticated.clone())
+        .and(include_pool.clone())
+        .and(include_context)
+        .and_then(get_hess_media_url_handler);
+
+    let update_hess_media = hess_media
+        .clone()
+        .and(warp::path::end())
+        .and(warp::patch())
+        .and(authenticated.clone())
+        .and(include_pool.clone())
+        .and(body_validation_filter::<UpdateHessMediaData>())
+        .and_then(update_hess_media_handler);
+
+    let delete_hess_media = hess_media
+        .clone()
+        .and(warp::path::end())
+        .and(warp::delete())
+        .and(authenticated.clone())
+        .and(include_pool.clone())
+        .and_then(delete_hess_media_handler);
+
+    let hess_media_captions = hess_media
+        .and(warp::path("captions"))
+        .and(warp::path::param::<String>())
+        .and(warp::path::end());
+
+    let get_hess_media_captions = hess_media_captions
+        .clone()
+        .and(warp::get())
+        .and(authenticated.clone())
+        .and(include_pool.clone())
+        .and_then(get_hess_media_captions_handler);
+
+    let upload_hess_media_captions = hess_media_captions
+        .clone()
+        .and(warp::put())
+        .and(authenticated.clone())
+        .and(include_pool.clone())
+        .and(raw_body_filter(captions_max_length))
+        .and_then(upload_hess_media_captions_handler);
+
+    let delete_hess_media_captions = hess_media_captions
+        .and(warp::delete())
+        .and(authenticated)
+        .and(include_pool)
+        .and_then(delete_hess_media_captions_handler);
+
+    upload_hess_media
+        .or(get_hess_media_list)
+        .or(get_hess_media)
+        .or(get_hess_media_url)
+        .or(update_hess_media)
+        .or(delete_hess_media)
+        .or(get_hess_media_captions)
+        .or(upload_hess_media_captions)
+        .or(delete_hess_media_captions)
+}
@@ -1,12 +1,40 @@
+mod admin_handler;
+mod audience_lists_handler;
 mod auth_handler;
+mod blobs_handler;
 mod blocked_users_handler;
+mod conversations_handler;
+mod devices_handler;
+mod digests_handler;
 mod feed_handler;
 mod follow_requests_handler;
 mod following_handler;
 mod hess_media_handler;
 mod hesses_handler;
 mod likes_handler;
+mod muted_users_handler;
+mod notification_preferences_handler;
+mod notifications_handler;
 mod privacy_preferences_handler;
 mod search_handler;
+mod stream_handler;
 mod user_profile_images_handler;
 mod users_handler;
+mod verification_requests_handler;
+
+pub use admin_handler::admin_routes;
+pub use audience_lists_handler::audience_lists_routes;
+pub use blobs_handler::blobs_routes;
+pub use conversations_handler::conversations_routes;
+pub use devices_handler::devices_routes;
+pub use digests_handler::digests_routes;
+pub use feed_handler::feed_routes;
+pub use hess_media_handler::hess_media_routes;
+pub use muted_users_handler::muted_users_routes;
+pub use notification_preferences_handler::notification_preferences_routes;
+pub use notifications_handler::notifications_routes;
+pub use privacy_preferences_handler::privacy_preferences_routes;
+pub use search_handler::search_routes;
+pub use stream_handler::stream_routes;
+pub use user_profile_images_handler::user_profile_images_routes;
+pub use verification_requests_handler::verification_requests_routes;
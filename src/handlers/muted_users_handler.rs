@@ -0,0 +1,81 @@
+use std::sync::Arc;
+
+use sqlx::{Pool, Postgres};
+use uuid::Uuid;
+use warp::{hyper::StatusCode, Filter};
+
+use crate::{
+    filters::{authentication_filter, required_uuid_param_filter},
+    models::User,
+    services::muted_users_service::{delete_muted_user, insert_muted_user},
+    utils::{
+        jwt::JwtConfig,
+        response::{ApiErrorType, ApiResource},
+    },
+};
+
+async fn mute_user_handler(
+    muted_id: Uuid,
+    user: User,
+    pool: Pool<Postgres>,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    if muted_id == user.id {
+        return Err(warp::reject::custom(ApiErrorType::InvalidIdParam(
+            ApiResource::Users,
+        )));
+    }
+
+    let muted_user = insert_muted_user(pool, user.id, muted_id)
+        .await
+        .map_err(warp::reject::custom)?;
+
+    Ok(warp::reply::with_status(
+        warp::reply::json(&muted_user),
+        StatusCode::CREATED,
+    ))
+}
+
+async fn unmute_user_handler(
+    muted_id: Uuid,
+    user: User,
+    pool: Pool<Postgres>,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    delete_muted_user(pool, user.id, muted_id)
+        .await
+        .map_err(warp::reject::custom)?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Creates the muting routes.
+///
+/// - **POST** `/users/:id/mute`: Mute a user.
+/// - **DELETE** `/users/:id/mute`: Unmute a user.
+///
+pub fn muted_users_routes(
+    jwt_config: Arc<JwtConfig>,
+    pool: Pool<Postgres>,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Clone {
+    let authenticated = authentication_filter(jwt_config, pool.clone());
+    let include_pool = warp::any().map(move || pool.clone());
+
+    let mute = warp::path("users")
+        .and(required_uuid_param_filter(ApiResource::Users))
+        .and(warp::path("mute"))
+        .and(warp::path::end());
+
+    let mute_user = mute
+        .clone()
+        .and(warp::post())
+        .and(authenticated.clone())
+        .and(include_pool.clone())
+        .and_then(mute_user_handler);
+
+    let unmute_user = mute
+        .and(warp::delete())
+        .and(authenticated)
+        .and(include_pool)
+        .and_then(unmute_user_handler);
+
+    mute_user.or(unmute_user)
+}
@@ -0,0 +1,105 @@
+use std::sync::Arc;
+
+use sqlx::{Pool, Postgres};
+use uuid::Uuid;
+use warp::Filter;
+
+use crate::{
+    filters::{authentication_filter, body_validation_filter, required_uuid_param_filter},
+    models::User,
+    models_validators::user_notification_preferences_validator::UpdateUserNotificationPreferencesData,
+    policies::{has_permission, Permission},
+    services::user_notification_preferences_service::{
+        get_user_notification_preferences_by_user_id, is_known_timezone,
+        update_user_notification_preferences,
+    },
+    utils::{
+        jwt::JwtConfig,
+        response::{ApiErrorType, ApiResource},
+    },
+};
+
+/// Makes sure the logged in user is either the owner of the notification preferences or an admin.
+fn ensure_owner_or_admin(user: &User, user_id: Uuid) -> Result<(), ApiErrorType> {
+    if user.id == user_id || has_permission(&user.role, Permission::ManageUsers) {
+        Ok(())
+    } else {
+        Err(ApiErrorType::Unauthorized)
+    }
+}
+
+async fn get_notification_preferences_handler(
+    user_id: Uuid,
+    user: User,
+    pool: Pool<Postgres>,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    ensure_owner_or_admin(&user, user_id).map_err(warp::reject::custom)?;
+
+    let preferences = get_user_notification_preferences_by_user_id(pool, user_id)
+        .await
+        .map_err(warp::reject::custom)?;
+
+    Ok(warp::reply::json(&preferences))
+}
+
+async fn update_notification_preferences_handler(
+    user_id: Uuid,
+    user: User,
+    pool: Pool<Postgres>,
+    data: UpdateUserNotificationPreferencesData,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    ensure_owner_or_admin(&user, user_id).map_err(warp::reject::custom)?;
+
+    if let Some(timezone) = &data.timezone {
+        let is_known = is_known_timezone(pool.clone(), timezone)
+            .await
+            .map_err(warp::reject::custom)?;
+
+        data.validate_timezone(is_known)
+            .map_err(warp::reject::custom)?;
+    }
+
+    let preferences = update_user_notification_preferences(pool, user_id, data)
+        .await
+        .map_err(warp::reject::custom)?;
+
+    Ok(warp::reply::json(&preferences))
+}
+
+/// Creates the notification preferences routes.
+///
+/// - **GET** `/users/:id/notification-preferences`: Get user's notification preferences.
+/// - **PUT** `/users/:id/notification-preferences`: Update user's notification preferences.
+///
+/// Both routes are restricted to the owner of the preferences and admins (managers and roots).
+///
+pub fn notification_preferences_routes(
+    jwt_config: Arc<JwtConfig>,
+    pool: Pool<Postgres>,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Clone {
+    let authenticated = authentication_filter(jwt_config, pool.clone());
+    let include_pool = warp::any().map(move || pool.clone());
+
+    let notification_preferences = warp::path("users")
+        .and(required_uuid_param_filter(ApiResource::Users))
+        .and(warp::path("notification-preferences"))
+        .and(warp::path::end());
+
+    let get_notification_preferences = notification_preferences
+        .clone()
+        .and(warp::get())
+        .and(authenticated.clone())
+        .and(include_pool.clone())
+        .and_then(get_notification_preferences_handler);
+
+    let update_notification_preferences = notification_preferences
+        .and(warp::put())
+        .and(authenticated)
+        .and(include_pool)
+        .and(body_validation_filter::<
+            UpdateUserNotificationPreferencesData,
+        >())
+        .and_then(update_notification_preferences_handler);
+
+    get_notification_preferences.or(update_notification_preferences)
+}
@@ -0,0 +1,152 @@
+use std::{collections::HashMap, num::NonZeroU64, sync::Arc};
+
+use serde_json::json;
+use sqlx::{Pool, Postgres};
+use uuid::Uuid;
+use warp::{hyper::StatusCode, Filter};
+
+use crate::{
+    filters::{authentication_filter, required_uuid_param_filter},
+    models::User,
+    services::notifications_service::{
+        count_unread_notifications, get_notifications, mark_all_notifications_as_read,
+        mark_notification_as_read, NotificationCursor,
+    },
+    utils::{
+        cursor::decode_cursor,
+        jwt::JwtConfig,
+        response::{ApiErrorType, ApiResource},
+    },
+};
+
+const DEFAULT_NOTIFICATIONS_PAGE_SIZE: u64 = 20;
+const MAX_NOTIFICATIONS_PAGE_SIZE: u64 = 100;
+
+/// Parses the `cursor` and `size` query fields of the notifications.
+fn parse_notifications_query(
+    query: &HashMap<String, String>,
+) -> Result<(Option<NotificationCursor>, i64), ApiErrorType> {
+    let cursor = match query.get("cursor") {
+        Some(cursor) => Some(
+            decode_cursor::<NotificationCursor>(cursor)
+                .ok_or(ApiErrorType::InvalidPaginationCursorQueryField)?,
+        ),
+        None => None,
+    };
+
+    let size = match query.get("size") {
+        Some(size) => size
+            .parse::<NonZeroU64>()
+            .map_err(|err| ApiErrorType::InvalidPaginationSizeQueryField(*err.kind()))?
+            .get()
+            .min(MAX_NOTIFICATIONS_PAGE_SIZE),
+        None => DEFAULT_NOTIFICATIONS_PAGE_SIZE,
+    };
+
+    Ok((cursor, size as i64))
+}
+
+async fn get_notifications_handler(
+    query: HashMap<String, String>,
+    user: User,
+    pool: Pool<Postgres>,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    let (cursor, size) = parse_notifications_query(&query).map_err(warp::reject::custom)?;
+
+    let notifications = get_notifications(pool, user.id, cursor, size)
+        .await
+        .map_err(warp::reject::custom)?;
+
+    Ok(warp::reply::json(&notifications))
+}
+
+async fn get_unread_notifications_count_handler(
+    user: User,
+    pool: Pool<Postgres>,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    let count = count_unread_notifications(pool, user.id)
+        .await
+        .map_err(warp::reject::custom)?;
+
+    Ok(warp::reply::json(&json!({ "count": count })))
+}
+
+async fn mark_notification_as_read_handler(
+    id: Uuid,
+    user: User,
+    pool: Pool<Postgres>,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    mark_notification_as_read(pool, user.id, id)
+        .await
+        .map_err(warp::reject::custom)?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+async fn mark_all_notifications_as_read_handler(
+    user: User,
+    pool: Pool<Postgres>,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    mark_all_notifications_as_read(pool, user.id)
+        .await
+        .map_err(warp::reject::custom)?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Creates the notifications routes.
+///
+/// - **GET** `/me/notifications?cursor=&size=`: Get the logged in user's notifications, latest first.
+/// - **GET** `/me/notifications/unread-count`: Count the logged in user's unread notifications.
+/// - **POST** `/me/notifications/:id/read`: Mark a notification as read.
+/// - **POST** `/me/notifications/read`: Mark all the notifications as read.
+///
+/// `size` defaults to 20 and is capped at 100, `cursor` is the `nextCursor` of the previous page.
+///
+pub fn notifications_routes(
+    jwt_config: Arc<JwtConfig>,
+    pool: Pool<Postgres>,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Clone {
+    let authenticated = authentication_filter(jwt_config, pool.clone());
+    let include_pool = warp::any().map(move || pool.clone());
+
+    let notifications = warp::path("me").and(warp::path("notifications"));
+
+    let get_notifications = notifications
+        .and(warp::path::end())
+        .and(warp::get())
+        .and(warp::query::<HashMap<String, String>>())
+        .and(authenticated.clone())
+        .and(include_pool.clone())
+        .and_then(get_notifications_handler);
+
+    let get_unread_notifications_count = notifications
+        .and(warp::path("unread-count"))
+        .and(warp::path::end())
+        .and(warp::get())
+        .and(authenticated.clone())
+        .and(include_pool.clone())
+        .and_then(get_unread_notifications_count_handler);
+
+    let mark_all_notifications_as_read = notifications
+        .and(warp::path("read"))
+        .and(warp::path::end())
+        .and(warp::post())
+        .and(authenticated.clone())
+        .and(include_pool.clone())
+        .and_then(mark_all_notifications_as_read_handler);
+
+    let mark_notification_as_read = notifications
+        .and(required_uuid_param_filter(ApiResource::Notifications))
+        .and(warp::path("read"))
+        .and(warp::path::end())
+        .and(warp::post())
+        .and(authenticated)
+        .and(include_pool)
+        .and_then(mark_notification_as_read_handler);
+
+    get_notifications
+        .or(get_unread_notifications_count)
+        .or(mark_all_notifications_as_read)
+        .or(mark_notification_as_read)
+}
@@ -0,0 +1,114 @@
+use std::sync::Arc;
+
+use sqlx::{Pool, Postgres};
+use uuid::Uuid;
+use warp::Filter;
+
+use crate::{
+    filters::{authentication_filter, body_validation_filter, required_uuid_param_filter},
+    models::User,
+    models_validators::user_privacy_preferences_validator::UpdateUserPrivacyPreferencesData,
+    policies::{has_permission, Permission},
+    services::{
+        audience_lists_service::get_audience_list_ids_by_owner_id,
+        user_privacy_preferences_service::{
+            get_user_privacy_preferences_by_user_id, update_user_privacy_preferences,
+        },
+    },
+    utils::{
+        jwt::JwtConfig,
+        response::{ApiErrorType, ApiResource},
+        PrivacyConfig,
+    },
+};
+
+/// Makes sure the logged in user is either the owner of the privacy preferences or an admin.
+fn ensure_owner_or_admin(user: &User, user_id: Uuid) -> Result<(), ApiErrorType> {
+    if user.id == user_id || has_permission(&user.role, Permission::ManageUsers) {
+        Ok(())
+    } else {
+        Err(ApiErrorType::Unauthorized)
+    }
+}
+
+async fn get_privacy_preferences_handler(
+    user_id: Uuid,
+    user: User,
+    pool: Pool<Postgres>,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    ensure_owner_or_admin(&user, user_id).map_err(warp::reject::custom)?;
+
+    let preferences = get_user_privacy_preferences_by_user_id(pool, user_id)
+        .await
+        .map_err(warp::reject::custom)?;
+
+    Ok(warp::reply::json(&preferences))
+}
+
+async fn update_privacy_preferences_handler(
+    user_id: Uuid,
+    user: User,
+    pool: Pool<Postgres>,
+    privacy_config: Arc<PrivacyConfig>,
+    data: UpdateUserPrivacyPreferencesData,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    ensure_owner_or_admin(&user, user_id).map_err(warp::reject::custom)?;
+
+    // Audience lists referenced by the preferences must belong to the preferences owner
+    let owned_list_ids = get_audience_list_ids_by_owner_id(pool.clone(), user_id)
+        .await
+        .map_err(warp::reject::custom)?;
+
+    data.validate_audience_lists(&owned_list_ids)
+        .map_err(warp::reject::custom)?;
+
+    let preferences = update_user_privacy_preferences(
+        pool,
+        user_id,
+        data,
+        privacy_config.auto_approve_follow_requests_on_public_profile,
+    )
+    .await
+    .map_err(warp::reject::custom)?;
+
+    Ok(warp::reply::json(&preferences))
+}
+
+/// Creates the privacy preferences routes.
+///
+/// - **GET** `/users/:id/privacy`: Get user's privacy preferences.
+/// - **PUT** `/users/:id/privacy`: Update user's privacy preferences.
+///
+/// Both routes are restricted to the owner of the preferences and admins (managers and roots).
+///
+pub fn privacy_preferences_routes(
+    jwt_config: Arc<JwtConfig>,
+    pool: Pool<Postgres>,
+    privacy_config: Arc<PrivacyConfig>,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Clone {
+    let authenticated = authentication_filter(jwt_config, pool.clone());
+    let include_pool = warp::any().map(move || pool.clone());
+    let include_privacy_config = warp::any().map(move || privacy_config.clone());
+
+    let privacy = warp::path("users")
+        .and(required_uuid_param_filter(ApiResource::Users))
+        .and(warp::path("privacy"))
+        .and(warp::path::end());
+
+    let get_privacy_preferences = privacy
+        .clone()
+        .and(warp::get())
+        .and(authenticated.clone())
+        .and(include_pool.clone())
+        .and_then(get_privacy_preferences_handler);
+
+    let update_privacy_preferences = privacy
+        .and(warp::put())
+        .and(authenticated)
+        .and(include_pool)
+        .and(include_privacy_config)
+        .and(body_validation_filter::<UpdateUserPrivacyPreferencesData>())
+        .and_then(update_privacy_preferences_handler);
+
+    get_privacy_preferences.or(update_privacy_preferences)
+}
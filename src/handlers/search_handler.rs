@@ -1 +1,163 @@
+use std::{collections::HashMap, num::NonZeroU64, sync::Arc};
 
+use sqlx::{Pool, Postgres};
+use warp::Filter;
+
+use crate::{
+    filters::authentication_filter,
+    models::User,
+    search::{HessSearchQuery, SearchIndex, UserSearchQuery},
+    services::{hess_media_service::attach_hess_media, search_service::get_user_suggestions},
+    utils::{jwt::JwtConfig, response::ApiErrorType},
+};
+
+const DEFAULT_SEARCH_PAGE_SIZE: u64 = 20;
+const MAX_SEARCH_PAGE_SIZE: u64 = 100;
+const DEFAULT_TYPEAHEAD_SIZE: u64 = 10;
+const MAX_TYPEAHEAD_SIZE: u64 = 20;
+
+/// Parses the `page` and `size` query fields of a search.
+fn parse_search_pagination(query: &HashMap<String, String>) -> Result<(i64, i64), ApiErrorType> {
+    let page = match query.get("page") {
+        Some(page) => page
+            .parse::<NonZeroU64>()
+            .map_err(|err| ApiErrorType::InvalidPaginationPageQueryField(*err.kind()))?
+            .get(),
+        None => 1,
+    };
+
+    let size = match query.get("size") {
+        Some(size) => size
+            .parse::<NonZeroU64>()
+            .map_err(|err| ApiErrorType::InvalidPaginationSizeQueryField(*err.kind()))?
+            .get()
+            .min(MAX_SEARCH_PAGE_SIZE),
+        None => DEFAULT_SEARCH_PAGE_SIZE,
+    };
+
+    Ok((
+        page.min(i64::MAX as u64 / MAX_SEARCH_PAGE_SIZE) as i64,
+        size as i64,
+    ))
+}
+
+async fn search_hesses_handler(
+    query: HashMap<String, String>,
+    user: User,
+    pool: Pool<Postgres>,
+    search_index: Arc<dyn SearchIndex>,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    let search_query = HessSearchQuery::parse(query.get("q").map(String::as_str).unwrap_or(""))
+        .map_err(warp::reject::custom)?;
+
+    let (page, size) = parse_search_pagination(&query).map_err(warp::reject::custom)?;
+
+    let mut results = search_index
+        .search_hesses(pool.clone(), user.id, &search_query, page, size)
+        .await
+        .map_err(warp::reject::custom)?;
+
+    attach_hess_media(pool, results.iter_mut().map(|result| &mut result.hess))
+        .await
+        .map_err(warp::reject::custom)?;
+
+    Ok(warp::reply::json(&results))
+}
+
+async fn search_users_handler(
+    query: HashMap<String, String>,
+    user: User,
+    pool: Pool<Postgres>,
+    search_index: Arc<dyn SearchIndex>,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    let search_query = UserSearchQuery::parse(query.get("q").map(String::as_str).unwrap_or(""))
+        .map_err(warp::reject::custom)?;
+
+    let (page, size) = parse_search_pagination(&query).map_err(warp::reject::custom)?;
+
+    let results = search_index
+        .search_users(pool, user.id, &search_query, page, size)
+        .await
+        .map_err(warp::reject::custom)?;
+
+    Ok(warp::reply::json(&results))
+}
+
+async fn users_typeahead_handler(
+    query: HashMap<String, String>,
+    user: User,
+    pool: Pool<Postgres>,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    let search_query = UserSearchQuery::parse(query.get("q").map(String::as_str).unwrap_or(""))
+        .map_err(warp::reject::custom)?;
+
+    let size = match query.get("size") {
+        Some(size) => size
+            .parse::<NonZeroU64>()
+            .map_err(|err| {
+                warp::reject::custom(ApiErrorType::InvalidPaginationSizeQueryField(*err.kind()))
+            })?
+            .get()
+            .min(MAX_TYPEAHEAD_SIZE),
+        None => DEFAULT_TYPEAHEAD_SIZE,
+    };
+
+    let suggestions = get_user_suggestions(pool, user.id, &search_query, size as i64)
+        .await
+        .map_err(warp::reject::custom)?;
+
+    Ok(warp::reply::json(&suggestions))
+}
+
+/// Creates the search routes.
+///
+/// - **GET** `/search/hesses?q=&page=&size=`: Search for hesses (see `HessSearchQuery` for the syntax).
+/// - **GET** `/search/users?q=&page=&size=`: Search for users by username and name.
+/// - **GET** `/search/users/typeahead?q=&size=`: Suggest users to mention.
+///
+/// Only the hesses the logged in user could otherwise view are returned.
+/// Hesses and users are searched with the configured `SearchIndex`, the typeahead always uses Postgres.
+///
+pub fn search_routes(
+    jwt_config: Arc<JwtConfig>,
+    pool: Pool<Postgres>,
+    search_index: Arc<dyn SearchIndex>,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Clone {
+    let authenticated = authentication_filter(jwt_config, pool.clone());
+    let include_pool = warp::any().map(move || pool.clone());
+    let include_search_index = warp::any().map(move || search_index.clone());
+
+    let search = warp::path("search");
+
+    let search_hesses = search
+        .and(warp::path("hesses"))
+        .and(warp::path::end())
+        .and(warp::get())
+        .and(warp::query::<HashMap<String, String>>())
+        .and(authenticated.clone())
+        .and(include_pool.clone())
+        .and(include_search_index.clone())
+        .and_then(search_hesses_handler);
+
+    let search_users = search
+        .and(warp::path("users"))
+        .and(warp::path::end())
+        .and(warp::get())
+        .and(warp::query::<HashMap<String, String>>())
+        .and(authenticated.clone())
+        .and(include_pool.clone())
+        .and(include_search_index)
+        .and_then(search_users_handler);
+
+    let users_typeahead = search
+        .and(warp::path("users"))
+        .and(warp::path("typeahead"))
+        .and(warp::path::end())
+        .and(warp::get())
+        .and(warp::query::<HashMap<String, String>>())
+        .and(authenticated)
+        .and(include_pool)
+        .and_then(users_typeahead_handler);
+
+    search_hesses.or(search_users).or(users_typeahead)
+}
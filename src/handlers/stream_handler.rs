@@ -0,0 +1,179 @@
+use std::{collections::HashMap, convert::Infallible, sync::Arc, time::Duration};
+
+use futures_util::{SinkExt, StreamExt};
+use sqlx::{Pool, Postgres};
+use warp::{
+    filters::ws::{Message, WebSocket, Ws},
+    sse::Event,
+    Filter,
+};
+
+use crate::{
+    filters::header_or_query_authentication_filter,
+    models::{StreamEvent, User},
+    stream::{StreamHub, StreamSubscription},
+    utils::{jwt::JwtConfig, response::ApiErrorType, StreamConfig},
+};
+
+/// Parses the ID of the last event the client got, if resuming.
+fn parse_last_event_id(last_event_id: Option<&String>) -> Result<Option<i64>, ApiErrorType> {
+    match last_event_id {
+        Some(last_event_id) => match last_event_id.parse::<i64>() {
+            Ok(last_event_id) => Ok(Some(last_event_id)),
+            Err(_) => Err(ApiErrorType::InvalidLastEventIdQueryField(
+                last_event_id.to_string(),
+            )),
+        },
+        None => Ok(None),
+    }
+}
+
+fn to_sse_event(event: &StreamEvent) -> Event {
+    Event::default()
+        .id(event.id.to_string())
+        .event(&event.event_type)
+        .data(event.payload.to_string())
+}
+
+/// Sends the events of the subscription as JSON text messages, and pings the client when idle.
+async fn run_websocket(
+    socket: WebSocket,
+    mut subscription: StreamSubscription,
+    heartbeat_interval: Duration,
+) {
+    let (mut sender, mut receiver) = socket.split();
+
+    let mut heartbeat = tokio::time::interval_at(
+        tokio::time::Instant::now() + heartbeat_interval,
+        heartbeat_interval,
+    );
+
+    loop {
+        tokio::select! {
+            event = subscription.next() => {
+                let event = match event {
+                    Some(event) => event,
+                    None => break,
+                };
+
+                let message = match serde_json::to_string(&*event) {
+                    Ok(message) => message,
+                    Err(_) => continue,
+                };
+
+                if sender.send(Message::text(message)).await.is_err() {
+                    break;
+                }
+
+                heartbeat.reset();
+            }
+            message = receiver.next() => match message {
+                // Client messages (and pongs) are ignored
+                Some(Ok(message)) if !message.is_close() => {}
+                _ => break,
+            },
+            _ = heartbeat.tick() => {
+                if sender.send(Message::ping(Vec::new())).await.is_err() {
+                    break;
+                }
+            }
+        }
+    }
+
+    let _ = sender.close().await;
+}
+
+async fn websocket_handler(
+    ws: Ws,
+    query: HashMap<String, String>,
+    user: User,
+    pool: Pool<Postgres>,
+    hub: Arc<StreamHub>,
+    stream_config: Arc<StreamConfig>,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    let last_event_id =
+        parse_last_event_id(query.get("lastEventId")).map_err(warp::reject::custom)?;
+
+    let subscription = StreamSubscription::new(pool, &hub, user.id, last_event_id)
+        .await
+        .map_err(warp::reject::custom)?;
+
+    let heartbeat_interval = Duration::from_secs(stream_config.heartbeat_interval_seconds);
+
+    Ok(ws.on_upgrade(move |socket| run_websocket(socket, subscription, heartbeat_interval)))
+}
+
+async fn sse_handler(
+    last_event_id_header: Option<String>,
+    query: HashMap<String, String>,
+    user: User,
+    pool: Pool<Postgres>,
+    hub: Arc<StreamHub>,
+    stream_config: Arc<StreamConfig>,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    // Browsers send the header when reconnecting by themselves
+    let last_event_id = parse_last_event_id(
+        last_event_id_header
+            .as_ref()
+            .or_else(|| query.get("lastEventId")),
+    )
+    .map_err(warp::reject::custom)?;
+
+    let subscription = StreamSubscription::new(pool, &hub, user.id, last_event_id)
+        .await
+        .map_err(warp::reject::custom)?;
+
+    let events = futures_util::stream::unfold(subscription, |mut subscription| async move {
+        subscription
+            .next()
+            .await
+            .map(|event| (Ok::<_, Infallible>(to_sse_event(&event)), subscription))
+    });
+
+    let keep_alive = warp::sse::keep_alive().interval(Duration::from_secs(
+        stream_config.heartbeat_interval_seconds,
+    ));
+
+    Ok(warp::sse::reply(keep_alive.stream(events)))
+}
+
+/// Creates the real-time stream routes.
+///
+/// - **GET** `/stream?lastEventId=`: Stream the events of the logged in user, over a WebSocket
+///   when upgrading the connection, as server-sent events otherwise.
+///
+/// The JWT may be passed in the `accessToken` query field instead of the "Authorization" header.
+///
+pub fn stream_routes(
+    jwt_config: Arc<JwtConfig>,
+    pool: Pool<Postgres>,
+    hub: Arc<StreamHub>,
+    stream_config: Arc<StreamConfig>,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Clone {
+    let authenticated = header_or_query_authentication_filter(jwt_config, pool.clone());
+    let include_pool = warp::any().map(move || pool.clone());
+    let include_hub = warp::any().map(move || hub.clone());
+    let include_stream_config = warp::any().map(move || stream_config.clone());
+
+    let stream = warp::path("stream").and(warp::path::end()).and(warp::get());
+
+    let websocket = stream
+        .and(warp::ws())
+        .and(warp::query::<HashMap<String, String>>())
+        .and(authenticated.clone())
+        .and(include_pool.clone())
+        .and(include_hub.clone())
+        .and(include_stream_config.clone())
+        .and_then(websocket_handler);
+
+    let sse = stream
+        .and(warp::header::optional::<String>("Last-Event-ID"))
+        .and(warp::query::<HashMap<String, String>>())
+        .and(authenticated)
+        .and(include_pool)
+        .and(include_hub)
+        .and(include_stream_config)
+        .and_then(sse_handler);
+
+    websocket.or(sse)
+}
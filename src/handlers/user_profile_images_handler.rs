@@ -0,0 +1,502 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    sync::Arc,
+};
+
+use chrono::{Duration, Utc};
+
+use sqlx::{Pool, Postgres};
+use uuid::Uuid;
+use warp::{hyper::StatusCode, multipart::FormData, Filter};
+
+use crate::{
+    filters::{
+        authentication_filter, download_headers_filter, list_query_filter, multipart_form_filter,
+        required_uuid_param_filter,
+    },
+    media::{
+        download_reply, process_profile_image, read_multipart_file, CacheControl,
+        ContentDisposition, Download, DownloadHeaders, ProcessedProfileImage,
+        MULTIPART_OVERHEAD_BYTES, PROFILE_IMAGE_THUMBNAIL_SIZES,
+    },
+    models::{SignedUrl, User, UserProfileImage},
+    policies::{has_permission, Permission},
+    services::{
+        blobs_service::release_blobs,
+        user_profile_images_services::{
+            delete_user_profile_image, get_user_profile_image_by_id,
+            get_user_profile_images_by_user_id, insert_user_profile_image,
+            InsertUserProfileImageData, USER_PROFILE_IMAGES_LIST_SPEC,
+        },
+        users_service::get_user_by_id,
+    },
+    storage::BlobStore,
+    utils::{
+        jwt::JwtConfig,
+        list_query::ListQuery,
+        response::{ApiErrorType, ApiResource, ValidationError},
+        MediaConfig, StorageConfig,
+    },
+};
+
+/// Makes sure the logged in user can manage the profile images of the given user,
+/// which is the case of the user themself and of the admins.
+fn ensure_can_manage_profile_images(user: &User, user_id: Uuid) -> Result<(), ApiErrorType> {
+    if user.id == user_id || has_permission(&user.role, Permission::ManageUsers) {
+        Ok(())
+    } else {
+        Err(ApiErrorType::Unauthorized)
+    }
+}
+
+/// Retrieves a (non deleted) user.
+async fn get_existing_user(pool: Pool<Postgres>, id: Uuid) -> Result<User, ApiErrorType> {
+    let user = get_user_by_id(pool, id).await?;
+
+    match user.deleted_at {
+        Some(_) => Err(ApiErrorType::ResourceNotFound(ApiResource::Users)),
+        None => Ok(user),
+    }
+}
+
+/// Retrieves a (non deleted) profile image, making sure it was uploaded by the given user.
+async fn get_owned_profile_image(
+    pool: Pool<Postgres>,
+    user_id: Uuid,
+    id: Uuid,
+) -> Result<UserProfileImage, ApiErrorType> {
+    let user_profile_image = get_user_profile_image_by_id(pool, id).await?;
+
+    if user_profile_image.user_id == user_id {
+        Ok(user_profile_image)
+    } else {
+        Err(ApiErrorType::ResourceNotFound(
+            ApiResource::UserProfileImages,
+        ))
+    }
+}
+
+/// Parses the `size` query field, one of the thumbnail sizes (the original when it's missing).
+fn parse_size_query(query: &HashMap<String, String>) -> Result<Option<u32>, ApiErrorType> {
+    let size = match query.get("size") {
+        Some(size) => size,
+        None => return Ok(None),
+    };
+
+    match size.parse::<u32>() {
+        Ok(size) if PROFILE_IMAGE_THUMBNAIL_SIZES.contains(&size) => Ok(Some(size)),
+        _ => Err(ApiErrorType::QueryValidationErrors(vec![
+            ValidationError::IncorrectEnumValue {
+                field_name: "size".to_string(),
+                passed_value: size.to_string(),
+                expected_values: PROFILE_IMAGE_THUMBNAIL_SIZES
+                    .iter()
+                    .map(|size| size.to_string())
+                    .collect(),
+            },
+        ])),
+    }
+}
+
+/// Stores the original and the thumbnails of a processed profile image, releasing whatever
+/// was stored if any of them fails.
+///
+/// # Returns
+///
+/// Returns a `Result` containing the key of the original and the keys of the thumbnails by size.
+///
+async fn store_profile_image(
+    pool: Pool<Postgres>,
+    blob_store: &dyn BlobStore,
+    processed_image: ProcessedProfileImage,
+) -> Result<(String, BTreeMap<u32, String>), ApiErrorType> {
+    let content_type = processed_image.content_type;
+    let files = std::iter::once((None, processed_image.original)).chain(
+        processed_image
+            .thumbnails
+            .into_iter()
+            .map(|(size, content)| (Some(size), content)),
+    );
+
+    let mut original_key = None;
+    let mut thumbnail_keys = BTreeMap::new();
+
+    for (size, content) in files {
+        let key = match blob_store.put_content(content, content_type).await {
+            Ok(key) => key,
+            Err(err) => {
+                let stored_keys = original_key.into_iter().chain(thumbnail_keys.into_values());
+                let _ = release_blobs(pool, &stored_keys.collect::<Vec<_>>()).await;
+
+                return Err(err);
+            }
+        };
+
+        match size {
+            Some(size) => {
+                thumbnail_keys.insert(size, key);
+            }
+            None => original_key = Some(key),
+        }
+    }
+
+    Ok((original_key.unwrap_or_default(), thumbnail_keys))
+}
+
+/// The key of the original or of a thumbnail of a profile image.
+fn profile_image_key(
+    user_profile_image: &UserProfileImage,
+    size: Option<u32>,
+) -> Result<&str, ApiErrorType> {
+    match size {
+        Some(size) => user_profile_image
+            .thumbnail_urls
+            .get(&size)
+            .map(String::as_str)
+            .ok_or(ApiErrorType::ResourceNotFound(
+                ApiResource::UserProfileImages,
+            )),
+        None => Ok(&user_profile_image.image_url),
+    }
+}
+
+/// Replies with the original or a thumbnail of a profile image.
+async fn profile_image_reply(
+    blob_store: Arc<dyn BlobStore>,
+    user_profile_image: &UserProfileImage,
+    query: &HashMap<String, String>,
+    headers: &DownloadHeaders,
+    cache_control: CacheControl,
+) -> Result<impl warp::Reply, ApiErrorType> {
+    let size = parse_size_query(query)?;
+
+    let download = Download {
+        key: profile_image_key(user_profile_image, size)?,
+        content_type: &user_profile_image.content_type,
+        file_name: match size {
+            Some(size) => format!("{}-{}", user_profile_image.id, size),
+            None => user_profile_image.id.to_string(),
+        },
+        disposition: ContentDisposition::from_query(query)?,
+        cache_control,
+    };
+
+    download_reply(blob_store, download, headers).await
+}
+
+async fn upload_profile_image_handler(
+    id: Uuid,
+    user: User,
+    pool: Pool<Postgres>,
+    media_config: Arc<MediaConfig>,
+    blob_store: Arc<dyn BlobStore>,
+    form: FormData,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    ensure_can_manage_profile_images(&user, id).map_err(warp::reject::custom)?;
+
+    get_existing_user(pool.clone(), id)
+        .await
+        .map_err(warp::reject::custom)?;
+
+    let content = read_multipart_file(form, "image", media_config.profile_image_max_size_bytes)
+        .await
+        .map_err(warp::reject::custom)?;
+
+    let processed_image = tokio::task::spawn_blocking(move || process_profile_image(&content))
+        .await
+        .map_err(|_| warp::reject::custom(ApiErrorType::InternalServerError))?
+        .map_err(warp::reject::custom)?;
+
+    let content_type = processed_image.content_type.to_string();
+    let (width, height) = (processed_image.width, processed_image.height);
+
+    let (image_url, thumbnail_urls) =
+        store_profile_image(pool.clone(), blob_store.as_ref(), processed_image)
+            .await
+            .map_err(warp::reject::custom)?;
+
+    let stored_keys = std::iter::once(image_url.clone())
+        .chain(thumbnail_urls.values().cloned())
+        .collect::<Vec<_>>();
+
+    let data = InsertUserProfileImageData {
+        id: Uuid::new_v4(),
+        image_url,
+        thumbnail_urls,
+        content_type,
+        width: width as i32,
+        height: height as i32,
+    };
+
+    match insert_user_profile_image(pool.clone(), id, data).await {
+        Ok(user_profile_image) => Ok(warp::reply::json(&user_profile_image)),
+        Err(err) => {
+            let _ = release_blobs(pool, &stored_keys).await;
+
+            Err(warp::reject::custom(err))
+        }
+    }
+}
+
+async fn get_profile_image_handler(
+    id: Uuid,
+    query: HashMap<String, String>,
+    headers: DownloadHeaders,
+    _: User,
+    pool: Pool<Postgres>,
+    blob_store: Arc<dyn BlobStore>,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    let user = get_existing_user(pool.clone(), id)
+        .await
+        .map_err(warp::reject::custom)?;
+
+    let user_profile_image_id = user.user_profile_image_id.ok_or_else(|| {
+        warp::reject::custom(ApiErrorType::ResourceNotFound(
+            ApiResource::UserProfileImages,
+        ))
+    })?;
+
+    let user_profile_image = get_user_profile_image_by_id(pool, user_profile_image_id)
+        .await
+        .map_err(warp::reject::custom)?;
+
+    // The current profile image changes under the same URL
+    profile_image_reply(
+        blob_store,
+        &user_profile_image,
+        &query,
+        &headers,
+        CacheControl::PublicRevalidated,
+    )
+    .await
+    .map_err(warp::reject::custom)
+}
+
+async fn delete_profile_image_handler(
+    id: Uuid,
+    user: User,
+    pool: Pool<Postgres>,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    ensure_can_manage_profile_images(&user, id).map_err(warp::reject::custom)?;
+
+    let target_user = get_existing_user(pool.clone(), id)
+        .await
+        .map_err(warp::reject::custom)?;
+
+    let user_profile_image_id = target_user.user_profile_image_id.ok_or_else(|| {
+        warp::reject::custom(ApiErrorType::ResourceNotFound(
+            ApiResource::UserProfileImages,
+        ))
+    })?;
+
+    delete_user_profile_image(pool, id, user_profile_image_id)
+        .await
+        .map_err(warp::reject::custom)?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+async fn get_profile_images_history_handler(
+    id: Uuid,
+    list_query: ListQuery,
+    user: User,
+    pool: Pool<Postgres>,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    ensure_can_manage_profile_images(&user, id).map_err(warp::reject::custom)?;
+
+    get_existing_user(pool.clone(), id)
+        .await
+        .map_err(warp::reject::custom)?;
+
+    let user_profile_images = get_user_profile_images_by_user_id(pool, id, list_query)
+        .await
+        .map_err(warp::reject::custom)?;
+
+    Ok(warp::reply::json(&user_profile_images))
+}
+
+async fn get_history_profile_image_handler(
+    id: Uuid,
+    image_id: Uuid,
+    query: HashMap<String, String>,
+    headers: DownloadHeaders,
+    user: User,
+    pool: Pool<Postgres>,
+    blob_store: Arc<dyn BlobStore>,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    ensure_can_manage_profile_images(&user, id).map_err(warp::reject::custom)?;
+
+    let user_profile_image = get_owned_profile_image(pool, id, image_id)
+        .await
+        .map_err(warp::reject::custom)?;
+
+    profile_image_reply(
+        blob_store,
+        &user_profile_image,
+        &query,
+        &headers,
+        CacheControl::Private,
+    )
+    .await
+    .map_err(warp::reject::custom)
+}
+
+async fn get_history_profile_image_url_handler(
+    id: Uuid,
+    image_id: Uuid,
+    query: HashMap<String, String>,
+    user: User,
+    pool: Pool<Postgres>,
+    storage_config: Arc<StorageConfig>,
+    blob_store: Arc<dyn BlobStore>,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    ensure_can_manage_profile_images(&user, id).map_err(warp::reject::custom)?;
+
+    let size = parse_size_query(&query).map_err(warp::reject::custom)?;
+
+    let user_profile_image = get_owned_profile_image(pool, id, image_id)
+        .await
+        .map_err(warp::reject::custom)?;
+
+    let key = profile_image_key(&user_profile_image, size).map_err(warp::reject::custom)?;
+    let expires_at = Utc::now() + Duration::minutes(storage_config.signed_url_expire_in_minutes);
+
+    Ok(warp::reply::json(&SignedUrl {
+        url: blob_store.signed_url(key, &user_profile_image.content_type, expires_at),
+        expires_at,
+    }))
+}
+
+async fn delete_history_profile_image_handler(
+    id: Uuid,
+    image_id: Uuid,
+    user: User,
+    pool: Pool<Postgres>,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    ensure_can_manage_profile_images(&user, id).map_err(warp::reject::custom)?;
+
+    delete_user_profile_image(pool, id, image_id)
+        .await
+        .map_err(warp::reject::custom)?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Creates the profile images routes.
+///
+/// - **PUT** `/users/:id/profile-image`: Upload a new profile image (the `image` part of a multipart form).
+/// - **GET** `/users/:id/profile-image?size=&download=`: Get the current profile image, or one of its thumbnails.
+/// - **DELETE** `/users/:id/profile-image`: Remove the current profile image.
+/// - **GET** `/users/:id/profile-images?sort=&page=&size=&cursor=`: List the profile images history.
+/// - **GET** `/users/:id/profile-images/:imageId?size=&download=`: Get a profile image of the history.
+/// - **DELETE** `/users/:id/profile-images/:imageId`: Remove a profile image from the history.
+/// - **GET** `/users/:id/profile-images/:imageId/url?size=`: Get a signed URL of a profile image of the history.
+///
+/// Uploaded images are sniffed from their magic bytes (JPEG, PNG, GIF and WebP are accepted),
+/// stripped from their metadata and stored along with square thumbnails of
+/// `PROFILE_IMAGE_THUMBNAIL_SIZES` pixels in the `BlobStore`. The previous profile images are
+/// kept in the history.
+///
+/// Images are served with byte ranges, ETags and caching headers (see `download_reply`).
+///
+/// Only the user themself and the admins can upload, delete or list the history.
+///
+pub fn user_profile_images_routes(
+    jwt_config: Arc<JwtConfig>,
+    pool: Pool<Postgres>,
+    media_config: Arc<MediaConfig>,
+    storage_config: Arc<StorageConfig>,
+    blob_store: Arc<dyn BlobStore>,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Clone {
+    let authenticated = authentication_filter(jwt_config, pool.clone());
+    let include_pool = warp::any().map(move || pool.clone());
+    let form_max_length = media_config.profile_image_max_size_bytes + MULTIPART_OVERHEAD_BYTES;
+    let include_media_config = warp::any().map(move || media_config.clone());
+    let include_storage_config = warp::any().map(move || storage_config.clone());
+    let include_blob_store = warp::any().map(move || blob_store.clone());
+
+    let profile_image = warp::path("users")
+        .and(required_uuid_param_filter(ApiResource::Users))
+        .and(warp::path("profile-image"))
+        .and(warp::path::end());
+
+    let upload_profile_image = profile_image
+        .clone()
+        .and(warp::put())
+        .and(authenticated.clone())
+        .and(include_pool.clone())
+        .and(include_media_config)
+        .and(include_blob_store.clone())
+        .and(multipart_form_filter(form_max_length))
+        .and_then(upload_profile_image_handler);
+
+    let get_profile_image = profile_image
+        .clone()
+        .and(warp::get())
+        .and(warp::query::<HashMap<String, String>>())
+        .and(download_headers_filter())
+        .and(authenticated.clone())
+        .and(include_pool.clone())
+        .and(include_blob_store.clone())
+        .and_then(get_profile_image_handler);
+
+    let delete_profile_image = profile_image
+        .and(warp::delete())
+        .and(authenticated.clone())
+        .and(include_pool.clone())
+        .and_then(delete_profile_image_handler);
+
+    let profile_images = warp::path("users")
+        .and(required_uuid_param_filter(ApiResource::Users))
+        .and(warp::path("profile-images"));
+
+    let get_profile_images_history = profile_images
+        .clone()
+        .and(warp::path::end())
+        .and(warp::get())
+        .and(list_query_filter(&USER_PROFILE_IMAGES_LIST_SPEC))
+        .and(authenticated.clone())
+        .and(include_pool.clone())
+        .and_then(get_profile_images_history_handler);
+
+    let history_profile_image =
+        profile_images.and(required_uuid_param_filter(ApiResource::UserProfileImages));
+
+    let get_history_profile_image = history_profile_image
+        .clone()
+        .and(warp::path::end())
+        .and(warp::get())
+        .and(warp::query::<HashMap<String, String>>())
+        .and(download_headers_filter())
+        .and(authenticated.clone())
+        .and(include_pool.clone())
+        .and(include_blob_store.clone())
+        .and_then(get_history_profile_image_handler);
+
+    let delete_history_profile_image = history_profile_image
+        .clone()
+        .and(warp::path::end())
+        .and(warp::delete())
+        .and(authenticated.clone())
+        .and(include_pool.clone())
+        .and_then(delete_history_profile_image_handler);
+
+    let get_history_profile_image_url = history_profile_image
+        .and(warp::path("url"))
+        .and(warp::path::end())
+        .and(warp::get())
+        .and(warp::query::<HashMap<String, String>>())
+        .and(authenticated)
+        .and(include_pool)
+        .and(include_storage_config)
+        .and(include_blob_store)
+        .and_then(get_history_profile_image_url_handler);
+
+    upload_profile_image
+        .or(get_profile_image)
+        .or(delete_profile_image)
+        .or(get_profile_images_history)
+        .or(get_history_profile_image)
+        .or(delete_history_profile_image)
+        .or(get_history_profile_image_url)
+}
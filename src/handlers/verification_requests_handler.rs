@@ -0,0 +1,229 @@
+use std::sync::Arc;
+
+use sqlx::{Pool, Postgres};
+use uuid::Uuid;
+use warp::{hyper::StatusCode, Filter};
+
+use crate::{
+    filters::{
+        authentication_filter, body_validation_filter, list_query_filter, require_permission,
+        required_uuid_param_filter,
+    },
+    models::{User, VerificationRequestStatus},
+    models_validators::verification_request_validator::{
+        InsertVerificationRequestData, ReviewVerificationData,
+    },
+    policies::Permission,
+    services::{
+        users_service::get_user_by_id,
+        verification_requests_service::{
+            get_verification_request_by_id, get_verification_requests,
+            get_verification_requests_by_user_id, insert_verification_request,
+            review_verification_request, revoke_user_verification, VERIFICATION_REQUESTS_LIST_SPEC,
+        },
+    },
+    utils::{
+        jwt::JwtConfig,
+        list_query::ListQuery,
+        response::{ApiErrorType, ApiResource},
+    },
+};
+
+async fn create_verification_request_handler(
+    user: User,
+    pool: Pool<Postgres>,
+    data: InsertVerificationRequestData,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    if user.verified {
+        return Err(warp::reject::custom(ApiErrorType::AlreadyVerified));
+    }
+
+    let request = insert_verification_request(pool, user.id, data)
+        .await
+        .map_err(warp::reject::custom)?;
+
+    Ok(warp::reply::with_status(
+        warp::reply::json(&request),
+        StatusCode::CREATED,
+    ))
+}
+
+async fn get_own_verification_requests_handler(
+    user: User,
+    pool: Pool<Postgres>,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    let requests = get_verification_requests_by_user_id(pool, user.id)
+        .await
+        .map_err(warp::reject::custom)?;
+
+    Ok(warp::reply::json(&requests))
+}
+
+async fn get_verification_requests_handler(
+    list_query: ListQuery,
+    _: User,
+    pool: Pool<Postgres>,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    let requests = get_verification_requests(pool, list_query)
+        .await
+        .map_err(warp::reject::custom)?;
+
+    Ok(warp::reply::json(&requests))
+}
+
+/// Approves or rejects a verification request, admins can't review their own requests.
+async fn review_verification_request_handler(
+    id: Uuid,
+    user: User,
+    pool: Pool<Postgres>,
+    data: ReviewVerificationData,
+    status: VerificationRequestStatus,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    let request = get_verification_request_by_id(pool.clone(), id)
+        .await
+        .map_err(warp::reject::custom)?;
+
+    if request.user_id == user.id {
+        return Err(warp::reject::custom(ApiErrorType::Unauthorized));
+    }
+
+    review_verification_request(pool.clone(), id, user.id, status, data)
+        .await
+        .map_err(warp::reject::custom)?;
+
+    let request = get_verification_request_by_id(pool, id)
+        .await
+        .map_err(warp::reject::custom)?;
+
+    Ok(warp::reply::json(&request))
+}
+
+async fn approve_verification_request_handler(
+    id: Uuid,
+    user: User,
+    pool: Pool<Postgres>,
+    data: ReviewVerificationData,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    review_verification_request_handler(id, user, pool, data, VerificationRequestStatus::Approved)
+        .await
+}
+
+async fn reject_verification_request_handler(
+    id: Uuid,
+    user: User,
+    pool: Pool<Postgres>,
+    data: ReviewVerificationData,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    review_verification_request_handler(id, user, pool, data, VerificationRequestStatus::Rejected)
+        .await
+}
+
+async fn revoke_user_verification_handler(
+    user_id: Uuid,
+    user: User,
+    pool: Pool<Postgres>,
+    data: ReviewVerificationData,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    if user_id == user.id {
+        return Err(warp::reject::custom(ApiErrorType::Unauthorized));
+    }
+
+    let target = get_user_by_id(pool.clone(), user_id)
+        .await
+        .map_err(warp::reject::custom)?;
+
+    revoke_user_verification(pool, target.id, user.id, data)
+        .await
+        .map_err(warp::reject::custom)?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Creates the account verification routes.
+///
+/// - **POST** `/me/verification-requests`: Request to be verified.
+/// - **GET** `/me/verification-requests`: Get the logged in user's verification requests, latest first.
+/// - **GET** `/admin/verification-requests?filter=&sort=&page=&size=&cursor=`: List the verification requests.
+/// - **POST** `/admin/verification-requests/:id/approve`: Approve a pending request, verifying its user.
+/// - **POST** `/admin/verification-requests/:id/reject`: Reject a pending request.
+/// - **POST** `/admin/users/:id/verification/revoke`: Revoke the verification of a user.
+///
+/// The admin routes require the `VerifyUsers` permission and take optional `notes`. The
+/// requests can be filtered by `status`, `userId`, `reviewerId` and `createdAt` and sorted by
+/// `createdAt` (the default, oldest first, so `filter=status:eq:PENDING` is the review queue).
+///
+pub fn verification_requests_routes(
+    jwt_config: Arc<JwtConfig>,
+    pool: Pool<Postgres>,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Clone {
+    let authenticated = authentication_filter(jwt_config.clone(), pool.clone());
+    let reviewer = require_permission(jwt_config, pool.clone(), Permission::VerifyUsers);
+    let include_pool = warp::any().map(move || pool.clone());
+
+    let own_requests = warp::path("me").and(warp::path("verification-requests"));
+    let requests = warp::path("admin").and(warp::path("verification-requests"));
+    let request = requests.and(required_uuid_param_filter(
+        ApiResource::VerificationRequests,
+    ));
+
+    let create_verification_request = own_requests
+        .and(warp::path::end())
+        .and(warp::post())
+        .and(authenticated.clone())
+        .and(include_pool.clone())
+        .and(body_validation_filter::<InsertVerificationRequestData>())
+        .and_then(create_verification_request_handler);
+
+    let get_own_verification_requests = own_requests
+        .and(warp::path::end())
+        .and(warp::get())
+        .and(authenticated)
+        .and(include_pool.clone())
+        .and_then(get_own_verification_requests_handler);
+
+    let get_verification_requests = requests
+        .and(warp::path::end())
+        .and(warp::get())
+        .and(list_query_filter(&VERIFICATION_REQUESTS_LIST_SPEC))
+        .and(reviewer.clone())
+        .and(include_pool.clone())
+        .and_then(get_verification_requests_handler);
+
+    let approve_verification_request = request
+        .clone()
+        .and(warp::path("approve"))
+        .and(warp::path::end())
+        .and(warp::post())
+        .and(reviewer.clone())
+        .and(include_pool.clone())
+        .and(body_validation_filter::<ReviewVerificationData>())
+        .and_then(approve_verification_request_handler);
+
+    let reject_verification_request = request
+        .and(warp::path("reject"))
+        .and(warp::path::end())
+        .and(warp::post())
+        .and(reviewer.clone())
+        .and(include_pool.clone())
+        .and(body_validation_filter::<ReviewVerificationData>())
+        .and_then(reject_verification_request_handler);
+
+    let revoke_user_verification = warp::path("admin")
+        .and(warp::path("users"))
+        .and(required_uuid_param_filter(ApiResource::Users))
+        .and(warp::path("verification"))
+        .and(warp::path("revoke"))
+        .and(warp::path::end())
+        .and(warp::post())
+        .and(reviewer)
+        .and(include_pool)
+        .and(body_validation_filter::<ReviewVerificationData>())
+        .and_then(revoke_user_verification_handler);
+
+    create_verification_request
+        .or(get_own_verification_requests)
+        .or(get_verification_requests)
+        .or(approve_verification_request)
+        .or(reject_verification_request)
+        .or(revoke_user_verification)
+}
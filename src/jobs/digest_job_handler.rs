@@ -0,0 +1,146 @@
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use handlebars::Handlebars;
+use serde_derive::Deserialize;
+use sqlx::{Pool, Postgres};
+use uuid::Uuid;
+
+use crate::{
+    emails_data::{CommonEmailDetails, DigestEmailData},
+    mailer::{Email, Mailer},
+    models::{DigestFrequency, Job},
+    services::{
+        digests_service::{get_digest, get_digest_unsubscribe_token},
+        jobs_service::enqueue_job,
+        user_notification_preferences_service::{
+            get_quiet_hours_left, get_user_notification_preferences_by_user_id,
+        },
+        users_service::get_user_by_id,
+    },
+    utils::response::ApiErrorType,
+};
+
+use super::JobHandler;
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct DigestPayload {
+    user_id: Uuid,
+    since: DateTime<Utc>,
+}
+
+/// Sends the digests enqueued by the digest scheduler in the `digests` queue (see
+/// `schedule_digests`), rendered with the `digest-email` template.
+///
+/// Nothing is sent to deleted users, to users who unsubscribed in the meantime, or when nothing
+/// happened during the period. Digests due during the quiet hours of the user are enqueued again
+/// for their end. The emails carry a one-click unsubscribe link.
+pub struct DigestJobHandler {
+    mailer: Arc<dyn Mailer>,
+    hb: Arc<Handlebars<'static>>,
+    common_email_details: Arc<CommonEmailDetails>,
+    public_base_url: String,
+}
+
+impl DigestJobHandler {
+    pub fn new(
+        mailer: Arc<dyn Mailer>,
+        hb: Arc<Handlebars<'static>>,
+        common_email_details: Arc<CommonEmailDetails>,
+        public_base_url: &str,
+    ) -> Self {
+        DigestJobHandler {
+            mailer,
+            hb,
+            common_email_details,
+            public_base_url: public_base_url.trim_end_matches('/').to_string(),
+        }
+    }
+}
+
+#[async_trait]
+impl JobHandler for DigestJobHandler {
+    fn queue(&self) -> &'static str {
+        "digests"
+    }
+
+    async fn handle(&self, pool: Pool<Postgres>, job: &Job) -> Result<(), ApiErrorType> {
+        let payload = serde_json::from_value::<DigestPayload>(job.payload.clone())
+            .map_err(|_| ApiErrorType::InternalServerError)?;
+
+        let user = match get_user_by_id(pool.clone(), payload.user_id).await {
+            Ok(user) if user.deleted_at.is_none() => user,
+            Ok(_) | Err(ApiErrorType::ResourceNotFound(_)) => return Ok(()),
+            Err(error) => return Err(error),
+        };
+
+        let period = match get_user_notification_preferences_by_user_id(pool.clone(), user.id)
+            .await?
+            .digest_frequency
+        {
+            DigestFrequency::Never => return Ok(()),
+            DigestFrequency::Daily => "daily",
+            DigestFrequency::Weekly => "weekly",
+        };
+
+        if let Some(left) = get_quiet_hours_left(pool.clone(), user.id).await? {
+            // Same payload, the digest still covers the activity since the start of its period
+            return enqueue_job(
+                pool,
+                self.queue(),
+                &job.kind,
+                &job.payload,
+                Utc::now() + left,
+            )
+            .await;
+        }
+
+        let digest = get_digest(pool.clone(), user.id, payload.since).await?;
+
+        if digest.is_empty() {
+            return Ok(());
+        }
+
+        let unsubscribe_link = format!(
+            "{}/api/v1/digests/unsubscribe?token={}",
+            self.public_base_url,
+            get_digest_unsubscribe_token(pool, user.id).await?
+        );
+
+        let email_data = DigestEmailData {
+            facebook_link: self.common_email_details.facebook_link.clone(),
+            twitter_link: self.common_email_details.twitter_link.clone(),
+            instagram_link: self.common_email_details.instagram_link.clone(),
+            linked_in_link: self.common_email_details.linked_in_link.clone(),
+            first_contatct_line: self.common_email_details.first_contatct_line.clone(),
+            second_contatct_line: self.common_email_details.second_contatct_line.clone(),
+            name: user.name,
+            period: period.to_string(),
+            other_new_followers_count: digest.new_followers_count
+                - digest.new_followers.len() as i64,
+            new_followers: digest.new_followers,
+            top_hesses: digest.top_hesses,
+            unread_mentions: digest.unread_mentions,
+            unsubscribe_link: unsubscribe_link.clone(),
+        };
+
+        let html = self
+            .hb
+            .render("digest-email", &email_data)
+            .map_err(|error| {
+                log::error!("Couldn't render a digest email: {:?}", error);
+                ApiErrorType::InternalServerError
+            })?;
+
+        self.mailer
+            .send(&Email {
+                to: user.email,
+                subject: format!("Your {} HessHub digest", period),
+                html,
+                list_unsubscribe: Some(unsubscribe_link),
+            })
+            .await
+    }
+}
@@ -0,0 +1,28 @@
+use std::time::Duration;
+
+use sqlx::{Pool, Postgres};
+
+use crate::services::digests_service::schedule_digests;
+
+const SCHEDULE_INTERVAL: Duration = Duration::from_secs(10 * 60);
+
+/// Spawns a background task enqueuing the digests due every few minutes (see `schedule_digests`),
+/// they're sent by the `DigestJobHandler`.
+///
+/// Several schedulers (even in different processes) can safely run at once.
+///
+pub fn spawn_digest_scheduler(pool: Pool<Postgres>) {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(SCHEDULE_INTERVAL);
+
+        loop {
+            interval.tick().await;
+
+            match schedule_digests(pool.clone()).await {
+                Ok(0) => {}
+                Ok(count) => log::info!("Enqueued {} digest(s)", count),
+                Err(_) => log::error!("Couldn't schedule the digests"),
+            }
+        }
+    });
+}
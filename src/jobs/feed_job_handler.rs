@@ -0,0 +1,168 @@
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use serde::de::DeserializeOwned;
+use serde_derive::Deserialize;
+use sqlx::{Pool, Postgres};
+use uuid::Uuid;
+
+use crate::{
+    models::Job,
+    services::feed_items_service::{
+        backfill_follow, fan_out_follow, fan_out_hess, fan_out_like, remove_event_feed_items,
+        remove_feed_items_between_users, remove_follow_feed_items,
+    },
+    services::stream_events_service::{
+        publish_follow_feed_item_hints, publish_hess_feed_item_hints, publish_like_feed_item_hints,
+    },
+    utils::{response::ApiErrorType, FeedConfig, FeedStrategy},
+};
+
+use super::JobHandler;
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct HessPayload {
+    hess_id: Uuid,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct LikePayload {
+    like_id: Uuid,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct FollowPayload {
+    follower_row_id: Uuid,
+    follower_id: Uuid,
+    followed_id: Uuid,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct BlockPayload {
+    blocker_id: Uuid,
+    blocked_id: Uuid,
+}
+
+fn parse_payload<T: DeserializeOwned>(job: &Job) -> Result<T, ApiErrorType> {
+    serde_json::from_value(job.payload.clone()).map_err(|_| ApiErrorType::InternalServerError)
+}
+
+/// Materializes the home feeds (`feed_items`) from the events enqueued by the database
+/// triggers in the `feed` queue, then hints the connected followers that their feed has new
+/// items (whatever the feed strategy is).
+pub struct FeedJobHandler {
+    feed_config: Arc<FeedConfig>,
+}
+
+impl FeedJobHandler {
+    pub fn new(feed_config: Arc<FeedConfig>) -> Self {
+        FeedJobHandler { feed_config }
+    }
+
+    async fn materialize(&self, pool: Pool<Postgres>, job: &Job) -> Result<(), ApiErrorType> {
+        let fan_out_limit = self.feed_config.fan_out_limit();
+
+        match job.kind.as_str() {
+            "HESS_CREATED" => {
+                let payload = parse_payload::<HessPayload>(job)?;
+                fan_out_hess(pool, payload.hess_id, fan_out_limit).await?;
+            }
+            "HESS_DELETED" => {
+                let payload = parse_payload::<HessPayload>(job)?;
+                remove_event_feed_items(pool, payload.hess_id).await?;
+            }
+            "LIKE_CREATED" => {
+                let payload = parse_payload::<LikePayload>(job)?;
+                fan_out_like(pool, payload.like_id, fan_out_limit).await?;
+            }
+            "LIKE_DELETED" => {
+                let payload = parse_payload::<LikePayload>(job)?;
+                remove_event_feed_items(pool, payload.like_id).await?;
+            }
+            "FOLLOW_CREATED" => {
+                let payload = parse_payload::<FollowPayload>(job)?;
+                fan_out_follow(pool.clone(), payload.follower_row_id, fan_out_limit).await?;
+                backfill_follow(
+                    pool,
+                    payload.follower_row_id,
+                    fan_out_limit,
+                    self.feed_config.backfill_size,
+                )
+                .await?;
+            }
+            "FOLLOW_UPDATED" => {
+                // The watch flags changed, the followed user's activity is copied again
+                let payload = parse_payload::<FollowPayload>(job)?;
+                remove_follow_feed_items(
+                    pool.clone(),
+                    payload.follower_row_id,
+                    payload.follower_id,
+                    payload.followed_id,
+                )
+                .await?;
+                fan_out_follow(pool.clone(), payload.follower_row_id, fan_out_limit).await?;
+                backfill_follow(
+                    pool,
+                    payload.follower_row_id,
+                    fan_out_limit,
+                    self.feed_config.backfill_size,
+                )
+                .await?;
+            }
+            "FOLLOW_DELETED" => {
+                let payload = parse_payload::<FollowPayload>(job)?;
+                remove_follow_feed_items(
+                    pool,
+                    payload.follower_row_id,
+                    payload.follower_id,
+                    payload.followed_id,
+                )
+                .await?;
+            }
+            "USER_BLOCKED" => {
+                let payload = parse_payload::<BlockPayload>(job)?;
+                remove_feed_items_between_users(pool, payload.blocker_id, payload.blocked_id)
+                    .await?;
+            }
+            kind => log::warn!("Dropping unknown feed job kind {}", kind),
+        }
+
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl JobHandler for FeedJobHandler {
+    fn queue(&self) -> &'static str {
+        "feed"
+    }
+
+    async fn handle(&self, pool: Pool<Postgres>, job: &Job) -> Result<(), ApiErrorType> {
+        // Feeds assembled on read have nothing to materialize
+        if self.feed_config.strategy != FeedStrategy::Read {
+            self.materialize(pool.clone(), job).await?;
+        }
+
+        match job.kind.as_str() {
+            "HESS_CREATED" => {
+                let payload = parse_payload::<HessPayload>(job)?;
+                publish_hess_feed_item_hints(pool, payload.hess_id).await?;
+            }
+            "LIKE_CREATED" => {
+                let payload = parse_payload::<LikePayload>(job)?;
+                publish_like_feed_item_hints(pool, payload.like_id).await?;
+            }
+            "FOLLOW_CREATED" => {
+                let payload = parse_payload::<FollowPayload>(job)?;
+                publish_follow_feed_item_hints(pool, payload.follower_row_id).await?;
+            }
+            _ => {}
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,153 @@
+use std::{collections::BTreeMap, sync::Arc};
+
+use async_trait::async_trait;
+use serde_derive::Deserialize;
+use sqlx::{Pool, Postgres};
+use uuid::Uuid;
+
+use crate::{
+    media::process_hess_media,
+    models::{HessMedia, Job, MediaProcessingStatus},
+    services::{
+        blobs_service::release_blobs,
+        hess_media_service::{
+            complete_hess_media_processing, fail_hess_media_processing, get_hess_media_by_id,
+            CompleteHessMediaProcessingData,
+        },
+    },
+    storage::BlobStore,
+    utils::response::ApiErrorType,
+};
+
+use super::{worker::MAX_ATTEMPTS, JobHandler};
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct HessMediaPayload {
+    hess_media_id: Uuid,
+}
+
+/// Processes the uploaded hess media (see `process_hess_media`): extracts their metadata,
+/// stores their derived assets and marks them as READY, or as FAILED when they can't be decoded.
+pub struct MediaJobHandler {
+    blob_store: Arc<dyn BlobStore>,
+}
+
+impl MediaJobHandler {
+    pub fn new(blob_store: Arc<dyn BlobStore>) -> Self {
+        MediaJobHandler { blob_store }
+    }
+
+    async fn process(
+        &self,
+        pool: Pool<Postgres>,
+        hess_media: HessMedia,
+    ) -> Result<(), ApiErrorType> {
+        let content = self.blob_store.get(&hess_media.media_url).await?;
+
+        let media_type = hess_media.media_type.clone();
+        let content_type = hess_media.content_type.clone();
+        let processing_result = tokio::task::spawn_blocking(move || {
+            process_hess_media(&media_type, &content_type, content)
+        })
+        .await
+        .map_err(|_| ApiErrorType::InternalServerError)?;
+
+        let processed_media = match processing_result {
+            Ok(processed_media) => processed_media,
+            Err(ApiErrorType::InternalServerError) => {
+                return Err(ApiErrorType::InternalServerError)
+            }
+            Err(error) => {
+                log::warn!(
+                    "Hess media {} can't be processed: {:?}",
+                    hess_media.id,
+                    error
+                );
+                return fail_hess_media_processing(pool, hess_media.id).await;
+            }
+        };
+
+        let mut thumbnail_urls = BTreeMap::new();
+
+        for (size, content) in processed_media.thumbnails {
+            let content_type = processed_media
+                .thumbnail_content_type
+                .ok_or(ApiErrorType::InternalServerError)?;
+
+            match self.blob_store.put_content(content, content_type).await {
+                Ok(key) => {
+                    thumbnail_urls.insert(size, key);
+                }
+                Err(error) => {
+                    let stored_keys = thumbnail_urls.into_values().collect::<Vec<_>>();
+                    let _ = release_blobs(pool, &stored_keys).await;
+
+                    return Err(error);
+                }
+            }
+        }
+
+        let stored_keys = thumbnail_urls.values().cloned().collect::<Vec<_>>();
+
+        let data = CompleteHessMediaProcessingData {
+            width: processed_media.width.map(|width| width as i32),
+            height: processed_media.height.map(|height| height as i32),
+            duration_ms: processed_media.duration_ms,
+            blurhash: processed_media.blurhash,
+            thumbnail_urls,
+            thumbnail_content_type: processed_media.thumbnail_content_type.map(str::to_string),
+        };
+
+        // The media may have been hard-deleted in the meantime
+        match complete_hess_media_processing(pool.clone(), hess_media.id, data).await {
+            Ok(true) => Ok(()),
+            Ok(false) => release_blobs(pool, &stored_keys).await,
+            Err(error) => {
+                let _ = release_blobs(pool, &stored_keys).await;
+
+                Err(error)
+            }
+        }
+    }
+}
+
+#[async_trait]
+impl JobHandler for MediaJobHandler {
+    fn queue(&self) -> &'static str {
+        "media"
+    }
+
+    async fn handle(&self, pool: Pool<Postgres>, job: &Job) -> Result<(), ApiErrorType> {
+        match job.kind.as_str() {
+            "HESS_MEDIA_UPLOADED" => {
+                let payload = serde_json::from_value::<HessMediaPayload>(job.payload.clone())
+                    .map_err(|_| ApiErrorType::InternalServerError)?;
+
+                let hess_media =
+                    match get_hess_media_by_id(pool.clone(), payload.hess_media_id).await {
+                        Ok(hess_media) => hess_media,
+                        Err(ApiErrorType::ResourceNotFound(_)) => return Ok(()),
+                        Err(error) => return Err(error),
+                    };
+
+                if hess_media.processing_status != MediaProcessingStatus::Pending {
+                    return Ok(());
+                }
+
+                let hess_media_id = hess_media.id;
+                let result = self.process(pool.clone(), hess_media).await;
+
+                // The media would stay PENDING forever once the job is given up
+                if result.is_err() && job.attempts >= MAX_ATTEMPTS {
+                    fail_hess_media_processing(pool, hess_media_id).await?;
+                }
+
+                result?;
+            }
+            kind => log::warn!("Dropping unknown media job kind {}", kind),
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,17 @@
+mod digest_job_handler;
+mod digest_scheduler;
+mod feed_job_handler;
+mod media_job_handler;
+mod notification_job_handler;
+mod search_job_handler;
+mod storage_job_handler;
+mod worker;
+
+pub use digest_job_handler::DigestJobHandler;
+pub use digest_scheduler::spawn_digest_scheduler;
+pub use feed_job_handler::FeedJobHandler;
+pub use media_job_handler::MediaJobHandler;
+pub use notification_job_handler::NotificationJobHandler;
+pub use search_job_handler::SearchJobHandler;
+pub use storage_job_handler::StorageJobHandler;
+pub use worker::{spawn_worker, JobHandler};
@@ -0,0 +1,176 @@
+use std::{collections::BTreeMap, sync::Arc};
+
+use async_trait::async_trait;
+use serde_derive::Deserialize;
+use sqlx::{Pool, Postgres};
+use uuid::Uuid;
+
+use crate::{
+    models::{Job, NotificationType},
+    policies::{authorize, PolicyAction},
+    push::{PushNotification, PushSender},
+    services::{
+        device_tokens_service::{delete_device_tokens, get_device_tokens_by_user_id},
+        hesses_service::get_hess_by_id,
+        notifications_service::upsert_notification,
+        user_notification_preferences_service::{
+            get_user_notification_preferences_by_user_id, is_in_quiet_hours,
+        },
+        users_service::get_user_by_id,
+        viewer_relationships_service::is_muted_or_blocked,
+    },
+    utils::response::ApiErrorType,
+};
+
+use super::JobHandler;
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct NotificationPayload {
+    recipient_id: Uuid,
+    actor_id: Uuid,
+    event_id: Uuid,
+    hess_id: Option<Uuid>,
+}
+
+/// Notifies the events enqueued by the database triggers in the `notifications` queue (follows,
+/// follow requests and their approvals, likes, replies and mentions) to their recipient: stores
+/// them in their notifications (see `upsert_notification`) then pushes them to their devices.
+///
+/// Nothing is notified for the user's own activity, for users they muted (or blocked, either way),
+/// or for hesses they can't see. Each channel is skipped when turned off in the notification
+/// preferences of the recipient, and nothing is pushed during their quiet hours (the notification
+/// is still stored, the digests are held back too). The tokens reported as invalid are removed.
+pub struct NotificationJobHandler {
+    push_sender: Arc<dyn PushSender>,
+}
+
+impl NotificationJobHandler {
+    pub fn new(push_sender: Arc<dyn PushSender>) -> Self {
+        NotificationJobHandler { push_sender }
+    }
+
+    async fn push(
+        &self,
+        pool: Pool<Postgres>,
+        notification_type: NotificationType,
+        payload: &NotificationPayload,
+        actors_count: usize,
+    ) -> Result<(), ApiErrorType> {
+        let tokens = get_device_tokens_by_user_id(pool.clone(), payload.recipient_id).await?;
+
+        if tokens.is_empty() {
+            return Ok(());
+        }
+
+        let actor = match get_user_by_id(pool.clone(), payload.actor_id).await {
+            Ok(actor) if actor.deleted_at.is_none() => actor,
+            Ok(_) | Err(ApiErrorType::ResourceNotFound(_)) => return Ok(()),
+            Err(error) => return Err(error),
+        };
+
+        let mut data = BTreeMap::from([
+            (
+                "type".to_string(),
+                notification_type.as_sql_str().to_string(),
+            ),
+            ("actorId".to_string(), actor.id.to_string()),
+        ]);
+
+        if let Some(hess_id) = payload.hess_id {
+            data.insert("hessId".to_string(), hess_id.to_string());
+        }
+
+        let notification = PushNotification {
+            title: notification_type.title().to_string(),
+            body: notification_type.summary(&actor.username, actors_count.saturating_sub(1)),
+            data,
+        };
+
+        let invalid_tokens = self.push_sender.send(&tokens, &notification).await?;
+
+        if !invalid_tokens.is_empty() {
+            log::info!("Pruning {} invalid device token(s)", invalid_tokens.len());
+            delete_device_tokens(pool, &invalid_tokens).await?;
+        }
+
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl JobHandler for NotificationJobHandler {
+    fn queue(&self) -> &'static str {
+        "notifications"
+    }
+
+    async fn handle(&self, pool: Pool<Postgres>, job: &Job) -> Result<(), ApiErrorType> {
+        let notification_type = match NotificationType::from_sql_str(&job.kind) {
+            Some(notification_type) => notification_type,
+            None => {
+                log::warn!("Dropping unknown notification job kind {}", job.kind);
+                return Ok(());
+            }
+        };
+
+        let payload = serde_json::from_value::<NotificationPayload>(job.payload.clone())
+            .map_err(|_| ApiErrorType::InternalServerError)?;
+
+        if payload.recipient_id == payload.actor_id
+            || is_muted_or_blocked(pool.clone(), payload.recipient_id, payload.actor_id).await?
+        {
+            return Ok(());
+        }
+
+        if let Some(hess_id) = payload.hess_id {
+            let hess = match get_hess_by_id(pool.clone(), hess_id).await {
+                Ok(hess) => hess,
+                Err(ApiErrorType::ResourceNotFound(_)) => return Ok(()),
+                Err(error) => return Err(error),
+            };
+
+            match authorize(
+                pool.clone(),
+                payload.recipient_id,
+                PolicyAction::View,
+                hess.user_id,
+                Some(&hess),
+            )
+            .await
+            {
+                Ok(()) => {}
+                Err(ApiErrorType::InternalServerError) => {
+                    return Err(ApiErrorType::InternalServerError)
+                }
+                Err(_) => return Ok(()),
+            }
+        }
+
+        let channels =
+            get_user_notification_preferences_by_user_id(pool.clone(), payload.recipient_id)
+                .await?
+                .channels(notification_type);
+
+        // Without the in-app channel the push stands on its own, as a single event
+        let actors_count = if channels.in_app {
+            upsert_notification(
+                pool.clone(),
+                payload.recipient_id,
+                notification_type,
+                payload.actor_id,
+                payload.event_id,
+                payload.hess_id,
+            )
+            .await?
+        } else {
+            1
+        };
+
+        if channels.push && !is_in_quiet_hours(pool.clone(), payload.recipient_id).await? {
+            self.push(pool, notification_type, &payload, actors_count)
+                .await?;
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,59 @@
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use serde_derive::Deserialize;
+use sqlx::{Pool, Postgres};
+use uuid::Uuid;
+
+use crate::{models::Job, search::SearchIndex, utils::response::ApiErrorType};
+
+use super::JobHandler;
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct HessPayload {
+    hess_id: Uuid,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct UserPayload {
+    user_id: Uuid,
+}
+
+/// Keeps the search index in sync with the changes enqueued by the database triggers
+/// in the `search` queue.
+pub struct SearchJobHandler {
+    search_index: Arc<dyn SearchIndex>,
+}
+
+impl SearchJobHandler {
+    pub fn new(search_index: Arc<dyn SearchIndex>) -> Self {
+        SearchJobHandler { search_index }
+    }
+}
+
+#[async_trait]
+impl JobHandler for SearchJobHandler {
+    fn queue(&self) -> &'static str {
+        "search"
+    }
+
+    async fn handle(&self, pool: Pool<Postgres>, job: &Job) -> Result<(), ApiErrorType> {
+        match job.kind.as_str() {
+            "HESS_CHANGED" => {
+                let payload = serde_json::from_value::<HessPayload>(job.payload.clone())
+                    .map_err(|_| ApiErrorType::InternalServerError)?;
+                self.search_index.index_hess(pool, payload.hess_id).await?;
+            }
+            "USER_CHANGED" => {
+                let payload = serde_json::from_value::<UserPayload>(job.payload.clone())
+                    .map_err(|_| ApiErrorType::InternalServerError)?;
+                self.search_index.index_user(pool, payload.user_id).await?;
+            }
+            kind => log::warn!("Dropping unknown search job kind {}", kind),
+        }
+
+        Ok(())
+    }
+}
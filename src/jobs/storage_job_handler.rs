@@ -0,0 +1,54 @@
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use serde_derive::Deserialize;
+use sqlx::{Pool, Postgres};
+
+use crate::{
+    models::Job, services::blobs_service::is_blob_referenced, storage::BlobStore,
+    utils::response::ApiErrorType,
+};
+
+use super::JobHandler;
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct BlobsPayload {
+    keys: Vec<String>,
+}
+
+/// Deletes the released blobs (see `release_blobs`) no row references anymore.
+pub struct StorageJobHandler {
+    blob_store: Arc<dyn BlobStore>,
+}
+
+impl StorageJobHandler {
+    pub fn new(blob_store: Arc<dyn BlobStore>) -> Self {
+        StorageJobHandler { blob_store }
+    }
+}
+
+#[async_trait]
+impl JobHandler for StorageJobHandler {
+    fn queue(&self) -> &'static str {
+        "storage"
+    }
+
+    async fn handle(&self, pool: Pool<Postgres>, job: &Job) -> Result<(), ApiErrorType> {
+        match job.kind.as_str() {
+            "BLOBS_RELEASED" => {
+                let payload = serde_json::from_value::<BlobsPayload>(job.payload.clone())
+                    .map_err(|_| ApiErrorType::InternalServerError)?;
+
+                for key in payload.keys {
+                    if !is_blob_referenced(pool.clone(), &key).await? {
+                        self.blob_store.delete(&key).await?;
+                    }
+                }
+            }
+            kind => log::warn!("Dropping unknown storage job kind {}", kind),
+        }
+
+        Ok(())
+    }
+}
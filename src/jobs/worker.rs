@@ -0,0 +1,69 @@
+use std::{sync::Arc, time::Duration};
+
+use async_trait::async_trait;
+use sqlx::{Pool, Postgres};
+
+use crate::{
+    models::Job,
+    services::jobs_service::{claim_jobs, complete_job, fail_job},
+    utils::response::ApiErrorType,
+};
+
+const POLL_INTERVAL: Duration = Duration::from_secs(1);
+const BATCH_SIZE: i64 = 50;
+/// Jobs failing this many times are left alone (in the `jobs` table, for debugging).
+pub(super) const MAX_ATTEMPTS: i32 = 5;
+
+/// Processes the jobs of a queue (see the `jobs` table).
+#[async_trait]
+pub trait JobHandler: Send + Sync {
+    /// The name of the queue handled.
+    fn queue(&self) -> &'static str;
+
+    /// Processes a single job, failed jobs are retried later (up to 5 attempts).
+    async fn handle(&self, pool: Pool<Postgres>, job: &Job) -> Result<(), ApiErrorType>;
+}
+
+/// Spawns a background task polling the queue of a handler and processing its jobs.
+///
+/// Several workers (even in different processes) can safely consume the same queue,
+/// jobs are claimed with `FOR UPDATE SKIP LOCKED`.
+///
+pub fn spawn_worker(pool: Pool<Postgres>, handler: Arc<dyn JobHandler>) {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(POLL_INTERVAL);
+
+        loop {
+            interval.tick().await;
+
+            let jobs =
+                match claim_jobs(pool.clone(), handler.queue(), BATCH_SIZE, MAX_ATTEMPTS).await {
+                    Ok(jobs) => jobs,
+                    Err(_) => {
+                        log::error!("Couldn't claim the jobs of the {} queue", handler.queue());
+                        continue;
+                    }
+                };
+
+            for job in jobs {
+                let result = match handler.handle(pool.clone(), &job).await {
+                    Ok(()) => complete_job(pool.clone(), job.id).await,
+                    Err(error) => {
+                        log::warn!(
+                            "Job {} ({} {}) failed: {:?}",
+                            job.id,
+                            job.queue,
+                            job.kind,
+                            error
+                        );
+                        fail_job(pool.clone(), job.id, format!("{:?}", error)).await
+                    }
+                };
+
+                if result.is_err() {
+                    log::error!("Couldn't release job {} ({})", job.id, job.queue);
+                }
+            }
+        }
+    });
+}
@@ -0,0 +1,26 @@
+use async_trait::async_trait;
+
+use crate::utils::response::ApiErrorType;
+
+/// An HTML email.
+#[derive(Debug, Clone)]
+pub struct Email {
+    pub to: String,
+    pub subject: String,
+    pub html: String,
+    /// The one-click unsubscribe link (RFC 8058), sent in the `List-Unsubscribe` header.
+    pub list_unsubscribe: Option<String>,
+}
+
+/// Delivers emails (see `open_mailer`).
+#[async_trait]
+pub trait Mailer: Send + Sync {
+    /// Sends an email.
+    ///
+    /// # Returns
+    ///
+    /// Returns a `Result` containing nothing.
+    /// If the email can't be sent, returns an `ApiErrorType::InternalServerError` error.
+    ///
+    async fn send(&self, email: &Email) -> Result<(), ApiErrorType>;
+}
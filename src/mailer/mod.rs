@@ -0,0 +1,33 @@
+mod email;
+mod recording_mailer;
+mod smtp_mailer;
+
+use std::sync::Arc;
+
+use crate::utils::{EmailBackend, EmailConfig};
+
+pub use email::{Email, Mailer};
+pub use recording_mailer::RecordingMailer;
+pub use smtp_mailer::SmtpMailer;
+
+/// Opens the email backend chosen in the configuration.
+///
+/// Panics if SMTP is chosen but its host isn't configured.
+///
+pub fn open_mailer(email_config: &EmailConfig) -> Arc<dyn Mailer> {
+    match email_config.backend {
+        EmailBackend::Smtp => Arc::new(SmtpMailer::new(
+            email_config
+                .smtp_host
+                .as_deref()
+                .expect("The SMTP email backend requires the SMTP host"),
+            email_config.smtp_port,
+            email_config
+                .smtp_username
+                .clone()
+                .zip(email_config.smtp_password.clone()),
+            &email_config.from,
+        )),
+        EmailBackend::Recording => Arc::new(RecordingMailer::default()),
+    }
+}
@@ -0,0 +1,38 @@
+use std::sync::Mutex;
+
+use async_trait::async_trait;
+
+use crate::utils::response::ApiErrorType;
+
+use super::{Email, Mailer};
+
+/// Records (and logs) the emails instead of delivering them, for development and tests.
+#[derive(Default)]
+pub struct RecordingMailer {
+    emails: Mutex<Vec<Email>>,
+}
+
+impl RecordingMailer {
+    /// The emails sent so far, oldest first.
+    #[allow(dead_code)]
+    pub fn emails(&self) -> Vec<Email> {
+        self.emails.lock().unwrap().clone()
+    }
+}
+
+#[async_trait]
+impl Mailer for RecordingMailer {
+    async fn send(&self, email: &Email) -> Result<(), ApiErrorType> {
+        log::info!(
+            "Recorded email \"{}\" to {} (unsubscribe: {:?}, {} bytes)",
+            email.subject,
+            email.to,
+            email.list_unsubscribe,
+            email.html.len()
+        );
+
+        self.emails.lock().unwrap().push(email.clone());
+
+        Ok(())
+    }
+}
@@ -0,0 +1,88 @@
+use async_trait::async_trait;
+use lettre::{
+    message::{
+        header::{ContentType, HeaderName, HeaderValue},
+        Mailbox,
+    },
+    transport::smtp::authentication::Credentials,
+    AsyncSmtpTransport, AsyncTransport, Message, Tokio1Executor,
+};
+
+use crate::utils::response::ApiErrorType;
+
+use super::{Email, Mailer};
+
+/// Sends the emails through an SMTP relay (with STARTTLS).
+pub struct SmtpMailer {
+    transport: AsyncSmtpTransport<Tokio1Executor>,
+    from: Mailbox,
+}
+
+impl SmtpMailer {
+    /// Panics if the relay or the sender address is invalid.
+    pub fn new(
+        host: &str,
+        port: Option<u16>,
+        credentials: Option<(String, String)>,
+        from: &str,
+    ) -> Self {
+        let mut builder = AsyncSmtpTransport::<Tokio1Executor>::starttls_relay(host)
+            .expect("The SMTP host must be a valid relay");
+
+        if let Some(port) = port {
+            builder = builder.port(port);
+        }
+
+        if let Some((username, password)) = credentials {
+            builder = builder.credentials(Credentials::new(username, password));
+        }
+
+        SmtpMailer {
+            transport: builder.build(),
+            from: from
+                .parse()
+                .expect("The email sender must be a valid address"),
+        }
+    }
+}
+
+#[async_trait]
+impl Mailer for SmtpMailer {
+    async fn send(&self, email: &Email) -> Result<(), ApiErrorType> {
+        let to = email.to.parse::<Mailbox>().map_err(|error| {
+            log::error!("Invalid email recipient {}: {:?}", email.to, error);
+            ApiErrorType::InternalServerError
+        })?;
+
+        let mut message = Message::builder()
+            .from(self.from.clone())
+            .to(to)
+            .subject(&email.subject)
+            .header(ContentType::TEXT_HTML)
+            .body(email.html.clone())
+            .map_err(|error| {
+                log::error!("Couldn't build an email: {:?}", error);
+                ApiErrorType::InternalServerError
+            })?;
+
+        if let Some(list_unsubscribe) = &email.list_unsubscribe {
+            let headers = message.headers_mut();
+
+            headers.insert_raw(HeaderValue::new(
+                HeaderName::new_from_ascii_str("List-Unsubscribe"),
+                format!("<{}>", list_unsubscribe),
+            ));
+            headers.insert_raw(HeaderValue::new(
+                HeaderName::new_from_ascii_str("List-Unsubscribe-Post"),
+                "List-Unsubscribe=One-Click".to_string(),
+            ));
+        }
+
+        self.transport.send(message).await.map_err(|error| {
+            log::error!("Couldn't send an email through SMTP: {:?}", error);
+            ApiErrorType::InternalServerError
+        })?;
+
+        Ok(())
+    }
+}
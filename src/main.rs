@@ -8,15 +8,44 @@ use warp::Filter;
 
 use crate::{
     emails_data::CommonEmailDetails,
-    utils::{jwt::JwtConfig, TokensConfig},
+    handlers::{
+        admin_routes, audience_lists_routes, blobs_routes, conversations_routes, devices_routes,
+        digests_routes, feed_routes, hess_media_routes, muted_users_routes,
+        notification_preferences_routes, notifications_routes, privacy_preferences_routes,
+        search_routes, stream_routes, user_profile_images_routes, verification_requests_routes,
+    },
+    jobs::{
+        spawn_digest_scheduler, spawn_worker, DigestJobHandler, FeedJobHandler, MediaJobHandler,
+        NotificationJobHandler, SearchJobHandler, StorageJobHandler,
+    },
+    mailer::open_mailer,
+    push::open_push_sender,
+    rankers::DefaultFeedRanker,
+    search::open_search_index,
+    storage::{open_blob_store, BlobUrlSigner},
+    stream::{spawn_stream_listener, StreamHub},
+    utils::{
+        jwt::JwtConfig, EmailBackend, EmailConfig, FeedConfig, FeedStrategy, MediaConfig,
+        PrivacyConfig, PushBackend, PushConfig, S3Config, SearchBackend, SearchConfig,
+        StorageBackend, StorageConfig, StreamConfig, TokensConfig,
+    },
 };
 
 mod emails_data;
 mod filters;
 mod handlers;
+mod jobs;
+mod mailer;
+mod media;
 mod models;
 mod models_validators;
+mod policies;
+mod push;
+mod rankers;
+mod search;
 mod services;
+mod storage;
+mod stream;
 mod utils;
 
 async fn init_app() -> (
@@ -25,6 +54,14 @@ async fn init_app() -> (
     Arc<Handlebars<'static>>,
     Arc<TokensConfig>,
     Arc<CommonEmailDetails>,
+    Arc<PrivacyConfig>,
+    Arc<FeedConfig>,
+    Arc<SearchConfig>,
+    Arc<MediaConfig>,
+    Arc<StorageConfig>,
+    Arc<PushConfig>,
+    Arc<StreamConfig>,
+    Arc<EmailConfig>,
 ) {
     log::info!("🔑 Getting the environment variables as documented 🔑");
 
@@ -52,6 +89,179 @@ async fn init_app() -> (
         password_reset_token_expire_in_hours,
     });
 
+    let auto_approve_follow_requests_on_public_profile =
+        env::var("AUTO_APPROVE_FOLLOW_REQUESTS_ON_PUBLIC_PROFILE")
+            .map(|value| value.parse::<bool>().unwrap())
+            .unwrap_or(true);
+
+    let privacy_config = Arc::new(PrivacyConfig {
+        auto_approve_follow_requests_on_public_profile,
+    });
+
+    let feed_strategy = env::var("FEED_STRATEGY")
+        .map(|value| value.parse::<FeedStrategy>().unwrap())
+        .unwrap_or(FeedStrategy::Read);
+
+    let feed_fan_out_follower_threshold = env::var("FEED_FAN_OUT_FOLLOWER_THRESHOLD")
+        .map(|value| value.parse::<i64>().unwrap())
+        .unwrap_or(10_000);
+
+    let feed_backfill_size = env::var("FEED_BACKFILL_SIZE")
+        .map(|value| value.parse::<i64>().unwrap())
+        .unwrap_or(50);
+
+    let feed_config = Arc::new(FeedConfig {
+        strategy: feed_strategy,
+        fan_out_follower_threshold: feed_fan_out_follower_threshold,
+        backfill_size: feed_backfill_size,
+    });
+
+    let search_backend = env::var("SEARCH_BACKEND")
+        .map(|value| value.parse::<SearchBackend>().unwrap())
+        .unwrap_or(SearchBackend::Postgres);
+
+    #[cfg(feature = "tantivy-search")]
+    let search_index_path =
+        env::var("SEARCH_INDEX_PATH").unwrap_or_else(|_| "./search_index".to_string());
+
+    let search_config = Arc::new(SearchConfig {
+        backend: search_backend,
+        #[cfg(feature = "tantivy-search")]
+        index_path: search_index_path,
+    });
+
+    let profile_image_max_size_bytes = env::var("PROFILE_IMAGE_MAX_SIZE_BYTES")
+        .map(|value| value.parse::<u64>().unwrap())
+        .unwrap_or(5 * 1024 * 1024);
+
+    let hess_photo_max_size_bytes = env::var("HESS_PHOTO_MAX_SIZE_BYTES")
+        .map(|value| value.parse::<u64>().unwrap())
+        .unwrap_or(10 * 1024 * 1024);
+
+    let hess_video_max_size_bytes = env::var("HESS_VIDEO_MAX_SIZE_BYTES")
+        .map(|value| value.parse::<u64>().unwrap())
+        .unwrap_or(100 * 1024 * 1024);
+
+    let hess_audio_max_size_bytes = env::var("HESS_AUDIO_MAX_SIZE_BYTES")
+        .map(|value| value.parse::<u64>().unwrap())
+        .unwrap_or(20 * 1024 * 1024);
+
+    let hess_max_photos = env::var("HESS_MAX_PHOTOS")
+        .map(|value| value.parse::<i64>().unwrap())
+        .unwrap_or(4);
+
+    let hess_max_videos = env::var("HESS_MAX_VIDEOS")
+        .map(|value| value.parse::<i64>().unwrap())
+        .unwrap_or(1);
+
+    let hess_max_audios = env::var("HESS_MAX_AUDIOS")
+        .map(|value| value.parse::<i64>().unwrap())
+        .unwrap_or(1);
+
+    let hess_media_captions_max_size_bytes = env::var("HESS_MEDIA_CAPTIONS_MAX_SIZE_BYTES")
+        .map(|value| value.parse::<u64>().unwrap())
+        .unwrap_or(512 * 1024);
+
+    let media_config = Arc::new(MediaConfig {
+        profile_image_max_size_bytes,
+        hess_photo_max_size_bytes,
+        hess_video_max_size_bytes,
+        hess_audio_max_size_bytes,
+        hess_max_photos,
+        hess_max_videos,
+        hess_max_audios,
+        hess_media_captions_max_size_bytes,
+    });
+
+    let storage_backend = env::var("STORAGE_BACKEND")
+        .map(|value| value.parse::<StorageBackend>().unwrap())
+        .unwrap_or(StorageBackend::Local);
+
+    let storage_local_path =
+        env::var("STORAGE_LOCAL_PATH").unwrap_or_else(|_| "./media".to_string());
+
+    let s3_config = match storage_backend {
+        StorageBackend::S3 => Some(S3Config {
+            endpoint: env::var("S3_ENDPOINT").unwrap(),
+            region: env::var("S3_REGION").unwrap_or_else(|_| "us-east-1".to_string()),
+            bucket: env::var("S3_BUCKET").unwrap(),
+            access_key_id: env::var("S3_ACCESS_KEY_ID").unwrap(),
+            secret_access_key: env::var("S3_SECRET_ACCESS_KEY").unwrap(),
+        }),
+        StorageBackend::Local => None,
+    };
+
+    let public_base_url =
+        env::var("PUBLIC_BASE_URL").unwrap_or_else(|_| "http://localhost:6060".to_string());
+
+    let storage_signing_secret =
+        env::var("STORAGE_SIGNING_SECRET").expect("STORAGE_SIGNING_SECRET must be set");
+
+    // A leaked media URL key must never be enough to forge auth tokens (and vice versa)
+    if storage_signing_secret == jwt_secret {
+        panic!("STORAGE_SIGNING_SECRET must be different from JWT_SECRET");
+    }
+
+    let signed_url_expire_in_minutes = env::var("SIGNED_URL_EXPIRE_IN_MINUTES")
+        .map(|value| value.parse::<i64>().unwrap())
+        .unwrap_or(15);
+
+    let storage_config = Arc::new(StorageConfig {
+        backend: storage_backend,
+        local_path: storage_local_path,
+        s3: s3_config,
+        public_base_url,
+        signing_secret: storage_signing_secret,
+        signed_url_expire_in_minutes,
+    });
+
+    let fcm_server_key = env::var("FCM_SERVER_KEY").ok();
+
+    // Pushes must never be silently dropped, so the fake backend has to be chosen explicitly
+    let push_backend = match env::var("PUSH_BACKEND") {
+        Ok(value) => value.parse::<PushBackend>().unwrap(),
+        Err(_) if fcm_server_key.is_some() => PushBackend::Fcm,
+        Err(_) => panic!("PUSH_BACKEND must be set (or FCM_SERVER_KEY, to use fcm)"),
+    };
+
+    let push_config = Arc::new(PushConfig {
+        backend: push_backend,
+        fcm_server_key,
+    });
+
+    let stream_heartbeat_interval_seconds = env::var("STREAM_HEARTBEAT_INTERVAL_SECONDS")
+        .map(|value| value.parse::<u64>().unwrap())
+        .unwrap_or(30);
+
+    let stream_events_retention_hours = env::var("STREAM_EVENTS_RETENTION_HOURS")
+        .map(|value| value.parse::<i64>().unwrap())
+        .unwrap_or(24);
+
+    let stream_config = Arc::new(StreamConfig {
+        heartbeat_interval_seconds: stream_heartbeat_interval_seconds,
+        events_retention_hours: stream_events_retention_hours,
+    });
+
+    // Emails must never be silently dropped, so the fake backend has to be chosen explicitly
+    let email_backend = env::var("EMAIL_BACKEND")
+        .expect("EMAIL_BACKEND must be set")
+        .parse::<EmailBackend>()
+        .unwrap();
+
+    let email_from =
+        env::var("EMAIL_FROM").unwrap_or_else(|_| "HessHub <no-reply@hesshub.com>".to_string());
+
+    let email_config = Arc::new(EmailConfig {
+        backend: email_backend,
+        from: email_from,
+        smtp_host: env::var("SMTP_HOST").ok(),
+        smtp_port: env::var("SMTP_PORT")
+            .ok()
+            .map(|value| value.parse::<u16>().unwrap()),
+        smtp_username: env::var("SMTP_USERNAME").ok(),
+        smtp_password: env::var("SMTP_PASSWORD").ok(),
+    });
+
     let facebook_link = env::var("FACEBOOK_LINK").ok();
     let twitter_link = env::var("TWITTER_LINK").ok();
     let instagram_link = env::var("INSTAGRAM_LINK").ok();
@@ -79,6 +289,9 @@ async fn init_app() -> (
     )
     .unwrap();
 
+    hb.register_template_file("digest-email", "./email_templates/digest_email.hbs")
+        .unwrap();
+
     let hb: Arc<Handlebars<'_>> = Arc::new(hb);
 
     log::info!("🔧 Creating a connection pool to the database 🔧");
@@ -102,7 +315,21 @@ async fn init_app() -> (
         expire_in_hours: jwt_expire_in_hours,
     });
 
-    (jwt_config, pool, hb, tokens_config, common_email_details)
+    (
+        jwt_config,
+        pool,
+        hb,
+        tokens_config,
+        common_email_details,
+        privacy_config,
+        feed_config,
+        search_config,
+        media_config,
+        storage_config,
+        push_config,
+        stream_config,
+        email_config,
+    )
 }
 
 #[tokio::main]
@@ -113,7 +340,140 @@ async fn main() {
 
     dotenv::dotenv().ok();
 
-    let (jwt_config, pool, hb, tokens_config, common_email_details) = init_app().await;
+    let (
+        jwt_config,
+        pool,
+        hb,
+        tokens_config,
+        common_email_details,
+        privacy_config,
+        feed_config,
+        search_config,
+        media_config,
+        storage_config,
+        push_config,
+        stream_config,
+        email_config,
+    ) = init_app().await;
+
+    log::info!("🔎 Opening the search index 🔎");
+
+    let search_index = open_search_index(&search_config);
+
+    if env::args().nth(1).as_deref() == Some("reindex") {
+        log::info!("🔎 Rebuilding the search index 🔎");
+
+        search_index.reindex_all(pool).await.unwrap();
+
+        log::info!("🚀 Finished rebuilding the search index 🚀");
+
+        return;
+    }
+
+    log::info!("📦 Opening the blob store 📦");
+
+    let blob_url_signer = Arc::new(BlobUrlSigner::new(
+        &storage_config.signing_secret,
+        &storage_config.public_base_url,
+    ));
+
+    let blob_store = open_blob_store(&storage_config, blob_url_signer.clone());
+
+    log::info!("👷 Starting the background workers 👷");
+
+    spawn_worker(
+        pool.clone(),
+        Arc::new(FeedJobHandler::new(feed_config.clone())),
+    );
+
+    spawn_worker(
+        pool.clone(),
+        Arc::new(SearchJobHandler::new(search_index.clone())),
+    );
+
+    spawn_worker(
+        pool.clone(),
+        Arc::new(StorageJobHandler::new(blob_store.clone())),
+    );
+
+    spawn_worker(
+        pool.clone(),
+        Arc::new(MediaJobHandler::new(blob_store.clone())),
+    );
+
+    spawn_worker(
+        pool.clone(),
+        Arc::new(NotificationJobHandler::new(open_push_sender(&push_config))),
+    );
+
+    spawn_worker(
+        pool.clone(),
+        Arc::new(DigestJobHandler::new(
+            open_mailer(&email_config),
+            hb.clone(),
+            common_email_details.clone(),
+            &storage_config.public_base_url,
+        )),
+    );
+
+    spawn_digest_scheduler(pool.clone());
+
+    log::info!("📡 Listening to the stream events 📡");
+
+    let stream_hub = Arc::new(StreamHub::default());
+
+    spawn_stream_listener(pool.clone(), stream_hub.clone(), stream_config.clone());
+
+    let audience_lists_routes = audience_lists_routes(jwt_config.clone(), pool.clone());
+
+    let privacy_preferences_routes =
+        privacy_preferences_routes(jwt_config.clone(), pool.clone(), privacy_config);
+
+    let muted_users_routes = muted_users_routes(jwt_config.clone(), pool.clone());
+
+    let feed_routes = feed_routes(
+        jwt_config.clone(),
+        pool.clone(),
+        feed_config,
+        Arc::new(DefaultFeedRanker::default()),
+    );
+
+    let search_routes = search_routes(jwt_config.clone(), pool.clone(), search_index);
+
+    let admin_routes = admin_routes(jwt_config.clone(), pool.clone());
+
+    let user_profile_images_routes = user_profile_images_routes(
+        jwt_config.clone(),
+        pool.clone(),
+        media_config.clone(),
+        storage_config.clone(),
+        blob_store.clone(),
+    );
+
+    let hess_media_routes = hess_media_routes(
+        jwt_config.clone(),
+        pool.clone(),
+        media_config,
+        storage_config,
+        blob_store.clone(),
+    );
+
+    let devices_routes = devices_routes(jwt_config.clone(), pool.clone());
+
+    let notifications_routes = notifications_routes(jwt_config.clone(), pool.clone());
+    let notification_preferences_routes =
+        notification_preferences_routes(jwt_config.clone(), pool.clone());
+
+    let stream_routes = stream_routes(jwt_config.clone(), pool.clone(), stream_hub, stream_config);
+
+    let conversations_routes = conversations_routes(jwt_config.clone(), pool.clone());
+
+    let verification_requests_routes =
+        verification_requests_routes(jwt_config.clone(), pool.clone());
+
+    let digests_routes = digests_routes(pool.clone());
+
+    let blobs_routes = blobs_routes(blob_store, blob_url_signer);
 
     let include_jwt_config = warp::any().map(move || jwt_config.clone());
 
@@ -129,9 +489,24 @@ async fn main() {
 
     log::info!("🔧 Initializing the filters (routes) 🔧");
 
-    let api_v1_routes = warp::path("api")
-        .and(warp::path("v1"))
-        .map(|| "Hello, World!");
+    let api_v1_routes = warp::path("api").and(warp::path("v1")).and(
+        audience_lists_routes
+            .or(privacy_preferences_routes)
+            .or(muted_users_routes)
+            .or(feed_routes)
+            .or(search_routes)
+            .or(admin_routes)
+            .or(user_profile_images_routes)
+            .or(hess_media_routes)
+            .or(devices_routes)
+            .or(notifications_routes)
+            .or(notification_preferences_routes)
+            .or(stream_routes)
+            .or(conversations_routes)
+            .or(verification_requests_routes)
+            .or(digests_routes)
+            .or(blobs_routes),
+    );
 
     log::info!("🚀 Starting the server 🚀");
 
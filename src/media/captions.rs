@@ -0,0 +1,116 @@
+use crate::utils::response::ApiErrorType;
+
+/// The content type of the captions, always stored and served as UTF-8 WebVTT.
+pub const CAPTIONS_CONTENT_TYPE: &str = "text/vtt; charset=utf-8";
+
+/// Parses a WebVTT timestamp (`hh:mm:ss.ttt`, the hours being optional) into milliseconds.
+fn parse_timestamp(value: &str) -> Option<u64> {
+    let (time, milliseconds) = value.split_once('.')?;
+    let parts = time.split(':').collect::<Vec<_>>();
+
+    let (hours, minutes, seconds) = match parts.as_slice() {
+        [hours, minutes, seconds] if hours.len() >= 2 => (*hours, *minutes, *seconds),
+        [minutes, seconds] => ("0", *minutes, *seconds),
+        _ => return None,
+    };
+
+    if minutes.len() != 2 || seconds.len() != 2 || milliseconds.len() != 3 {
+        return None;
+    }
+
+    let number = |value: &str| {
+        value
+            .bytes()
+            .all(|byte| byte.is_ascii_digit())
+            .then(|| value.parse::<u64>().ok())
+            .flatten()
+    };
+
+    let (hours, minutes, seconds, milliseconds) = (
+        number(hours)?,
+        number(minutes)?,
+        number(seconds)?,
+        number(milliseconds)?,
+    );
+
+    if minutes > 59 || seconds > 59 {
+        return None;
+    }
+
+    Some(((hours * 60 + minutes) * 60 + seconds) * 1000 + milliseconds)
+}
+
+/// Whether a WebVTT cue timings line (`start --> end`, followed by optional settings) is valid.
+fn is_valid_cue_timings(line: &str) -> bool {
+    let (start, end) = match line.split_once("-->") {
+        Some(timings) => timings,
+        None => return false,
+    };
+
+    let start = parse_timestamp(start.trim());
+    let end = end.split_whitespace().next().and_then(parse_timestamp);
+
+    matches!((start, end), (Some(start), Some(end)) if start <= end)
+}
+
+/// Validates uploaded captions (or transcripts) as WebVTT.
+///
+/// The file must be UTF-8, start with the `WEBVTT` signature and every cue must have valid
+/// timings. The cues themselves (text, settings, styles) are stored as is.
+///
+/// # Arguments
+///
+/// * `content` - The uploaded file.
+///
+/// # Returns
+///
+/// Returns a `Result` containing the captions, without byte order mark, if they're valid.
+/// Otherwise, returns an `ApiErrorType::InvalidCaptions` error.
+///
+pub fn parse_webvtt(content: &[u8]) -> Result<String, ApiErrorType> {
+    let content = std::str::from_utf8(content).map_err(|_| ApiErrorType::InvalidCaptions)?;
+    let content = content.strip_prefix('\u{FEFF}').unwrap_or(content);
+
+    let mut lines = content.lines();
+
+    match lines.next().and_then(|line| line.strip_prefix("WEBVTT")) {
+        Some(rest) if rest.is_empty() || rest.starts_with([' ', '\t']) => {}
+        _ => return Err(ApiErrorType::InvalidCaptions),
+    }
+
+    if lines
+        .filter(|line| line.contains("-->"))
+        .all(is_valid_cue_timings)
+    {
+        Ok(content.to_string())
+    } else {
+        Err(ApiErrorType::InvalidCaptions)
+    }
+}
+
+/// Parses the language of captions, a BCP 47 language tag (e.g. `en`, `pt-BR`, `zh-Hant`).
+///
+/// # Returns
+///
+/// Returns a `Result` containing the lowercased language tag if it's well formed.
+/// Otherwise, returns an `ApiErrorType::InvalidLanguageParam` error.
+///
+pub fn parse_language_tag(language: &str) -> Result<String, ApiErrorType> {
+    let mut subtags = language.split('-');
+
+    let is_valid = language.len() <= 35
+        && subtags.next().is_some_and(|primary| {
+            (2..=3).contains(&primary.len())
+                && primary.bytes().all(|byte| byte.is_ascii_alphabetic())
+        })
+        && subtags.all(|subtag| {
+            (1..=8).contains(&subtag.len())
+                && subtag.bytes().all(|byte| byte.is_ascii_alphanumeric())
+        });
+
+    if is_valid {
+        Ok(language.to_ascii_lowercase())
+    } else {
+        Err(ApiErrorType::InvalidLanguageParam(language.to_string()))
+    }
+}
@@ -0,0 +1,374 @@
+use std::{collections::HashMap, io, sync::Arc};
+
+use warp::{
+    http::{
+        header::{
+            ACCEPT_RANGES, CACHE_CONTROL, CONTENT_DISPOSITION, CONTENT_LENGTH, CONTENT_RANGE,
+            CONTENT_TYPE, ETAG,
+        },
+        response::Builder,
+        Response, StatusCode,
+    },
+    hyper::Body,
+};
+
+use crate::{
+    storage::BlobStore,
+    utils::response::{ApiErrorResponse, ApiErrorType, ValidationError},
+};
+
+/// The most bytes sent for an open-ended range (`bytes=start-`), clients ask for the rest with
+/// further ranges (RFC 9110 lets a server send less than such a range).
+const OPEN_RANGE_MAX_LENGTH: u64 = 2 * 1024 * 1024;
+
+/// The size of the chunks blobs are streamed in, so a download never holds more in memory.
+const STREAM_CHUNK_SIZE: u64 = 1024 * 1024;
+
+/// The headers of a download request the response depends on (see `download_headers_filter`).
+#[derive(Debug, Default, Clone)]
+pub struct DownloadHeaders {
+    /// The `Range` header, only single byte ranges are honored.
+    pub range: Option<String>,
+    /// The `If-None-Match` header.
+    pub if_none_match: Option<String>,
+    /// The `If-Range` header, the range is ignored unless it's the current ETag.
+    pub if_range: Option<String>,
+}
+
+/// How a downloaded file may be cached.
+#[derive(Debug, Clone, Copy)]
+pub enum CacheControl {
+    /// Any cache can store it, but must revalidate it (its content changes under the same URL).
+    PublicRevalidated,
+    /// Only the browser can store it, and must revalidate it (so the visibility is checked again).
+    Private,
+    /// Only the browser can reuse it, for the given number of seconds.
+    PrivateFor(u64),
+}
+
+impl CacheControl {
+    fn header_value(&self) -> String {
+        match self {
+            CacheControl::PublicRevalidated => "public, no-cache".to_string(),
+            CacheControl::Private => "private, no-cache".to_string(),
+            CacheControl::PrivateFor(max_age) => format!("private, max-age={}", max_age),
+        }
+    }
+}
+
+/// Whether a downloaded file is shown by the browser or saved.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ContentDisposition {
+    Inline,
+    Attachment,
+}
+
+impl ContentDisposition {
+    /// Parses the `download` query field (`true` to save the file, `false` by default).
+    pub fn from_query(query: &HashMap<String, String>) -> Result<Self, ApiErrorType> {
+        match query.get("download").map(String::as_str) {
+            None | Some("false") => Ok(ContentDisposition::Inline),
+            Some("true") => Ok(ContentDisposition::Attachment),
+            Some(download) => Err(ApiErrorType::QueryValidationErrors(vec![
+                ValidationError::IncorrectEnumValue {
+                    field_name: "download".to_string(),
+                    passed_value: download.to_string(),
+                    expected_values: vec!["true".to_string(), "false".to_string()],
+                },
+            ])),
+        }
+    }
+
+    fn header_value(&self, file_name: &str) -> String {
+        let disposition = match self {
+            ContentDisposition::Inline => "inline",
+            ContentDisposition::Attachment => "attachment",
+        };
+
+        format!("{}; filename=\"{}\"", disposition, file_name)
+    }
+}
+
+/// A blob to download, along with how it's presented and cached.
+pub struct Download<'a> {
+    pub key: &'a str,
+    pub content_type: &'a str,
+    /// The name of the file, without extension (deduced from the content type).
+    pub file_name: String,
+    pub disposition: ContentDisposition,
+    pub cache_control: CacheControl,
+}
+
+/// The extension of the files of a (sniffed) content type.
+fn file_extension(content_type: &str) -> &'static str {
+    match content_type {
+        "image/jpeg" => "jpg",
+        "image/png" => "png",
+        "image/gif" => "gif",
+        "image/webp" => "webp",
+        "video/mp4" => "mp4",
+        "video/quicktime" => "mov",
+        "video/webm" => "webm",
+        "audio/mp4" => "m4a",
+        "audio/mpeg" => "mp3",
+        "audio/ogg" => "ogg",
+        "audio/flac" => "flac",
+        "audio/wav" => "wav",
+        _ => "bin",
+    }
+}
+
+/// The (strong) ETag of a blob. Blobs are content-addressed, so their key changes along with
+/// their content and its last segment (the hash of the content) is enough.
+fn blob_etag(key: &str) -> String {
+    format!("\"{}\"", key.rsplit('/').next().unwrap_or(key))
+}
+
+/// Whether an `If-None-Match` header matches an ETag (weak comparison).
+fn etag_matches(if_none_match: &str, etag: &str) -> bool {
+    if_none_match
+        .split(',')
+        .map(str::trim)
+        .any(|candidate| candidate == "*" || candidate.trim_start_matches("W/") == etag)
+}
+
+/// Parses a `Range` header against the size of a blob.
+///
+/// # Returns
+///
+/// Returns a `Result` containing the first and the last byte (inclusive) of the range, or `None`
+/// when the header must be ignored (another unit, several ranges or an invalid syntax) and the
+/// whole blob is sent. Open-ended ranges are capped to `OPEN_RANGE_MAX_LENGTH` bytes.
+/// If the range starts after the end of the blob, returns an `ApiErrorType::RangeNotSatisfiable`
+/// error.
+///
+fn parse_byte_range(range: &str, size: u64) -> Result<Option<(u64, u64)>, ApiErrorType> {
+    let ranges = match range.trim().strip_prefix("bytes=") {
+        Some(ranges) if !ranges.contains(',') => ranges,
+        _ => return Ok(None),
+    };
+
+    let (first, last) = match ranges.split_once('-') {
+        Some((first, last)) => (first.trim(), last.trim()),
+        None => return Ok(None),
+    };
+
+    let (start, end) = match (first.parse::<u64>(), last.parse::<u64>()) {
+        // The last bytes
+        (Err(_), Ok(length)) if first.is_empty() => {
+            if length == 0 || size == 0 {
+                return Err(ApiErrorType::RangeNotSatisfiable(size));
+            }
+
+            (size.saturating_sub(length), size - 1)
+        }
+        (Ok(start), Err(_)) if last.is_empty() => {
+            (start, start.saturating_add(OPEN_RANGE_MAX_LENGTH - 1))
+        }
+        (Ok(start), Ok(end)) if start <= end => (start, end),
+        _ => return Ok(None),
+    };
+
+    if start >= size {
+        return Err(ApiErrorType::RangeNotSatisfiable(size));
+    }
+
+    Ok(Some((start, end.min(size - 1))))
+}
+
+fn build_response(builder: Builder, body: Body) -> Result<Response<Body>, ApiErrorType> {
+    builder
+        .body(body)
+        .map_err(|_| ApiErrorType::InternalServerError)
+}
+
+/// Streams the `start` to `end` bytes (inclusive) of a blob, reading `STREAM_CHUNK_SIZE` bytes
+/// at a time.
+fn blob_body(blob_store: Arc<dyn BlobStore>, key: String, start: u64, end: u64) -> Body {
+    let chunks = futures_util::stream::try_unfold(start, move |offset| {
+        let blob_store = blob_store.clone();
+        let key = key.clone();
+
+        async move {
+            if offset > end {
+                return Ok(None);
+            }
+
+            let chunk_end = end.min(offset + STREAM_CHUNK_SIZE - 1);
+            let chunk = blob_store
+                .get_range(&key, offset, chunk_end)
+                .await
+                .map_err(|_| io::Error::other(format!("Couldn't read blob {}", key)))?;
+
+            Ok::<_, io::Error>(Some((chunk, chunk_end + 1)))
+        }
+    });
+
+    Body::wrap_stream(chunks)
+}
+
+/// Replies with a blob, honoring the conditional and range headers of the request.
+///
+/// - `200 OK` with the whole blob by default.
+/// - `206 Partial Content` with a part of the blob, for a single byte range (see `parse_byte_range`).
+/// - `304 Not Modified` when the `If-None-Match` header matches the ETag of the blob.
+/// - `416 Range Not Satisfiable` when the range starts after the end of the blob.
+///
+/// The blob is streamed (see `blob_body`), it's never held whole in memory. The caller must have
+/// checked the visibility of the blob first.
+///
+/// # Arguments
+///
+/// * `blob_store` - The storage backend of the blob.
+/// * `download` - The blob and how it's presented and cached.
+/// * `headers` - The conditional and range headers of the request.
+///
+/// # Returns
+///
+/// Returns a `Result` containing the response if successful.
+/// If the blob can't be retrieved, returns an `ApiErrorType::InternalServerError` error.
+///
+pub async fn download_reply(
+    blob_store: Arc<dyn BlobStore>,
+    download: Download<'_>,
+    headers: &DownloadHeaders,
+) -> Result<Response<Body>, ApiErrorType> {
+    let etag = blob_etag(download.key);
+    let builder = Response::builder()
+        .header(ETAG, &etag)
+        .header(CACHE_CONTROL, download.cache_control.header_value())
+        .header(ACCEPT_RANGES, "bytes");
+
+    if let Some(if_none_match) = &headers.if_none_match {
+        if etag_matches(if_none_match, &etag) {
+            return build_response(builder.status(StatusCode::NOT_MODIFIED), Body::empty());
+        }
+    }
+
+    let content_disposition = download.disposition.header_value(&format!(
+        "{}.{}",
+        download.file_name,
+        file_extension(download.content_type)
+    ));
+
+    // A range of an outdated version would be mixed with the current one, send the whole blob
+    let range = headers
+        .range
+        .as_deref()
+        .filter(|_| match &headers.if_range {
+            Some(if_range) => if_range.trim() == etag,
+            None => true,
+        });
+
+    let size = blob_store.size(download.key).await?;
+
+    let (builder, start, end) = match range.map(|range| parse_byte_range(range, size)) {
+        Some(Ok(Some((start, end)))) => (
+            builder
+                .status(StatusCode::PARTIAL_CONTENT)
+                .header(CONTENT_RANGE, format!("bytes {}-{}/{}", start, end, size)),
+            start,
+            end,
+        ),
+        Some(Err(error)) => {
+            let body = serde_json::to_vec(&ApiErrorResponse {
+                code: error.code().to_string(),
+                details: error.details(),
+            })
+            .map_err(|_| ApiErrorType::InternalServerError)?;
+
+            return build_response(
+                builder
+                    .status(error.status_code())
+                    .header(CONTENT_RANGE, format!("bytes */{}", size))
+                    .header(CONTENT_TYPE, "application/json"),
+                Body::from(body),
+            );
+        }
+        _ if size == 0 => {
+            return build_response(
+                builder
+                    .header(CONTENT_TYPE, download.content_type)
+                    .header(CONTENT_DISPOSITION, content_disposition),
+                Body::empty(),
+            );
+        }
+        _ => (builder, 0, size - 1),
+    };
+
+    build_response(
+        builder
+            .header(CONTENT_TYPE, download.content_type)
+            .header(CONTENT_DISPOSITION, content_disposition)
+            .header(CONTENT_LENGTH, end - start + 1),
+        blob_body(blob_store, download.key.to_string(), start, end),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn closed_ranges_are_kept() {
+        assert_eq!(parse_byte_range("bytes=0-99", 1000).unwrap(), Some((0, 99)));
+        assert_eq!(
+            parse_byte_range("bytes=900-5000", 1000).unwrap(),
+            Some((900, 999))
+        );
+    }
+
+    #[test]
+    fn open_ranges_are_capped() {
+        let size = 100 * 1024 * 1024;
+
+        assert_eq!(
+            parse_byte_range("bytes=0-", size).unwrap(),
+            Some((0, OPEN_RANGE_MAX_LENGTH - 1))
+        );
+        assert_eq!(
+            parse_byte_range("bytes=1000-", size).unwrap(),
+            Some((1000, 1000 + OPEN_RANGE_MAX_LENGTH - 1))
+        );
+        assert_eq!(parse_byte_range("bytes=10-", 100).unwrap(), Some((10, 99)));
+        assert_eq!(
+            parse_byte_range(&format!("bytes={}-", u64::MAX - 1), u64::MAX).unwrap(),
+            Some((u64::MAX - 1, u64::MAX - 1))
+        );
+    }
+
+    #[test]
+    fn suffix_ranges_are_the_last_bytes() {
+        assert_eq!(
+            parse_byte_range("bytes=-100", 1000).unwrap(),
+            Some((900, 999))
+        );
+        assert_eq!(
+            parse_byte_range("bytes=-5000", 1000).unwrap(),
+            Some((0, 999))
+        );
+    }
+
+    #[test]
+    fn unsupported_ranges_are_ignored() {
+        for range in [
+            "items=0-10",
+            "bytes=0-10,20-30",
+            "bytes=10-5",
+            "bytes=abc",
+            "bytes=-",
+        ] {
+            assert_eq!(parse_byte_range(range, 1000).unwrap(), None, "{}", range);
+        }
+    }
+
+    #[test]
+    fn ranges_past_the_end_are_not_satisfiable() {
+        for range in ["bytes=1000-", "bytes=1000-2000", "bytes=-0"] {
+            assert!(matches!(
+                parse_byte_range(range, 1000),
+                Err(ApiErrorType::RangeNotSatisfiable(1000))
+            ));
+        }
+    }
+}
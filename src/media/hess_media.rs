@@ -0,0 +1,88 @@
+use image::ImageFormat;
+
+use crate::models::MediaType;
+
+use super::profile_image::sniff_image_format;
+
+/// The format of an uploaded hess media, as sniffed from its magic bytes.
+pub struct SniffedMedia {
+    pub media_type: MediaType,
+    pub content_type: &'static str,
+}
+
+impl SniffedMedia {
+    fn new(media_type: MediaType, content_type: &'static str) -> Self {
+        SniffedMedia {
+            media_type,
+            content_type,
+        }
+    }
+}
+
+/// Whether the content starts with an MPEG audio frame header (MP3 files without ID3 tag).
+fn is_mpeg_audio_frame(content: &[u8]) -> bool {
+    content.len() >= 2 && content[0] == 0xFF && content[1] & 0xE0 == 0xE0 && content[1] & 0x06 != 0
+}
+
+/// Sniffs the format of an uploaded hess media from its magic bytes, whatever content type
+/// the client claimed.
+///
+/// - Photos: JPEG, PNG, GIF and WebP.
+/// - Videos: MP4, QuickTime and WebM.
+/// - Audios: MP3, M4A, Ogg, WAV and FLAC.
+///
+/// # Arguments
+///
+/// * `content` - The beginning of the uploaded file (64 bytes are enough).
+///
+/// # Returns
+///
+/// Returns the media type and the content type of the file, or `None` when it isn't supported.
+///
+pub fn sniff_media(content: &[u8]) -> Option<SniffedMedia> {
+    if let Some(format) = sniff_image_format(content) {
+        let content_type = match format {
+            ImageFormat::Png => "image/png",
+            ImageFormat::Gif => "image/gif",
+            ImageFormat::WebP => "image/webp",
+            _ => "image/jpeg",
+        };
+
+        return Some(SniffedMedia::new(MediaType::Photo, content_type));
+    }
+
+    // ISO base media files (MP4, QuickTime, M4A) start with an `ftyp` box holding their brand
+    if content.len() >= 12 && &content[4..8] == b"ftyp" {
+        return Some(match &content[8..12] {
+            b"M4A " | b"M4B " => SniffedMedia::new(MediaType::Audio, "audio/mp4"),
+            b"qt  " => SniffedMedia::new(MediaType::Video, "video/quicktime"),
+            _ => SniffedMedia::new(MediaType::Video, "video/mp4"),
+        });
+    }
+
+    // WebM is a Matroska (EBML) file whose header declares the `webm` doc type
+    if content.starts_with(&[0x1A, 0x45, 0xDF, 0xA3]) {
+        return content
+            .windows(4)
+            .any(|window| window == b"webm")
+            .then(|| SniffedMedia::new(MediaType::Video, "video/webm"));
+    }
+
+    if content.starts_with(b"ID3") || is_mpeg_audio_frame(content) {
+        return Some(SniffedMedia::new(MediaType::Audio, "audio/mpeg"));
+    }
+
+    if content.starts_with(b"OggS") {
+        return Some(SniffedMedia::new(MediaType::Audio, "audio/ogg"));
+    }
+
+    if content.starts_with(b"fLaC") {
+        return Some(SniffedMedia::new(MediaType::Audio, "audio/flac"));
+    }
+
+    if content.len() >= 12 && content.starts_with(b"RIFF") && &content[8..12] == b"WAVE" {
+        return Some(SniffedMedia::new(MediaType::Audio, "audio/wav"));
+    }
+
+    None
+}
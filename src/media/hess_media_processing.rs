@@ -0,0 +1,173 @@
+use std::io::Cursor;
+
+use image::imageops::FilterType;
+use symphonia::core::{
+    formats::FormatOptions, io::MediaSourceStream, meta::MetadataOptions, probe::Hint,
+};
+
+use crate::{models::MediaType, utils::response::ApiErrorType};
+
+use super::{
+    profile_image::{decode_image, encode_image},
+    video_metadata::read_video_metadata,
+};
+
+/// The maximum sizes (in pixels, of the longest side) of the thumbnails generated for every
+/// photo attached to a hess. Photos smaller than a size don't get its thumbnail.
+pub const HESS_MEDIA_THUMBNAIL_SIZES: &[u32] = &[320, 640, 1280];
+
+/// Photos wider or taller than this are refused before being decoded.
+const MAX_HESS_PHOTO_DIMENSION: u32 = 16384;
+
+/// The number of horizontal and vertical components of the blurhash placeholders.
+const BLURHASH_COMPONENTS: (u32, u32) = (4, 3);
+
+/// The derived assets and the metadata of a processed hess media.
+#[derive(Default)]
+pub struct ProcessedHessMedia {
+    pub width: Option<u32>,
+    pub height: Option<u32>,
+    pub duration_ms: Option<i64>,
+    pub blurhash: Option<String>,
+    /// The content type shared by the thumbnails.
+    pub thumbnail_content_type: Option<&'static str>,
+    /// The thumbnails, along with their size (see `HESS_MEDIA_THUMBNAIL_SIZES`).
+    pub thumbnails: Vec<(u32, Vec<u8>)>,
+}
+
+fn process_photo(content: &[u8]) -> Result<ProcessedHessMedia, ApiErrorType> {
+    let image = decode_image(content, MAX_HESS_PHOTO_DIMENSION)?;
+
+    let placeholder = image.thumbnail(32, 32).to_rgba8();
+    let blurhash = blurhash::encode(
+        BLURHASH_COMPONENTS.0,
+        BLURHASH_COMPONENTS.1,
+        placeholder.width(),
+        placeholder.height(),
+        placeholder.as_raw(),
+    )
+    .map_err(|_| ApiErrorType::InternalServerError)?;
+
+    let mut thumbnail_content_type = None;
+    let mut thumbnails = Vec::new();
+
+    for &size in HESS_MEDIA_THUMBNAIL_SIZES {
+        if image.width().max(image.height()) <= size {
+            break;
+        }
+
+        let thumbnail = image.resize(size, size, FilterType::Lanczos3);
+        let (content_type, content) =
+            encode_image(&thumbnail).map_err(|_| ApiErrorType::InternalServerError)?;
+
+        thumbnail_content_type = Some(content_type);
+        thumbnails.push((size, content));
+    }
+
+    Ok(ProcessedHessMedia {
+        width: Some(image.width()),
+        height: Some(image.height()),
+        blurhash: Some(blurhash),
+        thumbnail_content_type,
+        thumbnails,
+        ..Default::default()
+    })
+}
+
+fn process_video(content_type: &str, content: &[u8]) -> Result<ProcessedHessMedia, ApiErrorType> {
+    let metadata =
+        read_video_metadata(content_type, content).ok_or(ApiErrorType::UnsupportedMediaType)?;
+
+    Ok(ProcessedHessMedia {
+        width: metadata.width,
+        height: metadata.height,
+        duration_ms: metadata.duration_ms,
+        ..Default::default()
+    })
+}
+
+fn process_audio(content_type: &str, content: Vec<u8>) -> Result<ProcessedHessMedia, ApiErrorType> {
+    let source = MediaSourceStream::new(Box::new(Cursor::new(content)), Default::default());
+    let mut hint = Hint::new();
+    hint.mime_type(content_type);
+
+    let mut format = symphonia::default::get_probe()
+        .format(
+            &hint,
+            source,
+            &FormatOptions::default(),
+            &MetadataOptions::default(),
+        )
+        .map_err(|_| ApiErrorType::UnsupportedMediaType)?
+        .format;
+
+    let track = format
+        .default_track()
+        .ok_or(ApiErrorType::UnsupportedMediaType)?;
+    let track_id = track.id;
+    let time_base = track.codec_params.time_base.or_else(|| {
+        track
+            .codec_params
+            .sample_rate
+            .map(|sample_rate| symphonia::core::units::TimeBase::new(1, sample_rate))
+    });
+    let mut frames = track.codec_params.n_frames;
+
+    // Without a header telling it (e.g. MP3 without Xing header), the packets are counted
+    if frames.is_none() {
+        let mut total = 0;
+
+        while let Ok(packet) = format.next_packet() {
+            if packet.track_id() == track_id {
+                total += packet.dur;
+            }
+        }
+
+        frames = Some(total);
+    }
+
+    let duration = time_base
+        .zip(frames)
+        .map(|(time_base, frames)| time_base.calc_time(frames))
+        .ok_or(ApiErrorType::UnsupportedMediaType)?;
+
+    Ok(ProcessedHessMedia {
+        duration_ms: Some((duration.seconds as f64 * 1000.0 + duration.frac * 1000.0) as i64),
+        ..Default::default()
+    })
+}
+
+/// Extracts the metadata of an uploaded hess media and generates its derived assets.
+///
+/// - Photos: their dimensions (once oriented), a blurhash placeholder and thumbnails fitting
+///   `HESS_MEDIA_THUMBNAIL_SIZES` (re-encoded without metadata, like the profile images).
+/// - Videos: their dimensions and duration, read from the container (MP4, QuickTime or WebM)
+///   without decoding the video.
+/// - Audios: their duration.
+///
+/// This is CPU bound, run it with `tokio::task::spawn_blocking`.
+///
+/// # Arguments
+///
+/// * `media_type` - The sniffed type of the media.
+/// * `content_type` - The sniffed content type of the media.
+/// * `content` - The uploaded file.
+///
+/// # Returns
+///
+/// Returns a `Result` containing the processed media if successful.
+/// If the file can't be decoded (or parsed), returns an `ApiErrorType::NotAnImage` error for
+/// photos and an `ApiErrorType::UnsupportedMediaType` error otherwise.
+/// If the thumbnails can't be encoded, returns an `ApiErrorType::InternalServerError` error.
+///
+pub fn process_hess_media(
+    media_type: &MediaType,
+    content_type: &str,
+    content: Vec<u8>,
+) -> Result<ProcessedHessMedia, ApiErrorType> {
+    match media_type {
+        MediaType::Photo => process_photo(&content),
+        MediaType::Video => process_video(content_type, &content),
+        MediaType::Audio => process_audio(content_type, content),
+    }
+}
@@ -0,0 +1,16 @@
+mod captions;
+mod download;
+mod hess_media;
+mod hess_media_processing;
+mod multipart;
+mod profile_image;
+mod video_metadata;
+
+pub use captions::{parse_language_tag, parse_webvtt, CAPTIONS_CONTENT_TYPE};
+pub use download::{download_reply, CacheControl, ContentDisposition, Download, DownloadHeaders};
+pub use hess_media::{sniff_media, SniffedMedia};
+pub use hess_media_processing::{process_hess_media, HESS_MEDIA_THUMBNAIL_SIZES};
+pub use multipart::{read_multipart_file, spool_multipart_part, MULTIPART_OVERHEAD_BYTES};
+pub use profile_image::{
+    process_profile_image, ProcessedProfileImage, PROFILE_IMAGE_THUMBNAIL_SIZES,
+};
@@ -0,0 +1,272 @@
+use std::path::PathBuf;
+
+use async_trait::async_trait;
+use futures_util::TryStreamExt;
+use sha2::{Digest, Sha256};
+use tokio::io::AsyncWriteExt;
+use uuid::Uuid;
+use warp::{
+    multipart::{FormData, Part},
+    Buf,
+};
+
+use crate::{storage::digest_key, utils::response::ApiErrorType};
+
+/// Room left in the multipart body for the boundaries and the headers of the parts.
+pub const MULTIPART_OVERHEAD_BYTES: u64 = 16 * 1024;
+
+/// The number of bytes buffered before the maximum size of a part is decided
+/// (see `stream_multipart_part`), enough to sniff every supported format.
+const SNIFFING_LENGTH: usize = 64;
+
+/// Where the content of a file part is streamed to (see `stream_multipart_part`).
+#[async_trait]
+trait PartSink: Send {
+    async fn write(&mut self, bytes: &[u8]) -> Result<(), ApiErrorType>;
+}
+
+#[async_trait]
+impl PartSink for Vec<u8> {
+    async fn write(&mut self, bytes: &[u8]) -> Result<(), ApiErrorType> {
+        self.extend_from_slice(bytes);
+
+        Ok(())
+    }
+}
+
+/// A temporary file, hashing what's written to it.
+struct HashedFileSink {
+    file: tokio::fs::File,
+    hasher: Sha256,
+}
+
+#[async_trait]
+impl PartSink for HashedFileSink {
+    async fn write(&mut self, bytes: &[u8]) -> Result<(), ApiErrorType> {
+        self.hasher.update(bytes);
+
+        self.file
+            .write_all(bytes)
+            .await
+            .map_err(|_| ApiErrorType::InternalServerError)
+    }
+}
+
+fn ensure_named(part: &Part) -> Result<(), ApiErrorType> {
+    match part.filename().is_none_or(str::is_empty) {
+        true => Err(ApiErrorType::UnnamedMultipartFile),
+        false => Ok(()),
+    }
+}
+
+/// Streams a file part of a multipart form into `sink`, stopping as soon as it's bigger than its
+/// maximum size.
+///
+/// The maximum size can depend on the file itself (e.g. on its sniffed format): `max_size_of`
+/// is given the first `SNIFFING_LENGTH` bytes of the file (or the whole file when it's smaller)
+/// and is called only once.
+///
+/// # Returns
+///
+/// Returns a `Result` containing the size of the file if successful.
+/// If the file is empty, returns an `ApiErrorType::EmptyFile` error.
+/// If the file is too large, returns an `ApiErrorType::FileTooLarge` error.
+/// If the body isn't a valid multipart form, returns an `ApiErrorType::InvalidMultipartBody` error.
+/// Any error returned by `max_size_of` or by the sink is returned as is.
+///
+async fn stream_multipart_part<F, S>(
+    part: Part,
+    max_size_of: F,
+    sink: &mut S,
+) -> Result<u64, ApiErrorType>
+where
+    F: FnOnce(&[u8]) -> Result<u64, ApiErrorType>,
+    S: PartSink,
+{
+    let mut max_size_of = Some(max_size_of);
+    let mut max_size = None;
+    let mut head = Vec::with_capacity(SNIFFING_LENGTH);
+    let mut size = 0;
+    let mut stream = Box::pin(part.stream());
+
+    while let Some(mut chunk) = stream
+        .try_next()
+        .await
+        .map_err(|_| ApiErrorType::InvalidMultipartBody)?
+    {
+        while chunk.has_remaining() {
+            let bytes = chunk.chunk();
+            let length = bytes.len();
+
+            if head.len() < SNIFFING_LENGTH {
+                let missing = (SNIFFING_LENGTH - head.len()).min(length);
+                head.extend_from_slice(&bytes[..missing]);
+            }
+
+            sink.write(bytes).await?;
+
+            size += length as u64;
+            chunk.advance(length);
+        }
+
+        if head.len() >= SNIFFING_LENGTH {
+            if let Some(max_size_of) = max_size_of.take() {
+                max_size = Some(max_size_of(&head)?);
+            }
+        }
+
+        if max_size.is_some_and(|max_size| size > max_size) {
+            return Err(ApiErrorType::FileTooLarge);
+        }
+    }
+
+    if size == 0 {
+        return Err(ApiErrorType::EmptyFile);
+    }
+
+    if let Some(max_size_of) = max_size_of.take() {
+        if size > max_size_of(&head)? {
+            return Err(ApiErrorType::FileTooLarge);
+        }
+    }
+
+    Ok(size)
+}
+
+/// Reads a file part of a multipart form, streaming it into memory and stopping as soon as it's
+/// bigger than its maximum size (see `stream_multipart_part`).
+///
+/// # Arguments
+///
+/// * `part` - The part holding the file.
+/// * `max_size_of` - Decides the maximum size of the file, in bytes, from its first bytes.
+///
+/// # Returns
+///
+/// Returns a `Result` containing the content of the file if successful.
+/// If the part has no file name, returns an `ApiErrorType::UnnamedMultipartFile` error.
+/// Otherwise fails like `stream_multipart_part`.
+///
+pub async fn read_multipart_part<F>(part: Part, max_size_of: F) -> Result<Vec<u8>, ApiErrorType>
+where
+    F: FnOnce(&[u8]) -> Result<u64, ApiErrorType>,
+{
+    ensure_named(&part)?;
+
+    let mut content = Vec::new();
+
+    stream_multipart_part(part, max_size_of, &mut content).await?;
+
+    Ok(content)
+}
+
+/// A file part of a multipart form spooled to a temporary file, which is removed once dropped.
+pub struct SpooledFile {
+    pub path: PathBuf,
+    /// The content-addressed key of the file (see `content_key`).
+    pub key: String,
+    pub size: u64,
+}
+
+impl Drop for SpooledFile {
+    fn drop(&mut self) {
+        let path = std::mem::take(&mut self.path);
+
+        // Dropped by the handlers (even when their request is cancelled), away from the runtime
+        match tokio::runtime::Handle::try_current() {
+            Ok(runtime) => {
+                runtime.spawn_blocking(move || std::fs::remove_file(path));
+            }
+            Err(_) => {
+                let _ = std::fs::remove_file(path);
+            }
+        }
+    }
+}
+
+/// Reads a file part of a multipart form like `read_multipart_part`, but writes it to a temporary
+/// file chunk by chunk instead of holding it in memory, hashing it on the way.
+///
+/// # Arguments
+///
+/// * `part` - The part holding the file.
+/// * `max_size_of` - Decides the maximum size of the file, in bytes, from its first bytes.
+///
+/// # Returns
+///
+/// Returns a `Result` containing the spooled file if successful.
+/// If the temporary file can't be written, returns an `ApiErrorType::InternalServerError` error.
+/// Otherwise fails like `read_multipart_part`.
+///
+pub async fn spool_multipart_part<F>(
+    part: Part,
+    max_size_of: F,
+) -> Result<SpooledFile, ApiErrorType>
+where
+    F: FnOnce(&[u8]) -> Result<u64, ApiErrorType>,
+{
+    ensure_named(&part)?;
+
+    let path = std::env::temp_dir().join(format!("hesshub-upload-{}", Uuid::new_v4()));
+    let file = tokio::fs::File::create(&path)
+        .await
+        .map_err(|_| ApiErrorType::InternalServerError)?;
+
+    // Owned from now on, so the file is removed whenever the part is refused
+    let mut spooled_file = SpooledFile {
+        path,
+        key: String::new(),
+        size: 0,
+    };
+
+    let mut sink = HashedFileSink {
+        file,
+        hasher: Sha256::new(),
+    };
+
+    spooled_file.size = stream_multipart_part(part, max_size_of, &mut sink).await?;
+
+    sink.file
+        .flush()
+        .await
+        .map_err(|_| ApiErrorType::InternalServerError)?;
+
+    spooled_file.key = digest_key(&sink.hasher.finalize());
+
+    Ok(spooled_file)
+}
+
+/// Reads the file sent in the `field_name` part of a multipart form, streaming it into memory
+/// and stopping as soon as it's bigger than `max_size` bytes.
+///
+/// The other parts of the form are skipped.
+///
+/// # Arguments
+///
+/// * `form` - The multipart form data.
+/// * `field_name` - The name of the part holding the file.
+/// * `max_size` - The maximum size of the file, in bytes.
+///
+/// # Returns
+///
+/// Returns a `Result` containing the content of the file if successful.
+/// If there's no such part, returns an `ApiErrorType::NoImage` error.
+/// Otherwise fails like `read_multipart_part`.
+///
+pub async fn read_multipart_file(
+    mut form: FormData,
+    field_name: &str,
+    max_size: u64,
+) -> Result<Vec<u8>, ApiErrorType> {
+    while let Some(part) = form
+        .try_next()
+        .await
+        .map_err(|_| ApiErrorType::InvalidMultipartBody)?
+    {
+        if part.name() == field_name {
+            return read_multipart_part(part, |_| Ok(max_size)).await;
+        }
+    }
+
+    Err(ApiErrorType::NoImage)
+}
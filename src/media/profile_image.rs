@@ -0,0 +1,125 @@
+use std::io::Cursor;
+
+use image::{
+    codecs::jpeg::JpegEncoder, imageops::FilterType, DynamicImage, ImageDecoder, ImageFormat,
+    ImageReader, ImageResult, Limits,
+};
+
+use crate::utils::response::ApiErrorType;
+
+/// The sizes (in pixels) of the square thumbnails generated for every profile image.
+pub const PROFILE_IMAGE_THUMBNAIL_SIZES: &[u32] = &[48, 96, 200, 400];
+
+/// Images wider or taller than this are refused before being decoded.
+const MAX_PROFILE_IMAGE_DIMENSION: u32 = 8192;
+
+const JPEG_QUALITY: u8 = 90;
+
+/// A profile image ready to be stored, the original and the thumbnails share the same format.
+pub struct ProcessedProfileImage {
+    pub content_type: &'static str,
+    /// The dimensions of the original, once oriented.
+    pub width: u32,
+    pub height: u32,
+    pub original: Vec<u8>,
+    /// The thumbnails, along with their size (see `PROFILE_IMAGE_THUMBNAIL_SIZES`).
+    pub thumbnails: Vec<(u32, Vec<u8>)>,
+}
+
+/// Sniffs the format of an uploaded image from its magic bytes, whatever content type
+/// the client claimed. Only JPEG, PNG, GIF and WebP images are accepted.
+pub fn sniff_image_format(content: &[u8]) -> Option<ImageFormat> {
+    match image::guess_format(content) {
+        Ok(
+            format @ (ImageFormat::Jpeg | ImageFormat::Png | ImageFormat::Gif | ImageFormat::WebP),
+        ) => Some(format),
+        _ => None,
+    }
+}
+
+/// Encodes an image as PNG when it has transparency, as JPEG otherwise.
+pub(super) fn encode_image(image: &DynamicImage) -> ImageResult<(&'static str, Vec<u8>)> {
+    let mut content = Vec::new();
+
+    if image.color().has_alpha() {
+        DynamicImage::ImageRgba8(image.to_rgba8())
+            .write_to(&mut Cursor::new(&mut content), ImageFormat::Png)?;
+
+        Ok(("image/png", content))
+    } else {
+        JpegEncoder::new_with_quality(&mut content, JPEG_QUALITY).encode_image(&image.to_rgb8())?;
+
+        Ok(("image/jpeg", content))
+    }
+}
+
+/// Decodes an uploaded image (sniffed with `sniff_image_format`), rotated according to its
+/// EXIF orientation. Images wider or taller than `max_dimension` are refused before being decoded.
+pub(super) fn decode_image(
+    content: &[u8],
+    max_dimension: u32,
+) -> Result<DynamicImage, ApiErrorType> {
+    let format = sniff_image_format(content).ok_or(ApiErrorType::NotAnImage)?;
+
+    let mut limits = Limits::default();
+    limits.max_image_width = Some(max_dimension);
+    limits.max_image_height = Some(max_dimension);
+
+    let mut reader = ImageReader::with_format(Cursor::new(content), format);
+    reader.limits(limits);
+
+    let mut decoder = reader
+        .into_decoder()
+        .map_err(|_| ApiErrorType::NotAnImage)?;
+    let orientation = decoder
+        .orientation()
+        .map_err(|_| ApiErrorType::NotAnImage)?;
+
+    let mut image = DynamicImage::from_decoder(decoder).map_err(|_| ApiErrorType::NotAnImage)?;
+    image.apply_orientation(orientation);
+
+    Ok(image)
+}
+
+/// Decodes an uploaded profile image and re-encodes it along with its square thumbnails.
+///
+/// The image is rotated according to its EXIF orientation before being re-encoded, which drops
+/// every metadata (EXIF, XMP, ICC...) of the upload. Only the first frame of animated images is
+/// kept, and the thumbnails are cropped around the center.
+///
+/// This is CPU bound, run it with `tokio::task::spawn_blocking`.
+///
+/// # Arguments
+///
+/// * `content` - The uploaded file.
+///
+/// # Returns
+///
+/// Returns a `Result` containing the processed image if successful.
+/// If the file isn't a (supported) image, or is too large to be decoded, returns an `ApiErrorType::NotAnImage` error.
+/// If the image can't be re-encoded, returns an `ApiErrorType::InternalServerError` error.
+///
+pub fn process_profile_image(content: &[u8]) -> Result<ProcessedProfileImage, ApiErrorType> {
+    let image = decode_image(content, MAX_PROFILE_IMAGE_DIMENSION)?;
+
+    let (content_type, original) =
+        encode_image(&image).map_err(|_| ApiErrorType::InternalServerError)?;
+
+    let thumbnails = PROFILE_IMAGE_THUMBNAIL_SIZES
+        .iter()
+        .map(|&size| {
+            let thumbnail = image.resize_to_fill(size, size, FilterType::Lanczos3);
+
+            encode_image(&thumbnail).map(|(_, content)| (size, content))
+        })
+        .collect::<ImageResult<Vec<_>>>()
+        .map_err(|_| ApiErrorType::InternalServerError)?;
+
+    Ok(ProcessedProfileImage {
+        content_type,
+        width: image.width(),
+        height: image.height(),
+        original,
+        thumbnails,
+    })
+}
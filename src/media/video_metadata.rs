@@ -0,0 +1,213 @@
+/// The metadata of a video, read from its container.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct VideoMetadata {
+    pub width: Option<u32>,
+    pub height: Option<u32>,
+    pub duration_ms: Option<i64>,
+}
+
+fn read_u32(content: &[u8], offset: usize) -> Option<u32> {
+    let bytes = content.get(offset..offset + 4)?;
+    Some(u32::from_be_bytes(bytes.try_into().ok()?))
+}
+
+fn read_u64(content: &[u8], offset: usize) -> Option<u64> {
+    let bytes = content.get(offset..offset + 8)?;
+    Some(u64::from_be_bytes(bytes.try_into().ok()?))
+}
+
+/// Iterates over the boxes of an ISO base media (MP4, QuickTime) container level,
+/// yielding their type and their content.
+fn iso_boxes(content: &[u8]) -> impl Iterator<Item = (&[u8], &[u8])> {
+    let mut offset = 0;
+
+    std::iter::from_fn(move || {
+        let size = read_u32(content, offset)? as usize;
+        let box_type = content.get(offset + 4..offset + 8)?;
+
+        let (header_size, size) = match size {
+            0 => (8, content.len() - offset),
+            1 => (16, read_u64(content, offset + 8)? as usize),
+            size => (8, size),
+        };
+
+        let end = offset.checked_add(size)?.min(content.len());
+        let body = content.get(offset + header_size..end)?;
+        offset = end.max(offset + header_size);
+
+        Some((box_type, body))
+    })
+}
+
+/// Reads the duration (from the `mvhd` box) and the dimensions (from the first `tkhd` box of a
+/// visual track) of an MP4 or QuickTime video.
+fn read_iso_metadata(content: &[u8]) -> Option<VideoMetadata> {
+    let (_, moov) = iso_boxes(content).find(|(box_type, _)| *box_type == b"moov")?;
+    let mut metadata = VideoMetadata::default();
+
+    for (box_type, body) in iso_boxes(moov) {
+        match box_type {
+            b"mvhd" => {
+                // Full box header, then the creation and modification times (32 or 64 bits)
+                let (timescale, duration) = match body.first()? {
+                    1 => (read_u32(body, 20)?, read_u64(body, 24)?),
+                    _ => (read_u32(body, 12)?, read_u32(body, 16)? as u64),
+                };
+
+                if timescale > 0 {
+                    metadata.duration_ms = Some((duration * 1000 / timescale as u64) as i64);
+                }
+            }
+            b"trak" if metadata.width.is_none() => {
+                if let Some((_, tkhd)) = iso_boxes(body).find(|(box_type, _)| *box_type == b"tkhd")
+                {
+                    // The dimensions are 16.16 fixed-point numbers ending the box
+                    let width = read_u32(tkhd, tkhd.len().checked_sub(8)?)? >> 16;
+                    let height = read_u32(tkhd, tkhd.len().checked_sub(4)?)? >> 16;
+
+                    if width > 0 && height > 0 {
+                        metadata.width = Some(width);
+                        metadata.height = Some(height);
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    Some(metadata)
+}
+
+/// Reads an EBML variable size integer, returning it along with its length.
+/// The length marker is kept for element IDs and dropped for sizes.
+fn read_ebml_vint(content: &[u8], offset: usize, keep_marker: bool) -> Option<(u64, usize)> {
+    let first = *content.get(offset)?;
+    let length = first.leading_zeros() as usize + 1;
+
+    if length > 8 {
+        return None;
+    }
+
+    let mut value = if keep_marker {
+        first as u64
+    } else {
+        (first as u64) & (0xFF >> length)
+    };
+
+    for index in 1..length {
+        value = (value << 8) | *content.get(offset + index)? as u64;
+    }
+
+    Some((value, length))
+}
+
+/// Iterates over the elements of an EBML (Matroska, WebM) master element,
+/// yielding their ID and their content.
+fn ebml_elements(content: &[u8]) -> impl Iterator<Item = (u64, &[u8])> {
+    let mut offset = 0;
+
+    std::iter::from_fn(move || {
+        let (id, id_length) = read_ebml_vint(content, offset, true)?;
+        let (size, size_length) = read_ebml_vint(content, offset + id_length, false)?;
+        let start = offset + id_length + size_length;
+
+        // All the bits set means an unknown size, which lasts until the end of the parent
+        let end = if size == (1 << (7 * size_length)) - 1 {
+            content.len()
+        } else {
+            start.checked_add(size as usize)?.min(content.len())
+        };
+
+        offset = end;
+        content.get(start..end).map(|body| (id, body))
+    })
+}
+
+fn read_ebml_uint(body: &[u8]) -> u64 {
+    body.iter()
+        .fold(0, |value, byte| (value << 8) | *byte as u64)
+}
+
+fn read_ebml_float(body: &[u8]) -> Option<f64> {
+    match body.len() {
+        4 => Some(f32::from_be_bytes(body.try_into().ok()?) as f64),
+        8 => Some(f64::from_be_bytes(body.try_into().ok()?)),
+        _ => None,
+    }
+}
+
+const EBML_SEGMENT: u64 = 0x18538067;
+const EBML_INFO: u64 = 0x1549A966;
+const EBML_TIMECODE_SCALE: u64 = 0x2AD7B1;
+const EBML_DURATION: u64 = 0x4489;
+const EBML_TRACKS: u64 = 0x1654AE6B;
+const EBML_TRACK_ENTRY: u64 = 0xAE;
+const EBML_VIDEO: u64 = 0xE0;
+const EBML_PIXEL_WIDTH: u64 = 0xB0;
+const EBML_PIXEL_HEIGHT: u64 = 0xBA;
+
+/// Reads the duration (from the `Info` element) and the dimensions (from the first video track)
+/// of a WebM video.
+fn read_webm_metadata(content: &[u8]) -> Option<VideoMetadata> {
+    let (_, segment) = ebml_elements(content).find(|(id, _)| *id == EBML_SEGMENT)?;
+    let mut metadata = VideoMetadata::default();
+
+    for (id, body) in ebml_elements(segment) {
+        match id {
+            EBML_INFO => {
+                let mut timecode_scale = 1_000_000;
+                let mut duration = None;
+
+                for (id, body) in ebml_elements(body) {
+                    match id {
+                        EBML_TIMECODE_SCALE => timecode_scale = read_ebml_uint(body),
+                        EBML_DURATION => duration = read_ebml_float(body),
+                        _ => {}
+                    }
+                }
+
+                metadata.duration_ms =
+                    duration.map(|duration| (duration * timecode_scale as f64 / 1e6) as i64);
+            }
+            EBML_TRACKS => {
+                let video = ebml_elements(body)
+                    .filter(|(id, _)| *id == EBML_TRACK_ENTRY)
+                    .find_map(|(_, track)| ebml_elements(track).find(|(id, _)| *id == EBML_VIDEO));
+
+                if let Some((_, video)) = video {
+                    for (id, body) in ebml_elements(video) {
+                        match id {
+                            EBML_PIXEL_WIDTH => metadata.width = Some(read_ebml_uint(body) as u32),
+                            EBML_PIXEL_HEIGHT => {
+                                metadata.height = Some(read_ebml_uint(body) as u32)
+                            }
+                            _ => {}
+                        }
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    Some(metadata)
+}
+
+/// Reads the dimensions and the duration of a video from its container, without decoding it.
+///
+/// # Arguments
+///
+/// * `content_type` - The sniffed content type of the video (see `sniff_media`).
+/// * `content` - The video.
+///
+/// # Returns
+///
+/// Returns the metadata found, or `None` when the container can't be parsed.
+///
+pub fn read_video_metadata(content_type: &str, content: &[u8]) -> Option<VideoMetadata> {
+    match content_type {
+        "video/mp4" | "video/quicktime" => read_iso_metadata(content),
+        "video/webm" => read_webm_metadata(content),
+        _ => None,
+    }
+}
@@ -0,0 +1,66 @@
+use chrono::{DateTime, Utc};
+use serde_derive::{Deserialize, Serialize};
+use sqlx::Type;
+use uuid::Uuid;
+
+use super::Message;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, Type)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+#[sqlx(
+    type_name = "CONVERSATION_PARTICIPANT_STATUS",
+    rename_all = "SCREAMING_SNAKE_CASE"
+)]
+pub enum ConversationParticipantStatus {
+    /// The conversation is in the inbox of the participant.
+    Accepted,
+
+    /// A message request, the participant doesn't follow whoever added them.
+    Pending,
+
+    /// The message request was declined, the conversation is hidden from the participant.
+    Declined,
+}
+
+/// A participant of a conversation, with their read receipt.
+#[derive(Debug, Serialize, Deserialize, sqlx::FromRow, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ConversationParticipant {
+    pub user_id: Uuid,
+    pub username: String,
+    pub name: String,
+    pub verified: bool,
+    pub status: ConversationParticipantStatus,
+    /// The latest message the participant read (absent for message requests, not to tell the
+    /// sender they were seen).
+    pub last_read_message_id: Option<Uuid>,
+    pub last_read_at: Option<DateTime<Utc>>,
+    pub joined_at: DateTime<Utc>,
+}
+
+/// A conversation as seen by one of its participants.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct Conversation {
+    pub id: Uuid,
+    pub creator_id: Uuid,
+    pub title: Option<String>,
+    pub is_group: bool,
+    /// The status of the conversation for the viewer.
+    pub status: ConversationParticipantStatus,
+    /// The participants who didn't leave, the viewer included.
+    pub participants: Vec<ConversationParticipant>,
+    pub last_message: Option<Message>,
+    /// The messages of the others the viewer didn't read yet.
+    pub unread_count: i64,
+    pub last_message_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ConversationsPage {
+    pub items: Vec<Conversation>,
+    /// Opaque cursor to pass back to get the next page, absent on the last page.
+    pub next_cursor: Option<String>,
+}
@@ -0,0 +1,48 @@
+use chrono::{DateTime, Utc};
+use serde_derive::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+
+/// A user listed in a digest.
+#[derive(Debug, Serialize, Deserialize, FromRow, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct DigestUser {
+    pub id: Uuid,
+    pub name: String,
+    pub username: String,
+}
+
+/// A hess listed in a digest, with its author.
+#[derive(Debug, Serialize, Deserialize, FromRow, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct DigestHess {
+    pub id: Uuid,
+    pub content: Option<String>,
+    pub author_name: String,
+    pub author_username: String,
+    pub likes_count: i64,
+    pub created_at: DateTime<Utc>,
+}
+
+/// The activity a user missed since a date, as summarized in their email digest.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct Digest {
+    /// The latest new followers (empty if the user turned off the email channel of follows).
+    pub new_followers: Vec<DigestUser>,
+    /// All the new followers, including the ones not listed.
+    pub new_followers_count: i64,
+    /// The most liked hesses of the followed users.
+    pub top_hesses: Vec<DigestHess>,
+    /// The hesses mentioning the user, whose notification is still unread (empty if the user
+    /// turned off the email channel of mentions).
+    pub unread_mentions: Vec<DigestHess>,
+}
+
+impl Digest {
+    pub fn is_empty(&self) -> bool {
+        self.new_followers_count == 0
+            && self.top_hesses.is_empty()
+            && self.unread_mentions.is_empty()
+    }
+}
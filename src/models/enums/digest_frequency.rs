@@ -0,0 +1,11 @@
+use serde_derive::{Deserialize, Serialize};
+use sqlx::Type;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, Type)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+#[sqlx(type_name = "DIGEST_FREQUENCY", rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum DigestFrequency {
+    Never,
+    Daily,
+    Weekly,
+}
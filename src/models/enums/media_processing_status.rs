@@ -0,0 +1,15 @@
+use serde_derive::{Deserialize, Serialize};
+use sqlx::Type;
+
+/// Where an uploaded hess media is in its background processing.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize, Type)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+#[sqlx(
+    type_name = "MEDIA_PROCESSING_STATUS",
+    rename_all = "SCREAMING_SNAKE_CASE"
+)]
+pub enum MediaProcessingStatus {
+    Pending,
+    Ready,
+    Failed,
+}
@@ -1,9 +1,14 @@
+mod digest_frequency;
 mod gender;
+mod media_processing_status;
 mod media_type;
 mod user_role;
 mod who_can;
 
+pub use digest_frequency::DigestFrequency;
 pub use gender::Gender;
+pub use media_processing_status::MediaProcessingStatus;
 pub use media_type::MediaType;
 pub use user_role::UserRole;
 pub use who_can::WhoCan;
+pub use who_can::WHO_CAN_LIST_PREFIX;
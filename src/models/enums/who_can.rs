@@ -1,10 +1,100 @@
+use std::{fmt, str::FromStr};
+
 use serde_derive::{Deserialize, Serialize};
-use sqlx::Type;
+use sqlx::{
+    encode::IsNull,
+    error::BoxDynError,
+    postgres::{PgArgumentBuffer, PgHasArrayType, PgTypeInfo, PgValueRef},
+    Decode, Encode, Postgres, Type,
+};
+use uuid::Uuid;
+
+/// The prefix used to reference an audience list inside a `WhoCan` value (`LIST:<list id>`).
+pub const WHO_CAN_LIST_PREFIX: &str = "LIST:";
 
-#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize, Type)]
-#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
-#[sqlx(type_name = "WHO_CAN", rename_all = "SCREAMING_SNAKE_CASE")]
+/// An audience that a `who_can_*` preference can be granted to.
+///
+/// It's stored (and exchanged over the API) as text, either `FOLLOWED`, `FOLLOWERS`
+/// or `LIST:<list id>` when it references one of the owner's audience lists.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(into = "String", try_from = "String")]
 pub enum WhoCan {
     Followed,
     Followers,
+    List(Uuid),
+}
+
+impl fmt::Display for WhoCan {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            WhoCan::Followed => write!(f, "FOLLOWED"),
+            WhoCan::Followers => write!(f, "FOLLOWERS"),
+            WhoCan::List(id) => write!(f, "{}{}", WHO_CAN_LIST_PREFIX, id),
+        }
+    }
+}
+
+impl FromStr for WhoCan {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "FOLLOWED" => Ok(WhoCan::Followed),
+            "FOLLOWERS" => Ok(WhoCan::Followers),
+            _ => match s.strip_prefix(WHO_CAN_LIST_PREFIX) {
+                Some(id) => Uuid::parse_str(id)
+                    .map(WhoCan::List)
+                    .map_err(|_| format!("invalid audience list id `{}`", id)),
+                None => Err(format!("invalid who can value `{}`", s)),
+            },
+        }
+    }
+}
+
+impl From<WhoCan> for String {
+    fn from(value: WhoCan) -> Self {
+        value.to_string()
+    }
+}
+
+impl TryFrom<String> for WhoCan {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        value.parse()
+    }
+}
+
+impl Type<Postgres> for WhoCan {
+    fn type_info() -> PgTypeInfo {
+        <String as Type<Postgres>>::type_info()
+    }
+
+    fn compatible(ty: &PgTypeInfo) -> bool {
+        <String as Type<Postgres>>::compatible(ty)
+    }
+}
+
+impl PgHasArrayType for WhoCan {
+    fn array_type_info() -> PgTypeInfo {
+        <String as PgHasArrayType>::array_type_info()
+    }
+
+    fn array_compatible(ty: &PgTypeInfo) -> bool {
+        <String as PgHasArrayType>::array_compatible(ty)
+    }
+}
+
+impl Encode<'_, Postgres> for WhoCan {
+    fn encode_by_ref(&self, buf: &mut PgArgumentBuffer) -> IsNull {
+        <String as Encode<Postgres>>::encode(self.to_string(), buf)
+    }
+}
+
+impl<'r> Decode<'r, Postgres> for WhoCan {
+    fn decode(value: PgValueRef<'r>) -> Result<Self, BoxDynError> {
+        let value = <&str as Decode<Postgres>>::decode(value)?;
+
+        Ok(value.parse()?)
+    }
 }
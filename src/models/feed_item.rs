@@ -0,0 +1,56 @@
+use chrono::{DateTime, Utc};
+use serde_derive::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use super::Hess;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum FeedItemType {
+    /// A followed user posted a new hess.
+    NewHess,
+
+    /// A followed user replied to a hess.
+    Reply,
+
+    /// A followed user liked a hess ("X liked this").
+    Like,
+
+    /// A followed user followed someone ("X followed Y").
+    Follow,
+}
+
+impl FeedItemType {
+    pub fn from_sql_str(value: &str) -> Option<FeedItemType> {
+        match value {
+            "NEW_HESS" => Some(FeedItemType::NewHess),
+            "REPLY" => Some(FeedItemType::Reply),
+            "LIKE" => Some(FeedItemType::Like),
+            "FOLLOW" => Some(FeedItemType::Follow),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct FeedItem {
+    /// The ID of the underlying event (hess, like or follower relationship).
+    pub id: Uuid,
+    pub item_type: FeedItemType,
+    pub occurred_at: DateTime<Utc>,
+    /// The followed user behind the event.
+    pub actor_id: Uuid,
+    /// The hess the event is about (absent for follows).
+    pub hess: Option<Hess>,
+    /// The user that got followed (only for follows).
+    pub followed_user_id: Option<Uuid>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct FeedPage {
+    pub items: Vec<FeedItem>,
+    /// Opaque cursor to pass back to get the next page, absent on the last page.
+    pub next_cursor: Option<String>,
+}
@@ -3,7 +3,7 @@ use serde_derive::{Deserialize, Serialize};
 use sqlx::FromRow;
 use uuid::Uuid;
 
-use super::WhoCan;
+use super::{HessMedia, WhoCan};
 
 #[derive(Debug, Serialize, Deserialize, FromRow, Clone)]
 #[serde(rename_all = "camelCase")]
@@ -19,4 +19,8 @@ pub struct Hess {
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
     pub deleted_at: Option<DateTime<Utc>>,
+    /// The (processed) media attached to the hess, filled in the hess responses.
+    #[sqlx(skip)]
+    #[serde(default)]
+    pub media: Vec<HessMedia>,
 }
@@ -1,9 +1,11 @@
+use std::collections::BTreeMap;
+
 use chrono::{DateTime, Utc};
 use serde_derive::{Deserialize, Serialize};
-use sqlx::FromRow;
+use sqlx::{types::Json, FromRow};
 use uuid::Uuid;
 
-use super::MediaType;
+use super::{MediaProcessingStatus, MediaType};
 
 #[derive(Debug, Serialize, Deserialize, FromRow, Clone)]
 #[serde(rename_all = "camelCase")]
@@ -12,6 +14,24 @@ pub struct HessMedia {
     pub hess_id: Uuid,
     pub media_type: MediaType,
     pub media_url: String,
+    pub content_type: String,
+    pub size_bytes: i64,
+    pub processing_status: MediaProcessingStatus,
+    /// The dimensions of photos and videos, once processed.
+    pub width: Option<i32>,
+    pub height: Option<i32>,
+    /// The duration of videos and audios, once processed.
+    pub duration_ms: Option<i64>,
+    /// A placeholder of photos, shown while they're loading.
+    pub blurhash: Option<String>,
+    /// The keys of the thumbnails of photos, by size.
+    pub thumbnail_urls: Json<BTreeMap<u32, String>>,
+    pub thumbnail_content_type: Option<String>,
+    pub processed_at: Option<DateTime<Utc>>,
+    /// The alternative text of the media, describing it to who can't see or hear it.
+    pub alt_text: Option<String>,
+    /// The languages of the WebVTT captions (or transcripts) of videos and audios.
+    pub caption_languages: Vec<String>,
     pub created_at: DateTime<Utc>,
     pub deleted_at: Option<DateTime<Utc>>,
 }
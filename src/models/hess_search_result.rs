@@ -0,0 +1,16 @@
+use serde_derive::{Deserialize, Serialize};
+use sqlx::FromRow;
+
+use super::Hess;
+
+#[derive(Debug, Serialize, Deserialize, FromRow, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct HessSearchResult {
+    #[sqlx(flatten)]
+    pub hess: Hess,
+    /// The relevance of the hess, 0 when the query has no full-text part.
+    pub rank: f32,
+    /// Excerpts of the content with the matches wrapped in `<mark></mark>`,
+    /// absent when the query has no full-text part.
+    pub snippet: Option<String>,
+}
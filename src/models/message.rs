@@ -0,0 +1,33 @@
+use chrono::{DateTime, Utc};
+use serde_derive::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use super::MediaType;
+
+/// A hess media referenced by a message, served by the hess media routes (to who can view the hess).
+#[derive(Debug, Serialize, Deserialize, sqlx::FromRow, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct MessageMedia {
+    pub hess_media_id: Uuid,
+    pub hess_id: Uuid,
+    pub media_type: MediaType,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct Message {
+    pub id: Uuid,
+    pub conversation_id: Uuid,
+    pub sender_id: Uuid,
+    pub content: Option<String>,
+    pub media: Vec<MessageMedia>,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct MessagesPage {
+    pub items: Vec<Message>,
+    /// Opaque cursor to pass back to get the next page, absent on the last page.
+    pub next_cursor: Option<String>,
+}
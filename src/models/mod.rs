@@ -1,30 +1,94 @@
+mod audience_list;
+mod audience_list_member;
 mod blocked_user;
+mod conversation;
+mod device_token;
+mod digest;
 mod enums;
+mod feed_item;
 mod follow_request;
 mod follower;
 mod hess;
 mod hess_like;
 mod hess_media;
 mod hess_mention;
+mod hess_search_result;
+mod job;
+mod message;
+mod muted_user;
+mod notification;
+mod page;
 mod password_reset_token;
+#[cfg(feature = "tantivy-search")]
+mod search_index_document;
+mod signed_url;
+mod stream_event;
 mod user;
 mod user_confirmation_token;
+mod user_notification_preferences;
+mod user_overview;
 mod user_privacy_preferences;
 mod user_profile_image;
+mod user_role_change;
+mod user_search_result;
+mod verification_request;
 
+pub use audience_list::AudienceList;
+pub use audience_list_member::AudienceListMember;
 pub use blocked_user::BlockedUser;
+pub use conversation::Conversation;
+pub use conversation::ConversationParticipant;
+pub use conversation::ConversationParticipantStatus;
+pub use conversation::ConversationsPage;
+pub use device_token::DeviceToken;
+pub use digest::Digest;
+pub use digest::DigestHess;
+pub use digest::DigestUser;
+pub use enums::DigestFrequency;
 pub use enums::Gender;
+pub use enums::MediaProcessingStatus;
 pub use enums::MediaType;
 pub use enums::UserRole;
 pub use enums::WhoCan;
+pub use enums::WHO_CAN_LIST_PREFIX;
+pub use feed_item::FeedItem;
+pub use feed_item::FeedItemType;
+pub use feed_item::FeedPage;
 pub use follow_request::FollowRequest;
 pub use follow_request::FollowRequestStatus;
 pub use follower::Follower;
 pub use hess::Hess;
 pub use hess_like::HessLike;
+pub use hess_media::HessMedia;
 pub use hess_mention::HessMention;
+pub use hess_search_result::HessSearchResult;
+pub use job::Job;
+pub use message::Message;
+pub use message::MessageMedia;
+pub use message::MessagesPage;
+pub use muted_user::MutedUser;
+pub use notification::Notification;
+pub use notification::NotificationActor;
+pub use notification::NotificationType;
+pub use notification::NotificationsPage;
+pub use page::Page;
 pub use password_reset_token::PasswordResetToken;
+#[cfg(feature = "tantivy-search")]
+pub use search_index_document::HessIndexDocument;
+#[cfg(feature = "tantivy-search")]
+pub use search_index_document::UserIndexDocument;
+pub use signed_url::SignedUrl;
+pub use stream_event::StreamEvent;
 pub use user::User;
 pub use user_confirmation_token::UserConfirmationToken;
+pub use user_notification_preferences::NotificationChannels;
+pub use user_notification_preferences::QuietHours;
+pub use user_notification_preferences::UserNotificationPreferences;
+pub use user_overview::UserOverview;
 pub use user_privacy_preferences::UserPrivacyPreferences;
 pub use user_profile_image::UserProfileImage;
+pub use user_role_change::UserRoleChange;
+pub use user_search_result::UserSearchResult;
+pub use user_search_result::UserSuggestion;
+pub use verification_request::VerificationRequest;
+pub use verification_request::VerificationRequestStatus;
@@ -0,0 +1,134 @@
+use chrono::{DateTime, Utc};
+use serde_derive::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum NotificationType {
+    /// Someone followed the user (grouped).
+    Follow,
+
+    /// Someone requested to follow the user.
+    FollowRequest,
+
+    /// Someone approved the follow request of the user.
+    FollowRequestApproved,
+
+    /// Someone liked a hess of the user (grouped by hess).
+    Like,
+
+    /// Someone replied to a hess of the user.
+    Reply,
+
+    /// Someone mentioned the user in a hess.
+    Mention,
+}
+
+impl NotificationType {
+    pub fn from_sql_str(value: &str) -> Option<NotificationType> {
+        match value {
+            "FOLLOW" => Some(NotificationType::Follow),
+            "FOLLOW_REQUEST" => Some(NotificationType::FollowRequest),
+            "FOLLOW_REQUEST_APPROVED" => Some(NotificationType::FollowRequestApproved),
+            "LIKE" => Some(NotificationType::Like),
+            "REPLY" => Some(NotificationType::Reply),
+            "MENTION" => Some(NotificationType::Mention),
+            _ => None,
+        }
+    }
+
+    pub fn as_sql_str(&self) -> &'static str {
+        match self {
+            NotificationType::Follow => "FOLLOW",
+            NotificationType::FollowRequest => "FOLLOW_REQUEST",
+            NotificationType::FollowRequestApproved => "FOLLOW_REQUEST_APPROVED",
+            NotificationType::Like => "LIKE",
+            NotificationType::Reply => "REPLY",
+            NotificationType::Mention => "MENTION",
+        }
+    }
+
+    /// The key of the notifications an event is merged into while they're unread, the likes
+    /// of a hess and the new followers are grouped, other events are their own group.
+    pub fn group_key(&self, event_id: Uuid, hess_id: Option<Uuid>) -> String {
+        match (self, hess_id) {
+            (NotificationType::Follow, _) => "FOLLOW".to_string(),
+            (NotificationType::Like, Some(hess_id)) => format!("LIKE:{}", hess_id),
+            _ => format!("{}:{}", self.as_sql_str(), event_id),
+        }
+    }
+
+    /// A short title, e.g. the title of push notifications.
+    pub fn title(&self) -> &'static str {
+        match self {
+            NotificationType::Follow => "New follower",
+            NotificationType::FollowRequest => "New follow request",
+            NotificationType::FollowRequestApproved => "Follow request approved",
+            NotificationType::Like => "New like",
+            NotificationType::Reply => "New reply",
+            NotificationType::Mention => "New mention",
+        }
+    }
+
+    /// Describes a notification, e.g. "@alice and 12 others liked your hess".
+    ///
+    /// # Arguments
+    ///
+    /// * `username` - The username of the latest actor.
+    /// * `others_count` - The number of other actors merged into the notification.
+    ///
+    pub fn summary(&self, username: &str, others_count: usize) -> String {
+        let actors = match others_count {
+            0 => format!("@{}", username),
+            1 => format!("@{} and 1 other", username),
+            others_count => format!("@{} and {} others", username, others_count),
+        };
+
+        match self {
+            NotificationType::Follow => format!("{} followed you", actors),
+            NotificationType::FollowRequest => format!("{} wants to follow you", actors),
+            NotificationType::FollowRequestApproved => {
+                format!("{} approved your follow request", actors)
+            }
+            NotificationType::Like => format!("{} liked your hess", actors),
+            NotificationType::Reply => format!("{} replied to your hess", actors),
+            NotificationType::Mention => format!("{} mentioned you", actors),
+        }
+    }
+}
+
+/// A user behind a notification.
+#[derive(Debug, Serialize, Deserialize, FromRow, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct NotificationActor {
+    pub id: Uuid,
+    pub username: String,
+    pub name: String,
+    pub verified: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct Notification {
+    pub id: Uuid,
+    pub notification_type: NotificationType,
+    /// The hess the notification is about (the liked hess, the reply or the hess mentioning).
+    pub hess_id: Option<Uuid>,
+    /// The latest actors (up to 3), latest first.
+    pub actors: Vec<NotificationActor>,
+    /// The number of actors merged into the notification.
+    pub actors_count: usize,
+    /// Describes the notification, e.g. "@alice and 12 others liked your hess".
+    pub summary: String,
+    pub occurred_at: DateTime<Utc>,
+    pub read_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct NotificationsPage {
+    pub items: Vec<Notification>,
+    /// Opaque cursor to pass back to get the next page, absent on the last page.
+    pub next_cursor: Option<String>,
+}
@@ -0,0 +1,12 @@
+use serde_derive::{Deserialize, Serialize};
+
+/// The standard envelope of the paginated list endpoints (see `ListQuery`).
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct Page<T> {
+    pub items: Vec<T>,
+    /// Opaque cursor to pass back to get the next page, absent on the last page.
+    pub next_cursor: Option<String>,
+    /// The number of rows in the whole list (all pages).
+    pub total: i64,
+}
@@ -0,0 +1,16 @@
+use uuid::Uuid;
+
+/// The searchable part of a hess, as indexed by an external search index.
+#[derive(Debug, sqlx::FromRow, Clone)]
+pub struct HessIndexDocument {
+    pub id: Uuid,
+    pub content: Option<String>,
+}
+
+/// The searchable part of a user, as indexed by an external search index.
+#[derive(Debug, sqlx::FromRow, Clone)]
+pub struct UserIndexDocument {
+    pub id: Uuid,
+    pub username: String,
+    pub name: String,
+}
@@ -0,0 +1,10 @@
+use chrono::{DateTime, Utc};
+use serde_derive::Serialize;
+
+/// A time-limited URL a blob can be downloaded from without authentication.
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct SignedUrl {
+    pub url: String,
+    pub expires_at: DateTime<Utc>,
+}
@@ -0,0 +1,19 @@
+use chrono::{DateTime, Utc};
+use serde_derive::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// A real-time event sent to the connections of a user (see `src/stream`), as stored in the
+/// `stream_events` table and announced on the `stream_events` channel.
+///
+/// Its `event_type` is either `NOTIFICATION`, `FEED_ITEM`, `FOLLOW_REQUEST`, `MESSAGE` or
+/// `MESSAGE_READ`.
+#[derive(Debug, Serialize, Deserialize, sqlx::FromRow, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct StreamEvent {
+    pub id: i64,
+    #[serde(skip_serializing)]
+    pub user_id: Uuid,
+    pub event_type: String,
+    pub payload: serde_json::Value,
+    pub created_at: DateTime<Utc>,
+}
@@ -0,0 +1,189 @@
+use chrono::{DateTime, Duration, NaiveTime, Utc};
+use serde_derive::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use super::{DigestFrequency, NotificationType};
+
+/// The channels an event is notified through.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy)]
+#[serde(rename_all = "camelCase")]
+pub struct NotificationChannels {
+    /// Stored in the notifications of the user.
+    pub in_app: bool,
+
+    /// Pushed to the devices of the user (outside of the quiet hours).
+    pub push: bool,
+
+    /// Summarized in the email digests of the user (new followers and mentions only).
+    pub email: bool,
+}
+
+/// The hours during which nothing is pushed nor emailed, in the timezone of the user. The end is
+/// excluded and may be before the start for quiet hours spanning midnight (e.g. 22:00 to 07:00).
+#[derive(Debug, Serialize, Deserialize, Clone, Copy)]
+#[serde(rename_all = "camelCase")]
+pub struct QuietHours {
+    pub start: NaiveTime,
+    pub end: NaiveTime,
+}
+
+impl QuietHours {
+    /// Whether a time of day (in the timezone of the user) is within the quiet hours.
+    pub fn contains(&self, time: NaiveTime) -> bool {
+        if self.start <= self.end {
+            time >= self.start && time < self.end
+        } else {
+            time >= self.start || time < self.end
+        }
+    }
+
+    /// How long the quiet hours still last at a time of day, `None` outside of them.
+    pub fn remaining_at(&self, time: NaiveTime) -> Option<Duration> {
+        if !self.contains(time) {
+            return None;
+        }
+
+        let remaining = self.end.signed_duration_since(time);
+
+        if remaining < Duration::zero() {
+            // Ends tomorrow, past midnight
+            Some(remaining + Duration::days(1))
+        } else {
+            Some(remaining)
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct UserNotificationPreferences {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub like: NotificationChannels,
+    pub reply: NotificationChannels,
+    pub mention: NotificationChannels,
+    pub follow: NotificationChannels,
+    /// Also covers the approvals of the user's follow requests.
+    pub follow_request: NotificationChannels,
+    pub quiet_hours: Option<QuietHours>,
+    pub timezone: String,
+    /// How often the user gets an email digest of the activity they missed.
+    pub digest_frequency: DigestFrequency,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl UserNotificationPreferences {
+    /// The channels a notification type is notified through.
+    pub fn channels(&self, notification_type: NotificationType) -> NotificationChannels {
+        match notification_type {
+            NotificationType::Like => self.like,
+            NotificationType::Reply => self.reply,
+            NotificationType::Mention => self.mention,
+            NotificationType::Follow => self.follow,
+            NotificationType::FollowRequest | NotificationType::FollowRequestApproved => {
+                self.follow_request
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn time(hour: u32, minute: u32) -> NaiveTime {
+        NaiveTime::from_hms_opt(hour, minute, 0).unwrap()
+    }
+
+    fn quiet_hours(start: NaiveTime, end: NaiveTime) -> QuietHours {
+        QuietHours { start, end }
+    }
+
+    #[test]
+    fn contains_within_the_same_day() {
+        let quiet_hours = quiet_hours(time(13, 0), time(15, 0));
+
+        let cases = [
+            (time(12, 59), false),
+            (time(13, 0), true),
+            (time(14, 0), true),
+            (time(14, 59), true),
+            (time(15, 0), false),
+            (time(0, 0), false),
+            (time(23, 0), false),
+        ];
+
+        for (at, expected) in cases {
+            assert_eq!(quiet_hours.contains(at), expected, "at {}", at);
+        }
+    }
+
+    #[test]
+    fn contains_past_midnight() {
+        let quiet_hours = quiet_hours(time(22, 0), time(7, 0));
+
+        let cases = [
+            (time(21, 59), false),
+            (time(22, 0), true),
+            (time(23, 59), true),
+            (time(0, 0), true),
+            (time(3, 0), true),
+            (time(6, 59), true),
+            (time(7, 0), false),
+            (time(12, 0), false),
+        ];
+
+        for (at, expected) in cases {
+            assert_eq!(quiet_hours.contains(at), expected, "at {}", at);
+        }
+    }
+
+    #[test]
+    fn contains_nothing_when_starting_and_ending_at_once() {
+        let quiet_hours = quiet_hours(time(8, 0), time(8, 0));
+
+        for at in [time(7, 59), time(8, 0), time(8, 1), time(0, 0)] {
+            assert!(!quiet_hours.contains(at), "at {}", at);
+        }
+    }
+
+    #[test]
+    fn remaining_within_the_same_day() {
+        let quiet_hours = quiet_hours(time(13, 0), time(15, 0));
+
+        assert_eq!(
+            quiet_hours.remaining_at(time(13, 0)),
+            Some(Duration::hours(2))
+        );
+        assert_eq!(
+            quiet_hours.remaining_at(time(14, 30)),
+            Some(Duration::minutes(30))
+        );
+        assert_eq!(quiet_hours.remaining_at(time(15, 0)), None);
+        assert_eq!(quiet_hours.remaining_at(time(9, 0)), None);
+    }
+
+    #[test]
+    fn remaining_past_midnight() {
+        let quiet_hours = quiet_hours(time(22, 0), time(7, 0));
+
+        assert_eq!(
+            quiet_hours.remaining_at(time(22, 0)),
+            Some(Duration::hours(9))
+        );
+        assert_eq!(
+            quiet_hours.remaining_at(time(23, 30)),
+            Some(Duration::minutes(7 * 60 + 30))
+        );
+        assert_eq!(
+            quiet_hours.remaining_at(time(0, 0)),
+            Some(Duration::hours(7))
+        );
+        assert_eq!(
+            quiet_hours.remaining_at(time(6, 45)),
+            Some(Duration::minutes(15))
+        );
+        assert_eq!(quiet_hours.remaining_at(time(7, 0)), None);
+        assert_eq!(quiet_hours.remaining_at(time(21, 0)), None);
+    }
+}
@@ -0,0 +1,21 @@
+use chrono::{DateTime, Utc};
+use serde_derive::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+
+use super::UserRole;
+
+/// A user as listed to admins (without the credentials).
+#[derive(Debug, Serialize, Deserialize, FromRow, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct UserOverview {
+    pub id: Uuid,
+    pub name: String,
+    pub username: String,
+    pub email: String,
+    pub role: UserRole,
+    pub activated: bool,
+    pub verified: bool,
+    pub created_at: DateTime<Utc>,
+    pub deleted_at: Option<DateTime<Utc>>,
+}
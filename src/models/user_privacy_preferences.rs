@@ -18,5 +18,6 @@ pub struct UserPrivacyPreferences {
     pub who_can_watch_replies: Option<Vec<WhoCan>>,
     pub who_can_watch_follows: Option<Vec<WhoCan>>,
     pub who_can_watch_likes: Option<Vec<WhoCan>>,
+    pub who_can_message_me: Option<Vec<WhoCan>>,
     pub updated_at: DateTime<Utc>,
 }
@@ -1,6 +1,8 @@
+use std::collections::BTreeMap;
+
 use chrono::{DateTime, Utc};
 use serde_derive::{Deserialize, Serialize};
-use sqlx::FromRow;
+use sqlx::{types::Json, FromRow};
 use uuid::Uuid;
 
 #[derive(Debug, Serialize, Deserialize, FromRow, Clone)]
@@ -9,6 +11,11 @@ pub struct UserProfileImage {
     pub id: Uuid,
     pub user_id: Uuid,
     pub image_url: String,
+    /// The keys of the thumbnails, by size.
+    pub thumbnail_urls: Json<BTreeMap<u32, String>>,
+    pub content_type: String,
+    pub width: Option<i32>,
+    pub height: Option<i32>,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
     pub deleted_at: Option<DateTime<Utc>>,
@@ -0,0 +1,19 @@
+use chrono::{DateTime, Utc};
+use serde_derive::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+
+use super::UserRole;
+
+/// A change of the role of a user, as audited.
+#[derive(Debug, Serialize, Deserialize, FromRow, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct UserRoleChange {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    /// The root who changed the role.
+    pub changed_by: Uuid,
+    pub old_role: UserRole,
+    pub new_role: UserRole,
+    pub created_at: DateTime<Utc>,
+}
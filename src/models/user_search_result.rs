@@ -0,0 +1,29 @@
+use serde_derive::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+
+#[derive(Debug, Serialize, Deserialize, FromRow, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct UserSearchResult {
+    pub id: Uuid,
+    pub name: String,
+    pub username: String,
+    pub bio: Option<String>,
+    pub user_profile_image_id: Option<Uuid>,
+    pub verified: bool,
+    /// Whether the searching user follows this user.
+    pub followed: bool,
+    /// The relevance of the user (similarity plus the prefix, verified and followed boosts).
+    pub score: f32,
+}
+
+/// A lighter user search result, for @mention autocompletion.
+#[derive(Debug, Serialize, Deserialize, FromRow, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct UserSuggestion {
+    pub id: Uuid,
+    pub name: String,
+    pub username: String,
+    pub user_profile_image_id: Option<Uuid>,
+    pub verified: bool,
+}
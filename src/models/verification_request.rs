@@ -0,0 +1,44 @@
+use chrono::{DateTime, Utc};
+use serde_derive::{Deserialize, Serialize};
+use sqlx::Type;
+use uuid::Uuid;
+
+/// A request of a user to be verified, as reviewed by the admins.
+#[derive(Debug, Serialize, Deserialize, sqlx::FromRow, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct VerificationRequest {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub username: String,
+    pub name: String,
+    /// What proves the identity of the user (links to official profiles, press...).
+    pub evidence: String,
+    pub status: VerificationRequestStatus,
+    pub reviewer_id: Option<Uuid>,
+    pub review_notes: Option<String>,
+    pub reviewed_at: Option<DateTime<Utc>>,
+    pub revoked_by: Option<Uuid>,
+    pub revocation_notes: Option<String>,
+    pub revoked_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, Type)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+#[sqlx(
+    type_name = "VERIFICATION_REQUEST_STATUS",
+    rename_all = "SCREAMING_SNAKE_CASE"
+)]
+pub enum VerificationRequestStatus {
+    /// Waiting in the review queue.
+    Pending,
+
+    /// The user got verified.
+    Approved,
+
+    Rejected,
+
+    /// The request was approved, then the verification of the user was revoked.
+    Revoked,
+}
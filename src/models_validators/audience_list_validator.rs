@@ -0,0 +1,42 @@
+use std::collections::HashMap;
+
+use serde_derive::Serialize;
+
+use crate::utils::{
+    response::{ApiErrorType, ValidationError},
+    validator::{validate_string_field, FieldLength},
+};
+
+/// Represents the data used for inserting or updating an audience list (e.g. close friends).
+#[derive(Debug, Serialize)]
+pub struct InsertOrUpdateAudienceListData {
+    /// The name of the audience list as shown to its owner.
+    pub name: String,
+}
+
+impl TryFrom<HashMap<String, serde_json::Value>> for InsertOrUpdateAudienceListData {
+    type Error = ApiErrorType;
+
+    fn try_from(value: HashMap<String, serde_json::Value>) -> Result<Self, Self::Error> {
+        let mut errors = Vec::<ValidationError>::new();
+
+        let name = validate_string_field(
+            &value.get("name"),
+            "name",
+            FieldLength {
+                min: Some(1),
+                max: Some(100),
+            },
+            &mut errors,
+            false,
+        );
+
+        if errors.is_empty() {
+            Ok(InsertOrUpdateAudienceListData {
+                name: name.unwrap(),
+            })
+        } else {
+            Err(ApiErrorType::BodyValidationErrors(errors))
+        }
+    }
+}
@@ -0,0 +1,61 @@
+use std::collections::HashMap;
+
+use serde_derive::Serialize;
+use uuid::Uuid;
+
+use crate::utils::{
+    response::{ApiErrorType, ValidationError},
+    validator::{validate_string_field, validate_uuid_array_field, FieldLength},
+};
+
+/// The maximum number of participants of a group, its creator included.
+pub const CONVERSATION_MAX_PARTICIPANTS: usize = 10;
+
+/// Represents the data used for starting a conversation.
+#[derive(Debug, Serialize)]
+pub struct InsertConversationData {
+    /// The users to talk with (the creator aside), a single one for a 1:1 conversation.
+    pub participant_ids: Vec<Uuid>,
+
+    /// The title of a group, ignored for 1:1 conversations.
+    pub title: Option<String>,
+}
+
+impl TryFrom<HashMap<String, serde_json::Value>> for InsertConversationData {
+    type Error = ApiErrorType;
+
+    fn try_from(value: HashMap<String, serde_json::Value>) -> Result<Self, Self::Error> {
+        let mut errors = Vec::<ValidationError>::new();
+
+        let participant_ids = validate_uuid_array_field(
+            &value.get("participantIds"),
+            "participantIds",
+            FieldLength {
+                min: Some(1),
+                max: Some(CONVERSATION_MAX_PARTICIPANTS - 1),
+            },
+            &mut errors,
+            false,
+        );
+
+        let title = validate_string_field(
+            &value.get("title"),
+            "title",
+            FieldLength {
+                min: Some(1),
+                max: Some(100),
+            },
+            &mut errors,
+            true,
+        );
+
+        if errors.is_empty() {
+            Ok(InsertConversationData {
+                participant_ids: participant_ids.unwrap(),
+                title,
+            })
+        } else {
+            Err(ApiErrorType::BodyValidationErrors(errors))
+        }
+    }
+}
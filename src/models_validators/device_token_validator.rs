@@ -0,0 +1,56 @@
+use std::collections::HashMap;
+
+use serde_derive::Serialize;
+
+use crate::utils::{
+    response::{ApiErrorType, ValidationError},
+    validator::{validate_string_field, FieldLength},
+};
+
+/// Represents the data used for registering the push notifications token of a device.
+#[derive(Debug, Serialize)]
+pub struct UpsertDeviceTokenData {
+    /// The identifier of the device, chosen by the client and kept across token refreshes.
+    pub device_id: String,
+    /// The FCM registration token of the device.
+    pub token: String,
+}
+
+impl TryFrom<HashMap<String, serde_json::Value>> for UpsertDeviceTokenData {
+    type Error = ApiErrorType;
+
+    fn try_from(value: HashMap<String, serde_json::Value>) -> Result<Self, Self::Error> {
+        let mut errors = Vec::<ValidationError>::new();
+
+        let device_id = validate_string_field(
+            &value.get("deviceId"),
+            "deviceId",
+            FieldLength {
+                min: Some(1),
+                max: Some(255),
+            },
+            &mut errors,
+            false,
+        );
+
+        let token = validate_string_field(
+            &value.get("token"),
+            "token",
+            FieldLength {
+                min: Some(1),
+                max: Some(4096),
+            },
+            &mut errors,
+            false,
+        );
+
+        if errors.is_empty() {
+            Ok(UpsertDeviceTokenData {
+                device_id: device_id.unwrap(),
+                token: token.unwrap(),
+            })
+        } else {
+            Err(ApiErrorType::BodyValidationErrors(errors))
+        }
+    }
+}
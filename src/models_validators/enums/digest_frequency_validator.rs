@@ -0,0 +1,41 @@
+use std::collections::HashMap;
+
+use crate::{
+    models::DigestFrequency,
+    utils::{response::ValidationError, validator::validate_enum_field},
+};
+
+/// Validates a digest frequency enum field based on a set of allowed enum values.
+///
+/// # Parameters
+///
+/// - `value`: A reference to an `Option<&serde_json::Value>` representing the digest frequency enum value.
+/// - `name`: The name of the digest frequency field, used for error reporting.
+/// - `errors`: A mutable reference to a vector of `ValidationError` instances, used to collect validation errors.
+/// - `optional`: A boolean flag indicating if the digest frequency field is optional.
+///
+/// # Returns
+///
+/// - If validation succeeds and the digest frequency enum value is valid and exists, returns `Some(DigestFrequency::ValidDigestFrequency)`.
+/// - If validation fails or the digest frequency field isn't optional and absent, returns `None` and adds validation errors.
+/// - If validation succeeds and the digest frequency field is optional and absent, returns `None`.
+///
+/// The individual enum value validation relies on the `validate_enum_field` function.
+///
+pub fn validate_digest_frequency_enum_field(
+    value: &Option<&serde_json::Value>,
+    name: &str,
+    errors: &mut Vec<ValidationError>,
+    optional: bool,
+) -> Option<DigestFrequency> {
+    let digest_frequency_values: HashMap<DigestFrequency, String> = [
+        (DigestFrequency::Never, "NEVER".to_string()),
+        (DigestFrequency::Daily, "DAILY".to_string()),
+        (DigestFrequency::Weekly, "WEEKLY".to_string()),
+    ]
+    .iter()
+    .cloned()
+    .collect();
+
+    validate_enum_field(value, name, &digest_frequency_values, errors, optional)
+}
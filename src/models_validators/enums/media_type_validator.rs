@@ -0,0 +1,41 @@
+use std::collections::HashMap;
+
+use crate::{
+    models::MediaType,
+    utils::{response::ValidationError, validator::validate_enum_field},
+};
+
+/// Validates a media type enum field based on a set of allowed enum values.
+///
+/// # Parameters
+///
+/// - `value`: A reference to an `Option<&serde_json::Value>` representing the media type enum value.
+/// - `name`: The name of the media type field, used for error reporting.
+/// - `errors`: A mutable reference to a vector of `ValidationError` instances, used to collect validation errors.
+/// - `optional`: A boolean flag indicating if the media type field is optional.
+///
+/// # Returns
+///
+/// - If validation succeeds and the media type enum value is valid and exists, returns `Some(MediaType::ValidMediaType)`.
+/// - If validation fails or the media type field isn't optional and absent, returns `None` and adds validation errors.
+/// - If validation succeeds and the media type field is optional and absent, returns `None`.
+///
+/// The individual enum value validation relies on the `validate_enum_field` function.
+///
+pub fn validate_media_type_enum_field(
+    value: &Option<&serde_json::Value>,
+    name: &str,
+    errors: &mut Vec<ValidationError>,
+    optional: bool,
+) -> Option<MediaType> {
+    let media_type_values: HashMap<MediaType, String> = [
+        (MediaType::Audio, "AUDIO".to_string()),
+        (MediaType::Video, "VIDEO".to_string()),
+        (MediaType::Photo, "PHOTO".to_string()),
+    ]
+    .iter()
+    .cloned()
+    .collect();
+
+    validate_enum_field(value, name, &media_type_values, errors, optional)
+}
@@ -1,3 +1,4 @@
+pub mod digest_frequency_validator;
 pub mod gender_validator;
 pub mod media_type_validator;
 pub mod user_role_validator;
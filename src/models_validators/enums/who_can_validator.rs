@@ -1,7 +1,9 @@
 use std::collections::HashMap;
 
+use uuid::Uuid;
+
 use crate::{
-    models::WhoCan,
+    models::{WhoCan, WHO_CAN_LIST_PREFIX},
     utils::{
         response::ValidationError,
         validator::{validate_array_field, validate_enum_field},
@@ -50,12 +52,29 @@ use crate::{
 ///
 /// - `IncorrectEnumValue`: If the field's string value does not match any of the expected enum values.
 ///
+/// - `InvalidAudienceListReference`: If the field references an audience list (`LIST:<id>`) with an invalid id.
+///
 pub fn validate_who_can_enum_field(
     value: &Option<&serde_json::Value>,
     name: &str,
     errors: &mut Vec<ValidationError>,
     optional: bool,
 ) -> Option<WhoCan> {
+    if let Some(serde_json::Value::String(passed_value)) = value {
+        if let Some(list_id) = passed_value.strip_prefix(WHO_CAN_LIST_PREFIX) {
+            return match Uuid::parse_str(list_id) {
+                Ok(list_id) => Some(WhoCan::List(list_id)),
+                Err(_) => {
+                    errors.push(ValidationError::InvalidAudienceListReference {
+                        field_name: name.to_string(),
+                        passed_value: passed_value.to_string(),
+                    });
+                    None
+                }
+            };
+        }
+    }
+
     let who_can_values: HashMap<WhoCan, String> = [
         (WhoCan::Followed, "FOLLOWED".to_string()),
         (WhoCan::Followers, "FOLLOWERS".to_string()),
@@ -110,17 +129,55 @@ pub fn validate_who_can_enum_array_field(
 
     match who_can {
         Some(Some(value)) => {
-            let mut res = value
-                .into_iter()
-                .map(|ref v| validate_who_can_enum_field(&Some(v), name, errors, false));
+            let res = value
+                .iter()
+                .map(|v| validate_who_can_enum_field(&Some(v), name, errors, false))
+                .collect::<Vec<_>>();
 
-            if res.any(|v| v.is_none()) {
+            if res.iter().any(|v| v.is_none()) {
                 None
             } else {
-                Some(Some(res.map(Option::unwrap).collect()))
+                Some(Some(res.into_iter().map(Option::unwrap).collect()))
             }
         }
         Some(None) => Some(None),
         None => None,
     }
 }
+
+/// Validates that every audience list referenced by a `WhoCan` array is owned by the caller.
+///
+/// This function is meant to be used after `validate_who_can_enum_array_field`, once the
+/// ids of the audience lists owned by the caller are known (which requires a database lookup
+/// that the synchronous validators can't do by themselves).
+///
+/// # Parameters
+///
+/// - `who_can`: The validated `WhoCan` array, in the same tri-state form returned by `validate_who_can_enum_array_field`.
+/// - `name`: The name of the array field, used for error reporting.
+/// - `owned_list_ids`: The ids of the (non deleted) audience lists owned by the caller.
+/// - `errors`: A mutable reference to a vector of `ValidationError` instances, used to collect validation errors.
+///
+/// # Errors
+///
+/// - `NonOwnedAudienceList`: For every referenced audience list that isn't one of `owned_list_ids`.
+///
+pub fn validate_who_can_audience_lists_ownership(
+    who_can: &Option<Option<Vec<WhoCan>>>,
+    name: &str,
+    owned_list_ids: &[Uuid],
+    errors: &mut Vec<ValidationError>,
+) {
+    if let Some(Some(who_can)) = who_can {
+        for value in who_can {
+            if let WhoCan::List(list_id) = value {
+                if !owned_list_ids.contains(list_id) {
+                    errors.push(ValidationError::NonOwnedAudienceList {
+                        field_name: name.to_string(),
+                        list_id: *list_id,
+                    });
+                }
+            }
+        }
+    }
+}
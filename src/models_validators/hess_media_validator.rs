@@ -0,0 +1,51 @@
+use std::collections::HashMap;
+
+use serde_derive::Serialize;
+
+use crate::utils::{
+    response::{ApiErrorType, ValidationError},
+    validator::{validate_string_field, FieldLength},
+};
+
+/// The maximum length of the alternative text of a hess media.
+pub const HESS_MEDIA_ALT_TEXT_MAX_LENGTH: usize = 1000;
+
+/// A struct representing hess media updates.
+#[derive(Debug, Serialize)]
+pub struct UpdateHessMediaData {
+    /// The alternative text of the media, describing it to who can't see or hear it.
+    ///
+    /// - `None` if the alternative text is not specified at all.
+    /// - `Some(None)` if the alternative text is present but set to null (removed).
+    /// - `Some(Some(...))` if the alternative text holds an actual value.
+    pub alt_text: Option<Option<String>>,
+}
+
+impl TryFrom<HashMap<String, serde_json::Value>> for UpdateHessMediaData {
+    type Error = ApiErrorType;
+
+    fn try_from(value: HashMap<String, serde_json::Value>) -> Result<Self, Self::Error> {
+        let mut errors = Vec::<ValidationError>::new();
+
+        let alt_text = match value.get("altText") {
+            Some(serde_json::Value::Null) => Some(None),
+            alt_text => validate_string_field(
+                &alt_text,
+                "altText",
+                FieldLength {
+                    min: Some(1),
+                    max: Some(HESS_MEDIA_ALT_TEXT_MAX_LENGTH),
+                },
+                &mut errors,
+                true,
+            )
+            .map(Some),
+        };
+
+        if errors.is_empty() {
+            Ok(UpdateHessMediaData { alt_text })
+        } else {
+            Err(ApiErrorType::BodyValidationErrors(errors))
+        }
+    }
+}
@@ -0,0 +1,64 @@
+use std::collections::HashMap;
+
+use serde_derive::Serialize;
+use uuid::Uuid;
+
+use crate::utils::{
+    response::{ApiErrorType, ValidationError},
+    validator::{validate_string_field, validate_uuid_array_field, FieldLength},
+};
+
+/// The maximum number of hess media a message can reference.
+const MESSAGE_MAX_MEDIA: usize = 4;
+
+/// Represents the data used for sending a message, it needs a content, media or both.
+#[derive(Debug, Serialize)]
+pub struct InsertMessageData {
+    pub content: Option<String>,
+
+    /// The hess media referenced by the message (that the sender can view), in order.
+    pub media_ids: Vec<Uuid>,
+}
+
+impl TryFrom<HashMap<String, serde_json::Value>> for InsertMessageData {
+    type Error = ApiErrorType;
+
+    fn try_from(value: HashMap<String, serde_json::Value>) -> Result<Self, Self::Error> {
+        let mut errors = Vec::<ValidationError>::new();
+
+        let content = validate_string_field(
+            &value.get("content"),
+            "content",
+            FieldLength {
+                min: Some(1),
+                max: Some(2000),
+            },
+            &mut errors,
+            true,
+        );
+
+        let media_ids = validate_uuid_array_field(
+            &value.get("mediaIds"),
+            "mediaIds",
+            FieldLength {
+                min: None,
+                max: Some(MESSAGE_MAX_MEDIA),
+            },
+            &mut errors,
+            true,
+        )
+        .unwrap_or_default();
+
+        if errors.is_empty() && content.is_none() && media_ids.is_empty() {
+            errors.push(ValidationError::RequiredFieldMissing {
+                field_name: "content".to_string(),
+            });
+        }
+
+        if errors.is_empty() {
+            Ok(InsertMessageData { content, media_ids })
+        } else {
+            Err(ApiErrorType::BodyValidationErrors(errors))
+        }
+    }
+}
@@ -1,6 +1,13 @@
+pub mod audience_list_validator;
 pub mod blocked_user_validator;
+pub mod conversation_validator;
+pub mod device_token_validator;
 pub mod enums;
 pub mod follower_validator;
+pub mod hess_media_validator;
 pub mod hess_validator;
+pub mod message_validator;
+pub mod user_notification_preferences_validator;
 pub mod user_privacy_preferences_validator;
 pub mod user_validator;
+pub mod verification_request_validator;
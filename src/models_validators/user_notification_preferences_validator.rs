@@ -0,0 +1,196 @@
+use std::collections::HashMap;
+
+use serde_derive::Serialize;
+
+use super::enums::digest_frequency_validator::validate_digest_frequency_enum_field;
+
+use crate::{
+    models::{DigestFrequency, QuietHours},
+    utils::{
+        response::{ApiErrorType, ValidationError},
+        validator::{
+            validate_boolean_field, validate_object_field, validate_string_field,
+            validate_time_field, FieldLength,
+        },
+    },
+};
+
+/// A struct representing the channel updates of a notified event.
+///
+/// Each field is `None` if the channel is not specified at all.
+#[derive(Debug, Serialize)]
+pub struct UpdateNotificationChannelsData {
+    /// Specifies whether the event is stored in the notifications of the user.
+    pub in_app: Option<bool>,
+
+    /// Specifies whether the event is pushed to the devices of the user.
+    pub push: Option<bool>,
+
+    /// Specifies whether the event is sent in the email digests of the user.
+    pub email: Option<bool>,
+}
+
+/// A struct representing user notification preference updates.
+#[derive(Debug, Serialize)]
+pub struct UpdateUserNotificationPreferencesData {
+    /// The channels of the likes of the user's hesses, `None` if not specified at all.
+    pub like: Option<UpdateNotificationChannelsData>,
+
+    /// The channels of the replies to the user's hesses, `None` if not specified at all.
+    pub reply: Option<UpdateNotificationChannelsData>,
+
+    /// The channels of the mentions of the user, `None` if not specified at all.
+    pub mention: Option<UpdateNotificationChannelsData>,
+
+    /// The channels of the new followers of the user, `None` if not specified at all.
+    pub follow: Option<UpdateNotificationChannelsData>,
+
+    /// The channels of the follow requests sent to the user and of the approvals of the
+    /// user's follow requests, `None` if not specified at all.
+    pub follow_request: Option<UpdateNotificationChannelsData>,
+
+    /// Specifies the hours during which nothing is pushed.
+    ///
+    /// - `None` if the preference is not specified at all.
+    /// - `Some(None)` if the preference is present but set to null (no quiet hours).
+    /// - `Some(Some(...))` if the preference holds actual quiet hours.
+    pub quiet_hours: Option<Option<QuietHours>>,
+
+    /// The IANA name of the user's timezone (e.g. `Africa/Cairo`), `None` if not specified at all.
+    pub timezone: Option<String>,
+
+    /// How often the user gets an email digest, `None` if not specified at all.
+    pub digest_frequency: Option<DigestFrequency>,
+}
+
+fn validate_channels_field(
+    value: &Option<&serde_json::Value>,
+    name: &str,
+    errors: &mut Vec<ValidationError>,
+) -> Option<UpdateNotificationChannelsData> {
+    let channels = validate_object_field(value, name, errors, true, false)??;
+
+    let mut channel = |field: &str| {
+        validate_boolean_field(
+            &channels.get(field),
+            &format!("{}.{}", name, field),
+            errors,
+            true,
+        )
+    };
+
+    Some(UpdateNotificationChannelsData {
+        in_app: channel("inApp"),
+        push: channel("push"),
+        email: channel("email"),
+    })
+}
+
+fn validate_quiet_hours_field(
+    value: &Option<&serde_json::Value>,
+    name: &str,
+    errors: &mut Vec<ValidationError>,
+) -> Option<Option<QuietHours>> {
+    let quiet_hours = match validate_object_field(value, name, errors, true, true)? {
+        Some(quiet_hours) => quiet_hours,
+        None => return Some(None),
+    };
+
+    let start = validate_time_field(
+        &quiet_hours.get("start"),
+        &format!("{}.start", name),
+        errors,
+        false,
+    );
+
+    let end = validate_time_field(
+        &quiet_hours.get("end"),
+        &format!("{}.end", name),
+        errors,
+        false,
+    );
+
+    Some(Some(QuietHours {
+        start: start?,
+        end: end?,
+    }))
+}
+
+impl TryFrom<HashMap<String, serde_json::Value>> for UpdateUserNotificationPreferencesData {
+    type Error = ApiErrorType;
+
+    fn try_from(value: HashMap<String, serde_json::Value>) -> Result<Self, Self::Error> {
+        let mut errors = Vec::<ValidationError>::new();
+
+        let like = validate_channels_field(&value.get("like"), "like", &mut errors);
+
+        let reply = validate_channels_field(&value.get("reply"), "reply", &mut errors);
+
+        let mention = validate_channels_field(&value.get("mention"), "mention", &mut errors);
+
+        let follow = validate_channels_field(&value.get("follow"), "follow", &mut errors);
+
+        let follow_request =
+            validate_channels_field(&value.get("followRequest"), "followRequest", &mut errors);
+
+        let quiet_hours =
+            validate_quiet_hours_field(&value.get("quietHours"), "quietHours", &mut errors);
+
+        let timezone = validate_string_field(
+            &value.get("timezone"),
+            "timezone",
+            FieldLength {
+                min: Some(1),
+                max: Some(64),
+            },
+            &mut errors,
+            true,
+        );
+
+        let digest_frequency = validate_digest_frequency_enum_field(
+            &value.get("digestFrequency"),
+            "digestFrequency",
+            &mut errors,
+            true,
+        );
+
+        if errors.is_empty() {
+            Ok(UpdateUserNotificationPreferencesData {
+                like,
+                reply,
+                mention,
+                follow,
+                follow_request,
+                quiet_hours,
+                timezone,
+                digest_frequency,
+            })
+        } else {
+            Err(ApiErrorType::BodyValidationErrors(errors))
+        }
+    }
+}
+
+impl UpdateUserNotificationPreferencesData {
+    /// Validates that the timezone, if any, is known.
+    ///
+    /// # Arguments
+    ///
+    /// * `is_known_timezone` - Whether the timezone is listed in the timezones of the database.
+    ///
+    /// # Returns
+    ///
+    /// Returns `Ok(())` if there's no timezone or if it's known,
+    /// otherwise returns an `ApiErrorType::BodyValidationErrors` error.
+    ///
+    pub fn validate_timezone(&self, is_known_timezone: bool) -> Result<(), ApiErrorType> {
+        match &self.timezone {
+            Some(timezone) if !is_known_timezone => Err(ApiErrorType::BodyValidationErrors(vec![
+                ValidationError::InvalidTimezone {
+                    passed_value: timezone.to_string(),
+                },
+            ])),
+            _ => Ok(()),
+        }
+    }
+}
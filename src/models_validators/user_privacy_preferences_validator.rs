@@ -1,17 +1,29 @@
 use std::collections::HashMap;
 
 use serde_derive::Serialize;
+use uuid::Uuid;
 
 use crate::{
     models::WhoCan,
-    utils::response::{ApiErrorType, ValidationError},
+    utils::{
+        response::{ApiErrorType, ValidationError},
+        validator::validate_boolean_field,
+    },
 };
 
-use super::enums::who_can_validator::validate_who_can_enum_array_field;
+use super::enums::who_can_validator::{
+    validate_who_can_audience_lists_ownership, validate_who_can_enum_array_field,
+};
 
 /// A struct representing user privacy preference updates.
 #[derive(Debug, Serialize)]
 pub struct UpdateUserPrivacyPreferencesData {
+    /// Specifies whether the user's profile is private (only visible to followers).
+    ///
+    /// - `None` if the preference is not specified at all.
+    /// - `Some(...)` if the preference holds an actual value.
+    pub is_private_profile: Option<bool>,
+
     /// Specifies who can reply to the user's content.
     ///
     /// - `None` if the preference is not specified at all.
@@ -60,6 +72,13 @@ pub struct UpdateUserPrivacyPreferencesData {
     /// - `Some(None)` if the preference is present but set to null.
     /// - `Some(Some(...))` if the preference holds actual value(s).
     pub who_can_watch_likes: Option<Option<Vec<WhoCan>>>,
+
+    /// Specifies who can start a conversation with the user (or add them to a group).
+    ///
+    /// - `None` if the preference is not specified at all.
+    /// - `Some(None)` if the preference is present but set to null.
+    /// - `Some(Some(...))` if the preference holds actual value(s).
+    pub who_can_message_me: Option<Option<Vec<WhoCan>>>,
 }
 
 impl TryFrom<HashMap<String, serde_json::Value>> for UpdateUserPrivacyPreferencesData {
@@ -68,6 +87,13 @@ impl TryFrom<HashMap<String, serde_json::Value>> for UpdateUserPrivacyPreference
     fn try_from(value: HashMap<String, serde_json::Value>) -> Result<Self, Self::Error> {
         let mut errors = Vec::<ValidationError>::new();
 
+        let is_private_profile = validate_boolean_field(
+            &value.get("isPrivateProfile"),
+            "isPrivateProfile",
+            &mut errors,
+            true,
+        );
+
         let who_can_reply = validate_who_can_enum_array_field(
             &value.get("whoCanReply"),
             "whoCanReply",
@@ -124,8 +150,17 @@ impl TryFrom<HashMap<String, serde_json::Value>> for UpdateUserPrivacyPreference
             true,
         );
 
+        let who_can_message_me = validate_who_can_enum_array_field(
+            &value.get("whoCanMessageMe"),
+            "whoCanMessageMe",
+            &mut errors,
+            true,
+            true,
+        );
+
         if errors.is_empty() {
             Ok(UpdateUserPrivacyPreferencesData {
+                is_private_profile,
                 who_can_reply,
                 who_can_like,
                 who_can_mention_me,
@@ -133,9 +168,46 @@ impl TryFrom<HashMap<String, serde_json::Value>> for UpdateUserPrivacyPreference
                 who_can_watch_replies,
                 who_can_watch_follows,
                 who_can_watch_likes,
+                who_can_message_me,
             })
         } else {
             Err(ApiErrorType::BodyValidationErrors(errors))
         }
     }
 }
+
+impl UpdateUserPrivacyPreferencesData {
+    /// Validates that every audience list referenced by the preferences is owned by the user.
+    ///
+    /// # Arguments
+    ///
+    /// * `owned_list_ids` - The ids of the (non deleted) audience lists owned by the user.
+    ///
+    /// # Returns
+    ///
+    /// Returns `Ok(())` if all the referenced lists are owned by the user,
+    /// otherwise returns an `ApiErrorType::BodyValidationErrors` error.
+    ///
+    pub fn validate_audience_lists(&self, owned_list_ids: &[Uuid]) -> Result<(), ApiErrorType> {
+        let mut errors = Vec::<ValidationError>::new();
+
+        for (who_can, name) in [
+            (&self.who_can_reply, "whoCanReply"),
+            (&self.who_can_like, "whoCanLike"),
+            (&self.who_can_mention_me, "whoCanMentionMe"),
+            (&self.who_can_watch_new_hesses, "whoCanWatchNewHesses"),
+            (&self.who_can_watch_replies, "whoCanWatchReplies"),
+            (&self.who_can_watch_follows, "whoCanWatchFollows"),
+            (&self.who_can_watch_likes, "whoCanWatchLikes"),
+            (&self.who_can_message_me, "whoCanMessageMe"),
+        ] {
+            validate_who_can_audience_lists_ownership(who_can, name, owned_list_ids, &mut errors);
+        }
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(ApiErrorType::BodyValidationErrors(errors))
+        }
+    }
+}
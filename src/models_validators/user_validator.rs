@@ -6,9 +6,7 @@ use crate::{
     models::{Gender, UserRole},
     utils::{
         response::{ApiErrorType, ValidationError},
-        validator::{
-            validate_boolean_field, validate_email_field, validate_string_field, FieldLength,
-        },
+        validator::{validate_email_field, validate_string_field, FieldLength},
     },
 };
 
@@ -17,17 +15,18 @@ use super::enums::{
     user_role_validator::validate_user_role_enum_field,
 };
 
+/// Represents the data used for signing up.
+///
+/// New users get the `USER` role, and aren't activated nor verified: the role is changed by
+/// roots (see `UpdateUserRoleData`), the activation goes through the confirmation email.
 #[derive(Debug, Serialize)]
 pub struct InsertUserData {
     pub name: String,
     pub gender: Gender,
-    pub role: UserRole,
     pub bio: Option<String>,
     pub email: String,
     pub username: String,
     pub password: String,
-    pub activated: bool,
-    pub verified: bool,
 }
 
 impl TryFrom<HashMap<String, serde_json::Value>> for InsertUserData {
@@ -91,17 +90,8 @@ impl TryFrom<HashMap<String, serde_json::Value>> for InsertUserData {
             false,
         );
 
-        let verified =
-            validate_boolean_field(&value.get("verified"), "verified", &mut errors, false);
-
-        let activated =
-            validate_boolean_field(&value.get("activated"), "activated", &mut errors, false);
-
         let gender = validate_gender_enum_field(&value.get("gender"), "gender", &mut errors, false);
 
-        let user_role =
-            validate_user_role_enum_field(&value.get("userRole"), "userRole", &mut errors, false);
-
         if errors.is_empty() {
             Ok(InsertUserData {
                 name: name.unwrap(),
@@ -110,9 +100,6 @@ impl TryFrom<HashMap<String, serde_json::Value>> for InsertUserData {
                 password: password.unwrap(),
                 gender: gender.unwrap(),
                 bio,
-                role: user_role.unwrap(),
-                verified: verified.unwrap(),
-                activated: activated.unwrap(),
             })
         } else {
             Err(ApiErrorType::BodyValidationErrors(errors))
@@ -120,17 +107,17 @@ impl TryFrom<HashMap<String, serde_json::Value>> for InsertUserData {
     }
 }
 
+/// Represents the data used for updating a user, by themselves or an admin.
+///
+/// The role, the activation and the verification aren't part of it, so users can't change them.
 #[derive(Debug, Serialize)]
 pub struct UpdateUserData {
     pub name: Option<String>,
     pub gender: Option<Gender>,
-    pub role: Option<UserRole>,
     pub bio: Option<String>,
     pub email: Option<String>,
     pub username: Option<String>,
     pub password: Option<String>,
-    pub activated: Option<bool>,
-    pub verified: Option<bool>,
 }
 
 impl TryFrom<HashMap<String, serde_json::Value>> for UpdateUserData {
@@ -194,17 +181,8 @@ impl TryFrom<HashMap<String, serde_json::Value>> for UpdateUserData {
             true,
         );
 
-        let verified =
-            validate_boolean_field(&value.get("verified"), "verified", &mut errors, true);
-
-        let activated =
-            validate_boolean_field(&value.get("activated"), "activated", &mut errors, true);
-
         let gender = validate_gender_enum_field(&value.get("gender"), "gender", &mut errors, true);
 
-        let user_role =
-            validate_user_role_enum_field(&value.get("userRole"), "userRole", &mut errors, true);
-
         if errors.is_empty() {
             Ok(UpdateUserData {
                 name,
@@ -213,9 +191,30 @@ impl TryFrom<HashMap<String, serde_json::Value>> for UpdateUserData {
                 password,
                 gender,
                 bio,
-                role: user_role,
-                verified,
-                activated,
+            })
+        } else {
+            Err(ApiErrorType::BodyValidationErrors(errors))
+        }
+    }
+}
+
+/// Represents the data used for changing the role of a user (see `ensure_can_change_role`).
+#[derive(Debug, Serialize)]
+pub struct UpdateUserRoleData {
+    pub role: UserRole,
+}
+
+impl TryFrom<HashMap<String, serde_json::Value>> for UpdateUserRoleData {
+    type Error = ApiErrorType;
+
+    fn try_from(value: HashMap<String, serde_json::Value>) -> Result<Self, Self::Error> {
+        let mut errors = Vec::<ValidationError>::new();
+
+        let role = validate_user_role_enum_field(&value.get("role"), "role", &mut errors, false);
+
+        if errors.is_empty() {
+            Ok(UpdateUserRoleData {
+                role: role.unwrap(),
             })
         } else {
             Err(ApiErrorType::BodyValidationErrors(errors))
@@ -0,0 +1,74 @@
+use std::collections::HashMap;
+
+use serde_derive::Serialize;
+
+use crate::utils::{
+    response::{ApiErrorType, ValidationError},
+    validator::{validate_string_field, FieldLength},
+};
+
+/// Represents the data used for requesting to be verified.
+#[derive(Debug, Serialize)]
+pub struct InsertVerificationRequestData {
+    pub evidence: String,
+}
+
+impl TryFrom<HashMap<String, serde_json::Value>> for InsertVerificationRequestData {
+    type Error = ApiErrorType;
+
+    fn try_from(value: HashMap<String, serde_json::Value>) -> Result<Self, Self::Error> {
+        let mut errors = Vec::<ValidationError>::new();
+
+        let evidence = validate_string_field(
+            &value.get("evidence"),
+            "evidence",
+            FieldLength {
+                min: Some(1),
+                max: Some(2000),
+            },
+            &mut errors,
+            false,
+        );
+
+        if errors.is_empty() {
+            Ok(InsertVerificationRequestData {
+                evidence: evidence.unwrap(),
+            })
+        } else {
+            Err(ApiErrorType::BodyValidationErrors(errors))
+        }
+    }
+}
+
+/// Represents the data used for approving or rejecting a verification request, or revoking
+/// the verification of a user.
+#[derive(Debug, Serialize)]
+pub struct ReviewVerificationData {
+    /// Why the admin took the decision, shown to the user.
+    pub notes: Option<String>,
+}
+
+impl TryFrom<HashMap<String, serde_json::Value>> for ReviewVerificationData {
+    type Error = ApiErrorType;
+
+    fn try_from(value: HashMap<String, serde_json::Value>) -> Result<Self, Self::Error> {
+        let mut errors = Vec::<ValidationError>::new();
+
+        let notes = validate_string_field(
+            &value.get("notes"),
+            "notes",
+            FieldLength {
+                min: Some(1),
+                max: Some(1000),
+            },
+            &mut errors,
+            true,
+        );
+
+        if errors.is_empty() {
+            Ok(ReviewVerificationData { notes })
+        } else {
+            Err(ApiErrorType::BodyValidationErrors(errors))
+        }
+    }
+}
@@ -0,0 +1,10 @@
+mod role_policy;
+mod visibility_policy;
+
+pub use role_policy::ensure_can_change_role;
+pub use role_policy::has_permission;
+pub use role_policy::Permission;
+pub use visibility_policy::authorize;
+pub use visibility_policy::policy_sql_predicate;
+pub use visibility_policy::PolicyAction;
+pub use visibility_policy::ViewerRelationship;
@@ -0,0 +1,218 @@
+use crate::{
+    models::{User, UserRole},
+    utils::response::ApiErrorType,
+};
+
+/// A capability granted to some roles, on top of what every user can do with their own content.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Permission {
+    /// Listing the users and the hesses (soft-deleted ones included), and the role changes.
+    AccessAdminTools,
+
+    /// Managing the settings and the profile images of the other users.
+    ManageUsers,
+
+    /// Seeing and removing the media of any hess.
+    ModerateContent,
+
+    /// Reviewing the verification requests and revoking verifications.
+    VerifyUsers,
+
+    /// Changing the role of the other users.
+    ChangeUserRoles,
+}
+
+/// Checks whether a role has a permission.
+///
+/// Managers have every permission but changing roles, which only roots can do.
+///
+pub fn has_permission(role: &UserRole, permission: Permission) -> bool {
+    match role {
+        UserRole::User => false,
+        UserRole::Manager => !matches!(permission, Permission::ChangeUserRoles),
+        UserRole::Root => true,
+    }
+}
+
+/// Makes sure a user is allowed to give a role to another user.
+///
+/// Only roots change roles, never their own (so nobody escalates themselves) nor the one of a
+/// root: roots are provisioned in the database, not granted through the API.
+///
+/// # Arguments
+///
+/// * `actor` - The user changing the role.
+/// * `target` - The user whose role is changed.
+/// * `role` - The new role.
+///
+/// # Returns
+///
+/// Returns `Ok(())` if the change is allowed, otherwise returns an `ApiErrorType::Unauthorized` error.
+///
+pub fn ensure_can_change_role(
+    actor: &User,
+    target: &User,
+    role: &UserRole,
+) -> Result<(), ApiErrorType> {
+    if !has_permission(&actor.role, Permission::ChangeUserRoles)
+        || actor.id == target.id
+        || target.role == UserRole::Root
+        || *role == UserRole::Root
+    {
+        return Err(ApiErrorType::Unauthorized);
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::Utc;
+    use uuid::Uuid;
+
+    use crate::models::Gender;
+
+    use super::*;
+
+    const PERMISSIONS: [Permission; 5] = [
+        Permission::AccessAdminTools,
+        Permission::ManageUsers,
+        Permission::ModerateContent,
+        Permission::VerifyUsers,
+        Permission::ChangeUserRoles,
+    ];
+
+    const ROLES: [UserRole; 3] = [UserRole::User, UserRole::Manager, UserRole::Root];
+
+    fn user(role: UserRole) -> User {
+        User {
+            id: Uuid::new_v4(),
+            name: "User".to_string(),
+            gender: Gender::Male,
+            role,
+            bio: None,
+            email: "user@hesshub.com".to_string(),
+            user_profile_image_id: None,
+            username: "user".to_string(),
+            password: "password".to_string(),
+            activated: true,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+            deleted_at: None,
+            verified: false,
+            verified_at: None,
+            verified_by: None,
+        }
+    }
+
+    /// The permissions a role has.
+    fn permissions(role: &UserRole) -> Vec<Permission> {
+        PERMISSIONS
+            .into_iter()
+            .filter(|permission| has_permission(role, *permission))
+            .collect()
+    }
+
+    fn can_change_role(actor: &User, target: &User, role: &UserRole) -> bool {
+        ensure_can_change_role(actor, target, role).is_ok()
+    }
+
+    #[test]
+    fn users_have_no_permission() {
+        assert_eq!(permissions(&UserRole::User), vec![]);
+    }
+
+    #[test]
+    fn managers_have_every_permission_but_changing_roles() {
+        assert_eq!(
+            permissions(&UserRole::Manager),
+            vec![
+                Permission::AccessAdminTools,
+                Permission::ManageUsers,
+                Permission::ModerateContent,
+                Permission::VerifyUsers,
+            ]
+        );
+    }
+
+    #[test]
+    fn roots_have_every_permission() {
+        assert_eq!(permissions(&UserRole::Root), PERMISSIONS.to_vec());
+    }
+
+    #[test]
+    fn roots_change_the_role_of_users_and_managers() {
+        let root = user(UserRole::Root);
+
+        for target_role in [UserRole::User, UserRole::Manager] {
+            for role in [UserRole::User, UserRole::Manager] {
+                assert!(
+                    can_change_role(&root, &user(target_role.clone()), &role),
+                    "{:?} to {:?}",
+                    target_role,
+                    role
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn only_roots_change_roles() {
+        for actor_role in [UserRole::User, UserRole::Manager] {
+            let actor = user(actor_role.clone());
+
+            for target_role in ROLES {
+                for role in ROLES {
+                    let target = user(target_role.clone());
+
+                    assert!(
+                        matches!(
+                            ensure_can_change_role(&actor, &target, &role),
+                            Err(ApiErrorType::Unauthorized)
+                        ),
+                        "{:?} changing {:?} to {:?}",
+                        actor_role,
+                        target_role,
+                        role
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn roots_dont_change_their_own_role() {
+        let root = user(UserRole::Root);
+
+        for role in ROLES {
+            assert!(!can_change_role(&root, &root, &role), "to {:?}", role);
+        }
+    }
+
+    #[test]
+    fn roots_dont_demote_other_roots() {
+        let root = user(UserRole::Root);
+        let other_root = user(UserRole::Root);
+
+        for role in [UserRole::User, UserRole::Manager] {
+            assert!(!can_change_role(&root, &other_root, &role), "to {:?}", role);
+        }
+    }
+
+    #[test]
+    fn nobody_grants_root() {
+        for actor_role in ROLES {
+            for target_role in [UserRole::User, UserRole::Manager] {
+                let actor = user(actor_role.clone());
+                let target = user(target_role.clone());
+
+                assert!(
+                    !can_change_role(&actor, &target, &UserRole::Root),
+                    "{:?} promoting {:?}",
+                    actor_role,
+                    target_role
+                );
+            }
+        }
+    }
+}
@@ -0,0 +1,572 @@
+use sqlx::{Pool, Postgres};
+use uuid::Uuid;
+
+use crate::{
+    models::{Hess, UserPrivacyPreferences, WhoCan},
+    services::{
+        user_privacy_preferences_service::get_user_privacy_preferences_by_user_id,
+        viewer_relationships_service::get_viewer_relationship,
+    },
+    utils::response::ApiErrorType,
+};
+
+/// An action a viewer can try to perform on an author (or on one of their hesses).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PolicyAction {
+    /// Seeing the author's profile or one of their hesses.
+    View,
+
+    /// Replying to a hess.
+    #[allow(dead_code)]
+    Reply,
+
+    /// Liking a hess.
+    #[allow(dead_code)]
+    Like,
+
+    /// Mentioning the author in a hess.
+    Mention,
+
+    /// Watching the new hesses of the author (in the feed).
+    WatchNewHesses,
+
+    /// Watching the replies of the author (in the feed).
+    WatchReplies,
+
+    /// Watching the likes of the author (in the feed).
+    WatchLikes,
+
+    /// Watching the follows of the author (in the feed).
+    WatchFollows,
+
+    /// Starting a conversation with the author.
+    Message,
+}
+
+impl PolicyAction {
+    /// The name of the action as understood by the `viewer_can` SQL function.
+    pub fn as_sql_str(&self) -> &'static str {
+        match self {
+            PolicyAction::View => "VIEW",
+            PolicyAction::Reply => "REPLY",
+            PolicyAction::Like => "LIKE",
+            PolicyAction::Mention => "MENTION",
+            PolicyAction::WatchNewHesses => "WATCH_NEW_HESSES",
+            PolicyAction::WatchReplies => "WATCH_REPLIES",
+            PolicyAction::WatchLikes => "WATCH_LIKES",
+            PolicyAction::WatchFollows => "WATCH_FOLLOWS",
+            PolicyAction::Message => "MESSAGE",
+        }
+    }
+}
+
+/// Everything the policy needs to know about the relationship between a viewer and an author.
+#[derive(Debug, Clone, Default)]
+pub struct ViewerRelationship {
+    /// Whether the viewer is the author.
+    pub is_author: bool,
+
+    /// Whether the viewer follows the author (matches `WhoCan::Followers`).
+    pub viewer_follows_author: bool,
+
+    /// Whether the author follows the viewer (matches `WhoCan::Followed`).
+    pub author_follows_viewer: bool,
+
+    /// Whether any of them blocked the other.
+    pub is_blocked: bool,
+
+    /// Whether the viewer is mentioned in the targeted hess.
+    pub is_mentioned: bool,
+
+    /// The (non deleted) audience lists of the author the viewer is a member of.
+    pub audience_list_ids: Vec<Uuid>,
+}
+
+/// Resolves which `WhoCan` rule applies to an action.
+///
+/// Hess level overrides win over the author's privacy preferences, as long as they aren't `None`.
+/// The returned value keeps the same meaning as the stored arrays, `None` means public and
+/// an empty array means no one (except who's mentioned).
+///
+pub fn resolve_who_can<'a>(
+    action: PolicyAction,
+    preferences: &'a UserPrivacyPreferences,
+    hess: Option<&'a Hess>,
+) -> Option<&'a Vec<WhoCan>> {
+    let hess_override = |select: fn(&Hess) -> &Option<Vec<WhoCan>>| {
+        hess.and_then(|hess| select(hess).as_ref())
+    };
+
+    match action {
+        PolicyAction::View => None,
+        PolicyAction::Reply => {
+            hess_override(|hess| &hess.who_can_reply).or(preferences.who_can_reply.as_ref())
+        }
+        PolicyAction::Like => {
+            hess_override(|hess| &hess.who_can_like).or(preferences.who_can_like.as_ref())
+        }
+        PolicyAction::Mention => preferences.who_can_mention_me.as_ref(),
+        PolicyAction::WatchNewHesses => preferences.who_can_watch_new_hesses.as_ref(),
+        PolicyAction::WatchReplies => hess_override(|hess| &hess.who_can_watch_replies)
+            .or(preferences.who_can_watch_replies.as_ref()),
+        PolicyAction::WatchLikes => hess_override(|hess| &hess.who_can_watch_likes)
+            .or(preferences.who_can_watch_likes.as_ref()),
+        PolicyAction::WatchFollows => preferences.who_can_watch_follows.as_ref(),
+        PolicyAction::Message => preferences.who_can_message_me.as_ref(),
+    }
+}
+
+/// Checks whether the viewer is part of at least one of the given audiences.
+pub fn who_can_matches(who_can: Option<&Vec<WhoCan>>, relationship: &ViewerRelationship) -> bool {
+    match who_can {
+        None => true,
+        Some(who_can) => who_can.iter().any(|audience| match audience {
+            WhoCan::Followed => relationship.author_follows_viewer,
+            WhoCan::Followers => relationship.viewer_follows_author,
+            WhoCan::List(list_id) => relationship.audience_list_ids.contains(list_id),
+        }),
+    }
+}
+
+/// Evaluates whether a viewer is allowed to perform an action on an author (or on one of their hesses).
+///
+/// The rules are applied in order:
+///
+/// 1. The author can always do everything.
+/// 2. Blocks (in either direction) deny everything.
+/// 3. Private profiles deny everything to whoever isn't a follower (or mentioned in the hess).
+/// 4. Users mentioned in the hess are always part of the audience.
+/// 5. The resolved `WhoCan` rule (see `resolve_who_can`) must match the viewer.
+///
+/// This must be kept in sync with the `viewer_can` SQL function (see `policy_sql_predicate`).
+///
+pub fn is_allowed(
+    action: PolicyAction,
+    relationship: &ViewerRelationship,
+    preferences: &UserPrivacyPreferences,
+    hess: Option<&Hess>,
+) -> bool {
+    if relationship.is_author {
+        return true;
+    }
+
+    if relationship.is_blocked {
+        return false;
+    }
+
+    if preferences.is_private_profile
+        && !relationship.viewer_follows_author
+        && !relationship.is_mentioned
+    {
+        return false;
+    }
+
+    if action == PolicyAction::View || relationship.is_mentioned {
+        return true;
+    }
+
+    who_can_matches(resolve_who_can(action, preferences, hess), relationship)
+}
+
+/// Builds a SQL predicate evaluating the policy inside a query, to filter lists of content.
+///
+/// The arguments are SQL expressions (a bind parameter like `$1` or a column like `h.user_id`),
+/// they are inserted as is so they must never come from user input.
+///
+/// # Arguments
+///
+/// * `action` - The action to check.
+/// * `viewer` - SQL expression of the viewer's id.
+/// * `author` - SQL expression of the author's id.
+/// * `hess` - SQL expression of the targeted hess's id, if any.
+///
+/// # Returns
+///
+/// Returns a boolean SQL expression, e.g. `viewer_can('REPLY', $1, h.user_id, h.id)`.
+///
+pub fn policy_sql_predicate(
+    action: PolicyAction,
+    viewer: &str,
+    author: &str,
+    hess: Option<&str>,
+) -> String {
+    format!(
+        "viewer_can('{}', {}, {}, {})",
+        action.as_sql_str(),
+        viewer,
+        author,
+        hess.unwrap_or("NULL")
+    )
+}
+
+/// Makes sure a viewer is allowed to perform an action on an author (or on one of their hesses).
+///
+/// # Arguments
+///
+/// * `pool` - A database connection pool.
+/// * `viewer_id` - The UUID of the user trying to perform the action.
+/// * `action` - The action to check.
+/// * `author_id` - The UUID of the user owning the targeted content (or profile).
+/// * `hess` - The targeted hess, if any.
+///
+/// # Returns
+///
+/// Returns `Ok(())` if the action is allowed, otherwise returns an `ApiErrorType::Unauthorized` error.
+/// If any error occurs while loading the policy inputs, returns an `ApiErrorType::InternalServerError` error.
+///
+pub async fn authorize(
+    pool: Pool<Postgres>,
+    viewer_id: Uuid,
+    action: PolicyAction,
+    author_id: Uuid,
+    hess: Option<&Hess>,
+) -> Result<(), ApiErrorType> {
+    if viewer_id == author_id {
+        return Ok(());
+    }
+
+    let relationship =
+        get_viewer_relationship(pool.clone(), viewer_id, author_id, hess.map(|hess| hess.id))
+            .await?;
+
+    let preferences = get_user_privacy_preferences_by_user_id(pool, author_id)
+        .await
+        .map_err(|_| ApiErrorType::InternalServerError)?;
+
+    if is_allowed(action, &relationship, &preferences, hess) {
+        Ok(())
+    } else {
+        Err(ApiErrorType::Unauthorized)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::Utc;
+
+    use super::*;
+
+    const ACTIONS: [PolicyAction; 9] = [
+        PolicyAction::View,
+        PolicyAction::Reply,
+        PolicyAction::Like,
+        PolicyAction::Mention,
+        PolicyAction::WatchNewHesses,
+        PolicyAction::WatchReplies,
+        PolicyAction::WatchLikes,
+        PolicyAction::WatchFollows,
+        PolicyAction::Message,
+    ];
+
+    /// The actions a hess can override the rule of.
+    const HESS_ACTIONS: [PolicyAction; 4] = [
+        PolicyAction::Reply,
+        PolicyAction::Like,
+        PolicyAction::WatchReplies,
+        PolicyAction::WatchLikes,
+    ];
+
+    /// Privacy preferences with the same rule for every action.
+    fn preferences(who_can: Option<Vec<WhoCan>>) -> UserPrivacyPreferences {
+        UserPrivacyPreferences {
+            id: Uuid::new_v4(),
+            user_id: Uuid::new_v4(),
+            is_private_profile: false,
+            who_can_reply: who_can.clone(),
+            who_can_like: who_can.clone(),
+            who_can_mention_me: who_can.clone(),
+            who_can_watch_new_hesses: who_can.clone(),
+            who_can_watch_replies: who_can.clone(),
+            who_can_watch_follows: who_can.clone(),
+            who_can_watch_likes: who_can.clone(),
+            who_can_message_me: who_can,
+            updated_at: Utc::now(),
+        }
+    }
+
+    fn private_preferences() -> UserPrivacyPreferences {
+        UserPrivacyPreferences {
+            is_private_profile: true,
+            ..preferences(None)
+        }
+    }
+
+    /// A hess overriding every rule it can with the same one.
+    fn hess(who_can: Option<Vec<WhoCan>>) -> Hess {
+        Hess {
+            id: Uuid::new_v4(),
+            user_id: Uuid::new_v4(),
+            content: Some("hess".to_string()),
+            parent_hess_id: None,
+            who_can_reply: who_can.clone(),
+            who_can_like: who_can.clone(),
+            who_can_watch_replies: who_can.clone(),
+            who_can_watch_likes: who_can,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+            deleted_at: None,
+            media: vec![],
+        }
+    }
+
+    fn stranger() -> ViewerRelationship {
+        ViewerRelationship::default()
+    }
+
+    fn follower() -> ViewerRelationship {
+        ViewerRelationship {
+            viewer_follows_author: true,
+            ..Default::default()
+        }
+    }
+
+    fn followed() -> ViewerRelationship {
+        ViewerRelationship {
+            author_follows_viewer: true,
+            ..Default::default()
+        }
+    }
+
+    fn mentioned() -> ViewerRelationship {
+        ViewerRelationship {
+            is_mentioned: true,
+            ..Default::default()
+        }
+    }
+
+    fn allowed_actions(
+        relationship: &ViewerRelationship,
+        preferences: &UserPrivacyPreferences,
+        hess: Option<&Hess>,
+    ) -> Vec<PolicyAction> {
+        ACTIONS
+            .into_iter()
+            .filter(|action| is_allowed(*action, relationship, preferences, hess))
+            .collect()
+    }
+
+    #[test]
+    fn author_can_do_everything() {
+        let relationship = ViewerRelationship {
+            is_author: true,
+            is_blocked: true,
+            ..Default::default()
+        };
+        let preferences = UserPrivacyPreferences {
+            is_private_profile: true,
+            ..preferences(Some(vec![]))
+        };
+
+        assert_eq!(
+            allowed_actions(&relationship, &preferences, Some(&hess(Some(vec![])))),
+            ACTIONS
+        );
+    }
+
+    #[test]
+    fn blocks_deny_everything() {
+        let relationship = ViewerRelationship {
+            is_blocked: true,
+            viewer_follows_author: true,
+            author_follows_viewer: true,
+            is_mentioned: true,
+            ..Default::default()
+        };
+
+        assert!(allowed_actions(&relationship, &preferences(None), Some(&hess(None))).is_empty());
+    }
+
+    #[test]
+    fn public_rules_allow_everyone() {
+        assert_eq!(
+            allowed_actions(&stranger(), &preferences(None), None),
+            ACTIONS
+        );
+    }
+
+    #[test]
+    fn empty_rules_only_allow_viewing() {
+        for relationship in [stranger(), follower(), followed()] {
+            assert_eq!(
+                allowed_actions(&relationship, &preferences(Some(vec![])), None),
+                [PolicyAction::View]
+            );
+        }
+    }
+
+    #[test]
+    fn private_profiles_deny_everything_to_non_followers() {
+        for relationship in [stranger(), followed()] {
+            assert!(allowed_actions(&relationship, &private_preferences(), None).is_empty());
+        }
+    }
+
+    #[test]
+    fn private_profiles_apply_the_rules_to_followers() {
+        assert_eq!(
+            allowed_actions(&follower(), &private_preferences(), None),
+            ACTIONS
+        );
+
+        let preferences = UserPrivacyPreferences {
+            is_private_profile: true,
+            ..preferences(Some(vec![WhoCan::Followed]))
+        };
+
+        assert_eq!(
+            allowed_actions(&follower(), &preferences, None),
+            [PolicyAction::View]
+        );
+    }
+
+    #[test]
+    fn private_profiles_allow_mentioned_viewers() {
+        assert_eq!(
+            allowed_actions(&mentioned(), &private_preferences(), Some(&hess(None))),
+            ACTIONS
+        );
+    }
+
+    #[test]
+    fn mentioned_viewers_are_always_part_of_the_audience() {
+        assert_eq!(
+            allowed_actions(
+                &mentioned(),
+                &preferences(Some(vec![])),
+                Some(&hess(Some(vec![])))
+            ),
+            ACTIONS
+        );
+    }
+
+    #[test]
+    fn followers_audience_matches_followers_only() {
+        let preferences = preferences(Some(vec![WhoCan::Followers]));
+
+        assert_eq!(allowed_actions(&follower(), &preferences, None), ACTIONS);
+        assert_eq!(
+            allowed_actions(&followed(), &preferences, None),
+            [PolicyAction::View]
+        );
+        assert_eq!(
+            allowed_actions(&stranger(), &preferences, None),
+            [PolicyAction::View]
+        );
+    }
+
+    #[test]
+    fn followed_audience_matches_followed_users_only() {
+        let preferences = preferences(Some(vec![WhoCan::Followed]));
+
+        assert_eq!(allowed_actions(&followed(), &preferences, None), ACTIONS);
+        assert_eq!(
+            allowed_actions(&follower(), &preferences, None),
+            [PolicyAction::View]
+        );
+    }
+
+    #[test]
+    fn list_audience_matches_list_members_only() {
+        let list_id = Uuid::new_v4();
+        let preferences = preferences(Some(vec![WhoCan::List(list_id)]));
+
+        let member = ViewerRelationship {
+            audience_list_ids: vec![list_id],
+            ..Default::default()
+        };
+        let other_list_member = ViewerRelationship {
+            audience_list_ids: vec![Uuid::new_v4()],
+            viewer_follows_author: true,
+            author_follows_viewer: true,
+            ..Default::default()
+        };
+
+        assert_eq!(allowed_actions(&member, &preferences, None), ACTIONS);
+        assert_eq!(
+            allowed_actions(&other_list_member, &preferences, None),
+            [PolicyAction::View]
+        );
+    }
+
+    #[test]
+    fn any_matching_audience_is_enough() {
+        let list_id = Uuid::new_v4();
+        let preferences = preferences(Some(vec![WhoCan::Followed, WhoCan::List(list_id)]));
+
+        let member = ViewerRelationship {
+            audience_list_ids: vec![list_id],
+            ..Default::default()
+        };
+
+        assert_eq!(allowed_actions(&member, &preferences, None), ACTIONS);
+        assert_eq!(allowed_actions(&followed(), &preferences, None), ACTIONS);
+    }
+
+    #[test]
+    fn hess_overrides_win_over_preferences() {
+        let restricted = hess(Some(vec![]));
+        let allowed = allowed_actions(&stranger(), &preferences(None), Some(&restricted));
+
+        for action in ACTIONS {
+            assert_eq!(allowed.contains(&action), !HESS_ACTIONS.contains(&action));
+        }
+
+        let opened = hess(Some(vec![WhoCan::Followers]));
+        let allowed = allowed_actions(&follower(), &preferences(Some(vec![])), Some(&opened));
+
+        for action in ACTIONS {
+            assert_eq!(
+                allowed.contains(&action),
+                action == PolicyAction::View || HESS_ACTIONS.contains(&action)
+            );
+        }
+    }
+
+    #[test]
+    fn missing_hess_overrides_fall_back_to_preferences() {
+        assert_eq!(
+            allowed_actions(&stranger(), &preferences(Some(vec![])), Some(&hess(None))),
+            [PolicyAction::View]
+        );
+    }
+
+    #[test]
+    fn resolve_who_can_picks_the_rule_of_each_action() {
+        let list_id = Uuid::new_v4();
+        let preferences = preferences(Some(vec![WhoCan::Followers]));
+        let hess = hess(Some(vec![WhoCan::List(list_id)]));
+
+        for action in ACTIONS {
+            let expected = match action {
+                PolicyAction::View => None,
+                _ if HESS_ACTIONS.contains(&action) => Some(vec![WhoCan::List(list_id)]),
+                _ => Some(vec![WhoCan::Followers]),
+            };
+
+            assert_eq!(
+                resolve_who_can(action, &preferences, Some(&hess)).cloned(),
+                expected,
+                "{:?}",
+                action
+            );
+            assert_eq!(
+                resolve_who_can(action, &preferences, None).cloned(),
+                expected.map(|_| vec![WhoCan::Followers]),
+                "{:?}",
+                action
+            );
+        }
+    }
+
+    #[test]
+    fn policy_sql_predicate_passes_the_expressions() {
+        assert_eq!(
+            policy_sql_predicate(PolicyAction::WatchReplies, "$1", "h.user_id", Some("h.id")),
+            "viewer_can('WATCH_REPLIES', $1, h.user_id, h.id)"
+        );
+        assert_eq!(
+            policy_sql_predicate(PolicyAction::Message, "$1", "u.id", None),
+            "viewer_can('MESSAGE', $1, u.id, NULL)"
+        );
+    }
+}
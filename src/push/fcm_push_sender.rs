@@ -0,0 +1,74 @@
+use async_trait::async_trait;
+use fcm::{Client, ErrorReason, MessageBuilder, NotificationBuilder};
+
+use crate::utils::response::ApiErrorType;
+
+use super::{PushNotification, PushSender};
+
+/// The maximum number of registration tokens of a single FCM message.
+const FCM_MAX_REGISTRATION_IDS: usize = 1000;
+
+/// Sends the push notifications through Firebase Cloud Messaging.
+pub struct FcmPushSender {
+    client: Client,
+    server_key: String,
+}
+
+impl FcmPushSender {
+    pub fn new(server_key: String) -> Self {
+        FcmPushSender {
+            client: Client::new(),
+            server_key,
+        }
+    }
+}
+
+#[async_trait]
+impl PushSender for FcmPushSender {
+    async fn send(
+        &self,
+        tokens: &[String],
+        notification: &PushNotification,
+    ) -> Result<Vec<String>, ApiErrorType> {
+        let mut invalid_tokens = Vec::new();
+
+        for tokens in tokens.chunks(FCM_MAX_REGISTRATION_IDS) {
+            let mut notification_builder = NotificationBuilder::new();
+            notification_builder.title(&notification.title);
+            notification_builder.body(&notification.body);
+
+            let mut message_builder = MessageBuilder::new_multi(&self.server_key, tokens);
+            message_builder.notification(notification_builder.finalize());
+            message_builder
+                .data(&notification.data)
+                .map_err(|_| ApiErrorType::InternalServerError)?;
+
+            let response = self
+                .client
+                .send(message_builder.finalize())
+                .await
+                .map_err(|error| {
+                    log::error!("Couldn't send a push notification through FCM: {:?}", error);
+                    ApiErrorType::InternalServerError
+                })?;
+
+            // The results are in the same order as the tokens
+            let results = response.results.unwrap_or_default();
+
+            for (token, result) in tokens.iter().zip(results) {
+                if matches!(
+                    result.error,
+                    Some(
+                        ErrorReason::NotRegistered
+                            | ErrorReason::InvalidRegistration
+                            | ErrorReason::MissingRegistration
+                    )
+                ) {
+                    invalid_tokens.push(token.clone());
+                }
+            }
+        }
+
+        Ok(invalid_tokens)
+    }
+}
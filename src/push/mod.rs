@@ -0,0 +1,27 @@
+mod fcm_push_sender;
+mod push_sender;
+mod recording_push_sender;
+
+use std::sync::Arc;
+
+use crate::utils::{PushBackend, PushConfig};
+
+pub use fcm_push_sender::FcmPushSender;
+pub use push_sender::{PushNotification, PushSender};
+pub use recording_push_sender::RecordingPushSender;
+
+/// Opens the push notifications backend chosen in the configuration.
+///
+/// Panics if FCM is chosen but its server key isn't configured.
+///
+pub fn open_push_sender(push_config: &PushConfig) -> Arc<dyn PushSender> {
+    match push_config.backend {
+        PushBackend::Fcm => Arc::new(FcmPushSender::new(
+            push_config
+                .fcm_server_key
+                .clone()
+                .expect("The FCM push backend requires the FCM server key"),
+        )),
+        PushBackend::Recording => Arc::new(RecordingPushSender::default()),
+    }
+}
@@ -0,0 +1,32 @@
+use std::collections::BTreeMap;
+
+use async_trait::async_trait;
+
+use crate::utils::response::ApiErrorType;
+
+/// A push notification, as shown on the devices of its recipient.
+#[derive(Debug, Clone)]
+pub struct PushNotification {
+    pub title: String,
+    pub body: String,
+    /// The data handed to the app when the notification is opened (e.g. `type`, `hessId`).
+    pub data: BTreeMap<String, String>,
+}
+
+/// Delivers push notifications to devices (see `open_push_sender`).
+#[async_trait]
+pub trait PushSender: Send + Sync {
+    /// Sends a notification to devices, identified by their registration tokens.
+    ///
+    /// # Returns
+    ///
+    /// Returns a `Result` containing the tokens reported as invalid (the app was uninstalled,
+    /// the token expired...), which must be removed.
+    /// If the notification can't be sent, returns an `ApiErrorType::InternalServerError` error.
+    ///
+    async fn send(
+        &self,
+        tokens: &[String],
+        notification: &PushNotification,
+    ) -> Result<Vec<String>, ApiErrorType>;
+}
@@ -0,0 +1,139 @@
+use std::sync::Mutex;
+
+use async_trait::async_trait;
+
+use crate::utils::response::ApiErrorType;
+
+use super::{PushNotification, PushSender};
+
+/// Tokens starting with this prefix are reported as invalid, to exercise their pruning.
+const INVALID_TOKEN_PREFIX: &str = "invalid-";
+
+/// A push sent by the `RecordingPushSender`.
+#[derive(Debug, Clone)]
+pub struct RecordedPush {
+    tokens: Vec<String>,
+    notification: PushNotification,
+}
+
+#[allow(dead_code)]
+impl RecordedPush {
+    /// The registration tokens of the devices the push was sent to.
+    pub fn tokens(&self) -> &[String] {
+        &self.tokens
+    }
+
+    /// The notification that was pushed.
+    pub fn notification(&self) -> &PushNotification {
+        &self.notification
+    }
+}
+
+/// Records (and logs) the push notifications instead of delivering them, for development and tests.
+#[derive(Default)]
+pub struct RecordingPushSender {
+    pushes: Mutex<Vec<RecordedPush>>,
+}
+
+impl RecordingPushSender {
+    /// The pushes sent so far, oldest first.
+    #[allow(dead_code)]
+    pub fn pushes(&self) -> Vec<RecordedPush> {
+        self.pushes.lock().unwrap().clone()
+    }
+}
+
+#[async_trait]
+impl PushSender for RecordingPushSender {
+    async fn send(
+        &self,
+        tokens: &[String],
+        notification: &PushNotification,
+    ) -> Result<Vec<String>, ApiErrorType> {
+        log::info!(
+            "Recorded push \"{}: {}\" {:?} to {} device(s)",
+            notification.title,
+            notification.body,
+            notification.data,
+            tokens.len()
+        );
+
+        self.pushes.lock().unwrap().push(RecordedPush {
+            tokens: tokens.to_vec(),
+            notification: notification.clone(),
+        });
+
+        Ok(tokens
+            .iter()
+            .filter(|token| token.starts_with(INVALID_TOKEN_PREFIX))
+            .cloned()
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use super::*;
+
+    fn notification(title: &str) -> PushNotification {
+        PushNotification {
+            title: title.to_string(),
+            body: "Bob liked your hess".to_string(),
+            data: BTreeMap::from([("type".to_string(), "like".to_string())]),
+        }
+    }
+
+    fn tokens(tokens: &[&str]) -> Vec<String> {
+        tokens.iter().map(|token| token.to_string()).collect()
+    }
+
+    #[tokio::test]
+    async fn pushes_are_recorded_in_order() {
+        let sender = RecordingPushSender::default();
+
+        sender
+            .send(&tokens(&["phone", "tablet"]), &notification("First"))
+            .await
+            .unwrap();
+        sender
+            .send(&tokens(&["laptop"]), &notification("Second"))
+            .await
+            .unwrap();
+
+        let pushes = sender.pushes();
+
+        assert_eq!(pushes.len(), 2);
+        assert_eq!(pushes[0].tokens(), ["phone", "tablet"]);
+        assert_eq!(pushes[0].notification().title, "First");
+        assert_eq!(pushes[0].notification().body, "Bob liked your hess");
+        assert_eq!(pushes[0].notification().data["type"], "like");
+        assert_eq!(pushes[1].tokens(), ["laptop"]);
+        assert_eq!(pushes[1].notification().title, "Second");
+    }
+
+    #[tokio::test]
+    async fn invalid_tokens_are_reported_but_still_recorded() {
+        let sender = RecordingPushSender::default();
+
+        let invalid_tokens = sender
+            .send(
+                &tokens(&["phone", "invalid-tablet", "invalid-laptop"]),
+                &notification("Hi"),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(invalid_tokens, ["invalid-tablet", "invalid-laptop"]);
+        assert_eq!(
+            sender.pushes()[0].tokens(),
+            ["phone", "invalid-tablet", "invalid-laptop"]
+        );
+    }
+
+    #[tokio::test]
+    async fn nothing_is_recorded_before_sending() {
+        assert!(RecordingPushSender::default().pushes().is_empty());
+    }
+}
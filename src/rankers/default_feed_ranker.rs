@@ -0,0 +1,214 @@
+use chrono::{DateTime, Utc};
+
+use super::{CandidateSource, FeedCandidate, FeedRanker};
+
+/// The default ranking, a weighted engagement score decaying with the age of the hess:
+///
+/// `source weight * (1 + like weight * ln(1 + likes) + reply weight * ln(1 + replies)
+/// + affinity weight * ln(1 + affinity)) * 0.5 ^ (age in hours / half life)`
+///
+pub struct DefaultFeedRanker {
+    /// The age (in hours) at which a hess loses half of its score.
+    pub half_life_hours: f64,
+    pub like_weight: f64,
+    pub reply_weight: f64,
+    pub affinity_weight: f64,
+    pub followed_author_weight: f64,
+    pub second_degree_weight: f64,
+    pub popular_weight: f64,
+}
+
+impl Default for DefaultFeedRanker {
+    fn default() -> Self {
+        DefaultFeedRanker {
+            half_life_hours: 12.0,
+            like_weight: 1.0,
+            reply_weight: 1.5,
+            affinity_weight: 2.0,
+            followed_author_weight: 1.0,
+            second_degree_weight: 0.6,
+            popular_weight: 0.4,
+        }
+    }
+}
+
+impl FeedRanker for DefaultFeedRanker {
+    fn score(&self, candidate: &FeedCandidate, now: DateTime<Utc>) -> f64 {
+        let source_weight = match candidate.source {
+            CandidateSource::FollowedAuthor => self.followed_author_weight,
+            CandidateSource::SecondDegree => self.second_degree_weight,
+            CandidateSource::Popular => self.popular_weight,
+        };
+
+        // Hesses "from the future" (clock skew) are treated as brand new
+        let age_hours = ((now - candidate.hess.created_at).num_seconds().max(0) as f64) / 3600.0;
+        let decay = 0.5_f64.powf(age_hours / self.half_life_hours);
+
+        let engagement = 1.0
+            + self.like_weight * (candidate.like_count.max(0) as f64).ln_1p()
+            + self.reply_weight * (candidate.reply_count.max(0) as f64).ln_1p()
+            + self.affinity_weight * (candidate.author_affinity.max(0) as f64).ln_1p();
+
+        source_weight * engagement * decay
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{Duration, TimeZone};
+    use uuid::Uuid;
+
+    use super::*;
+    use crate::models::Hess;
+
+    fn now() -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2024, 1, 1, 12, 0, 0).unwrap()
+    }
+
+    fn candidate(source: CandidateSource, age_hours: i64) -> FeedCandidate {
+        FeedCandidate {
+            hess: Hess {
+                id: Uuid::new_v4(),
+                user_id: Uuid::new_v4(),
+                content: Some("hess".to_string()),
+                parent_hess_id: None,
+                who_can_reply: None,
+                who_can_like: None,
+                who_can_watch_replies: None,
+                who_can_watch_likes: None,
+                created_at: now() - Duration::hours(age_hours),
+                updated_at: now() - Duration::hours(age_hours),
+                deleted_at: None,
+                media: vec![],
+            },
+            source,
+            like_count: 0,
+            reply_count: 0,
+            author_affinity: 0,
+        }
+    }
+
+    fn ids(candidates: &[FeedCandidate]) -> Vec<Uuid> {
+        candidates
+            .iter()
+            .map(|candidate| candidate.hess.id)
+            .collect()
+    }
+
+    #[test]
+    fn scores_halve_every_half_life() {
+        let ranker = DefaultFeedRanker::default();
+
+        let fresh = ranker.score(&candidate(CandidateSource::FollowedAuthor, 0), now());
+        let half_life_old = ranker.score(&candidate(CandidateSource::FollowedAuthor, 12), now());
+        let two_half_lives_old =
+            ranker.score(&candidate(CandidateSource::FollowedAuthor, 24), now());
+
+        assert!((fresh - 1.0).abs() < 1e-9);
+        assert!((half_life_old - 0.5).abs() < 1e-9);
+        assert!((two_half_lives_old - 0.25).abs() < 1e-9);
+    }
+
+    #[test]
+    fn future_hesses_are_treated_as_brand_new() {
+        let ranker = DefaultFeedRanker::default();
+
+        assert_eq!(
+            ranker.score(&candidate(CandidateSource::FollowedAuthor, -5), now()),
+            ranker.score(&candidate(CandidateSource::FollowedAuthor, 0), now())
+        );
+    }
+
+    #[test]
+    fn sources_are_weighted() {
+        let ranker = DefaultFeedRanker::default();
+
+        let followed_author = candidate(CandidateSource::FollowedAuthor, 1);
+        let second_degree = candidate(CandidateSource::SecondDegree, 1);
+        let popular = candidate(CandidateSource::Popular, 1);
+
+        let followed_author_score = ranker.score(&followed_author, now());
+
+        assert!((ranker.score(&second_degree, now()) - followed_author_score * 0.6).abs() < 1e-9);
+        assert!((ranker.score(&popular, now()) - followed_author_score * 0.4).abs() < 1e-9);
+
+        let ranked = ranker.rank(
+            vec![
+                popular.clone(),
+                second_degree.clone(),
+                followed_author.clone(),
+            ],
+            now(),
+        );
+
+        assert_eq!(
+            ids(&ranked),
+            ids(&[followed_author, second_degree, popular])
+        );
+    }
+
+    #[test]
+    fn engagement_can_outweigh_age_and_source() {
+        let ranker = DefaultFeedRanker::default();
+
+        let quiet = candidate(CandidateSource::FollowedAuthor, 1);
+        let popular = FeedCandidate {
+            like_count: 500,
+            reply_count: 100,
+            ..candidate(CandidateSource::Popular, 6)
+        };
+
+        let ranked = ranker.rank(vec![quiet.clone(), popular.clone()], now());
+
+        assert_eq!(ids(&ranked), ids(&[popular, quiet]));
+    }
+
+    #[test]
+    fn newer_hesses_rank_first() {
+        let ranker = DefaultFeedRanker::default();
+
+        let old = candidate(CandidateSource::FollowedAuthor, 30);
+        let recent = candidate(CandidateSource::FollowedAuthor, 2);
+        let new = candidate(CandidateSource::FollowedAuthor, 0);
+
+        let ranked = ranker.rank(vec![old.clone(), new.clone(), recent.clone()], now());
+
+        assert_eq!(ids(&ranked), ids(&[new, recent, old]));
+    }
+
+    #[test]
+    fn ties_are_broken_by_id() {
+        let ranker = DefaultFeedRanker::default();
+
+        let mut candidates = (0..5)
+            .map(|_| candidate(CandidateSource::FollowedAuthor, 3))
+            .collect::<Vec<_>>();
+
+        let mut expected = ids(&candidates);
+        expected.sort_by(|a, b| b.cmp(a));
+
+        assert_eq!(ids(&ranker.rank(candidates.clone(), now())), expected);
+
+        candidates.reverse();
+
+        assert_eq!(ids(&ranker.rank(candidates, now())), expected);
+    }
+
+    #[test]
+    fn ties_are_broken_by_creation_date_first() {
+        // Without decay, the score no longer depends on the age of the hesses
+        let ranker = DefaultFeedRanker {
+            half_life_hours: f64::INFINITY,
+            ..Default::default()
+        };
+
+        let old = candidate(CandidateSource::FollowedAuthor, 10);
+        let new = candidate(CandidateSource::FollowedAuthor, 1);
+
+        assert_eq!(ranker.score(&old, now()), ranker.score(&new, now()));
+        assert_eq!(
+            ids(&ranker.rank(vec![old.clone(), new.clone()], now())),
+            ids(&[new, old])
+        );
+    }
+}
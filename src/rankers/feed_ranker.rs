@@ -0,0 +1,69 @@
+use chrono::{DateTime, Utc};
+
+use crate::models::Hess;
+
+/// Where a ranked feed candidate comes from, in order of precedence
+/// (a hess found through several sources keeps the first one).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CandidateSource {
+    /// A hess of a user the viewer follows.
+    FollowedAuthor,
+
+    /// A hess of a user followed by the users the viewer follows.
+    SecondDegree,
+
+    /// One of the most liked recent hesses.
+    Popular,
+}
+
+impl CandidateSource {
+    pub fn from_sql_str(value: &str) -> Option<CandidateSource> {
+        match value {
+            "FOLLOWED_AUTHOR" => Some(CandidateSource::FollowedAuthor),
+            "SECOND_DEGREE" => Some(CandidateSource::SecondDegree),
+            "POPULAR" => Some(CandidateSource::Popular),
+            _ => None,
+        }
+    }
+}
+
+/// A hess that may be shown in the ranked feed, with everything needed to score it.
+#[derive(Debug, Clone)]
+pub struct FeedCandidate {
+    pub hess: Hess,
+    pub source: CandidateSource,
+    /// The number of likes of the hess.
+    pub like_count: i64,
+    /// The number of replies to the hess.
+    pub reply_count: i64,
+    /// How many times the viewer recently interacted (likes and replies) with the author's hesses.
+    pub author_affinity: i64,
+}
+
+/// Scores the candidates of the ranked ("For You") feed.
+///
+/// Implementations must be pure: the same candidates and `now` must always give the same scores,
+/// pages of the ranked feed are sliced from a ranking computed again for every page.
+///
+pub trait FeedRanker: Send + Sync {
+    /// Scores a candidate, the higher the better.
+    fn score(&self, candidate: &FeedCandidate, now: DateTime<Utc>) -> f64;
+
+    /// Sorts the candidates by descending score, ties are broken by descending
+    /// creation date then by ID so the order is always the same.
+    fn rank(&self, candidates: Vec<FeedCandidate>, now: DateTime<Utc>) -> Vec<FeedCandidate> {
+        let mut scored = candidates
+            .into_iter()
+            .map(|candidate| (self.score(&candidate, now), candidate))
+            .collect::<Vec<_>>();
+
+        scored.sort_by(|(a_score, a), (b_score, b)| {
+            b_score
+                .total_cmp(a_score)
+                .then_with(|| b.hess.created_at.cmp(&a.hess.created_at))
+                .then_with(|| b.hess.id.cmp(&a.hess.id))
+        });
+
+        scored.into_iter().map(|(_, candidate)| candidate).collect()
+    }
+}
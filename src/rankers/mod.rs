@@ -0,0 +1,5 @@
+mod default_feed_ranker;
+mod feed_ranker;
+
+pub use default_feed_ranker::DefaultFeedRanker;
+pub use feed_ranker::{CandidateSource, FeedCandidate, FeedRanker};
@@ -0,0 +1,332 @@
+use chrono::{DateTime, Days, NaiveDate, Utc};
+
+use crate::utils::response::ApiErrorType;
+
+/// A parsed hess search query (the `q` query field).
+///
+/// The syntax is made of whitespace separated tokens:
+///
+/// - `word`: the hess must contain the word (stemmed, e.g. `running` matches `runs`).
+/// - `"some words"`: the hess must contain the phrase.
+/// - `from:username`: the hess must be written by this user.
+/// - `#tag`: the hess must contain the hashtag.
+/// - `since:YYYY-MM-DD` / `until:YYYY-MM-DD`: the hess must be created in this (inclusive) date range (UTC).
+/// - `has:media`: the hess must have media attached.
+///
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct HessSearchQuery {
+    pub words: Vec<String>,
+    pub phrases: Vec<String>,
+    pub from_username: Option<String>,
+    pub hashtags: Vec<String>,
+    pub since: Option<NaiveDate>,
+    pub until: Option<NaiveDate>,
+    pub has_media: bool,
+}
+
+/// Splits a query into tokens, double quoted phrases being a single token (quotes included).
+fn tokenize(query: &str) -> Result<Vec<String>, ApiErrorType> {
+    let mut tokens = Vec::new();
+    let mut current = String::new();
+    let mut in_phrase = false;
+
+    for character in query.chars() {
+        match character {
+            '"' if in_phrase => {
+                current.push(character);
+                tokens.push(std::mem::take(&mut current));
+                in_phrase = false;
+            }
+            '"' => {
+                if !current.is_empty() {
+                    tokens.push(std::mem::take(&mut current));
+                }
+                current.push(character);
+                in_phrase = true;
+            }
+            character if character.is_whitespace() && !in_phrase => {
+                if !current.is_empty() {
+                    tokens.push(std::mem::take(&mut current));
+                }
+            }
+            character => current.push(character),
+        }
+    }
+
+    if in_phrase {
+        return Err(ApiErrorType::InvalidSearchQueryField(current));
+    }
+
+    if !current.is_empty() {
+        tokens.push(current);
+    }
+
+    Ok(tokens)
+}
+
+fn is_hashtag_character(character: char) -> bool {
+    character.is_alphanumeric() || character == '_'
+}
+
+impl HessSearchQuery {
+    /// Parses a search query, see `HessSearchQuery` for the syntax.
+    ///
+    /// # Returns
+    ///
+    /// Returns the parsed query, or an `ApiErrorType::InvalidSearchQueryField` error holding the
+    /// offending token (e.g. an invalid date or an unterminated phrase), or an empty string if
+    /// the query holds nothing to search for.
+    ///
+    pub fn parse(query: &str) -> Result<HessSearchQuery, ApiErrorType> {
+        let mut parsed = HessSearchQuery::default();
+
+        for token in tokenize(query)? {
+            let invalid = || ApiErrorType::InvalidSearchQueryField(token.clone());
+
+            if let Some(phrase) = token.strip_prefix('"') {
+                let phrase = phrase.trim_end_matches('"').trim();
+
+                if !phrase.is_empty() {
+                    parsed.phrases.push(phrase.to_string());
+                }
+            } else if let Some(username) = token.strip_prefix("from:") {
+                let username = username.strip_prefix('@').unwrap_or(username);
+
+                if username.is_empty() || username.len() > 100 || parsed.from_username.is_some() {
+                    return Err(invalid());
+                }
+
+                parsed.from_username = Some(username.to_string());
+            } else if let Some(hashtag) = token.strip_prefix('#') {
+                if hashtag.is_empty() || !hashtag.chars().all(is_hashtag_character) {
+                    return Err(invalid());
+                }
+
+                parsed.hashtags.push(hashtag.to_lowercase());
+            } else if let Some(date) = token.strip_prefix("since:") {
+                parsed.since =
+                    Some(NaiveDate::parse_from_str(date, "%Y-%m-%d").map_err(|_| invalid())?);
+            } else if let Some(date) = token.strip_prefix("until:") {
+                parsed.until =
+                    Some(NaiveDate::parse_from_str(date, "%Y-%m-%d").map_err(|_| invalid())?);
+            } else if let Some(filter) = token.strip_prefix("has:") {
+                match filter {
+                    "media" => parsed.has_media = true,
+                    _ => return Err(invalid()),
+                }
+            } else {
+                parsed.words.push(token.clone());
+            }
+        }
+
+        if parsed == HessSearchQuery::default() {
+            return Err(ApiErrorType::InvalidSearchQueryField(String::new()));
+        }
+
+        Ok(parsed)
+    }
+
+    /// The full-text part of the query, in the `websearch_to_tsquery` syntax
+    /// (words, quoted phrases and the words of the hashtags), empty if there's none.
+    pub fn to_websearch(&self) -> String {
+        self.words
+            .iter()
+            .map(|word| word.replace('"', ""))
+            .chain(self.phrases.iter().map(|phrase| format!("\"{}\"", phrase)))
+            .chain(self.hashtags.iter().cloned())
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+
+    /// The start of the date range (inclusive).
+    pub fn since_timestamp(&self) -> Option<DateTime<Utc>> {
+        self.since
+            .and_then(|date| date.and_hms_opt(0, 0, 0))
+            .map(|date| date.and_utc())
+    }
+
+    /// The end of the date range (exclusive), the day after `until`.
+    pub fn until_timestamp(&self) -> Option<DateTime<Utc>> {
+        self.until
+            .and_then(|date| date.checked_add_days(Days::new(1)))
+            .and_then(|date| date.and_hms_opt(0, 0, 0))
+            .map(|date| date.and_utc())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(date: &str) -> Option<NaiveDate> {
+        Some(NaiveDate::parse_from_str(date, "%Y-%m-%d").unwrap())
+    }
+
+    fn strings(strings: &[&str]) -> Vec<String> {
+        strings.iter().map(|string| string.to_string()).collect()
+    }
+
+    /// The offending token of a rejected query.
+    fn invalid_token(query: &str) -> String {
+        match HessSearchQuery::parse(query) {
+            Err(ApiErrorType::InvalidSearchQueryField(token)) => token,
+            result => panic!("{:?} parsed as {:?}", query, result),
+        }
+    }
+
+    #[test]
+    fn valid_queries_are_parsed() {
+        let cases = [
+            (
+                "rust warp",
+                HessSearchQuery {
+                    words: strings(&["rust", "warp"]),
+                    ..Default::default()
+                },
+            ),
+            (
+                "  rust \t warp  ",
+                HessSearchQuery {
+                    words: strings(&["rust", "warp"]),
+                    ..Default::default()
+                },
+            ),
+            (
+                "\"hello world\" rust",
+                HessSearchQuery {
+                    words: strings(&["rust"]),
+                    phrases: strings(&["hello world"]),
+                    ..Default::default()
+                },
+            ),
+            (
+                "say\"hello world\"",
+                HessSearchQuery {
+                    words: strings(&["say"]),
+                    phrases: strings(&["hello world"]),
+                    ..Default::default()
+                },
+            ),
+            (
+                "rust -java",
+                HessSearchQuery {
+                    words: strings(&["rust", "-java"]),
+                    ..Default::default()
+                },
+            ),
+            (
+                "from:alice",
+                HessSearchQuery {
+                    from_username: Some("alice".to_string()),
+                    ..Default::default()
+                },
+            ),
+            (
+                "from:@alice news",
+                HessSearchQuery {
+                    words: strings(&["news"]),
+                    from_username: Some("alice".to_string()),
+                    ..Default::default()
+                },
+            ),
+            (
+                "#Rust #web_dev",
+                HessSearchQuery {
+                    hashtags: strings(&["rust", "web_dev"]),
+                    ..Default::default()
+                },
+            ),
+            (
+                "since:2024-01-31 until:2024-02-29",
+                HessSearchQuery {
+                    since: date("2024-01-31"),
+                    until: date("2024-02-29"),
+                    ..Default::default()
+                },
+            ),
+            (
+                "has:media",
+                HessSearchQuery {
+                    has_media: true,
+                    ..Default::default()
+                },
+            ),
+            (
+                "\"\" cats",
+                HessSearchQuery {
+                    words: strings(&["cats"]),
+                    ..Default::default()
+                },
+            ),
+        ];
+
+        for (query, expected) in cases {
+            assert_eq!(
+                HessSearchQuery::parse(query).unwrap(),
+                expected,
+                "{:?}",
+                query
+            );
+        }
+    }
+
+    #[test]
+    fn invalid_tokens_are_reported() {
+        let cases = [
+            ("\"hello world", "\"hello world"),
+            ("rust \"unterminated", "\"unterminated"),
+            ("from:", "from:"),
+            ("from:@", "from:@"),
+            ("from:alice from:bob", "from:bob"),
+            ("#", "#"),
+            ("#c++", "#c++"),
+            ("since:yesterday", "since:yesterday"),
+            ("until:2024-02-30", "until:2024-02-30"),
+            ("has:links", "has:links"),
+        ];
+
+        for (query, token) in cases {
+            assert_eq!(invalid_token(query), token, "{:?}", query);
+        }
+
+        let long_username = format!("from:{}", "a".repeat(101));
+        assert_eq!(invalid_token(&long_username), long_username);
+    }
+
+    #[test]
+    fn empty_queries_are_rejected() {
+        for query in ["", "   ", "\"\"", "\" \""] {
+            assert_eq!(invalid_token(query), "", "{:?}", query);
+        }
+    }
+
+    #[test]
+    fn operator_only_queries_are_accepted() {
+        let query = HessSearchQuery::parse("from:alice has:media").unwrap();
+
+        assert_eq!(query.from_username.as_deref(), Some("alice"));
+        assert!(query.has_media);
+        assert_eq!(query.to_websearch(), "");
+    }
+
+    #[test]
+    fn to_websearch_keeps_phrases_and_negations() {
+        let query = HessSearchQuery::parse("rust -java \"hello world\" #Warp").unwrap();
+
+        assert_eq!(query.to_websearch(), "rust -java \"hello world\" warp");
+    }
+
+    #[test]
+    fn date_range_is_inclusive() {
+        let query = HessSearchQuery::parse("since:2024-02-28 until:2024-02-28").unwrap();
+
+        assert_eq!(
+            query.since_timestamp().unwrap().to_rfc3339(),
+            "2024-02-28T00:00:00+00:00"
+        );
+        assert_eq!(
+            query.until_timestamp().unwrap().to_rfc3339(),
+            "2024-02-29T00:00:00+00:00"
+        );
+    }
+}
@@ -0,0 +1,37 @@
+mod hess_search_query;
+mod postgres_search_index;
+mod search_index;
+#[cfg(feature = "tantivy-search")]
+mod tantivy_search_index;
+mod user_search_query;
+
+use std::sync::Arc;
+
+use crate::utils::{SearchBackend, SearchConfig};
+
+pub use hess_search_query::HessSearchQuery;
+pub use postgres_search_index::PostgresSearchIndex;
+pub use search_index::SearchIndex;
+#[cfg(feature = "tantivy-search")]
+pub use tantivy_search_index::TantivySearchIndex;
+pub use user_search_query::UserSearchQuery;
+
+/// Opens the search backend chosen in the configuration.
+///
+/// Panics if the index can't be opened, or if Tantivy is chosen but the app
+/// was built without the `tantivy-search` feature.
+///
+pub fn open_search_index(search_config: &SearchConfig) -> Arc<dyn SearchIndex> {
+    match search_config.backend {
+        SearchBackend::Postgres => Arc::new(PostgresSearchIndex),
+        #[cfg(feature = "tantivy-search")]
+        SearchBackend::Tantivy => Arc::new(
+            TantivySearchIndex::open(&search_config.index_path)
+                .expect("Couldn't open the Tantivy search index"),
+        ),
+        #[cfg(not(feature = "tantivy-search"))]
+        SearchBackend::Tantivy => {
+            panic!("The Tantivy search backend requires building with the `tantivy-search` feature")
+        }
+    }
+}
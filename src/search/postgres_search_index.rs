@@ -0,0 +1,56 @@
+use async_trait::async_trait;
+use sqlx::{Pool, Postgres};
+use uuid::Uuid;
+
+use crate::{
+    models::{HessSearchResult, UserSearchResult},
+    services::search_service,
+    utils::response::ApiErrorType,
+};
+
+use super::{HessSearchQuery, SearchIndex, UserSearchQuery};
+
+/// The default search backend, running the searches on the Postgres full-text (`search_vector`)
+/// and trigram indexes.
+///
+/// The indexes are maintained by Postgres itself, so there's nothing to (re)index.
+///
+#[derive(Default)]
+pub struct PostgresSearchIndex;
+
+#[async_trait]
+impl SearchIndex for PostgresSearchIndex {
+    async fn search_hesses(
+        &self,
+        pool: Pool<Postgres>,
+        viewer_id: Uuid,
+        query: &HessSearchQuery,
+        page: i64,
+        size: i64,
+    ) -> Result<Vec<HessSearchResult>, ApiErrorType> {
+        search_service::search_hesses(pool, viewer_id, query, page, size).await
+    }
+
+    async fn search_users(
+        &self,
+        pool: Pool<Postgres>,
+        viewer_id: Uuid,
+        query: &UserSearchQuery,
+        page: i64,
+        size: i64,
+    ) -> Result<Vec<UserSearchResult>, ApiErrorType> {
+        search_service::search_users(pool, viewer_id, query, page, size).await
+    }
+
+    async fn index_hess(&self, _pool: Pool<Postgres>, _hess_id: Uuid) -> Result<(), ApiErrorType> {
+        Ok(())
+    }
+
+    async fn index_user(&self, _pool: Pool<Postgres>, _user_id: Uuid) -> Result<(), ApiErrorType> {
+        Ok(())
+    }
+
+    async fn reindex_all(&self, _pool: Pool<Postgres>) -> Result<(), ApiErrorType> {
+        Ok(())
+    }
+}
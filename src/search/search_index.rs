@@ -0,0 +1,50 @@
+use async_trait::async_trait;
+use sqlx::{Pool, Postgres};
+use uuid::Uuid;
+
+use crate::{
+    models::{HessSearchResult, UserSearchResult},
+    utils::response::ApiErrorType,
+};
+
+use super::{HessSearchQuery, UserSearchQuery};
+
+/// A search backend, indexing and querying hesses and users.
+///
+/// Whatever the backend, the results are always checked against the database: only the hesses
+/// the viewer could otherwise view, and the users they could otherwise find, are returned.
+///
+/// The index is kept in sync by the search worker, which calls `index_hess` and `index_user`
+/// for every change enqueued by the database triggers in the `search` queue.
+///
+#[async_trait]
+pub trait SearchIndex: Send + Sync {
+    /// Searches the hesses visible to a viewer, best matches first.
+    async fn search_hesses(
+        &self,
+        pool: Pool<Postgres>,
+        viewer_id: Uuid,
+        query: &HessSearchQuery,
+        page: i64,
+        size: i64,
+    ) -> Result<Vec<HessSearchResult>, ApiErrorType>;
+
+    /// Searches users by username and name, best matches first.
+    async fn search_users(
+        &self,
+        pool: Pool<Postgres>,
+        viewer_id: Uuid,
+        query: &UserSearchQuery,
+        page: i64,
+        size: i64,
+    ) -> Result<Vec<UserSearchResult>, ApiErrorType>;
+
+    /// Adds, updates or removes (if soft-deleted) a hess from the index.
+    async fn index_hess(&self, pool: Pool<Postgres>, hess_id: Uuid) -> Result<(), ApiErrorType>;
+
+    /// Adds, updates or removes (if soft-deleted or unactivated) a user from the index.
+    async fn index_user(&self, pool: Pool<Postgres>, user_id: Uuid) -> Result<(), ApiErrorType>;
+
+    /// Rebuilds the whole index from the database.
+    async fn reindex_all(&self, pool: Pool<Postgres>) -> Result<(), ApiErrorType>;
+}
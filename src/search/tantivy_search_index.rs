@@ -0,0 +1,551 @@
+use std::{
+    collections::HashMap,
+    path::Path,
+    sync::{Arc, Mutex},
+};
+
+use async_trait::async_trait;
+use sqlx::{Pool, Postgres};
+use tantivy::{
+    collector::TopDocs,
+    directory::MmapDirectory,
+    doc,
+    query::{BooleanQuery, BoostQuery, FuzzyTermQuery, Occur, PhraseQuery, Query, TermQuery},
+    schema::{
+        Field, IndexRecordOption, OwnedValue, Schema, TextFieldIndexing, TextOptions, STORED,
+        STRING,
+    },
+    tokenizer::TokenStream,
+    DocAddress, Index, IndexReader, IndexWriter, ReloadPolicy, Searcher, SnippetGenerator,
+    TantivyDocument, Term,
+};
+use tokio::{sync::mpsc, task::JoinHandle};
+use uuid::Uuid;
+
+use crate::{
+    models::{HessSearchResult, UserSearchResult},
+    services::search_service::{
+        self, filter_hess_search_candidates, filter_user_search_candidates,
+        get_hess_index_document, get_hess_index_documents, get_user_index_document,
+        get_user_index_documents,
+    },
+    utils::response::ApiErrorType,
+};
+
+use super::{HessSearchQuery, SearchIndex, UserSearchQuery};
+
+const WRITER_MEMORY_BUDGET: usize = 50_000_000;
+/// How many candidates are taken from the index at once, before being checked against the database.
+const CANDIDATES_BATCH_SIZE: usize = 200;
+const REINDEX_BATCH_SIZE: i64 = 1000;
+
+fn internal_error<E: std::fmt::Debug>(error: E) -> ApiErrorType {
+    log::error!("Search index error: {:?}", error);
+    ApiErrorType::InternalServerError
+}
+
+/// An on-disk index of one kind of document, identified by their `id` field.
+struct Collection {
+    index: Index,
+    reader: IndexReader,
+    writer: Arc<Mutex<IndexWriter>>,
+    id_field: Field,
+}
+
+impl Collection {
+    fn open(path: &Path, schema: Schema) -> tantivy::Result<Collection> {
+        std::fs::create_dir_all(path)?;
+
+        let directory = MmapDirectory::open(path)?;
+        let index = Index::open_or_create(directory, schema)?;
+        let reader = index
+            .reader_builder()
+            .reload_policy(ReloadPolicy::OnCommitWithDelay)
+            .try_into()?;
+        let writer = index.writer(WRITER_MEMORY_BUDGET)?;
+        let id_field = index.schema().get_field("id")?;
+
+        Ok(Collection {
+            index,
+            reader,
+            writer: Arc::new(Mutex::new(writer)),
+            id_field,
+        })
+    }
+
+    fn field(&self, name: &str) -> Field {
+        self.index.schema().get_field(name).unwrap()
+    }
+
+    /// Splits a text into tokens the same way the field is indexed.
+    fn tokens(&self, field: Field, text: &str) -> Result<Vec<String>, ApiErrorType> {
+        let mut analyzer = self
+            .index
+            .tokenizer_for_field(field)
+            .map_err(internal_error)?;
+        let mut stream = analyzer.token_stream(text);
+        let mut tokens = Vec::new();
+
+        while stream.advance() {
+            tokens.push(stream.token().text.clone());
+        }
+
+        Ok(tokens)
+    }
+
+    fn terms(&self, field: Field, text: &str) -> Result<Vec<Term>, ApiErrorType> {
+        Ok(self
+            .tokens(field, text)?
+            .iter()
+            .map(|token| Term::from_field_text(field, token))
+            .collect())
+    }
+
+    /// Gets a batch of matching documents ids, best matches first.
+    fn candidates(
+        &self,
+        searcher: &Searcher,
+        query: &dyn Query,
+        offset: usize,
+    ) -> Result<Vec<(f32, Uuid)>, ApiErrorType> {
+        let top_docs = searcher
+            .search(
+                query,
+                &TopDocs::with_limit(CANDIDATES_BATCH_SIZE).and_offset(offset),
+            )
+            .map_err(internal_error)?;
+
+        let mut candidates = Vec::with_capacity(top_docs.len());
+
+        for (score, address) in top_docs {
+            candidates.push((score, self.document_id(searcher, address)?));
+        }
+
+        Ok(candidates)
+    }
+
+    fn document_id(&self, searcher: &Searcher, address: DocAddress) -> Result<Uuid, ApiErrorType> {
+        let document = searcher
+            .doc::<TantivyDocument>(address)
+            .map_err(internal_error)?;
+
+        match document.get_first(self.id_field) {
+            Some(OwnedValue::Str(id)) => Uuid::parse_str(id).map_err(internal_error),
+            _ => Err(ApiErrorType::InternalServerError),
+        }
+    }
+
+    /// Replaces (or removes, if there's no new document) a document then commits.
+    async fn replace(
+        &self,
+        id: Uuid,
+        document: Option<TantivyDocument>,
+    ) -> Result<(), ApiErrorType> {
+        let writer = self.writer.clone();
+        let term = Term::from_field_text(self.id_field, &id.to_string());
+
+        tokio::task::spawn_blocking(move || {
+            let mut writer = writer.lock().unwrap();
+
+            writer.delete_term(term);
+
+            if let Some(document) = document {
+                writer.add_document(document)?;
+            }
+
+            writer.commit().map(|_| ())
+        })
+        .await
+        .map_err(internal_error)?
+        .map_err(internal_error)
+    }
+
+    /// Starts rebuilding the collection from the batches of documents sent to the returned
+    /// channel, an empty batch ends the rebuild and commits it.
+    ///
+    /// The writer is held for the whole rebuild, so that no other commit (see `replace`) publishes
+    /// a partially rebuilt index, searches keep using the previous one until the end. If the
+    /// channel is closed before the empty batch, the rebuild is rolled back.
+    fn rebuild(
+        &self,
+    ) -> (
+        mpsc::Sender<Vec<TantivyDocument>>,
+        JoinHandle<Result<(), ApiErrorType>>,
+    ) {
+        let writer = self.writer.clone();
+        let (sender, mut receiver) = mpsc::channel::<Vec<TantivyDocument>>(1);
+
+        let task = tokio::task::spawn_blocking(move || {
+            let mut writer = writer.lock().unwrap();
+
+            let result = (|| {
+                writer.delete_all_documents()?;
+
+                while let Some(documents) = receiver.blocking_recv() {
+                    if documents.is_empty() {
+                        return writer.commit().map(|_| true);
+                    }
+
+                    for document in documents {
+                        writer.add_document(document)?;
+                    }
+                }
+
+                Ok(false)
+            })();
+
+            match result {
+                Ok(true) => Ok(()),
+                Ok(false) => {
+                    writer.rollback().map_err(internal_error)?;
+                    Err(ApiErrorType::InternalServerError)
+                }
+                Err(error) => {
+                    writer.rollback().map_err(internal_error)?;
+                    Err(internal_error(error))
+                }
+            }
+        });
+
+        (sender, task)
+    }
+}
+
+fn hesses_schema() -> Schema {
+    let mut builder = Schema::builder();
+
+    builder.add_text_field("id", STRING | STORED);
+    builder.add_text_field(
+        "content",
+        TextOptions::default().set_indexing_options(
+            TextFieldIndexing::default()
+                .set_tokenizer("en_stem")
+                .set_index_option(IndexRecordOption::WithFreqsAndPositions),
+        ),
+    );
+
+    builder.build()
+}
+
+fn users_schema() -> Schema {
+    let mut builder = Schema::builder();
+
+    builder.add_text_field("id", STRING | STORED);
+    builder.add_text_field(
+        "username",
+        TextOptions::default().set_indexing_options(TextFieldIndexing::default()),
+    );
+    builder.add_text_field(
+        "name",
+        TextOptions::default().set_indexing_options(TextFieldIndexing::default()),
+    );
+
+    builder.build()
+}
+
+/// An embedded search backend, running the searches on a Tantivy index stored on disk
+/// (`hesses` and `users` sub-directories), with no external service.
+///
+/// The index only holds the searchable text: the filters of the queries (`from:`, hashtags, dates,
+/// `has:media`), the visibility policy and the user exclusions are applied by checking the matches
+/// against the database, by batches, until the page is full.
+///
+/// Hess queries without words, phrases nor hashtags to match (filters or exclusions only) are run
+/// on Postgres, there's nothing to rank. The followed and verified boosts of the Postgres user
+/// search aren't applied, users are only ranked by how well they match.
+///
+pub struct TantivySearchIndex {
+    hesses: Collection,
+    users: Collection,
+}
+
+impl TantivySearchIndex {
+    /// Opens the index stored in a directory, creating it if needed.
+    pub fn open(path: &str) -> tantivy::Result<TantivySearchIndex> {
+        let path = Path::new(path);
+
+        Ok(TantivySearchIndex {
+            hesses: Collection::open(&path.join("hesses"), hesses_schema())?,
+            users: Collection::open(&path.join("users"), users_schema())?,
+        })
+    }
+
+    fn hesses_query(
+        &self,
+        query: &HessSearchQuery,
+    ) -> Result<Option<Box<dyn Query>>, ApiErrorType> {
+        let content = self.hesses.field("content");
+        let mut clauses: Vec<(Occur, Box<dyn Query>)> = Vec::new();
+
+        // Same as the Postgres websearch: hashtags are words too, `-word` excludes the word
+        for word in query.words.iter().chain(&query.hashtags) {
+            let (occur, word) = match word.strip_prefix('-') {
+                Some(excluded) => (Occur::MustNot, excluded),
+                None => (Occur::Must, word.as_str()),
+            };
+
+            for term in self.hesses.terms(content, word)? {
+                clauses.push((
+                    occur,
+                    Box::new(TermQuery::new(term, IndexRecordOption::WithFreqs)),
+                ));
+            }
+        }
+
+        for phrase in &query.phrases {
+            let mut terms = self.hesses.terms(content, phrase)?;
+
+            match terms.len() {
+                0 => {}
+                1 => clauses.push((
+                    Occur::Must,
+                    Box::new(TermQuery::new(
+                        terms.remove(0),
+                        IndexRecordOption::WithFreqs,
+                    )),
+                )),
+                _ => clauses.push((Occur::Must, Box::new(PhraseQuery::new(terms)))),
+            }
+        }
+
+        // Only exclusions can't be ranked either
+        if clauses.iter().all(|(occur, _)| *occur == Occur::MustNot) {
+            return Ok(None);
+        }
+
+        Ok(Some(Box::new(BooleanQuery::new(clauses))))
+    }
+
+    fn users_query(&self, query: &UserSearchQuery) -> Result<Box<dyn Query>, ApiErrorType> {
+        let username = self.users.field("username");
+        let name = self.users.field("name");
+        let mut clauses: Vec<(Occur, Box<dyn Query>)> = Vec::new();
+
+        for text in self.users.tokens(name, &query.text)? {
+            // The longer the word, the more typos are tolerated
+            let distance = match text.chars().count() {
+                0..=2 => 0,
+                3..=5 => 1,
+                _ => 2,
+            };
+
+            let mut alternatives: Vec<(Occur, Box<dyn Query>)> = Vec::new();
+
+            for field in [username, name] {
+                let term = Term::from_field_text(field, &text);
+
+                alternatives.push((
+                    Occur::Should,
+                    Box::new(BoostQuery::new(
+                        Box::new(TermQuery::new(term.clone(), IndexRecordOption::WithFreqs)),
+                        3.0,
+                    )),
+                ));
+                alternatives.push((
+                    Occur::Should,
+                    Box::new(BoostQuery::new(
+                        Box::new(FuzzyTermQuery::new_prefix(term.clone(), 0, true)),
+                        2.0,
+                    )),
+                ));
+                alternatives.push((
+                    Occur::Should,
+                    Box::new(FuzzyTermQuery::new(term, distance, true)),
+                ));
+            }
+
+            clauses.push((Occur::Must, Box::new(BooleanQuery::new(alternatives))));
+        }
+
+        Ok(Box::new(BooleanQuery::new(clauses)))
+    }
+}
+
+#[async_trait]
+impl SearchIndex for TantivySearchIndex {
+    async fn search_hesses(
+        &self,
+        pool: Pool<Postgres>,
+        viewer_id: Uuid,
+        query: &HessSearchQuery,
+        page: i64,
+        size: i64,
+    ) -> Result<Vec<HessSearchResult>, ApiErrorType> {
+        let text_query = match self.hesses_query(query)? {
+            Some(text_query) => text_query,
+            None => return search_service::search_hesses(pool, viewer_id, query, page, size).await,
+        };
+
+        let searcher = self.hesses.reader.searcher();
+        let skipped = ((page - 1) * size) as usize;
+        let wanted = skipped + size as usize;
+
+        let mut results = Vec::new();
+        let mut offset = 0;
+
+        loop {
+            let candidates = self
+                .hesses
+                .candidates(&searcher, text_query.as_ref(), offset)?;
+            let scores: HashMap<Uuid, f32> =
+                candidates.iter().map(|(score, id)| (*id, *score)).collect();
+            let ids: Vec<Uuid> = candidates.iter().map(|(_, id)| *id).collect();
+
+            for hess in filter_hess_search_candidates(pool.clone(), viewer_id, query, &ids).await? {
+                let rank = scores.get(&hess.id).copied().unwrap_or_default();
+                results.push((hess, rank));
+            }
+
+            if results.len() >= wanted || candidates.len() < CANDIDATES_BATCH_SIZE {
+                break;
+            }
+
+            offset += CANDIDATES_BATCH_SIZE;
+        }
+
+        let content = self.hesses.field("content");
+        let snippet_generator = SnippetGenerator::create(&searcher, text_query.as_ref(), content)
+            .map_err(internal_error)?;
+
+        Ok(results
+            .into_iter()
+            .skip(skipped)
+            .take(size as usize)
+            .map(|(hess, rank)| {
+                let mut snippet = snippet_generator.snippet(hess.content.as_deref().unwrap_or(""));
+                snippet.set_snippet_prefix_postfix("<mark>", "</mark>");
+
+                HessSearchResult {
+                    snippet: (!snippet.is_empty()).then(|| snippet.to_html()),
+                    hess,
+                    rank,
+                }
+            })
+            .collect())
+    }
+
+    async fn search_users(
+        &self,
+        pool: Pool<Postgres>,
+        viewer_id: Uuid,
+        query: &UserSearchQuery,
+        page: i64,
+        size: i64,
+    ) -> Result<Vec<UserSearchResult>, ApiErrorType> {
+        let text_query = self.users_query(query)?;
+
+        let searcher = self.users.reader.searcher();
+        let skipped = ((page - 1) * size) as usize;
+        let wanted = skipped + size as usize;
+
+        let mut results = Vec::new();
+        let mut offset = 0;
+
+        loop {
+            let candidates = self
+                .users
+                .candidates(&searcher, text_query.as_ref(), offset)?;
+            let scores: HashMap<Uuid, f32> =
+                candidates.iter().map(|(score, id)| (*id, *score)).collect();
+            let ids: Vec<Uuid> = candidates.iter().map(|(_, id)| *id).collect();
+
+            for mut user in filter_user_search_candidates(pool.clone(), viewer_id, &ids).await? {
+                user.score = scores.get(&user.id).copied().unwrap_or_default();
+                results.push(user);
+            }
+
+            if results.len() >= wanted || candidates.len() < CANDIDATES_BATCH_SIZE {
+                break;
+            }
+
+            offset += CANDIDATES_BATCH_SIZE;
+        }
+
+        Ok(results
+            .into_iter()
+            .skip(skipped)
+            .take(size as usize)
+            .collect())
+    }
+
+    async fn index_hess(&self, pool: Pool<Postgres>, hess_id: Uuid) -> Result<(), ApiErrorType> {
+        let document = get_hess_index_document(pool, hess_id).await?.map(|hess| {
+            doc!(
+                self.hesses.id_field => hess.id.to_string(),
+                self.hesses.field("content") => hess.content.unwrap_or_default(),
+            )
+        });
+
+        self.hesses.replace(hess_id, document).await
+    }
+
+    async fn index_user(&self, pool: Pool<Postgres>, user_id: Uuid) -> Result<(), ApiErrorType> {
+        let document = get_user_index_document(pool, user_id).await?.map(|user| {
+            doc!(
+                self.users.id_field => user.id.to_string(),
+                self.users.field("username") => user.username,
+                self.users.field("name") => user.name,
+            )
+        });
+
+        self.users.replace(user_id, document).await
+    }
+
+    async fn reindex_all(&self, pool: Pool<Postgres>) -> Result<(), ApiErrorType> {
+        let content = self.hesses.field("content");
+        let (batches, rebuild) = self.hesses.rebuild();
+        let mut after_id = None;
+
+        loop {
+            let hesses =
+                get_hess_index_documents(pool.clone(), after_id, REINDEX_BATCH_SIZE).await?;
+
+            after_id = hesses.last().map(|hess| hess.id);
+
+            let documents = hesses
+                .into_iter()
+                .map(|hess| {
+                    doc!(
+                        self.hesses.id_field => hess.id.to_string(),
+                        content => hess.content.unwrap_or_default(),
+                    )
+                })
+                .collect();
+
+            if batches.send(documents).await.is_err() || after_id.is_none() {
+                break;
+            }
+        }
+
+        rebuild.await.map_err(internal_error)??;
+
+        let username = self.users.field("username");
+        let name = self.users.field("name");
+        let (batches, rebuild) = self.users.rebuild();
+        let mut after_id = None;
+
+        loop {
+            let users =
+                get_user_index_documents(pool.clone(), after_id, REINDEX_BATCH_SIZE).await?;
+
+            after_id = users.last().map(|user| user.id);
+
+            let documents = users
+                .into_iter()
+                .map(|user| {
+                    doc!(
+                        self.users.id_field => user.id.to_string(),
+                        username => user.username,
+                        name => user.name,
+                    )
+                })
+                .collect();
+
+            if batches.send(documents).await.is_err() || after_id.is_none() {
+                break;
+            }
+        }
+
+        rebuild.await.map_err(internal_error)?
+    }
+}
@@ -0,0 +1,112 @@
+use crate::utils::response::ApiErrorType;
+
+/// A normalized user search query (the `q` query field), matched against usernames and names.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UserSearchQuery {
+    /// The trimmed and lowercased query, without a leading `@`.
+    pub text: String,
+}
+
+impl UserSearchQuery {
+    /// Normalizes a user search query.
+    ///
+    /// # Returns
+    ///
+    /// Returns the normalized query, or an `ApiErrorType::InvalidSearchQueryField` error
+    /// if it's empty or longer than 100 characters.
+    ///
+    pub fn parse(query: &str) -> Result<UserSearchQuery, ApiErrorType> {
+        let trimmed = query.trim();
+        let text = trimmed
+            .strip_prefix('@')
+            .unwrap_or(trimmed)
+            .trim()
+            .to_lowercase();
+
+        if text.is_empty() || text.chars().count() > 100 {
+            return Err(ApiErrorType::InvalidSearchQueryField(query.to_string()));
+        }
+
+        Ok(UserSearchQuery { text })
+    }
+
+    /// The query as a `LIKE` prefix pattern (with the wildcards of the query escaped).
+    pub fn like_prefix_pattern(&self) -> String {
+        let escaped = self
+            .text
+            .replace('\\', "\\\\")
+            .replace('%', "\\%")
+            .replace('_', "\\_");
+
+        format!("{}%", escaped)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn text(query: &str) -> String {
+        UserSearchQuery::parse(query).unwrap().text
+    }
+
+    fn is_invalid(query: &str) -> bool {
+        matches!(
+            UserSearchQuery::parse(query),
+            Err(ApiErrorType::InvalidSearchQueryField(field)) if field == query
+        )
+    }
+
+    #[test]
+    fn queries_are_normalized() {
+        let cases = [
+            ("alice", "alice"),
+            ("  Alice  ", "alice"),
+            ("@Alice", "alice"),
+            ("  @ alice ", "alice"),
+            ("@@alice", "@alice"),
+            ("alice@hesshub", "alice@hesshub"),
+            ("Alice Smith", "alice smith"),
+            ("ÉLODIE", "élodie"),
+        ];
+
+        for (query, expected) in cases {
+            assert_eq!(text(query), expected, "{:?}", query);
+        }
+    }
+
+    #[test]
+    fn empty_queries_are_rejected() {
+        for query in ["", "   ", "@", " @ "] {
+            assert!(is_invalid(query), "{:?}", query);
+        }
+    }
+
+    #[test]
+    fn length_is_limited_to_100_characters() {
+        assert_eq!(text(&"a".repeat(100)), "a".repeat(100));
+        assert_eq!(text(&format!("@{}", "a".repeat(100))), "a".repeat(100));
+        assert_eq!(text(&"é".repeat(100)), "é".repeat(100));
+        assert!(is_invalid(&"a".repeat(101)));
+        assert!(is_invalid(&"é".repeat(101)));
+    }
+
+    #[test]
+    fn like_prefix_pattern_escapes_wildcards() {
+        let cases = [
+            ("alice", "alice%"),
+            ("al_ice", "al\\_ice%"),
+            ("100%", "100\\%%"),
+            ("back\\slash", "back\\\\slash%"),
+        ];
+
+        for (query, expected) in cases {
+            assert_eq!(
+                UserSearchQuery::parse(query).unwrap().like_prefix_pattern(),
+                expected,
+                "{:?}",
+                query
+            );
+        }
+    }
+}
@@ -0,0 +1,416 @@
+use chrono::Utc;
+use sqlx::{Pool, Postgres};
+use uuid::Uuid;
+
+use crate::{
+    models::{AudienceList, AudienceListMember, Page},
+    models_validators::audience_list_validator::InsertOrUpdateAudienceListData,
+    utils::{
+        list_query::{ListQuery, ListSpec, SortField},
+        response::{ApiErrorType, ApiResource},
+    },
+};
+
+/// Retrieves a (non deleted) audience list by its ID from the database.
+///
+/// # Arguments
+///
+/// * `pool` - A database connection pool.
+/// * `id` - The UUID of the audience list to retrieve.
+///
+/// # Returns
+///
+/// Returns a `Result` containing the retrieved `AudienceList` if successful.
+/// If no audience list is found, returns an `ApiErrorType::ResourceNotFound` error.
+/// If any other error occurs during the database query, returns an `ApiErrorType::InternalServerError` error.
+///
+pub async fn get_audience_list_by_id(
+    pool: Pool<Postgres>,
+    id: Uuid,
+) -> Result<AudienceList, ApiErrorType> {
+    let query_result = sqlx::query_as!(
+        AudienceList,
+        r#"SELECT id, owner_id, name, created_at, updated_at, deleted_at
+        FROM audience_lists WHERE id = $1 AND deleted_at IS NULL
+        "#,
+        id
+    )
+    .fetch_optional(&pool)
+    .await;
+
+    match query_result {
+        Ok(Some(audience_list)) => Ok(audience_list),
+        Ok(None) => Err(ApiErrorType::ResourceNotFound(ApiResource::AudienceLists)),
+        Err(_) => Err(ApiErrorType::InternalServerError),
+    }
+}
+
+/// The sortable fields of the audience lists.
+pub static AUDIENCE_LISTS_LIST_SPEC: ListSpec = ListSpec {
+    table: "audience_lists",
+    sort_fields: &[
+        SortField {
+            name: "name",
+            column: "name",
+        },
+        SortField {
+            name: "createdAt",
+            column: "created_at",
+        },
+        SortField {
+            name: "updatedAt",
+            column: "updated_at",
+        },
+    ],
+    default_sort: "createdAt",
+    filter_fields: &[],
+};
+
+/// Retrieves a page of the (non deleted) audience lists owned by a user.
+///
+/// # Arguments
+///
+/// * `pool` - A database connection pool.
+/// * `owner_id` - The UUID of the user owning the audience lists.
+/// * `list_query` - The sorting and pagination, parsed against `AUDIENCE_LISTS_LIST_SPEC`.
+///
+/// # Returns
+///
+/// Returns a `Result` containing the page of audience lists.
+/// If any error occurs during the database query, returns an `ApiErrorType::InternalServerError` error.
+///
+pub async fn get_audience_lists_by_owner_id(
+    pool: Pool<Postgres>,
+    owner_id: Uuid,
+    list_query: ListQuery,
+) -> Result<Page<AudienceList>, ApiErrorType> {
+    let total = sqlx::query_scalar!(
+        r#"SELECT COUNT(*) AS "count!" FROM audience_lists WHERE owner_id = $1 AND deleted_at IS NULL"#,
+        owner_id
+    )
+    .fetch_one(&pool)
+    .await
+    .map_err(|_| ApiErrorType::InternalServerError)?;
+
+    let query_string = format!(
+        r#"SELECT l.id, l.owner_id, l.name, l.created_at, l.updated_at, l.deleted_at
+        FROM audience_lists l
+        {}
+        WHERE l.owner_id = $1 AND l.deleted_at IS NULL AND {}
+        ORDER BY {}
+        LIMIT $3 OFFSET $4
+        "#,
+        list_query.keyset_join_sql("$2"),
+        list_query.keyset_condition_sql("l"),
+        list_query.order_by_sql("l")
+    );
+
+    let query_result = sqlx::query_as::<_, AudienceList>(query_string.as_str())
+        .bind(owner_id)
+        .bind(list_query.after_id)
+        .bind(list_query.limit())
+        .bind(list_query.offset())
+        .fetch_all(&pool)
+        .await;
+
+    match query_result {
+        Ok(audience_lists) => Ok(list_query.into_page(audience_lists, total, |list| list.id)),
+        Err(_) => Err(ApiErrorType::InternalServerError),
+    }
+}
+
+/// Retrieves the IDs of all the (non deleted) audience lists owned by a user.
+///
+/// This is mainly used to validate `WhoCan::List` references before persisting them.
+///
+/// # Arguments
+///
+/// * `pool` - A database connection pool.
+/// * `owner_id` - The UUID of the user owning the audience lists.
+///
+/// # Returns
+///
+/// Returns a `Result` containing the audience lists IDs.
+/// If any error occurs during the database query, returns an `ApiErrorType::InternalServerError` error.
+///
+pub async fn get_audience_list_ids_by_owner_id(
+    pool: Pool<Postgres>,
+    owner_id: Uuid,
+) -> Result<Vec<Uuid>, ApiErrorType> {
+    let query_result = sqlx::query_scalar!(
+        r#"SELECT id FROM audience_lists WHERE owner_id = $1 AND deleted_at IS NULL"#,
+        owner_id
+    )
+    .fetch_all(&pool)
+    .await;
+
+    match query_result {
+        Ok(ids) => Ok(ids),
+        Err(_) => Err(ApiErrorType::InternalServerError),
+    }
+}
+
+/// Inserts a new audience list into the database.
+///
+/// # Arguments
+///
+/// * `pool` - A database connection pool.
+/// * `owner_id` - The UUID of the user owning the audience list.
+/// * `insert_audience_list_data` - Data to insert for the new audience list.
+///
+/// # Returns
+///
+/// Returns a `Result` containing the inserted `AudienceList` if successful.
+/// If any error occurs during database insertion, returns an `ApiErrorType::InternalServerError` error.
+///
+pub async fn insert_audience_list(
+    pool: Pool<Postgres>,
+    owner_id: Uuid,
+    insert_audience_list_data: InsertOrUpdateAudienceListData,
+) -> Result<AudienceList, ApiErrorType> {
+    let query_result = sqlx::query_as!(
+        AudienceList,
+        r#"INSERT INTO audience_lists (owner_id, name) VALUES ($1, $2)
+        RETURNING id, owner_id, name, created_at, updated_at, deleted_at
+        "#,
+        owner_id,
+        insert_audience_list_data.name
+    )
+    .fetch_one(&pool)
+    .await;
+
+    match query_result {
+        Ok(audience_list) => Ok(audience_list),
+        Err(_) => Err(ApiErrorType::InternalServerError),
+    }
+}
+
+/// Updates an audience list's information in the database.
+///
+/// # Arguments
+///
+/// * `pool` - A database connection pool.
+/// * `id` - The UUID of the audience list to update.
+/// * `update_audience_list_data` - Data containing the fields to update.
+///
+/// # Returns
+///
+/// Returns a `Result` containing the updated `AudienceList` if successful.
+/// If no audience list with the provided ID and isn't deleted is found, returns an `ApiErrorType::ResourceNotFound` error.
+/// If any other error occurs during database update, returns an `ApiErrorType::InternalServerError` error.
+///
+pub async fn update_audience_list(
+    pool: Pool<Postgres>,
+    id: Uuid,
+    update_audience_list_data: InsertOrUpdateAudienceListData,
+) -> Result<AudienceList, ApiErrorType> {
+    let query_result = sqlx::query_as!(
+        AudienceList,
+        r#"UPDATE audience_lists SET name = $1 WHERE id = $2 AND deleted_at IS NULL
+        RETURNING id, owner_id, name, created_at, updated_at, deleted_at
+        "#,
+        update_audience_list_data.name,
+        id
+    )
+    .fetch_optional(&pool)
+    .await;
+
+    match query_result {
+        Ok(Some(audience_list)) => Ok(audience_list),
+        Ok(None) => Err(ApiErrorType::ResourceNotFound(ApiResource::AudienceLists)),
+        Err(_) => Err(ApiErrorType::InternalServerError),
+    }
+}
+
+/// Softly deletes an audience list from the database.
+///
+/// Privacy preferences still referencing the deleted list simply stop matching anyone.
+///
+/// # Arguments
+///
+/// * `pool` - A database connection pool.
+/// * `id` - The UUID of the audience list to delete.
+///
+/// # Returns
+///
+/// Returns a `Result<(), ApiErrorType>` indicating success or an `ApiErrorType::InternalServerError` error
+/// if any error occurs during database deletion.
+/// If no audience list with the provided ID is found for deletion or if the list is already soft deleted,
+/// returns an `ApiErrorType::ResourceNotFound` error.
+///
+pub async fn delete_audience_list(pool: Pool<Postgres>, id: Uuid) -> Result<(), ApiErrorType> {
+    let current_date = Utc::now();
+
+    let query_result = sqlx::query!(
+        "UPDATE audience_lists SET deleted_at = $1 WHERE id = $2 AND deleted_at IS NULL",
+        current_date,
+        id,
+    )
+    .execute(&pool)
+    .await;
+
+    match query_result {
+        Ok(result) => {
+            if result.rows_affected() == 0 {
+                Err(ApiErrorType::ResourceNotFound(ApiResource::AudienceLists))
+            } else {
+                Ok(())
+            }
+        }
+        Err(_) => Err(ApiErrorType::InternalServerError),
+    }
+}
+
+/// The sortable fields of the audience list members.
+pub static AUDIENCE_LIST_MEMBERS_LIST_SPEC: ListSpec = ListSpec {
+    table: "audience_list_members",
+    sort_fields: &[SortField {
+        name: "createdAt",
+        column: "created_at",
+    }],
+    default_sort: "createdAt",
+    filter_fields: &[],
+};
+
+/// Retrieves a page of the (non deleted) members of an audience list.
+///
+/// # Arguments
+///
+/// * `pool` - A database connection pool.
+/// * `list_id` - The UUID of the audience list.
+/// * `list_query` - The sorting and pagination, parsed against `AUDIENCE_LIST_MEMBERS_LIST_SPEC`.
+///
+/// # Returns
+///
+/// Returns a `Result` containing the page of audience list members.
+/// If any error occurs during the database query, returns an `ApiErrorType::InternalServerError` error.
+///
+pub async fn get_audience_list_members(
+    pool: Pool<Postgres>,
+    list_id: Uuid,
+    list_query: ListQuery,
+) -> Result<Page<AudienceListMember>, ApiErrorType> {
+    let total = sqlx::query_scalar!(
+        r#"SELECT COUNT(*) AS "count!" FROM audience_list_members WHERE list_id = $1 AND deleted_at IS NULL"#,
+        list_id
+    )
+    .fetch_one(&pool)
+    .await
+    .map_err(|_| ApiErrorType::InternalServerError)?;
+
+    let query_string = format!(
+        r#"SELECT m.id, m.list_id, m.user_id, m.created_at, m.deleted_at
+        FROM audience_list_members m
+        {}
+        WHERE m.list_id = $1 AND m.deleted_at IS NULL AND {}
+        ORDER BY {}
+        LIMIT $3 OFFSET $4
+        "#,
+        list_query.keyset_join_sql("$2"),
+        list_query.keyset_condition_sql("m"),
+        list_query.order_by_sql("m")
+    );
+
+    let query_result = sqlx::query_as::<_, AudienceListMember>(query_string.as_str())
+        .bind(list_id)
+        .bind(list_query.after_id)
+        .bind(list_query.limit())
+        .bind(list_query.offset())
+        .fetch_all(&pool)
+        .await;
+
+    match query_result {
+        Ok(members) => Ok(list_query.into_page(members, total, |member| member.id)),
+        Err(_) => Err(ApiErrorType::InternalServerError),
+    }
+}
+
+/// Adds a user to an audience list.
+///
+/// # Arguments
+///
+/// * `pool` - A database connection pool.
+/// * `list_id` - The UUID of the audience list.
+/// * `user_id` - The UUID of the user to add to the list.
+///
+/// # Returns
+///
+/// Returns a `Result` containing the inserted `AudienceListMember` if successful.
+/// If the user is already a member of the list, returns an `ApiErrorType::AlreadyExists` error.
+/// If the user doesn't exist, returns an `ApiErrorType::ResourceNotFound` error.
+/// If any other error occurs during database insertion, returns an `ApiErrorType::InternalServerError` error.
+///
+pub async fn insert_audience_list_member(
+    pool: Pool<Postgres>,
+    list_id: Uuid,
+    user_id: Uuid,
+) -> Result<AudienceListMember, ApiErrorType> {
+    let query_result = sqlx::query_as!(
+        AudienceListMember,
+        r#"INSERT INTO audience_list_members (list_id, user_id) VALUES ($1, $2)
+        RETURNING id, list_id, user_id, created_at, deleted_at
+        "#,
+        list_id,
+        user_id
+    )
+    .fetch_one(&pool)
+    .await;
+
+    match query_result {
+        Ok(member) => Ok(member),
+        Err(e) => match e {
+            sqlx::Error::Database(e) if e.is_unique_violation() => Err(
+                ApiErrorType::AlreadyExists(ApiResource::AudienceListMembers),
+            ),
+            sqlx::Error::Database(e) if e.is_foreign_key_violation() => {
+                Err(ApiErrorType::ResourceNotFound(ApiResource::Users))
+            }
+            _ => Err(ApiErrorType::InternalServerError),
+        },
+    }
+}
+
+/// Softly removes a user from an audience list.
+///
+/// # Arguments
+///
+/// * `pool` - A database connection pool.
+/// * `list_id` - The UUID of the audience list.
+/// * `user_id` - The UUID of the user to remove from the list.
+///
+/// # Returns
+///
+/// Returns a `Result<(), ApiErrorType>` indicating success or an `ApiErrorType::InternalServerError` error
+/// if any error occurs during database deletion.
+/// If the user isn't an active member of the list, returns an `ApiErrorType::ResourceNotFound` error.
+///
+pub async fn delete_audience_list_member(
+    pool: Pool<Postgres>,
+    list_id: Uuid,
+    user_id: Uuid,
+) -> Result<(), ApiErrorType> {
+    let current_date = Utc::now();
+
+    let query_result = sqlx::query!(
+        "UPDATE audience_list_members SET deleted_at = $1
+        WHERE list_id = $2 AND user_id = $3 AND deleted_at IS NULL",
+        current_date,
+        list_id,
+        user_id,
+    )
+    .execute(&pool)
+    .await;
+
+    match query_result {
+        Ok(result) => {
+            if result.rows_affected() == 0 {
+                Err(ApiErrorType::ResourceNotFound(
+                    ApiResource::AudienceListMembers,
+                ))
+            } else {
+                Ok(())
+            }
+        }
+        Err(_) => Err(ApiErrorType::InternalServerError),
+    }
+}
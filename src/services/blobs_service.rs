@@ -0,0 +1,65 @@
+use sqlx::{types::Json, Pool, Postgres};
+
+use crate::utils::response::ApiErrorType;
+
+/// Releases blobs no row references (e.g. after a failed upload), the storage worker deleting
+/// them later on if they're still unreferenced by then.
+///
+/// # Arguments
+///
+/// * `pool` - A database connection pool.
+/// * `keys` - The keys of the blobs.
+///
+/// # Returns
+///
+/// Returns a `Result<(), ApiErrorType>` indicating success.
+/// If any error occurs during the database query, returns an `ApiErrorType::InternalServerError` error.
+///
+pub async fn release_blobs(pool: Pool<Postgres>, keys: &[String]) -> Result<(), ApiErrorType> {
+    let query_result = sqlx::query!("SELECT release_blobs($1)", Json(keys) as _)
+        .execute(&pool)
+        .await;
+
+    match query_result {
+        Ok(_) => Ok(()),
+        Err(_) => Err(ApiErrorType::InternalServerError),
+    }
+}
+
+/// Checks whether any row (soft-deleted ones included) references a blob.
+///
+/// # Arguments
+///
+/// * `pool` - A database connection pool.
+/// * `key` - The key of the blob.
+///
+/// # Returns
+///
+/// Returns a `Result` containing whether the blob is referenced if successful.
+/// If any error occurs during the database query, returns an `ApiErrorType::InternalServerError` error.
+///
+pub async fn is_blob_referenced(pool: Pool<Postgres>, key: &str) -> Result<bool, ApiErrorType> {
+    let query_result = sqlx::query_scalar!(
+        r#"SELECT
+            EXISTS (SELECT 1 FROM user_profile_images WHERE image_url = $1)
+            OR EXISTS (
+                SELECT 1 FROM user_profile_images
+                WHERE jsonb_path_query_array(thumbnail_urls, '$.*') @> to_jsonb($1::TEXT)
+            )
+            OR EXISTS (SELECT 1 FROM hess_media WHERE media_url = $1)
+            OR EXISTS (
+                SELECT 1 FROM hess_media
+                WHERE jsonb_path_query_array(thumbnail_urls, '$.*') @> to_jsonb($1::TEXT)
+            )
+            AS "referenced!"
+        "#,
+        key
+    )
+    .fetch_one(&pool)
+    .await;
+
+    match query_result {
+        Ok(referenced) => Ok(referenced),
+        Err(_) => Err(ApiErrorType::InternalServerError),
+    }
+}
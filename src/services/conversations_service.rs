@@ -0,0 +1,474 @@
+use std::collections::HashMap;
+
+use chrono::{DateTime, Utc};
+use serde_derive::{Deserialize, Serialize};
+use sqlx::{FromRow, Pool, Postgres};
+use uuid::Uuid;
+
+use crate::{
+    models::{
+        Conversation, ConversationParticipant, ConversationParticipantStatus, ConversationsPage,
+    },
+    utils::{
+        cursor::encode_cursor,
+        response::{ApiErrorType, ApiResource},
+    },
+};
+
+use super::messages_service::{get_last_messages, VISIBLE_MESSAGE_PREDICATE};
+
+/// The position of the last conversation of a page.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ConversationCursor {
+    /// The date of the last message of the conversation, or its creation date if it has none.
+    pub active_at: DateTime<Utc>,
+    pub id: Uuid,
+}
+
+#[derive(Debug, FromRow)]
+struct ConversationRow {
+    id: Uuid,
+    creator_id: Uuid,
+    title: Option<String>,
+    is_group: bool,
+    status: ConversationParticipantStatus,
+    unread_count: i64,
+    last_message_at: Option<DateTime<Utc>>,
+    created_at: DateTime<Utc>,
+    active_at: DateTime<Utc>,
+}
+
+#[derive(Debug, FromRow)]
+struct ConversationParticipantRow {
+    conversation_id: Uuid,
+    #[sqlx(flatten)]
+    participant: ConversationParticipant,
+}
+
+/// The conversations of a viewer (`$1`) having one of the statuses `$2` (the conversation `$3`
+/// only, if not null), paginated with `$4`, `$5` (the cursor) and `$6` (the size).
+///
+/// Message requests are only listed once they have a message, but can be retrieved by id.
+///
+const CONVERSATIONS_QUERY: &str = r#"SELECT
+    c.id,
+    c.creator_id,
+    c.title,
+    c.is_group,
+    me.status,
+    (
+        SELECT COUNT(*) FROM messages m
+        WHERE m.conversation_id = c.id
+            AND m.sender_id <> $1
+            AND (lr.id IS NULL OR (m.created_at, m.id) > (lr.created_at, lr.id))
+            AND {VISIBLE_MESSAGE_PREDICATE}
+    ) AS unread_count,
+    c.last_message_at,
+    c.created_at,
+    COALESCE(c.last_message_at, c.created_at) AS active_at
+FROM conversation_participants me
+JOIN conversations c ON c.id = me.conversation_id
+LEFT JOIN messages lr ON lr.id = me.last_read_message_id
+WHERE me.user_id = $1
+    AND me.left_at IS NULL
+    AND me.status::TEXT = ANY($2)
+    AND (me.status <> 'PENDING' OR c.last_message_at IS NOT NULL OR $3::UUID IS NOT NULL)
+    AND ($3::UUID IS NULL OR c.id = $3)
+    AND ($4::TIMESTAMPTZ IS NULL OR (COALESCE(c.last_message_at, c.created_at), c.id) < ($4, $5))
+ORDER BY active_at DESC, c.id DESC
+LIMIT $6"#;
+
+/// Retrieves conversations of a viewer (see `CONVERSATIONS_QUERY`) with their participants and
+/// last message.
+async fn fetch_conversations(
+    pool: Pool<Postgres>,
+    viewer_id: Uuid,
+    statuses: &[&str],
+    conversation_id: Option<Uuid>,
+    cursor: Option<&ConversationCursor>,
+    limit: i64,
+) -> Result<Vec<(Conversation, DateTime<Utc>)>, ApiErrorType> {
+    let query_string =
+        CONVERSATIONS_QUERY.replace("{VISIBLE_MESSAGE_PREDICATE}", VISIBLE_MESSAGE_PREDICATE);
+
+    let rows = sqlx::query_as::<_, ConversationRow>(&query_string)
+        .bind(viewer_id)
+        .bind(statuses)
+        .bind(conversation_id)
+        .bind(cursor.map(|cursor| cursor.active_at))
+        .bind(cursor.map(|cursor| cursor.id))
+        .bind(limit)
+        .fetch_all(&pool)
+        .await
+        .map_err(|_| ApiErrorType::InternalServerError)?;
+
+    let conversation_ids = rows.iter().map(|row| row.id).collect::<Vec<_>>();
+
+    // The others can't tell whether a participant declined the conversation or just didn't
+    // accept it yet, nor when they read messages before accepting it
+    let mut participants = HashMap::<Uuid, Vec<ConversationParticipant>>::new();
+
+    for row in sqlx::query_as::<_, ConversationParticipantRow>(
+        r#"SELECT
+            p.conversation_id,
+            p.user_id,
+            u.username,
+            u.name,
+            u.verified,
+            CASE WHEN p.status = 'DECLINED' AND p.user_id <> $2 THEN 'PENDING' ELSE p.status END
+                AS status,
+            CASE WHEN p.status = 'ACCEPTED' OR p.user_id = $2 THEN p.last_read_message_id END
+                AS last_read_message_id,
+            CASE WHEN p.status = 'ACCEPTED' OR p.user_id = $2 THEN p.last_read_at END
+                AS last_read_at,
+            p.joined_at
+        FROM conversation_participants p
+        JOIN users u ON u.id = p.user_id
+        WHERE p.conversation_id = ANY($1) AND p.left_at IS NULL
+        ORDER BY p.joined_at, u.username
+        "#,
+    )
+    .bind(&conversation_ids)
+    .bind(viewer_id)
+    .fetch_all(&pool)
+    .await
+    .map_err(|_| ApiErrorType::InternalServerError)?
+    {
+        participants
+            .entry(row.conversation_id)
+            .or_default()
+            .push(row.participant);
+    }
+
+    let mut last_messages = get_last_messages(pool, viewer_id, &conversation_ids).await?;
+
+    Ok(rows
+        .into_iter()
+        .map(|row| {
+            (
+                Conversation {
+                    participants: participants.remove(&row.id).unwrap_or_default(),
+                    last_message: last_messages.remove(&row.id),
+                    id: row.id,
+                    creator_id: row.creator_id,
+                    title: row.title,
+                    is_group: row.is_group,
+                    status: row.status,
+                    unread_count: row.unread_count,
+                    last_message_at: row.last_message_at,
+                    created_at: row.created_at,
+                },
+                row.active_at,
+            )
+        })
+        .collect())
+}
+
+/// Starts a conversation, or returns the existing one for 1:1 conversations.
+///
+/// The participants who follow the creator get it in their inbox, the others get a message
+/// request. The creator getting back to a 1:1 conversation they declined accepts it.
+///
+/// # Arguments
+///
+/// * `pool` - A database connection pool.
+/// * `creator_id` - The UUID of the user starting the conversation.
+/// * `participant_ids` - The UUIDs of the other participants, a single one for a 1:1 conversation.
+/// * `title` - The title of a group, ignored for 1:1 conversations.
+///
+/// # Returns
+///
+/// Returns a `Result` containing the UUID of the conversation if successful.
+/// If any error occurs during the database queries, returns an `ApiErrorType::InternalServerError` error.
+///
+pub async fn insert_conversation(
+    pool: Pool<Postgres>,
+    creator_id: Uuid,
+    participant_ids: &[Uuid],
+    title: Option<String>,
+) -> Result<Uuid, ApiErrorType> {
+    let is_group = participant_ids.len() > 1;
+
+    let direct_key = if is_group {
+        None
+    } else {
+        let mut pair = [creator_id, participant_ids[0]];
+        pair.sort();
+        Some(format!("{}:{}", pair[0], pair[1]))
+    };
+
+    let mut tx = pool
+        .begin()
+        .await
+        .map_err(|_| ApiErrorType::InternalServerError)?;
+
+    let inserted_id = sqlx::query_scalar!(
+        r#"INSERT INTO conversations (creator_id, title, is_group, direct_key)
+        VALUES ($1, $2, $3, $4)
+        ON CONFLICT (direct_key) DO NOTHING
+        RETURNING id
+        "#,
+        creator_id,
+        if is_group { title } else { None },
+        is_group,
+        direct_key
+    )
+    .fetch_optional(&mut *tx)
+    .await
+    .map_err(|_| ApiErrorType::InternalServerError)?;
+
+    let id = match inserted_id {
+        Some(id) => {
+            let user_ids = [&[creator_id], participant_ids].concat();
+
+            sqlx::query!(
+                r#"INSERT INTO conversation_participants (conversation_id, user_id, status)
+                SELECT $1, u.id, CASE
+                    WHEN u.id = $2 OR EXISTS (
+                        SELECT 1 FROM followers f
+                        WHERE f.follower_id = u.id AND f.followed_id = $2 AND f.deleted_at IS NULL
+                    ) THEN 'ACCEPTED'
+                    ELSE 'PENDING'
+                END::CONVERSATION_PARTICIPANT_STATUS
+                FROM unnest($3::UUID[]) AS u(id)
+                "#,
+                id,
+                creator_id,
+                &user_ids
+            )
+            .execute(&mut *tx)
+            .await
+            .map_err(|_| ApiErrorType::InternalServerError)?;
+
+            id
+        }
+        None => {
+            let id = sqlx::query_scalar!(
+                "SELECT id FROM conversations WHERE direct_key = $1",
+                direct_key
+            )
+            .fetch_one(&mut *tx)
+            .await
+            .map_err(|_| ApiErrorType::InternalServerError)?;
+
+            sqlx::query!(
+                r#"UPDATE conversation_participants SET status = 'ACCEPTED'
+                WHERE conversation_id = $1 AND user_id = $2 AND status = 'DECLINED'
+                "#,
+                id,
+                creator_id
+            )
+            .execute(&mut *tx)
+            .await
+            .map_err(|_| ApiErrorType::InternalServerError)?;
+
+            id
+        }
+    };
+
+    tx.commit()
+        .await
+        .map_err(|_| ApiErrorType::InternalServerError)?;
+
+    Ok(id)
+}
+
+/// Retrieves a conversation as seen by one of its participants.
+///
+/// # Arguments
+///
+/// * `pool` - A database connection pool.
+/// * `viewer_id` - The UUID of the participant.
+/// * `id` - The UUID of the conversation.
+///
+/// # Returns
+///
+/// Returns a `Result` containing the `Conversation` if successful.
+/// If the viewer isn't a participant (or left, or declined the conversation), returns an
+/// `ApiErrorType::ResourceNotFound` error.
+/// If any other error occurs during the database queries, returns an `ApiErrorType::InternalServerError` error.
+///
+pub async fn get_conversation_by_id(
+    pool: Pool<Postgres>,
+    viewer_id: Uuid,
+    id: Uuid,
+) -> Result<Conversation, ApiErrorType> {
+    fetch_conversations(pool, viewer_id, &["ACCEPTED", "PENDING"], Some(id), None, 1)
+        .await?
+        .pop()
+        .map(|(conversation, _)| conversation)
+        .ok_or(ApiErrorType::ResourceNotFound(ApiResource::Conversations))
+}
+
+/// Retrieves a page of the conversations of a user, latest active first.
+///
+/// # Arguments
+///
+/// * `pool` - A database connection pool.
+/// * `user_id` - The UUID of the user.
+/// * `status` - `Accepted` for the inbox of the user, `Pending` for their message requests.
+/// * `cursor` - The position of the last conversation of the previous page, `None` for the first page.
+/// * `size` - The maximum number of conversations in the page.
+///
+/// # Returns
+///
+/// Returns a `Result` containing the `ConversationsPage`, with a `next_cursor` if there may be more
+/// conversations.
+/// If any error occurs during the database queries, returns an `ApiErrorType::InternalServerError` error.
+///
+pub async fn get_conversations(
+    pool: Pool<Postgres>,
+    user_id: Uuid,
+    status: ConversationParticipantStatus,
+    cursor: Option<ConversationCursor>,
+    size: i64,
+) -> Result<ConversationsPage, ApiErrorType> {
+    let status = match status {
+        ConversationParticipantStatus::Accepted => "ACCEPTED",
+        ConversationParticipantStatus::Pending => "PENDING",
+        ConversationParticipantStatus::Declined => "DECLINED",
+    };
+
+    let mut conversations =
+        fetch_conversations(pool, user_id, &[status], None, cursor.as_ref(), size + 1).await?;
+
+    let has_more = conversations.len() as i64 > size;
+    conversations.truncate(size as usize);
+
+    let next_cursor = match conversations.last() {
+        Some((last, active_at)) if has_more => Some(encode_cursor(&ConversationCursor {
+            active_at: *active_at,
+            id: last.id,
+        })),
+        _ => None,
+    };
+
+    Ok(ConversationsPage {
+        items: conversations
+            .into_iter()
+            .map(|(conversation, _)| conversation)
+            .collect(),
+        next_cursor,
+    })
+}
+
+/// Accepts or declines a message request.
+///
+/// # Arguments
+///
+/// * `pool` - A database connection pool.
+/// * `id` - The UUID of the conversation.
+/// * `user_id` - The UUID of the participant the request was sent to.
+/// * `status` - `Accepted` or `Declined`.
+///
+/// # Returns
+///
+/// Returns a `Result` containing nothing.
+/// If the conversation isn't a pending message request of the user, returns an
+/// `ApiErrorType::ResourceNotFound` error.
+/// If any other error occurs during the database update, returns an `ApiErrorType::InternalServerError` error.
+///
+pub async fn answer_message_request(
+    pool: Pool<Postgres>,
+    id: Uuid,
+    user_id: Uuid,
+    status: ConversationParticipantStatus,
+) -> Result<(), ApiErrorType> {
+    let query_result = sqlx::query!(
+        r#"UPDATE conversation_participants SET status = $3
+        WHERE conversation_id = $1 AND user_id = $2 AND status = 'PENDING' AND left_at IS NULL
+        "#,
+        id,
+        user_id,
+        status as ConversationParticipantStatus
+    )
+    .execute(&pool)
+    .await;
+
+    match query_result {
+        Ok(result) if result.rows_affected() == 0 => {
+            Err(ApiErrorType::ResourceNotFound(ApiResource::Conversations))
+        }
+        Ok(_) => Ok(()),
+        Err(_) => Err(ApiErrorType::InternalServerError),
+    }
+}
+
+/// Marks the messages of a conversation as read by a participant, up to the latest one.
+///
+/// The read receipt is streamed to the other participants once the conversation is accepted.
+///
+/// # Arguments
+///
+/// * `pool` - A database connection pool.
+/// * `id` - The UUID of the conversation.
+/// * `user_id` - The UUID of the participant.
+///
+/// # Returns
+///
+/// Returns a `Result` containing nothing.
+/// If any error occurs during the database update, returns an `ApiErrorType::InternalServerError` error.
+///
+pub async fn mark_conversation_as_read(
+    pool: Pool<Postgres>,
+    id: Uuid,
+    user_id: Uuid,
+) -> Result<(), ApiErrorType> {
+    let query_result = sqlx::query!(
+        r#"UPDATE conversation_participants p SET last_read_message_id = latest.id, last_read_at = NOW()
+        FROM (
+            SELECT m.id FROM messages m
+            WHERE m.conversation_id = $1 AND m.deleted_at IS NULL
+            ORDER BY m.created_at DESC, m.id DESC
+            LIMIT 1
+        ) latest
+        WHERE p.conversation_id = $1
+            AND p.user_id = $2
+            AND p.last_read_message_id IS DISTINCT FROM latest.id
+        "#,
+        id,
+        user_id
+    )
+    .execute(&pool)
+    .await;
+
+    match query_result {
+        Ok(_) => Ok(()),
+        Err(_) => Err(ApiErrorType::InternalServerError),
+    }
+}
+
+/// Removes a participant from a group.
+///
+/// # Arguments
+///
+/// * `pool` - A database connection pool.
+/// * `id` - The UUID of the group.
+/// * `user_id` - The UUID of the participant leaving.
+///
+/// # Returns
+///
+/// Returns a `Result` containing nothing.
+/// If any error occurs during the database update, returns an `ApiErrorType::InternalServerError` error.
+///
+pub async fn leave_conversation(
+    pool: Pool<Postgres>,
+    id: Uuid,
+    user_id: Uuid,
+) -> Result<(), ApiErrorType> {
+    let query_result = sqlx::query!(
+        r#"UPDATE conversation_participants SET left_at = NOW()
+        WHERE conversation_id = $1 AND user_id = $2 AND left_at IS NULL
+        "#,
+        id,
+        user_id
+    )
+    .execute(&pool)
+    .await;
+
+    match query_result {
+        Ok(_) => Ok(()),
+        Err(_) => Err(ApiErrorType::InternalServerError),
+    }
+}
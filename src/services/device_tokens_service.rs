@@ -0,0 +1,156 @@
+use sqlx::{Pool, Postgres};
+use uuid::Uuid;
+
+use crate::{
+    models::DeviceToken,
+    models_validators::device_token_validator::UpsertDeviceTokenData,
+    utils::response::{ApiErrorType, ApiResource},
+};
+
+/// Registers the push notifications token of a device of a user.
+///
+/// A device keeps a single token, registering it again replaces its previous token (FCM tokens
+/// are refreshed from time to time). A token belongs to the last user who registered it, it's
+/// removed from the devices of other users (e.g. someone else signed in on the device).
+///
+/// # Arguments
+///
+/// * `pool` - A database connection pool.
+/// * `user_id` - The UUID of the user owning the device.
+/// * `data` - The identifier of the device and its token.
+///
+/// # Returns
+///
+/// Returns a `Result` containing the registered `DeviceToken` if successful.
+/// If any error occurs during the database queries, returns an `ApiErrorType::InternalServerError` error.
+///
+pub async fn upsert_device_token(
+    pool: Pool<Postgres>,
+    user_id: Uuid,
+    data: UpsertDeviceTokenData,
+) -> Result<DeviceToken, ApiErrorType> {
+    let mut tx = pool
+        .begin()
+        .await
+        .map_err(|_| ApiErrorType::InternalServerError)?;
+
+    sqlx::query!(
+        "DELETE FROM device_tokens WHERE token = $1 AND NOT (user_id = $2 AND device_id = $3)",
+        data.token,
+        user_id,
+        data.device_id
+    )
+    .execute(&mut *tx)
+    .await
+    .map_err(|_| ApiErrorType::InternalServerError)?;
+
+    let device_token = sqlx::query_as!(
+        DeviceToken,
+        r#"INSERT INTO device_tokens (user_id, device_id, token) VALUES ($1, $2, $3)
+        ON CONFLICT (user_id, device_id) DO UPDATE SET token = EXCLUDED.token, updated_at = NOW()
+        RETURNING id, user_id, device_id, token, created_at, updated_at
+        "#,
+        user_id,
+        data.device_id,
+        data.token
+    )
+    .fetch_one(&mut *tx)
+    .await
+    .map_err(|_| ApiErrorType::InternalServerError)?;
+
+    tx.commit()
+        .await
+        .map_err(|_| ApiErrorType::InternalServerError)?;
+
+    Ok(device_token)
+}
+
+/// Unregisters a device of a user (e.g. when signing out), it won't receive pushes anymore.
+///
+/// # Arguments
+///
+/// * `pool` - A database connection pool.
+/// * `user_id` - The UUID of the user owning the device.
+/// * `device_id` - The identifier of the device.
+///
+/// # Returns
+///
+/// Returns a `Result<(), ApiErrorType>` indicating success or an `ApiErrorType::InternalServerError` error
+/// if any error occurs during database deletion.
+/// If the device isn't registered, returns an `ApiErrorType::ResourceNotFound` error.
+///
+pub async fn delete_device_token(
+    pool: Pool<Postgres>,
+    user_id: Uuid,
+    device_id: &str,
+) -> Result<(), ApiErrorType> {
+    let query_result = sqlx::query!(
+        "DELETE FROM device_tokens WHERE user_id = $1 AND device_id = $2",
+        user_id,
+        device_id
+    )
+    .execute(&pool)
+    .await;
+
+    match query_result {
+        Ok(result) => {
+            if result.rows_affected() == 0 {
+                Err(ApiErrorType::ResourceNotFound(ApiResource::DeviceTokens))
+            } else {
+                Ok(())
+            }
+        }
+        Err(_) => Err(ApiErrorType::InternalServerError),
+    }
+}
+
+/// Retrieves the push notifications tokens of all the devices of a user.
+///
+/// # Arguments
+///
+/// * `pool` - A database connection pool.
+/// * `user_id` - The UUID of the user.
+///
+/// # Returns
+///
+/// Returns a `Result` containing the tokens if successful.
+/// If any error occurs during the database query, returns an `ApiErrorType::InternalServerError` error.
+///
+pub async fn get_device_tokens_by_user_id(
+    pool: Pool<Postgres>,
+    user_id: Uuid,
+) -> Result<Vec<String>, ApiErrorType> {
+    sqlx::query_scalar!(
+        "SELECT token FROM device_tokens WHERE user_id = $1 ORDER BY created_at",
+        user_id
+    )
+    .fetch_all(&pool)
+    .await
+    .map_err(|_| ApiErrorType::InternalServerError)
+}
+
+/// Removes tokens from the devices they're registered to, whoever owns them.
+///
+/// Used to prune the tokens the push provider reports as invalid (the app was uninstalled,
+/// the token expired...).
+///
+/// # Arguments
+///
+/// * `pool` - A database connection pool.
+/// * `tokens` - The tokens to remove.
+///
+/// # Returns
+///
+/// Returns a `Result<(), ApiErrorType>` indicating success or an `ApiErrorType::InternalServerError` error
+/// if any error occurs during database deletion.
+///
+pub async fn delete_device_tokens(
+    pool: Pool<Postgres>,
+    tokens: &[String],
+) -> Result<(), ApiErrorType> {
+    sqlx::query!("DELETE FROM device_tokens WHERE token = ANY($1)", tokens)
+        .execute(&pool)
+        .await
+        .map(|_| ())
+        .map_err(|_| ApiErrorType::InternalServerError)
+}
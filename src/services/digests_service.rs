@@ -0,0 +1,347 @@
+use chrono::{DateTime, Utc};
+use sqlx::{FromRow, Pool, Postgres};
+use uuid::Uuid;
+
+use crate::{
+    models::{Digest, DigestHess, DigestUser},
+    policies::{policy_sql_predicate, PolicyAction},
+    utils::response::{ApiErrorType, ApiResource},
+};
+
+/// The maximum number of items listed in each section of a digest.
+const DIGEST_MAX_LISTED_ITEMS: i64 = 5;
+
+/// The activity of a user isn't updated more often than this, to avoid a write per request.
+const USER_ACTIVITY_THROTTLE_MINUTES: i32 = 5;
+
+#[derive(Debug, FromRow)]
+struct DigestFollowerRow {
+    id: Uuid,
+    name: String,
+    username: String,
+    total_count: i64,
+}
+
+/// The authors the recipient (`$1`) muted, blocked or got blocked by are left out of the digests.
+const NOT_MUTED_OR_BLOCKED_AUTHOR: &str = r#"NOT EXISTS (
+        SELECT 1 FROM muted_users m
+        WHERE m.muter_id = $1 AND m.muted_id = u.id AND m.deleted_at IS NULL
+    )
+    AND NOT EXISTS (
+        SELECT 1 FROM blocked_users b
+        WHERE b.deleted_at IS NULL AND (
+            (b.blocker_id = $1 AND b.blocked_id = u.id)
+            OR (b.blocker_id = u.id AND b.blocked_id = $1)
+        )
+    )"#;
+
+/// Enqueues the digests due in the `digests` queue.
+///
+/// A digest is due when the frequency chosen by the user elapsed since their last digest, their
+/// last activity and their sign-up, so active users never get one. It covers the activity since
+/// the latest of these (at most one period). The users are marked as digested right away, and
+/// locked with `FOR UPDATE SKIP LOCKED` so concurrent schedulers don't enqueue the same digests.
+///
+/// # Arguments
+///
+/// * `pool` - A database connection pool.
+///
+/// # Returns
+///
+/// Returns a `Result` containing the number of digests enqueued.
+/// If any error occurs during the database query, returns an `ApiErrorType::InternalServerError` error.
+///
+pub async fn schedule_digests(pool: Pool<Postgres>) -> Result<usize, ApiErrorType> {
+    let query_result = sqlx::query_scalar!(
+        r#"WITH due AS (
+            SELECT
+                p.user_id,
+                GREATEST(
+                    NOW() - period.length,
+                    a.last_digest_at,
+                    a.last_active_at
+                ) AS since
+            FROM user_notification_preferences p
+            JOIN users u ON u.id = p.user_id
+            LEFT JOIN user_activity a ON a.user_id = p.user_id
+            CROSS JOIN LATERAL (
+                SELECT CASE p.digest_frequency
+                    WHEN 'DAILY' THEN INTERVAL '1 day'
+                    ELSE INTERVAL '7 days'
+                END AS length
+            ) period
+            WHERE p.digest_frequency <> 'NEVER'
+                AND u.activated
+                AND u.deleted_at IS NULL
+                AND GREATEST(u.created_at, a.last_digest_at, a.last_active_at) <= NOW() - period.length
+            FOR UPDATE OF p SKIP LOCKED
+        ), digested AS (
+            INSERT INTO user_activity (user_id, last_digest_at)
+            SELECT user_id, NOW() FROM due
+            ON CONFLICT (user_id) DO UPDATE SET last_digest_at = EXCLUDED.last_digest_at
+        )
+        INSERT INTO jobs (queue, kind, payload)
+        SELECT 'digests', 'DIGEST', jsonb_build_object('userId', user_id, 'since', since)
+        FROM due
+        RETURNING id
+        "#
+    )
+    .fetch_all(&pool)
+    .await;
+
+    match query_result {
+        Ok(ids) => Ok(ids.len()),
+        Err(_) => Err(ApiErrorType::InternalServerError),
+    }
+}
+
+/// Retrieves the activity a user missed since a date, as summarized in their digest.
+///
+/// The new followers and the unread mentions are only listed when the email channel of their
+/// event is on in the notification preferences of the user. The top hesses are the most liked
+/// hesses (replies aside) of the followed users the user can see.
+///
+/// # Arguments
+///
+/// * `pool` - A database connection pool.
+/// * `user_id` - The UUID of the user getting the digest.
+/// * `since` - The start of the period covered.
+///
+/// # Returns
+///
+/// Returns a `Result` containing the `Digest` of the user.
+/// If any error occurs during the database queries, returns an `ApiErrorType::InternalServerError` error.
+///
+pub async fn get_digest(
+    pool: Pool<Postgres>,
+    user_id: Uuid,
+    since: DateTime<Utc>,
+) -> Result<Digest, ApiErrorType> {
+    let channels = sqlx::query!(
+        r#"SELECT follow_email, mention_email
+        FROM user_notification_preferences WHERE user_id = $1
+        "#,
+        user_id
+    )
+    .fetch_optional(&pool)
+    .await
+    .map_err(|_| ApiErrorType::InternalServerError)?;
+
+    let (follow_email, mention_email) = channels
+        .map(|row| (row.follow_email, row.mention_email))
+        .unwrap_or((false, false));
+
+    let (new_followers, new_followers_count) = if follow_email {
+        let rows = sqlx::query_as::<_, DigestFollowerRow>(&format!(
+            r#"SELECT u.id, u.name, u.username, COUNT(*) OVER () AS total_count
+            FROM followers f
+            JOIN users u ON u.id = f.follower_id AND u.deleted_at IS NULL
+            WHERE f.followed_id = $1
+                AND f.deleted_at IS NULL
+                AND f.created_at > $2
+                AND {}
+            ORDER BY f.created_at DESC
+            LIMIT $3
+            "#,
+            NOT_MUTED_OR_BLOCKED_AUTHOR
+        ))
+        .bind(user_id)
+        .bind(since)
+        .bind(DIGEST_MAX_LISTED_ITEMS)
+        .fetch_all(&pool)
+        .await
+        .map_err(|_| ApiErrorType::InternalServerError)?;
+
+        let total_count = rows.first().map(|row| row.total_count).unwrap_or(0);
+
+        (
+            rows.into_iter()
+                .map(|row| DigestUser {
+                    id: row.id,
+                    name: row.name,
+                    username: row.username,
+                })
+                .collect(),
+            total_count,
+        )
+    } else {
+        (vec![], 0)
+    };
+
+    let can_view_hess = policy_sql_predicate(PolicyAction::View, "$1", "h.user_id", Some("h.id"));
+
+    let top_hesses = sqlx::query_as::<_, DigestHess>(&format!(
+        r#"SELECT
+            h.id,
+            h.content,
+            u.name AS author_name,
+            u.username AS author_username,
+            COUNT(l.id) AS likes_count,
+            h.created_at
+        FROM followers f
+        JOIN hesses h ON h.user_id = f.followed_id
+            AND h.deleted_at IS NULL
+            AND h.parent_hess_id IS NULL
+            AND h.created_at > $2
+        JOIN users u ON u.id = h.user_id AND u.deleted_at IS NULL
+        LEFT JOIN likes l ON l.hess_id = h.id AND l.deleted_at IS NULL
+        WHERE f.follower_id = $1
+            AND f.deleted_at IS NULL
+            AND {}
+            AND {}
+        GROUP BY h.id, u.id
+        ORDER BY likes_count DESC, h.created_at DESC
+        LIMIT $3
+        "#,
+        NOT_MUTED_OR_BLOCKED_AUTHOR, can_view_hess
+    ))
+    .bind(user_id)
+    .bind(since)
+    .bind(DIGEST_MAX_LISTED_ITEMS)
+    .fetch_all(&pool)
+    .await
+    .map_err(|_| ApiErrorType::InternalServerError)?;
+
+    let unread_mentions = if mention_email {
+        sqlx::query_as::<_, DigestHess>(&format!(
+            r#"SELECT
+                h.id,
+                h.content,
+                u.name AS author_name,
+                u.username AS author_username,
+                (
+                    SELECT COUNT(*) FROM likes l WHERE l.hess_id = h.id AND l.deleted_at IS NULL
+                ) AS likes_count,
+                h.created_at
+            FROM notifications n
+            JOIN hesses h ON h.id = n.hess_id AND h.deleted_at IS NULL
+            JOIN users u ON u.id = h.user_id AND u.deleted_at IS NULL
+            WHERE n.recipient_id = $1
+                AND n.notification_type = 'MENTION'
+                AND n.read_at IS NULL
+                AND n.occurred_at > $2
+                AND {}
+                AND {}
+            ORDER BY n.occurred_at DESC
+            LIMIT $3
+            "#,
+            NOT_MUTED_OR_BLOCKED_AUTHOR, can_view_hess
+        ))
+        .bind(user_id)
+        .bind(since)
+        .bind(DIGEST_MAX_LISTED_ITEMS)
+        .fetch_all(&pool)
+        .await
+        .map_err(|_| ApiErrorType::InternalServerError)?
+    } else {
+        vec![]
+    };
+
+    Ok(Digest {
+        new_followers,
+        new_followers_count,
+        top_hesses,
+        unread_mentions,
+    })
+}
+
+/// Retrieves the unsubscribe token of the digests of a user.
+///
+/// # Arguments
+///
+/// * `pool` - A database connection pool.
+/// * `user_id` - The UUID of the user.
+///
+/// # Returns
+///
+/// Returns a `Result` containing the token.
+/// If no notification preferences are found for the user, returns an `ApiErrorType::ResourceNotFound` error.
+/// If any other error occurs during the database query, returns an `ApiErrorType::InternalServerError` error.
+///
+pub async fn get_digest_unsubscribe_token(
+    pool: Pool<Postgres>,
+    user_id: Uuid,
+) -> Result<Uuid, ApiErrorType> {
+    let query_result = sqlx::query_scalar!(
+        "SELECT digest_unsubscribe_token FROM user_notification_preferences WHERE user_id = $1",
+        user_id
+    )
+    .fetch_optional(&pool)
+    .await;
+
+    match query_result {
+        Ok(Some(token)) => Ok(token),
+        Ok(None) => Err(ApiErrorType::ResourceNotFound(
+            ApiResource::UserNotificationPreferences,
+        )),
+        Err(_) => Err(ApiErrorType::InternalServerError),
+    }
+}
+
+/// Turns the digests off for the user owning an unsubscribe token (see the unsubscribe links).
+///
+/// # Arguments
+///
+/// * `pool` - A database connection pool.
+/// * `token` - The unsubscribe token of the user.
+///
+/// # Returns
+///
+/// Returns a `Result` containing nothing.
+/// If the token is unknown, returns an `ApiErrorType::ResourceNotFound` error.
+/// If any other error occurs during the database update, returns an `ApiErrorType::InternalServerError` error.
+///
+pub async fn unsubscribe_from_digests(
+    pool: Pool<Postgres>,
+    token: Uuid,
+) -> Result<(), ApiErrorType> {
+    let query_result = sqlx::query!(
+        r#"UPDATE user_notification_preferences SET digest_frequency = 'NEVER'
+        WHERE digest_unsubscribe_token = $1
+        "#,
+        token
+    )
+    .execute(&pool)
+    .await;
+
+    match query_result {
+        Ok(result) if result.rows_affected() == 0 => Err(ApiErrorType::ResourceNotFound(
+            ApiResource::UserNotificationPreferences,
+        )),
+        Ok(_) => Ok(()),
+        Err(_) => Err(ApiErrorType::InternalServerError),
+    }
+}
+
+/// Records that a user is using the app, active users don't get digests.
+///
+/// The activity is only updated if the last one is older than a few minutes.
+///
+/// # Arguments
+///
+/// * `pool` - A database connection pool.
+/// * `user_id` - The UUID of the active user.
+///
+/// # Returns
+///
+/// Returns a `Result` containing nothing.
+/// If any error occurs during the database query, returns an `ApiErrorType::InternalServerError` error.
+///
+pub async fn touch_user_activity(pool: Pool<Postgres>, user_id: Uuid) -> Result<(), ApiErrorType> {
+    let query_result = sqlx::query!(
+        r#"INSERT INTO user_activity (user_id, last_active_at) VALUES ($1, NOW())
+        ON CONFLICT (user_id) DO UPDATE SET last_active_at = EXCLUDED.last_active_at
+        WHERE user_activity.last_active_at IS NULL
+            OR user_activity.last_active_at < NOW() - make_interval(mins => $2)
+        "#,
+        user_id,
+        USER_ACTIVITY_THROTTLE_MINUTES
+    )
+    .execute(&pool)
+    .await;
+
+    match query_result {
+        Ok(_) => Ok(()),
+        Err(_) => Err(ApiErrorType::InternalServerError),
+    }
+}
@@ -0,0 +1,328 @@
+use sqlx::{Pool, Postgres};
+use uuid::Uuid;
+
+use crate::utils::response::ApiErrorType;
+
+// Materialized feed items are only candidates: the visibility policy, mutes and the current
+// watch flags are still checked when the feed is read (see `feed_service`), so these functions
+// only need to be eventually consistent.
+//
+// Every fan-out takes a `fan_out_limit`, authors with at least that many followers are skipped
+// (they're merged at read time), `None` fans out everyone.
+
+fn map_query_result(
+    query_result: Result<sqlx::postgres::PgQueryResult, sqlx::Error>,
+) -> Result<u64, ApiErrorType> {
+    match query_result {
+        Ok(result) => Ok(result.rows_affected()),
+        Err(_) => Err(ApiErrorType::InternalServerError),
+    }
+}
+
+/// Copies a new hess (or reply) to the feeds of its author's followers watching it.
+///
+/// # Arguments
+///
+/// * `pool` - A database connection pool.
+/// * `hess_id` - The UUID of the created hess.
+/// * `fan_out_limit` - See the module comment.
+///
+/// # Returns
+///
+/// Returns a `Result` containing the number of inserted feed items if successful.
+/// If any error occurs during the database query, returns an `ApiErrorType::InternalServerError` error.
+///
+pub async fn fan_out_hess(
+    pool: Pool<Postgres>,
+    hess_id: Uuid,
+    fan_out_limit: Option<i64>,
+) -> Result<u64, ApiErrorType> {
+    let query_result = sqlx::query!(
+        r#"INSERT INTO feed_items (owner_id, item_type, event_id, occurred_at, actor_id, hess_id)
+        SELECT
+            f.follower_id,
+            CASE WHEN h.parent_hess_id IS NULL THEN 'NEW_HESS' ELSE 'REPLY' END,
+            h.id,
+            h.created_at,
+            h.user_id,
+            h.id
+        FROM hesses h
+        JOIN followers f ON f.followed_id = h.user_id AND f.deleted_at IS NULL
+        WHERE h.id = $1
+            AND h.deleted_at IS NULL
+            AND CASE WHEN h.parent_hess_id IS NULL THEN f.watch_new_hesses ELSE f.watch_replies END
+            AND (
+                $2::BIGINT IS NULL
+                OR (SELECT COUNT(*) FROM followers c WHERE c.followed_id = h.user_id AND c.deleted_at IS NULL) < $2
+            )
+        ON CONFLICT (owner_id, event_id) DO NOTHING
+        "#,
+        hess_id,
+        fan_out_limit
+    )
+    .execute(&pool)
+    .await;
+
+    map_query_result(query_result)
+}
+
+/// Copies a new like to the feeds of the liker's followers watching their likes.
+///
+/// # Arguments
+///
+/// * `pool` - A database connection pool.
+/// * `like_id` - The UUID of the created like.
+/// * `fan_out_limit` - See the module comment.
+///
+/// # Returns
+///
+/// Returns a `Result` containing the number of inserted feed items if successful.
+/// If any error occurs during the database query, returns an `ApiErrorType::InternalServerError` error.
+///
+pub async fn fan_out_like(
+    pool: Pool<Postgres>,
+    like_id: Uuid,
+    fan_out_limit: Option<i64>,
+) -> Result<u64, ApiErrorType> {
+    let query_result = sqlx::query!(
+        r#"INSERT INTO feed_items (owner_id, item_type, event_id, occurred_at, actor_id, hess_id)
+        SELECT f.follower_id, 'LIKE', l.id, l.created_at, l.user_id, l.hess_id
+        FROM likes l
+        JOIN followers f ON f.followed_id = l.user_id AND f.deleted_at IS NULL
+        WHERE l.id = $1
+            AND l.deleted_at IS NULL
+            AND f.watch_likes
+            AND (
+                $2::BIGINT IS NULL
+                OR (SELECT COUNT(*) FROM followers c WHERE c.followed_id = l.user_id AND c.deleted_at IS NULL) < $2
+            )
+        ON CONFLICT (owner_id, event_id) DO NOTHING
+        "#,
+        like_id,
+        fan_out_limit
+    )
+    .execute(&pool)
+    .await;
+
+    map_query_result(query_result)
+}
+
+/// Copies a new follow ("X followed Y") to the feeds of X's followers watching their follows.
+///
+/// # Arguments
+///
+/// * `pool` - A database connection pool.
+/// * `follower_row_id` - The UUID of the created followers row.
+/// * `fan_out_limit` - See the module comment.
+///
+/// # Returns
+///
+/// Returns a `Result` containing the number of inserted feed items if successful.
+/// If any error occurs during the database query, returns an `ApiErrorType::InternalServerError` error.
+///
+pub async fn fan_out_follow(
+    pool: Pool<Postgres>,
+    follower_row_id: Uuid,
+    fan_out_limit: Option<i64>,
+) -> Result<u64, ApiErrorType> {
+    let query_result = sqlx::query!(
+        r#"INSERT INTO feed_items (owner_id, item_type, event_id, occurred_at, actor_id, followed_user_id)
+        SELECT f.follower_id, 'FOLLOW', fo.id, fo.created_at, fo.follower_id, fo.followed_id
+        FROM followers fo
+        JOIN followers f ON f.followed_id = fo.follower_id AND f.deleted_at IS NULL
+        WHERE fo.id = $1
+            AND fo.deleted_at IS NULL
+            AND f.watch_follows
+            AND f.follower_id <> fo.followed_id
+            AND (
+                $2::BIGINT IS NULL
+                OR (SELECT COUNT(*) FROM followers c WHERE c.followed_id = fo.follower_id AND c.deleted_at IS NULL) < $2
+            )
+        ON CONFLICT (owner_id, event_id) DO NOTHING
+        "#,
+        follower_row_id,
+        fan_out_limit
+    )
+    .execute(&pool)
+    .await;
+
+    map_query_result(query_result)
+}
+
+/// Copies the recent activity of a newly followed user to the follower's feed,
+/// according to the watch flags of the follow.
+///
+/// # Arguments
+///
+/// * `pool` - A database connection pool.
+/// * `follower_row_id` - The UUID of the followers row.
+/// * `fan_out_limit` - See the module comment.
+/// * `backfill_size` - The maximum number of events copied for each type (hesses, likes and follows).
+///
+/// # Returns
+///
+/// Returns a `Result` containing the number of inserted feed items if successful.
+/// If any error occurs during the database query, returns an `ApiErrorType::InternalServerError` error.
+///
+pub async fn backfill_follow(
+    pool: Pool<Postgres>,
+    follower_row_id: Uuid,
+    fan_out_limit: Option<i64>,
+    backfill_size: i64,
+) -> Result<u64, ApiErrorType> {
+    let query_result = sqlx::query!(
+        r#"INSERT INTO feed_items (owner_id, item_type, event_id, occurred_at, actor_id, hess_id, followed_user_id)
+        SELECT f.follower_id, e.item_type, e.event_id, e.occurred_at, f.followed_id, e.hess_id, e.followed_user_id
+        FROM followers f
+        CROSS JOIN LATERAL (
+            (
+                SELECT
+                    CASE WHEN h.parent_hess_id IS NULL THEN 'NEW_HESS' ELSE 'REPLY' END AS item_type,
+                    h.id AS event_id,
+                    h.created_at AS occurred_at,
+                    h.id AS hess_id,
+                    NULL::UUID AS followed_user_id
+                FROM hesses h
+                WHERE h.user_id = f.followed_id
+                    AND h.deleted_at IS NULL
+                    AND CASE WHEN h.parent_hess_id IS NULL THEN f.watch_new_hesses ELSE f.watch_replies END
+                ORDER BY h.created_at DESC
+                LIMIT $3
+            )
+            UNION ALL
+            (
+                SELECT 'LIKE', l.id, l.created_at, l.hess_id, NULL
+                FROM likes l
+                WHERE f.watch_likes AND l.user_id = f.followed_id AND l.deleted_at IS NULL
+                ORDER BY l.created_at DESC
+                LIMIT $3
+            )
+            UNION ALL
+            (
+                SELECT 'FOLLOW', fo.id, fo.created_at, NULL, fo.followed_id
+                FROM followers fo
+                WHERE f.watch_follows
+                    AND fo.follower_id = f.followed_id
+                    AND fo.followed_id <> f.follower_id
+                    AND fo.deleted_at IS NULL
+                ORDER BY fo.created_at DESC
+                LIMIT $3
+            )
+        ) e
+        WHERE f.id = $1
+            AND f.deleted_at IS NULL
+            AND (
+                $2::BIGINT IS NULL
+                OR (SELECT COUNT(*) FROM followers c WHERE c.followed_id = f.followed_id AND c.deleted_at IS NULL) < $2
+            )
+        ON CONFLICT (owner_id, event_id) DO NOTHING
+        "#,
+        follower_row_id,
+        fan_out_limit,
+        backfill_size
+    )
+    .execute(&pool)
+    .await;
+
+    map_query_result(query_result)
+}
+
+/// Removes a followed user's activity from the follower's feed,
+/// as well as the follow itself from the feeds watching the follower.
+///
+/// # Arguments
+///
+/// * `pool` - A database connection pool.
+/// * `follower_row_id` - The UUID of the followers row.
+/// * `follower_id` - The UUID of the follower.
+/// * `followed_id` - The UUID of the followed user.
+///
+/// # Returns
+///
+/// Returns a `Result` containing the number of removed feed items if successful.
+/// If any error occurs during the database query, returns an `ApiErrorType::InternalServerError` error.
+///
+pub async fn remove_follow_feed_items(
+    pool: Pool<Postgres>,
+    follower_row_id: Uuid,
+    follower_id: Uuid,
+    followed_id: Uuid,
+) -> Result<u64, ApiErrorType> {
+    let query_result = sqlx::query!(
+        r#"DELETE FROM feed_items
+        WHERE (owner_id = $2 AND actor_id = $3) OR event_id = $1
+        "#,
+        follower_row_id,
+        follower_id,
+        followed_id
+    )
+    .execute(&pool)
+    .await;
+
+    map_query_result(query_result)
+}
+
+/// Removes a deleted hess (or like) from all the feeds.
+///
+/// Removing a hess also removes the likes of the hess.
+///
+/// # Arguments
+///
+/// * `pool` - A database connection pool.
+/// * `event_id` - The UUID of the deleted hess or like.
+///
+/// # Returns
+///
+/// Returns a `Result` containing the number of removed feed items if successful.
+/// If any error occurs during the database query, returns an `ApiErrorType::InternalServerError` error.
+///
+pub async fn remove_event_feed_items(
+    pool: Pool<Postgres>,
+    event_id: Uuid,
+) -> Result<u64, ApiErrorType> {
+    let query_result = sqlx::query!(
+        "DELETE FROM feed_items WHERE event_id = $1 OR hess_id = $1",
+        event_id
+    )
+    .execute(&pool)
+    .await;
+
+    map_query_result(query_result)
+}
+
+/// Removes everything involving one of two users from the feed of the other (after a block).
+///
+/// # Arguments
+///
+/// * `pool` - A database connection pool.
+/// * `first_user_id` - The UUID of one of the users.
+/// * `second_user_id` - The UUID of the other user.
+///
+/// # Returns
+///
+/// Returns a `Result` containing the number of removed feed items if successful.
+/// If any error occurs during the database query, returns an `ApiErrorType::InternalServerError` error.
+///
+pub async fn remove_feed_items_between_users(
+    pool: Pool<Postgres>,
+    first_user_id: Uuid,
+    second_user_id: Uuid,
+) -> Result<u64, ApiErrorType> {
+    let query_result = sqlx::query!(
+        r#"DELETE FROM feed_items fi
+        USING (VALUES ($1::UUID, $2::UUID), ($2::UUID, $1::UUID)) AS pair (owner_id, other_id)
+        WHERE fi.owner_id = pair.owner_id
+            AND (
+                fi.actor_id = pair.other_id
+                OR fi.followed_user_id = pair.other_id
+                OR fi.hess_id IN (SELECT h.id FROM hesses h WHERE h.user_id = pair.other_id)
+            )
+        "#,
+        first_user_id,
+        second_user_id
+    )
+    .execute(&pool)
+    .await;
+
+    map_query_result(query_result)
+}
@@ -0,0 +1,532 @@
+use chrono::{DateTime, Utc};
+use serde_derive::{Deserialize, Serialize};
+use sqlx::{FromRow, Pool, Postgres};
+use uuid::Uuid;
+
+use crate::{
+    models::{FeedItem, FeedItemType, FeedPage, Hess, WhoCan},
+    policies::{policy_sql_predicate, PolicyAction},
+    rankers::{CandidateSource, FeedCandidate, FeedRanker},
+    utils::{cursor::encode_cursor, response::ApiErrorType, FeedConfig, FeedStrategy},
+};
+
+/// The position of the last item of a feed page.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct FeedCursor {
+    /// The feed is frozen at this instant while paging,
+    /// so events inserted in the meantime can't shift or duplicate items across pages.
+    pub as_of: DateTime<Utc>,
+    pub occurred_at: DateTime<Utc>,
+    pub id: Uuid,
+}
+
+#[derive(Debug, FromRow)]
+struct FeedItemRow {
+    item_type: String,
+    event_id: Uuid,
+    occurred_at: DateTime<Utc>,
+    actor_id: Uuid,
+    followed_user_id: Option<Uuid>,
+    hess_id: Option<Uuid>,
+    hess_user_id: Option<Uuid>,
+    hess_content: Option<String>,
+    hess_parent_hess_id: Option<Uuid>,
+    hess_who_can_reply: Option<Vec<WhoCan>>,
+    hess_who_can_like: Option<Vec<WhoCan>>,
+    hess_who_can_watch_replies: Option<Vec<WhoCan>>,
+    hess_who_can_watch_likes: Option<Vec<WhoCan>>,
+    hess_created_at: Option<DateTime<Utc>>,
+    hess_updated_at: Option<DateTime<Utc>>,
+    hess_deleted_at: Option<DateTime<Utc>>,
+}
+
+impl FeedItemRow {
+    fn into_feed_item(self) -> Option<FeedItem> {
+        let hess = match (
+            self.hess_id,
+            self.hess_user_id,
+            self.hess_created_at,
+            self.hess_updated_at,
+        ) {
+            (Some(id), Some(user_id), Some(created_at), Some(updated_at)) => Some(Hess {
+                id,
+                user_id,
+                content: self.hess_content,
+                parent_hess_id: self.hess_parent_hess_id,
+                who_can_reply: self.hess_who_can_reply,
+                who_can_like: self.hess_who_can_like,
+                who_can_watch_replies: self.hess_who_can_watch_replies,
+                who_can_watch_likes: self.hess_who_can_watch_likes,
+                created_at,
+                updated_at,
+                deleted_at: self.hess_deleted_at,
+                media: Vec::new(),
+            }),
+            _ => None,
+        };
+
+        Some(FeedItem {
+            id: self.event_id,
+            item_type: FeedItemType::from_sql_str(&self.item_type)?,
+            occurred_at: self.occurred_at,
+            actor_id: self.actor_id,
+            hess,
+            followed_user_id: self.followed_user_id,
+        })
+    }
+}
+
+/// Builds the home feed query of a viewer.
+///
+/// The feed merges, for every user the viewer follows and according to the watch flags of
+/// the follower relationship:
+///
+/// - `NEW_HESS`: their new hesses (`watch_new_hesses`).
+/// - `REPLY`: their replies (`watch_replies`).
+/// - `LIKE`: the hesses they liked (`watch_likes`).
+/// - `FOLLOW`: the users they followed (`watch_follows`).
+///
+/// The events of a followed user are either assembled live from their activity or read from
+/// the materialized `feed_items`, depending on the feed strategy (see `FeedConfig`).
+///
+/// Each event must be allowed by the `who_can_watch_*` preferences of the followed user,
+/// hesses (and followed users) must be visible to the viewer, and anything involving a muted
+/// user is hidden. Hesses appearing through several events are deduplicated, keeping the latest event.
+///
+/// Parameters: `$1` viewer, `$2` as of, `$3` cursor occurred at, `$4` cursor id, `$5` limit.
+///
+fn home_feed_query(feed_config: &FeedConfig) -> String {
+    // Whether the events of a followed user are assembled live (instead of being materialized)
+    let is_live = match feed_config.strategy {
+        FeedStrategy::Read => "TRUE".to_string(),
+        FeedStrategy::Write => "FALSE".to_string(),
+        FeedStrategy::Hybrid => format!(
+            "(SELECT COUNT(*) FROM followers c WHERE c.followed_id = f.followed_id AND c.deleted_at IS NULL) >= {}",
+            feed_config.fan_out_follower_threshold
+        ),
+    };
+
+    let can_watch_new_hesses = policy_sql_predicate(
+        PolicyAction::WatchNewHesses,
+        "$1",
+        "e.actor_id",
+        Some("e.hess_id"),
+    );
+    let can_watch_replies = policy_sql_predicate(
+        PolicyAction::WatchReplies,
+        "$1",
+        "e.actor_id",
+        Some("e.hess_id"),
+    );
+    let can_watch_likes = policy_sql_predicate(PolicyAction::WatchLikes, "$1", "e.actor_id", None);
+    let can_watch_follows =
+        policy_sql_predicate(PolicyAction::WatchFollows, "$1", "e.actor_id", None);
+    let can_view_followed_user =
+        policy_sql_predicate(PolicyAction::View, "$1", "e.followed_user_id", None);
+    let can_view_hess = policy_sql_predicate(PolicyAction::View, "$1", "h.user_id", Some("h.id"));
+
+    format!(
+        r#"WITH followed AS (
+            SELECT
+                f.followed_id,
+                f.watch_new_hesses,
+                f.watch_replies,
+                f.watch_likes,
+                f.watch_follows,
+                {is_live} AS is_live
+            FROM followers f
+            WHERE f.follower_id = $1 AND f.deleted_at IS NULL
+        ),
+        events AS (
+            SELECT
+                CASE WHEN h.parent_hess_id IS NULL THEN 'NEW_HESS' ELSE 'REPLY' END AS item_type,
+                h.id AS event_id,
+                h.created_at AS occurred_at,
+                h.user_id AS actor_id,
+                h.id AS hess_id,
+                NULL::UUID AS followed_user_id
+            FROM followed fd
+            JOIN hesses h ON h.user_id = fd.followed_id
+            WHERE fd.is_live
+                AND CASE WHEN h.parent_hess_id IS NULL THEN fd.watch_new_hesses ELSE fd.watch_replies END
+                AND h.deleted_at IS NULL
+                AND h.created_at <= $2
+
+            UNION ALL
+
+            SELECT 'LIKE', l.id, l.created_at, l.user_id, l.hess_id, NULL
+            FROM followed fd
+            JOIN likes l ON l.user_id = fd.followed_id
+            WHERE fd.is_live
+                AND fd.watch_likes
+                AND l.deleted_at IS NULL
+                AND l.created_at <= $2
+
+            UNION ALL
+
+            SELECT 'FOLLOW', fo.id, fo.created_at, fo.follower_id, NULL, fo.followed_id
+            FROM followed fd
+            JOIN followers fo ON fo.follower_id = fd.followed_id
+            WHERE fd.is_live
+                AND fd.watch_follows
+                AND fo.deleted_at IS NULL
+                AND fo.created_at <= $2
+                AND fo.followed_id <> $1
+
+            UNION ALL
+
+            -- The watch flags are checked again, the feed worker may lag behind
+            SELECT fi.item_type, fi.event_id, fi.occurred_at, fi.actor_id, fi.hess_id, fi.followed_user_id
+            FROM followed fd
+            JOIN feed_items fi ON fi.owner_id = $1 AND fi.actor_id = fd.followed_id
+            WHERE NOT fd.is_live
+                AND CASE fi.item_type
+                    WHEN 'NEW_HESS' THEN fd.watch_new_hesses
+                    WHEN 'REPLY' THEN fd.watch_replies
+                    WHEN 'LIKE' THEN fd.watch_likes
+                    WHEN 'FOLLOW' THEN fd.watch_follows
+                END
+                AND fi.occurred_at <= $2
+        ),
+        visible AS (
+            SELECT e.*
+            FROM events e
+            LEFT JOIN hesses h ON h.id = e.hess_id
+            WHERE CASE e.item_type
+                    WHEN 'NEW_HESS' THEN {can_watch_new_hesses}
+                    WHEN 'REPLY' THEN {can_watch_replies}
+                    WHEN 'LIKE' THEN {can_watch_likes}
+                    WHEN 'FOLLOW' THEN {can_watch_follows} AND {can_view_followed_user}
+                END
+                AND (e.hess_id IS NULL OR (h.deleted_at IS NULL AND {can_view_hess}))
+                AND NOT EXISTS (
+                    SELECT 1 FROM muted_users m
+                    WHERE m.muter_id = $1
+                        AND m.deleted_at IS NULL
+                        AND m.muted_id IN (e.actor_id, h.user_id, e.followed_user_id)
+                )
+        ),
+        deduplicated AS (
+            SELECT DISTINCT ON (COALESCE(v.hess_id, v.event_id)) v.*
+            FROM visible v
+            ORDER BY COALESCE(v.hess_id, v.event_id), v.occurred_at DESC, v.event_id DESC
+        )
+        SELECT
+            d.item_type,
+            d.event_id,
+            d.occurred_at,
+            d.actor_id,
+            d.followed_user_id,
+            h.id AS hess_id,
+            h.user_id AS hess_user_id,
+            h.content AS hess_content,
+            h.parent_hess_id AS hess_parent_hess_id,
+            h.who_can_reply AS hess_who_can_reply,
+            h.who_can_like AS hess_who_can_like,
+            h.who_can_watch_replies AS hess_who_can_watch_replies,
+            h.who_can_watch_likes AS hess_who_can_watch_likes,
+            h.created_at AS hess_created_at,
+            h.updated_at AS hess_updated_at,
+            h.deleted_at AS hess_deleted_at
+        FROM deduplicated d
+        LEFT JOIN hesses h ON h.id = d.hess_id
+        WHERE $3::TIMESTAMPTZ IS NULL OR (d.occurred_at, d.event_id) < ($3, $4)
+        ORDER BY d.occurred_at DESC, d.event_id DESC
+        LIMIT $5
+        "#
+    )
+}
+
+/// Retrieves a page of the home feed of a viewer (see `home_feed_query` for what it's made of).
+///
+/// # Arguments
+///
+/// * `pool` - A database connection pool.
+/// * `feed_config` - The feed configuration (strategy).
+/// * `viewer_id` - The UUID of the user whose feed is assembled.
+/// * `cursor` - The position of the last item of the previous page, `None` for the first page.
+/// * `size` - The maximum number of items in the page.
+///
+/// # Returns
+///
+/// Returns a `Result` containing the `FeedPage`, with a `next_cursor` if there may be more items.
+/// If any error occurs during the database query, returns an `ApiErrorType::InternalServerError` error.
+///
+pub async fn get_home_feed(
+    pool: Pool<Postgres>,
+    feed_config: &FeedConfig,
+    viewer_id: Uuid,
+    cursor: Option<FeedCursor>,
+    size: i64,
+) -> Result<FeedPage, ApiErrorType> {
+    let as_of = cursor
+        .as_ref()
+        .map(|cursor| cursor.as_of)
+        .unwrap_or_else(Utc::now);
+
+    let query_string = home_feed_query(feed_config);
+
+    let query_result = sqlx::query_as::<_, FeedItemRow>(query_string.as_str())
+        .bind(viewer_id)
+        .bind(as_of)
+        .bind(cursor.as_ref().map(|cursor| cursor.occurred_at))
+        .bind(cursor.as_ref().map(|cursor| cursor.id))
+        .bind(size + 1)
+        .fetch_all(&pool)
+        .await;
+
+    let mut rows = match query_result {
+        Ok(rows) => rows,
+        Err(_) => return Err(ApiErrorType::InternalServerError),
+    };
+
+    let has_more = rows.len() as i64 > size;
+    rows.truncate(size as usize);
+
+    let items = rows
+        .into_iter()
+        .filter_map(FeedItemRow::into_feed_item)
+        .collect::<Vec<_>>();
+
+    let next_cursor = match items.last() {
+        Some(last) if has_more => Some(encode_cursor(&FeedCursor {
+            as_of,
+            occurred_at: last.occurred_at,
+            id: last.id,
+        })),
+        _ => None,
+    };
+
+    Ok(FeedPage { items, next_cursor })
+}
+
+/// The position of the last item of a ranked feed page.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct RankedFeedCursor {
+    /// Candidates and engagement counts are frozen at this instant while paging,
+    /// so the ranking (and thus the pages) stays the same.
+    pub as_of: DateTime<Utc>,
+    pub offset: usize,
+}
+
+/// How old (in days) hesses can be to be ranked.
+const RANKING_WINDOW_DAYS: i32 = 7;
+
+/// The maximum number of candidates generated by each source.
+const RANKING_CANDIDATES_PER_SOURCE: i64 = 200;
+
+#[derive(Debug, FromRow)]
+struct FeedCandidateRow {
+    source: String,
+    like_count: i64,
+    reply_count: i64,
+    author_affinity: i64,
+    #[sqlx(flatten)]
+    hess: Hess,
+}
+
+/// Builds the ranked feed candidates query of a viewer.
+///
+/// Candidates are the recent hesses of the followed users, of the users they follow
+/// (second-degree) and the most liked recent hesses, visible to the viewer and not
+/// involving muted users. The viewer's own hesses are left out.
+///
+/// Parameters: `$1` viewer, `$2` as of, `$3` window in days, `$4` candidates per source.
+///
+fn ranked_feed_candidates_query() -> String {
+    let can_view_hess = policy_sql_predicate(PolicyAction::View, "$1", "h.user_id", Some("h.id"));
+
+    format!(
+        r#"WITH followed AS (
+            SELECT f.followed_id
+            FROM followers f
+            WHERE f.follower_id = $1 AND f.deleted_at IS NULL
+        ),
+        second_degree AS (
+            SELECT DISTINCT f.followed_id
+            FROM followers f
+            JOIN followed fd ON fd.followed_id = f.follower_id
+            WHERE f.deleted_at IS NULL
+                AND f.followed_id <> $1
+                AND f.followed_id NOT IN (SELECT followed_id FROM followed)
+        ),
+        recent_hesses AS (
+            SELECT h.*
+            FROM hesses h
+            WHERE h.deleted_at IS NULL
+                AND h.user_id <> $1
+                AND h.created_at <= $2
+                AND h.created_at > $2 - make_interval(days => $3)
+        ),
+        candidates AS (
+            (
+                SELECT h.id, 'FOLLOWED_AUTHOR' AS source, 0 AS precedence
+                FROM recent_hesses h
+                JOIN followed fd ON fd.followed_id = h.user_id
+                ORDER BY h.created_at DESC
+                LIMIT $4
+            )
+            UNION ALL
+            (
+                SELECT h.id, 'SECOND_DEGREE', 1
+                FROM recent_hesses h
+                JOIN second_degree sd ON sd.followed_id = h.user_id
+                ORDER BY h.created_at DESC
+                LIMIT $4
+            )
+            UNION ALL
+            (
+                SELECT h.id, 'POPULAR', 2
+                FROM recent_hesses h
+                JOIN likes l ON l.hess_id = h.id AND l.deleted_at IS NULL AND l.created_at <= $2
+                GROUP BY h.id
+                ORDER BY COUNT(*) DESC, h.id
+                LIMIT $4
+            )
+        ),
+        best_candidates AS (
+            SELECT DISTINCT ON (c.id) c.id, c.source
+            FROM candidates c
+            ORDER BY c.id, c.precedence
+        )
+        SELECT
+            c.source,
+            (
+                SELECT COUNT(*) FROM likes l
+                WHERE l.hess_id = h.id AND l.deleted_at IS NULL AND l.created_at <= $2
+            ) AS like_count,
+            (
+                SELECT COUNT(*) FROM hesses r
+                WHERE r.parent_hess_id = h.id AND r.deleted_at IS NULL AND r.created_at <= $2
+            ) AS reply_count,
+            (
+                SELECT COUNT(*) FROM likes l
+                JOIN hesses lh ON lh.id = l.hess_id
+                WHERE l.user_id = $1
+                    AND lh.user_id = h.user_id
+                    AND l.deleted_at IS NULL
+                    AND l.created_at <= $2
+                    AND l.created_at > $2 - INTERVAL '30 days'
+            ) + (
+                SELECT COUNT(*) FROM hesses r
+                JOIN hesses p ON p.id = r.parent_hess_id
+                WHERE r.user_id = $1
+                    AND p.user_id = h.user_id
+                    AND r.deleted_at IS NULL
+                    AND r.created_at <= $2
+                    AND r.created_at > $2 - INTERVAL '30 days'
+            ) AS author_affinity,
+            h.id,
+            h.user_id,
+            h.content,
+            h.parent_hess_id,
+            h.who_can_reply,
+            h.who_can_like,
+            h.who_can_watch_replies,
+            h.who_can_watch_likes,
+            h.created_at,
+            h.updated_at,
+            h.deleted_at
+        FROM best_candidates c
+        JOIN hesses h ON h.id = c.id
+        WHERE {can_view_hess}
+            AND NOT EXISTS (
+                SELECT 1 FROM muted_users m
+                WHERE m.muter_id = $1 AND m.deleted_at IS NULL AND m.muted_id = h.user_id
+            )
+        "#
+    )
+}
+
+/// Retrieves a page of the ranked ("For You") feed of a viewer
+/// (see `ranked_feed_candidates_query` for the candidates).
+///
+/// # Arguments
+///
+/// * `pool` - A database connection pool.
+/// * `ranker` - The ranker scoring the candidates.
+/// * `viewer_id` - The UUID of the user whose feed is assembled.
+/// * `cursor` - The position of the last item of the previous page, `None` for the first page.
+/// * `size` - The maximum number of items in the page.
+///
+/// # Returns
+///
+/// Returns a `Result` containing the `FeedPage`, with a `next_cursor` if there are more items.
+/// If any error occurs during the database query, returns an `ApiErrorType::InternalServerError` error.
+///
+pub async fn get_ranked_feed(
+    pool: Pool<Postgres>,
+    ranker: &dyn FeedRanker,
+    viewer_id: Uuid,
+    cursor: Option<RankedFeedCursor>,
+    size: i64,
+) -> Result<FeedPage, ApiErrorType> {
+    let as_of = cursor
+        .as_ref()
+        .map(|cursor| cursor.as_of)
+        .unwrap_or_else(Utc::now);
+    let offset = cursor.as_ref().map(|cursor| cursor.offset).unwrap_or(0);
+
+    let query_string = ranked_feed_candidates_query();
+
+    let query_result = sqlx::query_as::<_, FeedCandidateRow>(query_string.as_str())
+        .bind(viewer_id)
+        .bind(as_of)
+        .bind(RANKING_WINDOW_DAYS)
+        .bind(RANKING_CANDIDATES_PER_SOURCE)
+        .fetch_all(&pool)
+        .await;
+
+    let rows = match query_result {
+        Ok(rows) => rows,
+        Err(_) => return Err(ApiErrorType::InternalServerError),
+    };
+
+    let candidates = rows
+        .into_iter()
+        .filter_map(|row| {
+            Some(FeedCandidate {
+                source: CandidateSource::from_sql_str(&row.source)?,
+                like_count: row.like_count,
+                reply_count: row.reply_count,
+                author_affinity: row.author_affinity,
+                hess: row.hess,
+            })
+        })
+        .collect::<Vec<_>>();
+
+    let ranked = ranker.rank(candidates, as_of);
+    let total = ranked.len();
+
+    let items = ranked
+        .into_iter()
+        .skip(offset)
+        .take(size as usize)
+        .map(|candidate| FeedItem {
+            id: candidate.hess.id,
+            item_type: match candidate.hess.parent_hess_id {
+                Some(_) => FeedItemType::Reply,
+                None => FeedItemType::NewHess,
+            },
+            occurred_at: candidate.hess.created_at,
+            actor_id: candidate.hess.user_id,
+            hess: Some(candidate.hess),
+            followed_user_id: None,
+        })
+        .collect::<Vec<_>>();
+
+    let next_offset = offset + items.len();
+
+    let next_cursor = if next_offset < total {
+        Some(encode_cursor(&RankedFeedCursor {
+            as_of,
+            offset: next_offset,
+        }))
+    } else {
+        None
+    };
+
+    Ok(FeedPage { items, next_cursor })
+}
@@ -0,0 +1,70 @@
+use sqlx::PgConnection;
+use uuid::Uuid;
+
+use crate::utils::response::ApiErrorType;
+
+/// Approves all the pending follow requests sent to a user, turning them into followers.
+///
+/// This is meant to be run inside a transaction (e.g. when a user switches their profile
+/// from private to public), the watch preferences of each request are carried over to the
+/// created follower relationship. Requesters already following the user are only marked as approved.
+///
+/// # Arguments
+///
+/// * `connection` - A database connection (usually a transaction).
+/// * `requested_id` - The UUID of the user the follow requests were sent to.
+///
+/// # Returns
+///
+/// Returns a `Result` containing the number of created follower relationships if successful.
+/// If any error occurs during the database update, returns an `ApiErrorType::InternalServerError` error.
+///
+pub async fn approve_pending_follow_requests(
+    connection: &mut PgConnection,
+    requested_id: Uuid,
+) -> Result<u64, ApiErrorType> {
+    let query_result = sqlx::query!(
+        r#"WITH approved AS (
+            UPDATE follow_requests SET status = 'APPROVED', updated_at = NOW()
+            WHERE requested_id = $1 AND status = 'PENDING' AND deleted_at IS NULL
+            RETURNING
+                requester_id,
+                requested_id,
+                watch_new_hesses,
+                watch_replies,
+                watch_follows,
+                watch_likes
+        )
+        INSERT INTO followers (
+            follower_id,
+            followed_id,
+            watch_new_hesses,
+            watch_replies,
+            watch_follows,
+            watch_likes
+        )
+        SELECT
+            a.requester_id,
+            a.requested_id,
+            a.watch_new_hesses,
+            a.watch_replies,
+            a.watch_follows,
+            a.watch_likes
+        FROM approved a
+        WHERE NOT EXISTS (
+            SELECT 1 FROM followers f
+            WHERE f.follower_id = a.requester_id
+                AND f.followed_id = a.requested_id
+                AND f.deleted_at IS NULL
+        )
+        "#,
+        requested_id
+    )
+    .execute(connection)
+    .await;
+
+    match query_result {
+        Ok(result) => Ok(result.rows_affected()),
+        Err(_) => Err(ApiErrorType::InternalServerError),
+    }
+}
@@ -0,0 +1,601 @@
+use std::collections::{BTreeMap, HashMap};
+
+use chrono::Utc;
+use sqlx::{types::Json, Pool, Postgres};
+use uuid::Uuid;
+
+use crate::{
+    models::{Hess, HessMedia, MediaProcessingStatus, MediaType},
+    models_validators::hess_media_validator::UpdateHessMediaData,
+    utils::{
+        response::{ApiErrorType, ApiResource},
+        MediaConfig,
+    },
+};
+
+/// The data of an uploaded and stored hess media.
+pub struct InsertHessMediaData {
+    pub id: Uuid,
+    pub media_type: MediaType,
+    pub media_url: String,
+    pub content_type: String,
+    pub size_bytes: i64,
+}
+
+/// The metadata and the stored derived assets of a processed hess media.
+pub struct CompleteHessMediaProcessingData {
+    pub width: Option<i32>,
+    pub height: Option<i32>,
+    pub duration_ms: Option<i64>,
+    pub blurhash: Option<String>,
+    pub thumbnail_urls: BTreeMap<u32, String>,
+    pub thumbnail_content_type: Option<String>,
+}
+
+/// Retrieves a (non deleted) hess media by its ID from the database.
+///
+/// # Arguments
+///
+/// * `pool` - A database connection pool.
+/// * `id` - The UUID of the hess media to retrieve.
+///
+/// # Returns
+///
+/// Returns a `Result` containing the retrieved `HessMedia` if successful.
+/// If no hess media is found, returns an `ApiErrorType::ResourceNotFound` error.
+/// If any other error occurs during the database query, returns an `ApiErrorType::InternalServerError` error.
+///
+pub async fn get_hess_media_by_id(
+    pool: Pool<Postgres>,
+    id: Uuid,
+) -> Result<HessMedia, ApiErrorType> {
+    let query_result = sqlx::query_as!(
+        HessMedia,
+        r#"SELECT id, hess_id, media_type AS "media_type: MediaType", media_url, content_type,
+            size_bytes, processing_status AS "processing_status: MediaProcessingStatus",
+            width, height, duration_ms, blurhash,
+            thumbnail_urls AS "thumbnail_urls: Json<BTreeMap<u32, String>>",
+            thumbnail_content_type, processed_at, alt_text,
+            ARRAY(
+                SELECT c.language FROM hess_media_captions c
+                WHERE c.hess_media_id = hess_media.id ORDER BY c.language
+            ) AS "caption_languages!",
+            created_at, deleted_at
+        FROM hess_media WHERE id = $1 AND deleted_at IS NULL
+        "#,
+        id
+    )
+    .fetch_optional(&pool)
+    .await;
+
+    match query_result {
+        Ok(Some(hess_media)) => Ok(hess_media),
+        Ok(None) => Err(ApiErrorType::ResourceNotFound(ApiResource::HessMedia)),
+        Err(_) => Err(ApiErrorType::InternalServerError),
+    }
+}
+
+/// Retrieves the (non deleted) media attached to a hess, in the order they were uploaded.
+///
+/// # Arguments
+///
+/// * `pool` - A database connection pool.
+/// * `hess_id` - The UUID of the hess.
+/// * `media_type` - Only retrieves the media of this type, if any.
+/// * `ready_only` - Whether to leave out the media which aren't processed (or failed to be).
+///
+/// # Returns
+///
+/// Returns a `Result` containing the media of the hess.
+/// If any error occurs during the database query, returns an `ApiErrorType::InternalServerError` error.
+///
+pub async fn get_hess_media_by_hess_id(
+    pool: Pool<Postgres>,
+    hess_id: Uuid,
+    media_type: Option<MediaType>,
+    ready_only: bool,
+) -> Result<Vec<HessMedia>, ApiErrorType> {
+    let query_result = sqlx::query_as!(
+        HessMedia,
+        r#"SELECT id, hess_id, media_type AS "media_type: MediaType", media_url, content_type,
+            size_bytes, processing_status AS "processing_status: MediaProcessingStatus",
+            width, height, duration_ms, blurhash,
+            thumbnail_urls AS "thumbnail_urls: Json<BTreeMap<u32, String>>",
+            thumbnail_content_type, processed_at, alt_text,
+            ARRAY(
+                SELECT c.language FROM hess_media_captions c
+                WHERE c.hess_media_id = hess_media.id ORDER BY c.language
+            ) AS "caption_languages!",
+            created_at, deleted_at
+        FROM hess_media
+        WHERE hess_id = $1
+            AND deleted_at IS NULL
+            AND ($2::MEDIA_TYPE IS NULL OR media_type = $2)
+            AND (NOT $3 OR processing_status = 'READY')
+        ORDER BY created_at, id
+        "#,
+        hess_id,
+        media_type as Option<MediaType>,
+        ready_only,
+    )
+    .fetch_all(&pool)
+    .await;
+
+    match query_result {
+        Ok(hess_media) => Ok(hess_media),
+        Err(_) => Err(ApiErrorType::InternalServerError),
+    }
+}
+
+/// Fills the media of hesses with their (non deleted) processed media, in the order they were
+/// uploaded, for the hess responses.
+///
+/// # Arguments
+///
+/// * `pool` - A database connection pool.
+/// * `hesses` - The hesses to fill.
+///
+/// # Returns
+///
+/// Returns a `Result<(), ApiErrorType>` indicating success.
+/// If any error occurs during the database query, returns an `ApiErrorType::InternalServerError` error.
+///
+pub async fn attach_hess_media<'a>(
+    pool: Pool<Postgres>,
+    hesses: impl IntoIterator<Item = &'a mut Hess>,
+) -> Result<(), ApiErrorType> {
+    let mut hesses = hesses.into_iter().collect::<Vec<_>>();
+
+    if hesses.is_empty() {
+        return Ok(());
+    }
+
+    let hess_ids = hesses.iter().map(|hess| hess.id).collect::<Vec<_>>();
+
+    let query_result = sqlx::query_as!(
+        HessMedia,
+        r#"SELECT id, hess_id, media_type AS "media_type: MediaType", media_url, content_type,
+            size_bytes, processing_status AS "processing_status: MediaProcessingStatus",
+            width, height, duration_ms, blurhash,
+            thumbnail_urls AS "thumbnail_urls: Json<BTreeMap<u32, String>>",
+            thumbnail_content_type, processed_at, alt_text,
+            ARRAY(
+                SELECT c.language FROM hess_media_captions c
+                WHERE c.hess_media_id = hess_media.id ORDER BY c.language
+            ) AS "caption_languages!",
+            created_at, deleted_at
+        FROM hess_media
+        WHERE hess_id = ANY($1) AND deleted_at IS NULL AND processing_status = 'READY'
+        ORDER BY created_at, id
+        "#,
+        &hess_ids,
+    )
+    .fetch_all(&pool)
+    .await;
+
+    let mut media_by_hess_id = HashMap::<Uuid, Vec<HessMedia>>::new();
+
+    match query_result {
+        Ok(hess_media) => {
+            for hess_media in hess_media {
+                media_by_hess_id
+                    .entry(hess_media.hess_id)
+                    .or_default()
+                    .push(hess_media);
+            }
+        }
+        Err(_) => return Err(ApiErrorType::InternalServerError),
+    }
+
+    for hess in hesses.iter_mut() {
+        hess.media = media_by_hess_id.get(&hess.id).cloned().unwrap_or_default();
+    }
+
+    Ok(())
+}
+
+/// Counts the (non deleted) media attached to a hess, by media type.
+///
+/// # Arguments
+///
+/// * `executor` - A database connection pool or transaction.
+/// * `hess_id` - The UUID of the hess.
+///
+/// # Returns
+///
+/// Returns a `Result` containing the number of media of every type attached to the hess
+/// (types without media are missing).
+/// If any error occurs during the database query, returns an `ApiErrorType::InternalServerError` error.
+///
+pub async fn count_hess_media_by_type<'e, E>(
+    executor: E,
+    hess_id: Uuid,
+) -> Result<HashMap<MediaType, i64>, ApiErrorType>
+where
+    E: sqlx::Executor<'e, Database = Postgres>,
+{
+    let query_result = sqlx::query!(
+        r#"SELECT media_type AS "media_type!: MediaType", COUNT(*) AS "count!"
+        FROM hess_media WHERE hess_id = $1 AND deleted_at IS NULL
+        GROUP BY media_type
+        "#,
+        hess_id
+    )
+    .fetch_all(executor)
+    .await;
+
+    match query_result {
+        Ok(rows) => Ok(rows
+            .into_iter()
+            .map(|row| (row.media_type, row.count))
+            .collect()),
+        Err(_) => Err(ApiErrorType::InternalServerError),
+    }
+}
+
+/// Attaches uploaded media to a hess, in the same transaction.
+///
+/// The hess is locked while the media are counted, so concurrent uploads can't go over the
+/// maximum number of media of each type (see `MediaConfig`).
+///
+/// # Arguments
+///
+/// * `pool` - A database connection pool.
+/// * `hess_id` - The UUID of the hess.
+/// * `media` - The data of the uploaded and stored media.
+/// * `media_config` - The limits of the media attached to a hess.
+///
+/// # Returns
+///
+/// Returns a `Result` containing the inserted `HessMedia` if successful.
+/// If the hess doesn't exist (or is deleted), returns an `ApiErrorType::ResourceNotFound` error.
+/// If the hess would have too many media of a type, returns an `ApiErrorType::TooManyMedia` error.
+/// If any other error occurs during the database transaction, returns an `ApiErrorType::InternalServerError` error.
+///
+pub async fn insert_hess_media(
+    pool: Pool<Postgres>,
+    hess_id: Uuid,
+    media: Vec<InsertHessMediaData>,
+    media_config: &MediaConfig,
+) -> Result<Vec<HessMedia>, ApiErrorType> {
+    let mut tx = pool
+        .begin()
+        .await
+        .map_err(|_| ApiErrorType::InternalServerError)?;
+
+    let query_result = sqlx::query!(
+        "SELECT id FROM hesses WHERE id = $1 AND deleted_at IS NULL FOR UPDATE",
+        hess_id
+    )
+    .fetch_optional(&mut *tx)
+    .await;
+
+    match query_result {
+        Ok(Some(_)) => {}
+        Ok(None) => return Err(ApiErrorType::ResourceNotFound(ApiResource::Hesses)),
+        Err(_) => return Err(ApiErrorType::InternalServerError),
+    }
+
+    let mut counts = count_hess_media_by_type(&mut *tx, hess_id).await?;
+
+    for data in &media {
+        let count = counts.entry(data.media_type.clone()).or_insert(0);
+        *count += 1;
+
+        if *count > media_config.hess_max_media(&data.media_type) {
+            return Err(ApiErrorType::TooManyMedia(data.media_type.clone()));
+        }
+    }
+
+    let mut hess_media = Vec::with_capacity(media.len());
+
+    for data in media {
+        let inserted_hess_media = sqlx::query_as!(
+            HessMedia,
+            r#"INSERT INTO hess_media (id, hess_id, media_type, media_url, content_type, size_bytes, created_at)
+            VALUES ($1, $2, $3, $4, $5, $6, $7)
+            RETURNING id, hess_id, media_type AS "media_type: MediaType", media_url, content_type,
+                size_bytes, processing_status AS "processing_status: MediaProcessingStatus",
+                width, height, duration_ms, blurhash,
+                thumbnail_urls AS "thumbnail_urls: Json<BTreeMap<u32, String>>",
+                thumbnail_content_type, processed_at, alt_text,
+                ARRAY[]::VARCHAR[] AS "caption_languages!", created_at, deleted_at
+            "#,
+            data.id,
+            hess_id,
+            data.media_type as MediaType,
+            data.media_url,
+            data.content_type,
+            data.size_bytes,
+            Utc::now(),
+        )
+        .fetch_one(&mut *tx)
+        .await
+        .map_err(|_| ApiErrorType::InternalServerError)?;
+
+        hess_media.push(inserted_hess_media);
+    }
+
+    tx.commit()
+        .await
+        .map_err(|_| ApiErrorType::InternalServerError)?;
+
+    Ok(hess_media)
+}
+
+/// Soft deletes a media attached to a hess.
+///
+/// # Arguments
+///
+/// * `pool` - A database connection pool.
+/// * `hess_id` - The UUID of the hess.
+/// * `id` - The UUID of the hess media to delete.
+///
+/// # Returns
+///
+/// Returns a `Result<(), ApiErrorType>` indicating success.
+/// If the hess has no such (non deleted) media, returns an `ApiErrorType::ResourceNotFound` error.
+/// If any other error occurs during the database query, returns an `ApiErrorType::InternalServerError` error.
+///
+pub async fn delete_hess_media(
+    pool: Pool<Postgres>,
+    hess_id: Uuid,
+    id: Uuid,
+) -> Result<(), ApiErrorType> {
+    let query_result = sqlx::query!(
+        "UPDATE hess_media SET deleted_at = $1 WHERE id = $2 AND hess_id = $3 AND deleted_at IS NULL",
+        Utc::now(),
+        id,
+        hess_id,
+    )
+    .execute(&pool)
+    .await;
+
+    match query_result {
+        Ok(result) if result.rows_affected() == 0 => {
+            Err(ApiErrorType::ResourceNotFound(ApiResource::HessMedia))
+        }
+        Ok(_) => Ok(()),
+        Err(_) => Err(ApiErrorType::InternalServerError),
+    }
+}
+
+/// Marks a hess media as processed (READY), along with its metadata and derived assets.
+///
+/// # Arguments
+///
+/// * `pool` - A database connection pool.
+/// * `id` - The UUID of the hess media.
+/// * `data` - The metadata and the stored derived assets of the hess media.
+///
+/// # Returns
+///
+/// Returns a `Result` containing whether the hess media still exists (soft-deleted ones included).
+/// If any error occurs during the database query, returns an `ApiErrorType::InternalServerError` error.
+///
+pub async fn complete_hess_media_processing(
+    pool: Pool<Postgres>,
+    id: Uuid,
+    data: CompleteHessMediaProcessingData,
+) -> Result<bool, ApiErrorType> {
+    let query_result = sqlx::query!(
+        r#"UPDATE hess_media SET
+            processing_status = 'READY',
+            width = $2,
+            height = $3,
+            duration_ms = $4,
+            blurhash = $5,
+            thumbnail_urls = $6,
+            thumbnail_content_type = $7,
+            processed_at = $8
+        WHERE id = $1
+        "#,
+        id,
+        data.width,
+        data.height,
+        data.duration_ms,
+        data.blurhash,
+        Json(data.thumbnail_urls) as _,
+        data.thumbnail_content_type,
+        Utc::now(),
+    )
+    .execute(&pool)
+    .await;
+
+    match query_result {
+        Ok(result) => Ok(result.rows_affected() > 0),
+        Err(_) => Err(ApiErrorType::InternalServerError),
+    }
+}
+
+/// Marks a hess media as failed to be processed (FAILED), it stays hidden from other viewers.
+///
+/// # Arguments
+///
+/// * `pool` - A database connection pool.
+/// * `id` - The UUID of the hess media.
+///
+/// # Returns
+///
+/// Returns a `Result<(), ApiErrorType>` indicating success.
+/// If any error occurs during the database query, returns an `ApiErrorType::InternalServerError` error.
+///
+pub async fn fail_hess_media_processing(
+    pool: Pool<Postgres>,
+    id: Uuid,
+) -> Result<(), ApiErrorType> {
+    let query_result = sqlx::query!(
+        "UPDATE hess_media SET processing_status = 'FAILED', processed_at = $2 WHERE id = $1",
+        id,
+        Utc::now(),
+    )
+    .execute(&pool)
+    .await;
+
+    match query_result {
+        Ok(_) => Ok(()),
+        Err(_) => Err(ApiErrorType::InternalServerError),
+    }
+}
+
+/// Updates the accessibility metadata of a media attached to a hess.
+///
+/// # Arguments
+///
+/// * `pool` - A database connection pool.
+/// * `hess_id` - The UUID of the hess.
+/// * `id` - The UUID of the hess media to update.
+/// * `update_data` - The fields to update (the missing ones are left as is).
+///
+/// # Returns
+///
+/// Returns a `Result` containing the updated `HessMedia` if successful.
+/// If the hess has no such (non deleted) media, returns an `ApiErrorType::ResourceNotFound` error.
+/// If any other error occurs during the database query, returns an `ApiErrorType::InternalServerError` error.
+///
+pub async fn update_hess_media(
+    pool: Pool<Postgres>,
+    hess_id: Uuid,
+    id: Uuid,
+    update_data: UpdateHessMediaData,
+) -> Result<HessMedia, ApiErrorType> {
+    let query_result = sqlx::query_as!(
+        HessMedia,
+        r#"UPDATE hess_media SET alt_text = CASE WHEN $3 THEN $4 ELSE alt_text END
+        WHERE id = $1 AND hess_id = $2 AND deleted_at IS NULL
+        RETURNING id, hess_id, media_type AS "media_type: MediaType", media_url, content_type,
+            size_bytes, processing_status AS "processing_status: MediaProcessingStatus",
+            width, height, duration_ms, blurhash,
+            thumbnail_urls AS "thumbnail_urls: Json<BTreeMap<u32, String>>",
+            thumbnail_content_type, processed_at, alt_text,
+            ARRAY(
+                SELECT c.language FROM hess_media_captions c
+                WHERE c.hess_media_id = hess_media.id ORDER BY c.language
+            ) AS "caption_languages!",
+            created_at, deleted_at
+        "#,
+        id,
+        hess_id,
+        update_data.alt_text.is_some(),
+        update_data.alt_text.flatten(),
+    )
+    .fetch_optional(&pool)
+    .await;
+
+    match query_result {
+        Ok(Some(hess_media)) => Ok(hess_media),
+        Ok(None) => Err(ApiErrorType::ResourceNotFound(ApiResource::HessMedia)),
+        Err(_) => Err(ApiErrorType::InternalServerError),
+    }
+}
+
+/// Retrieves the WebVTT captions of a hess media in a language.
+///
+/// # Arguments
+///
+/// * `pool` - A database connection pool.
+/// * `hess_media_id` - The UUID of the hess media.
+/// * `language` - The (lowercased) language tag of the captions.
+///
+/// # Returns
+///
+/// Returns a `Result` containing the captions if successful.
+/// If the media has no captions in this language, returns an `ApiErrorType::ResourceNotFound` error.
+/// If any other error occurs during the database query, returns an `ApiErrorType::InternalServerError` error.
+///
+pub async fn get_hess_media_captions(
+    pool: Pool<Postgres>,
+    hess_media_id: Uuid,
+    language: &str,
+) -> Result<String, ApiErrorType> {
+    let query_result = sqlx::query_scalar!(
+        "SELECT content FROM hess_media_captions WHERE hess_media_id = $1 AND language = $2",
+        hess_media_id,
+        language,
+    )
+    .fetch_optional(&pool)
+    .await;
+
+    match query_result {
+        Ok(Some(content)) => Ok(content),
+        Ok(None) => Err(ApiErrorType::ResourceNotFound(
+            ApiResource::HessMediaCaptions,
+        )),
+        Err(_) => Err(ApiErrorType::InternalServerError),
+    }
+}
+
+/// Adds (or replaces) the WebVTT captions of a hess media in a language.
+///
+/// # Arguments
+///
+/// * `pool` - A database connection pool.
+/// * `hess_media_id` - The UUID of the hess media.
+/// * `language` - The (lowercased) language tag of the captions.
+/// * `content` - The validated WebVTT captions (see `parse_webvtt`).
+///
+/// # Returns
+///
+/// Returns a `Result<(), ApiErrorType>` indicating success.
+/// If any error occurs during the database query, returns an `ApiErrorType::InternalServerError` error.
+///
+pub async fn upsert_hess_media_captions(
+    pool: Pool<Postgres>,
+    hess_media_id: Uuid,
+    language: &str,
+    content: String,
+) -> Result<(), ApiErrorType> {
+    let query_result = sqlx::query!(
+        r#"INSERT INTO hess_media_captions (hess_media_id, language, content)
+        VALUES ($1, $2, $3)
+        ON CONFLICT (hess_media_id, language)
+        DO UPDATE SET content = EXCLUDED.content, updated_at = NOW()
+        "#,
+        hess_media_id,
+        language,
+        content,
+    )
+    .execute(&pool)
+    .await;
+
+    match query_result {
+        Ok(_) => Ok(()),
+        Err(_) => Err(ApiErrorType::InternalServerError),
+    }
+}
+
+/// Removes the WebVTT captions of a hess media in a language.
+///
+/// # Arguments
+///
+/// * `pool` - A database connection pool.
+/// * `hess_media_id` - The UUID of the hess media.
+/// * `language` - The (lowercased) language tag of the captions.
+///
+/// # Returns
+///
+/// Returns a `Result<(), ApiErrorType>` indicating success.
+/// If the media has no captions in this language, returns an `ApiErrorType::ResourceNotFound` error.
+/// If any other error occurs during the database query, returns an `ApiErrorType::InternalServerError` error.
+///
+pub async fn delete_hess_media_captions(
+    pool: Pool<Postgres>,
+    hess_media_id: Uuid,
+    language: &str,
+) -> Result<(), ApiErrorType> {
+    let query_result = sqlx::query!(
+        "DELETE FROM hess_media_captions WHERE hess_media_id = $1 AND language = $2",
+        hess_media_id,
+        language,
+    )
+    .execute(&pool)
+    .await;
+
+    match query_result {
+        Ok(result) if result.rows_affected() == 0 => Err(ApiErrorType::ResourceNotFound(
+            ApiResource::HessMediaCaptions,
+        )),
+        Ok(_) => Ok(()),
+        Err(_) => Err(ApiErrorType::InternalServerError),
+    }
+}
@@ -0,0 +1,153 @@
+use sqlx::{Pool, Postgres};
+use uuid::Uuid;
+
+use crate::{
+    models::{Hess, Page},
+    utils::{
+        list_filter::{FilterField, FilterType},
+        list_query::{ListQuery, ListSpec, SortField},
+        response::{ApiErrorType, ApiResource},
+    },
+};
+
+/// Retrieves a (non deleted) hess by its ID from the database.
+///
+/// # Arguments
+///
+/// * `pool` - A database connection pool.
+/// * `id` - The UUID of the hess to retrieve.
+///
+/// # Returns
+///
+/// Returns a `Result` containing the retrieved `Hess` if successful.
+/// If no hess is found, returns an `ApiErrorType::ResourceNotFound` error.
+/// If any other error occurs during the database query, returns an `ApiErrorType::InternalServerError` error.
+///
+pub async fn get_hess_by_id(pool: Pool<Postgres>, id: Uuid) -> Result<Hess, ApiErrorType> {
+    let query_result = sqlx::query_as::<_, Hess>(
+        r#"SELECT
+            id,
+            user_id,
+            content,
+            parent_hess_id,
+            who_can_reply,
+            who_can_like,
+            who_can_watch_replies,
+            who_can_watch_likes,
+            created_at,
+            updated_at,
+            deleted_at
+        FROM hesses WHERE id = $1 AND deleted_at IS NULL
+        "#,
+    )
+    .bind(id)
+    .fetch_optional(&pool)
+    .await;
+
+    match query_result {
+        Ok(Some(hess)) => Ok(hess),
+        Ok(None) => Err(ApiErrorType::ResourceNotFound(ApiResource::Hesses)),
+        Err(_) => Err(ApiErrorType::InternalServerError),
+    }
+}
+
+/// The sortable and filterable fields of the hesses (soft-deleted ones included), as listed to admins.
+pub static HESSES_LIST_SPEC: ListSpec = ListSpec {
+    table: "hesses",
+    sort_fields: &[SortField {
+        name: "createdAt",
+        column: "created_at",
+    }],
+    default_sort: "-createdAt",
+    filter_fields: &[
+        FilterField {
+            name: "userId",
+            expression: "h.user_id",
+            filter_type: FilterType::Uuid,
+        },
+        FilterField {
+            name: "createdAt",
+            expression: "h.created_at",
+            filter_type: FilterType::Timestamp,
+        },
+        FilterField {
+            name: "hasMedia",
+            expression: "EXISTS (SELECT 1 FROM hess_media m WHERE m.hess_id = h.id AND m.deleted_at IS NULL)",
+            filter_type: FilterType::Bool,
+        },
+        FilterField {
+            name: "deleted",
+            expression: "(h.deleted_at IS NOT NULL)",
+            filter_type: FilterType::Bool,
+        },
+    ],
+};
+
+/// Retrieves a page of hesses, soft-deleted ones included.
+///
+/// # Arguments
+///
+/// * `pool` - A database connection pool.
+/// * `list_query` - The filters, sorting and pagination, parsed against `HESSES_LIST_SPEC`.
+///
+/// # Returns
+///
+/// Returns a `Result` containing the page of hesses.
+/// If any error occurs during the database query, returns an `ApiErrorType::InternalServerError` error.
+///
+pub async fn get_hesses(
+    pool: Pool<Postgres>,
+    list_query: ListQuery,
+) -> Result<Page<Hess>, ApiErrorType> {
+    let count_query_string = format!(
+        "SELECT COUNT(*) FROM hesses h WHERE {}",
+        list_query.filters_sql(1)
+    );
+
+    let total = list_query
+        .bind_filters(sqlx::query_as::<_, (i64,)>(count_query_string.as_str()))
+        .fetch_one(&pool)
+        .await
+        .map_err(|_| ApiErrorType::InternalServerError)?
+        .0;
+
+    let query_string = format!(
+        r#"SELECT
+            h.id,
+            h.user_id,
+            h.content,
+            h.parent_hess_id,
+            h.who_can_reply,
+            h.who_can_like,
+            h.who_can_watch_replies,
+            h.who_can_watch_likes,
+            h.created_at,
+            h.updated_at,
+            h.deleted_at
+        FROM hesses h
+        {}
+        WHERE {} AND {}
+        ORDER BY {}
+        LIMIT $2 OFFSET $3
+        "#,
+        list_query.keyset_join_sql("$1"),
+        list_query.keyset_condition_sql("h"),
+        list_query.filters_sql(4),
+        list_query.order_by_sql("h")
+    );
+
+    let query_result = list_query
+        .bind_filters(
+            sqlx::query_as::<_, Hess>(query_string.as_str())
+                .bind(list_query.after_id)
+                .bind(list_query.limit())
+                .bind(list_query.offset()),
+        )
+        .fetch_all(&pool)
+        .await;
+
+    match query_result {
+        Ok(hesses) => Ok(list_query.into_page(hesses, total, |hess| hess.id)),
+        Err(_) => Err(ApiErrorType::InternalServerError),
+    }
+}
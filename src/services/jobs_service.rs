@@ -0,0 +1,154 @@
+use chrono::{DateTime, Utc};
+use sqlx::{Pool, Postgres};
+use uuid::Uuid;
+
+use crate::{models::Job, utils::response::ApiErrorType};
+
+/// Enqueues a job, to be processed by the handler of its queue once due.
+///
+/// # Arguments
+///
+/// * `pool` - A database connection pool.
+/// * `queue` - The name of the queue.
+/// * `kind` - The kind of job.
+/// * `payload` - The payload of the job.
+/// * `run_at` - When the job is due.
+///
+/// # Returns
+///
+/// Returns `Ok(())` if successful.
+/// If any error occurs during the database query, returns an `ApiErrorType::InternalServerError` error.
+///
+pub async fn enqueue_job(
+    pool: Pool<Postgres>,
+    queue: &str,
+    kind: &str,
+    payload: &serde_json::Value,
+    run_at: DateTime<Utc>,
+) -> Result<(), ApiErrorType> {
+    let query_result = sqlx::query!(
+        "INSERT INTO jobs (queue, kind, payload, run_at) VALUES ($1, $2, $3, $4)",
+        queue,
+        kind,
+        payload,
+        run_at
+    )
+    .execute(&pool)
+    .await;
+
+    match query_result {
+        Ok(_) => Ok(()),
+        Err(_) => Err(ApiErrorType::InternalServerError),
+    }
+}
+
+/// Claims a batch of due jobs of a queue, so they can be processed.
+///
+/// Claimed jobs are locked (and their attempts incremented), jobs locked by other workers are
+/// skipped, unless their lock is older than 5 minutes (the worker probably died while processing them).
+///
+/// # Arguments
+///
+/// * `pool` - A database connection pool.
+/// * `queue` - The name of the queue.
+/// * `batch_size` - The maximum number of jobs to claim.
+/// * `max_attempts` - Jobs that already failed this many times are left alone.
+///
+/// # Returns
+///
+/// Returns a `Result` containing the claimed jobs, oldest first.
+/// If any error occurs during the database query, returns an `ApiErrorType::InternalServerError` error.
+///
+pub async fn claim_jobs(
+    pool: Pool<Postgres>,
+    queue: &str,
+    batch_size: i64,
+    max_attempts: i32,
+) -> Result<Vec<Job>, ApiErrorType> {
+    let query_result = sqlx::query_as!(
+        Job,
+        r#"UPDATE jobs SET locked_at = NOW(), attempts = attempts + 1
+        WHERE id IN (
+            SELECT id FROM jobs
+            WHERE queue = $1
+                AND run_at <= NOW()
+                AND attempts < $3
+                AND (locked_at IS NULL OR locked_at < NOW() - INTERVAL '5 minutes')
+            ORDER BY run_at, created_at
+            LIMIT $2
+            FOR UPDATE SKIP LOCKED
+        )
+        RETURNING id, queue, kind, payload, attempts, last_error, run_at, locked_at, created_at
+        "#,
+        queue,
+        batch_size,
+        max_attempts
+    )
+    .fetch_all(&pool)
+    .await;
+
+    match query_result {
+        Ok(mut jobs) => {
+            // RETURNING doesn't keep the order of the subquery
+            jobs.sort_by_key(|job| (job.run_at, job.created_at));
+            Ok(jobs)
+        }
+        Err(_) => Err(ApiErrorType::InternalServerError),
+    }
+}
+
+/// Deletes a job once it has been successfully processed.
+///
+/// # Arguments
+///
+/// * `pool` - A database connection pool.
+/// * `id` - The UUID of the job.
+///
+/// # Returns
+///
+/// Returns `Ok(())` if successful.
+/// If any error occurs during the database query, returns an `ApiErrorType::InternalServerError` error.
+///
+pub async fn complete_job(pool: Pool<Postgres>, id: Uuid) -> Result<(), ApiErrorType> {
+    let query_result = sqlx::query!("DELETE FROM jobs WHERE id = $1", id)
+        .execute(&pool)
+        .await;
+
+    match query_result {
+        Ok(_) => Ok(()),
+        Err(_) => Err(ApiErrorType::InternalServerError),
+    }
+}
+
+/// Releases a failed job, it's retried later with a quadratic backoff (10s, 40s, 90s...).
+///
+/// # Arguments
+///
+/// * `pool` - A database connection pool.
+/// * `id` - The UUID of the job.
+/// * `error` - A description of the error, kept for debugging.
+///
+/// # Returns
+///
+/// Returns `Ok(())` if successful.
+/// If any error occurs during the database query, returns an `ApiErrorType::InternalServerError` error.
+///
+pub async fn fail_job(pool: Pool<Postgres>, id: Uuid, error: String) -> Result<(), ApiErrorType> {
+    let query_result = sqlx::query!(
+        r#"UPDATE jobs SET
+            locked_at = NULL,
+            last_error = $2,
+            run_at = NOW() + (attempts * attempts * INTERVAL '10 seconds')
+        WHERE id = $1
+        "#,
+        id,
+        error
+    )
+    .execute(&pool)
+    .await;
+
+    match query_result {
+        Ok(_) => Ok(()),
+        Err(_) => Err(ApiErrorType::InternalServerError),
+    }
+}
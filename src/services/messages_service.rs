@@ -0,0 +1,365 @@
+use std::collections::HashMap;
+
+use chrono::{DateTime, Utc};
+use serde_derive::{Deserialize, Serialize};
+use sqlx::{FromRow, Pool, Postgres};
+use uuid::Uuid;
+
+use crate::{
+    models::{MediaType, Message, MessageMedia, MessagesPage},
+    models_validators::message_validator::InsertMessageData,
+    policies::{policy_sql_predicate, PolicyAction},
+    utils::{
+        cursor::encode_cursor,
+        response::{ApiErrorType, ApiResource},
+    },
+};
+
+/// The messages shown to a viewer (`$1`), the deleted ones and the ones of users they blocked
+/// (or got blocked by) are hidden.
+pub const VISIBLE_MESSAGE_PREDICATE: &str = r#"m.deleted_at IS NULL AND (
+        m.sender_id = $1 OR NOT EXISTS (
+            SELECT 1 FROM blocked_users b
+            WHERE b.deleted_at IS NULL AND (
+                (b.blocker_id = $1 AND b.blocked_id = m.sender_id)
+                OR (b.blocker_id = m.sender_id AND b.blocked_id = $1)
+            )
+        )
+    )"#;
+
+/// The position of the last message of a page.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct MessageCursor {
+    pub created_at: DateTime<Utc>,
+    pub id: Uuid,
+}
+
+#[derive(Debug, FromRow)]
+struct MessageRow {
+    id: Uuid,
+    conversation_id: Uuid,
+    sender_id: Uuid,
+    content: Option<String>,
+    created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, FromRow)]
+struct MessageMediaRow {
+    message_id: Uuid,
+    hess_media_id: Uuid,
+    hess_id: Uuid,
+    media_type: MediaType,
+}
+
+/// Attaches their media to message rows, keeping their order.
+async fn into_messages(
+    pool: Pool<Postgres>,
+    rows: Vec<MessageRow>,
+) -> Result<Vec<Message>, ApiErrorType> {
+    let message_ids = rows.iter().map(|row| row.id).collect::<Vec<_>>();
+
+    let mut media = HashMap::<Uuid, Vec<MessageMedia>>::new();
+
+    for row in sqlx::query_as::<_, MessageMediaRow>(
+        r#"SELECT mm.message_id, hm.id AS hess_media_id, hm.hess_id, hm.media_type
+        FROM message_media mm
+        JOIN hess_media hm ON hm.id = mm.hess_media_id AND hm.deleted_at IS NULL
+        WHERE mm.message_id = ANY($1)
+        ORDER BY mm.message_id, mm.position
+        "#,
+    )
+    .bind(&message_ids)
+    .fetch_all(&pool)
+    .await
+    .map_err(|_| ApiErrorType::InternalServerError)?
+    {
+        media.entry(row.message_id).or_default().push(MessageMedia {
+            hess_media_id: row.hess_media_id,
+            hess_id: row.hess_id,
+            media_type: row.media_type,
+        });
+    }
+
+    Ok(rows
+        .into_iter()
+        .map(|row| Message {
+            media: media.remove(&row.id).unwrap_or_default(),
+            id: row.id,
+            conversation_id: row.conversation_id,
+            sender_id: row.sender_id,
+            content: row.content,
+            created_at: row.created_at,
+        })
+        .collect())
+}
+
+/// Counts the hess media (among the given ones) a viewer can see, i.e. that they can reference
+/// in a message.
+///
+/// # Arguments
+///
+/// * `pool` - A database connection pool.
+/// * `viewer_id` - The UUID of the sender of the message.
+/// * `media_ids` - The UUIDs of the hess media.
+///
+/// # Returns
+///
+/// Returns a `Result` containing the number of media the viewer can see.
+/// If any error occurs during the database query, returns an `ApiErrorType::InternalServerError` error.
+///
+pub async fn count_viewable_hess_media(
+    pool: Pool<Postgres>,
+    viewer_id: Uuid,
+    media_ids: &[Uuid],
+) -> Result<i64, ApiErrorType> {
+    let can_view_hess = policy_sql_predicate(PolicyAction::View, "$1", "h.user_id", Some("h.id"));
+
+    sqlx::query_scalar::<_, i64>(&format!(
+        r#"SELECT COUNT(*) FROM hess_media hm
+        JOIN hesses h ON h.id = hm.hess_id AND h.deleted_at IS NULL
+        WHERE hm.id = ANY($2) AND hm.deleted_at IS NULL AND {}
+        "#,
+        can_view_hess
+    ))
+    .bind(viewer_id)
+    .bind(media_ids)
+    .fetch_one(&pool)
+    .await
+    .map_err(|_| ApiErrorType::InternalServerError)
+}
+
+/// Sends a message in a conversation.
+///
+/// Replying to a message request accepts it. The message is streamed to the participants
+/// by the database triggers.
+///
+/// # Arguments
+///
+/// * `pool` - A database connection pool.
+/// * `conversation_id` - The UUID of the conversation.
+/// * `sender_id` - The UUID of the sender, a participant of the conversation.
+/// * `data` - The content and media of the message.
+///
+/// # Returns
+///
+/// Returns a `Result` containing the sent `Message` if successful.
+/// If any error occurs during the database queries, returns an `ApiErrorType::InternalServerError` error.
+///
+pub async fn insert_message(
+    pool: Pool<Postgres>,
+    conversation_id: Uuid,
+    sender_id: Uuid,
+    data: InsertMessageData,
+) -> Result<Message, ApiErrorType> {
+    let mut tx = pool
+        .begin()
+        .await
+        .map_err(|_| ApiErrorType::InternalServerError)?;
+
+    // Before inserting the message, so it's streamed to the sender as accepted
+    sqlx::query!(
+        r#"UPDATE conversation_participants SET status = 'ACCEPTED'
+        WHERE conversation_id = $1 AND user_id = $2 AND status = 'PENDING'
+        "#,
+        conversation_id,
+        sender_id
+    )
+    .execute(&mut *tx)
+    .await
+    .map_err(|_| ApiErrorType::InternalServerError)?;
+
+    let row = sqlx::query_as!(
+        MessageRow,
+        r#"INSERT INTO messages (conversation_id, sender_id, content) VALUES ($1, $2, $3)
+        RETURNING id, conversation_id, sender_id, content, created_at
+        "#,
+        conversation_id,
+        sender_id,
+        data.content
+    )
+    .fetch_one(&mut *tx)
+    .await
+    .map_err(|_| ApiErrorType::InternalServerError)?;
+
+    if !data.media_ids.is_empty() {
+        sqlx::query!(
+            r#"INSERT INTO message_media (message_id, hess_media_id, position)
+            SELECT $1, media.id, media.position
+            FROM unnest($2::UUID[]) WITH ORDINALITY AS media(id, position)
+            "#,
+            row.id,
+            &data.media_ids
+        )
+        .execute(&mut *tx)
+        .await
+        .map_err(|_| ApiErrorType::InternalServerError)?;
+    }
+
+    sqlx::query!(
+        "UPDATE conversations SET last_message_at = $2 WHERE id = $1",
+        conversation_id,
+        row.created_at
+    )
+    .execute(&mut *tx)
+    .await
+    .map_err(|_| ApiErrorType::InternalServerError)?;
+
+    tx.commit()
+        .await
+        .map_err(|_| ApiErrorType::InternalServerError)?;
+
+    Ok(into_messages(pool, vec![row]).await?.remove(0))
+}
+
+/// Retrieves a page of the messages of a conversation as shown to a participant, latest first
+/// (see `VISIBLE_MESSAGE_PREDICATE`).
+///
+/// # Arguments
+///
+/// * `pool` - A database connection pool.
+/// * `viewer_id` - The UUID of the participant.
+/// * `conversation_id` - The UUID of the conversation.
+/// * `cursor` - The position of the last message of the previous page, `None` for the first page.
+/// * `size` - The maximum number of messages in the page.
+///
+/// # Returns
+///
+/// Returns a `Result` containing the `MessagesPage`, with a `next_cursor` if there may be more messages.
+/// If any error occurs during the database queries, returns an `ApiErrorType::InternalServerError` error.
+///
+pub async fn get_messages(
+    pool: Pool<Postgres>,
+    viewer_id: Uuid,
+    conversation_id: Uuid,
+    cursor: Option<MessageCursor>,
+    size: i64,
+) -> Result<MessagesPage, ApiErrorType> {
+    let mut rows = sqlx::query_as::<_, MessageRow>(&format!(
+        r#"SELECT m.id, m.conversation_id, m.sender_id, m.content, m.created_at
+        FROM messages m
+        WHERE m.conversation_id = $2
+            AND {}
+            AND ($3::TIMESTAMPTZ IS NULL OR (m.created_at, m.id) < ($3, $4))
+        ORDER BY m.created_at DESC, m.id DESC
+        LIMIT $5
+        "#,
+        VISIBLE_MESSAGE_PREDICATE
+    ))
+    .bind(viewer_id)
+    .bind(conversation_id)
+    .bind(cursor.as_ref().map(|cursor| cursor.created_at))
+    .bind(cursor.as_ref().map(|cursor| cursor.id))
+    .bind(size + 1)
+    .fetch_all(&pool)
+    .await
+    .map_err(|_| ApiErrorType::InternalServerError)?;
+
+    let has_more = rows.len() as i64 > size;
+    rows.truncate(size as usize);
+
+    let items = into_messages(pool, rows).await?;
+
+    let next_cursor = match items.last() {
+        Some(last) if has_more => Some(encode_cursor(&MessageCursor {
+            created_at: last.created_at,
+            id: last.id,
+        })),
+        _ => None,
+    };
+
+    Ok(MessagesPage { items, next_cursor })
+}
+
+/// Retrieves the latest message of conversations, as shown to a participant (see
+/// `VISIBLE_MESSAGE_PREDICATE`).
+///
+/// # Arguments
+///
+/// * `pool` - A database connection pool.
+/// * `viewer_id` - The UUID of the participant.
+/// * `conversation_ids` - The UUIDs of the conversations.
+///
+/// # Returns
+///
+/// Returns a `Result` containing the latest message of each conversation having one, by conversation.
+/// If any error occurs during the database queries, returns an `ApiErrorType::InternalServerError` error.
+///
+pub async fn get_last_messages(
+    pool: Pool<Postgres>,
+    viewer_id: Uuid,
+    conversation_ids: &[Uuid],
+) -> Result<HashMap<Uuid, Message>, ApiErrorType> {
+    let rows = sqlx::query_as::<_, MessageRow>(&format!(
+        r#"SELECT DISTINCT ON (m.conversation_id)
+            m.id, m.conversation_id, m.sender_id, m.content, m.created_at
+        FROM messages m
+        WHERE m.conversation_id = ANY($2) AND {}
+        ORDER BY m.conversation_id, m.created_at DESC, m.id DESC
+        "#,
+        VISIBLE_MESSAGE_PREDICATE
+    ))
+    .bind(viewer_id)
+    .bind(conversation_ids)
+    .fetch_all(&pool)
+    .await
+    .map_err(|_| ApiErrorType::InternalServerError)?;
+
+    Ok(into_messages(pool, rows)
+        .await?
+        .into_iter()
+        .map(|message| (message.conversation_id, message))
+        .collect())
+}
+
+/// Deletes a message sent by a user (soft delete), it's streamed to the participants as deleted.
+///
+/// # Arguments
+///
+/// * `pool` - A database connection pool.
+/// * `conversation_id` - The UUID of the conversation of the message.
+/// * `message_id` - The UUID of the message.
+/// * `sender_id` - The UUID of the user deleting the message.
+///
+/// # Returns
+///
+/// Returns a `Result` containing nothing.
+/// If the message doesn't exist in the conversation, returns an `ApiErrorType::ResourceNotFound` error.
+/// If the message wasn't sent by the user, returns an `ApiErrorType::Unauthorized` error.
+/// If any other error occurs during the database queries, returns an `ApiErrorType::InternalServerError` error.
+///
+pub async fn delete_message(
+    pool: Pool<Postgres>,
+    conversation_id: Uuid,
+    message_id: Uuid,
+    sender_id: Uuid,
+) -> Result<(), ApiErrorType> {
+    let query_result = sqlx::query_scalar!(
+        r#"SELECT sender_id FROM messages
+        WHERE id = $1 AND conversation_id = $2 AND deleted_at IS NULL
+        "#,
+        message_id,
+        conversation_id
+    )
+    .fetch_optional(&pool)
+    .await;
+
+    match query_result {
+        Ok(Some(message_sender_id)) if message_sender_id == sender_id => {}
+        Ok(Some(_)) => return Err(ApiErrorType::Unauthorized),
+        Ok(None) => return Err(ApiErrorType::ResourceNotFound(ApiResource::Messages)),
+        Err(_) => return Err(ApiErrorType::InternalServerError),
+    }
+
+    let query_result = sqlx::query!(
+        "UPDATE messages SET deleted_at = NOW() WHERE id = $1 AND deleted_at IS NULL",
+        message_id
+    )
+    .execute(&pool)
+    .await;
+
+    match query_result {
+        Ok(_) => Ok(()),
+        Err(_) => Err(ApiErrorType::InternalServerError),
+    }
+}
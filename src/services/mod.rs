@@ -1,13 +1,30 @@
+pub mod audience_lists_service;
+pub mod blobs_service;
 pub mod blocked_users_service;
+pub mod conversations_service;
+pub mod device_tokens_service;
+pub mod digests_service;
 pub mod emails_service;
+pub mod feed_items_service;
+pub mod feed_service;
 pub mod follow_requests_service;
 pub mod followers_service;
 pub mod hess_likes_services;
 pub mod hess_media_service;
 pub mod hess_mentions_service;
 pub mod hesses_service;
+pub mod jobs_service;
+pub mod messages_service;
+pub mod muted_users_service;
+pub mod notifications_service;
 pub mod password_reset_tokens_service;
+pub mod search_service;
+pub mod stream_events_service;
 pub mod user_confirmation_tokens_service;
+pub mod user_notification_preferences_service;
 pub mod user_privacy_preferences_service;
 pub mod user_profile_images_services;
+pub mod user_role_changes_service;
 pub mod users_service;
+pub mod verification_requests_service;
+pub mod viewer_relationships_service;
@@ -0,0 +1,99 @@
+use chrono::Utc;
+use sqlx::{Pool, Postgres};
+use uuid::Uuid;
+
+use crate::{
+    models::MutedUser,
+    utils::response::{ApiErrorType, ApiResource},
+};
+
+/// Inserts a new muted user entry into the database.
+///
+/// Unlike blocking, muting is one-sided and silent, the muted user's activity is simply
+/// hidden from the muter (feed, notifications...) without affecting what they can do.
+///
+/// # Arguments
+///
+/// * `pool` - A database connection pool.
+/// * `muter_id` - UUID of the user who is muting.
+/// * `muted_id` - UUID of the user who is being muted.
+///
+/// # Returns
+///
+/// Returns a `Result` containing the inserted `MutedUser` if successful.
+/// If the user is already muted, returns an `ApiErrorType::AlreadyExists` error.
+/// If the muted user doesn't exist, returns an `ApiErrorType::ResourceNotFound` error.
+/// If any other error occurs during database insertion, returns an `ApiErrorType::InternalServerError` error.
+///
+pub async fn insert_muted_user(
+    pool: Pool<Postgres>,
+    muter_id: Uuid,
+    muted_id: Uuid,
+) -> Result<MutedUser, ApiErrorType> {
+    let query_result = sqlx::query_as!(
+        MutedUser,
+        r#"INSERT INTO muted_users (muter_id, muted_id) VALUES ($1, $2)
+        RETURNING id, muter_id, muted_id, created_at, deleted_at
+        "#,
+        muter_id,
+        muted_id
+    )
+    .fetch_one(&pool)
+    .await;
+
+    match query_result {
+        Ok(muted_user) => Ok(muted_user),
+        Err(e) => match e {
+            sqlx::Error::Database(e) if e.is_unique_violation() => {
+                Err(ApiErrorType::AlreadyExists(ApiResource::MutedUsers))
+            }
+            sqlx::Error::Database(e) if e.is_foreign_key_violation() => {
+                Err(ApiErrorType::ResourceNotFound(ApiResource::Users))
+            }
+            _ => Err(ApiErrorType::InternalServerError),
+        },
+    }
+}
+
+/// Softly deletes a muted user entry from the database (unmutes the user).
+///
+/// # Arguments
+///
+/// * `pool` - A database connection pool.
+/// * `muter_id` - UUID of the user who muted.
+/// * `muted_id` - UUID of the user who was muted.
+///
+/// # Returns
+///
+/// Returns a `Result<(), ApiErrorType>` indicating success or an `ApiErrorType::InternalServerError` error
+/// if any error occurs during database deletion.
+/// If the user isn't muted, returns an `ApiErrorType::ResourceNotFound` error.
+///
+pub async fn delete_muted_user(
+    pool: Pool<Postgres>,
+    muter_id: Uuid,
+    muted_id: Uuid,
+) -> Result<(), ApiErrorType> {
+    let current_date = Utc::now();
+
+    let query_result = sqlx::query!(
+        "UPDATE muted_users SET deleted_at = $1
+        WHERE muter_id = $2 AND muted_id = $3 AND deleted_at IS NULL",
+        current_date,
+        muter_id,
+        muted_id,
+    )
+    .execute(&pool)
+    .await;
+
+    match query_result {
+        Ok(result) => {
+            if result.rows_affected() == 0 {
+                Err(ApiErrorType::ResourceNotFound(ApiResource::MutedUsers))
+            } else {
+                Ok(())
+            }
+        }
+        Err(_) => Err(ApiErrorType::InternalServerError),
+    }
+}
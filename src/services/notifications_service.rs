@@ -0,0 +1,320 @@
+use std::collections::HashMap;
+
+use chrono::{DateTime, Utc};
+use serde_derive::{Deserialize, Serialize};
+use sqlx::{FromRow, Pool, Postgres};
+use uuid::Uuid;
+
+use crate::{
+    models::{Notification, NotificationActor, NotificationType, NotificationsPage},
+    utils::{
+        cursor::encode_cursor,
+        response::{ApiErrorType, ApiResource},
+    },
+};
+
+/// The maximum number of actors listed in a notification, the others are only counted.
+const NOTIFICATION_MAX_LISTED_ACTORS: usize = 3;
+
+/// The position of the last notification of a page.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct NotificationCursor {
+    pub occurred_at: DateTime<Utc>,
+    pub id: Uuid,
+}
+
+#[derive(Debug, FromRow)]
+struct NotificationRow {
+    id: Uuid,
+    notification_type: String,
+    hess_id: Option<Uuid>,
+    occurred_at: DateTime<Utc>,
+    read_at: Option<DateTime<Utc>>,
+    actor_ids: Vec<Uuid>,
+}
+
+/// The notifications of a recipient (`$1`) as they're shown to them.
+///
+/// The actors the recipient muted, blocked (or got blocked by) or who were deleted since are
+/// removed, and the notifications left without actors, or about a deleted hess, are hidden.
+///
+const VISIBLE_NOTIFICATIONS_QUERY: &str = r#"SELECT
+    n.id,
+    n.notification_type,
+    n.hess_id,
+    n.occurred_at,
+    n.read_at,
+    ARRAY(
+        SELECT a.actor_id FROM unnest(n.actor_ids) WITH ORDINALITY AS a(actor_id, position)
+        JOIN users u ON u.id = a.actor_id AND u.deleted_at IS NULL
+        WHERE NOT EXISTS (
+            SELECT 1 FROM muted_users m
+            WHERE m.muter_id = n.recipient_id AND m.muted_id = a.actor_id AND m.deleted_at IS NULL
+        )
+        AND NOT EXISTS (
+            SELECT 1 FROM blocked_users b
+            WHERE b.deleted_at IS NULL AND (
+                (b.blocker_id = n.recipient_id AND b.blocked_id = a.actor_id)
+                OR (b.blocker_id = a.actor_id AND b.blocked_id = n.recipient_id)
+            )
+        )
+        ORDER BY a.position
+    ) AS actor_ids
+FROM notifications n
+LEFT JOIN hesses h ON h.id = n.hess_id
+WHERE n.recipient_id = $1 AND (n.hess_id IS NULL OR h.deleted_at IS NULL)"#;
+
+/// Stores the notification of an event, merging it into the unread notification of its group
+/// if any (see `NotificationType::group_key`).
+///
+/// Storing the same event twice (e.g. a retried job) doesn't merge it again.
+///
+/// # Arguments
+///
+/// * `pool` - A database connection pool.
+/// * `recipient_id` - The UUID of the notified user.
+/// * `notification_type` - The type of the event.
+/// * `actor_id` - The UUID of the user behind the event.
+/// * `event_id` - The UUID of the event (like, follower relationship, hess...).
+/// * `hess_id` - The UUID of the hess the event is about, if any.
+///
+/// # Returns
+///
+/// Returns a `Result` containing the number of actors merged into the notification if successful.
+/// If any error occurs during the database query, returns an `ApiErrorType::InternalServerError` error.
+///
+pub async fn upsert_notification(
+    pool: Pool<Postgres>,
+    recipient_id: Uuid,
+    notification_type: NotificationType,
+    actor_id: Uuid,
+    event_id: Uuid,
+    hess_id: Option<Uuid>,
+) -> Result<usize, ApiErrorType> {
+    let query_result = sqlx::query_scalar!(
+        r#"INSERT INTO notifications (
+            recipient_id,
+            notification_type,
+            hess_id,
+            group_key,
+            actor_ids,
+            event_ids
+        ) VALUES ($1, $2, $3, $4, ARRAY[$5::UUID], ARRAY[$6::UUID])
+        ON CONFLICT (recipient_id, group_key) WHERE read_at IS NULL DO UPDATE SET
+            actor_ids = CASE WHEN $6 = ANY(notifications.event_ids) THEN notifications.actor_ids
+                ELSE $5 || array_remove(notifications.actor_ids, $5) END,
+            event_ids = CASE WHEN $6 = ANY(notifications.event_ids) THEN notifications.event_ids
+                ELSE notifications.event_ids || $6 END,
+            occurred_at = CASE WHEN $6 = ANY(notifications.event_ids) THEN notifications.occurred_at
+                ELSE NOW() END
+        RETURNING cardinality(actor_ids) AS "actors_count!"
+        "#,
+        recipient_id,
+        notification_type.as_sql_str(),
+        hess_id,
+        notification_type.group_key(event_id, hess_id),
+        actor_id,
+        event_id
+    )
+    .fetch_one(&pool)
+    .await;
+
+    match query_result {
+        Ok(actors_count) => Ok(actors_count as usize),
+        Err(_) => Err(ApiErrorType::InternalServerError),
+    }
+}
+
+/// Retrieves a page of the notifications of a user, latest first (see `VISIBLE_NOTIFICATIONS_QUERY`).
+///
+/// # Arguments
+///
+/// * `pool` - A database connection pool.
+/// * `recipient_id` - The UUID of the notified user.
+/// * `cursor` - The position of the last notification of the previous page, `None` for the first page.
+/// * `size` - The maximum number of notifications in the page.
+///
+/// # Returns
+///
+/// Returns a `Result` containing the `NotificationsPage`, with a `next_cursor` if there may be more
+/// notifications.
+/// If any error occurs during the database queries, returns an `ApiErrorType::InternalServerError` error.
+///
+pub async fn get_notifications(
+    pool: Pool<Postgres>,
+    recipient_id: Uuid,
+    cursor: Option<NotificationCursor>,
+    size: i64,
+) -> Result<NotificationsPage, ApiErrorType> {
+    let query_string = format!(
+        r#"WITH visible AS ({VISIBLE_NOTIFICATIONS_QUERY})
+        SELECT * FROM visible
+        WHERE cardinality(actor_ids) > 0
+            AND ($2::TIMESTAMPTZ IS NULL OR (occurred_at, id) < ($2, $3))
+        ORDER BY occurred_at DESC, id DESC
+        LIMIT $4
+        "#
+    );
+
+    let mut rows = sqlx::query_as::<_, NotificationRow>(&query_string)
+        .bind(recipient_id)
+        .bind(cursor.as_ref().map(|cursor| cursor.occurred_at))
+        .bind(cursor.as_ref().map(|cursor| cursor.id))
+        .bind(size + 1)
+        .fetch_all(&pool)
+        .await
+        .map_err(|_| ApiErrorType::InternalServerError)?;
+
+    let has_more = rows.len() as i64 > size;
+    rows.truncate(size as usize);
+
+    let listed_actor_ids = rows
+        .iter()
+        .flat_map(|row| row.actor_ids.iter().take(NOTIFICATION_MAX_LISTED_ACTORS))
+        .copied()
+        .collect::<Vec<_>>();
+
+    let actors = sqlx::query_as!(
+        NotificationActor,
+        "SELECT id, username, name, verified FROM users WHERE id = ANY($1)",
+        &listed_actor_ids
+    )
+    .fetch_all(&pool)
+    .await
+    .map_err(|_| ApiErrorType::InternalServerError)?
+    .into_iter()
+    .map(|actor| (actor.id, actor))
+    .collect::<HashMap<_, _>>();
+
+    let items = rows
+        .into_iter()
+        .filter_map(|row| {
+            let notification_type = NotificationType::from_sql_str(&row.notification_type)?;
+            let actors = row
+                .actor_ids
+                .iter()
+                .take(NOTIFICATION_MAX_LISTED_ACTORS)
+                .filter_map(|id| actors.get(id).cloned())
+                .collect::<Vec<_>>();
+            let actors_count = row.actor_ids.len();
+
+            Some(Notification {
+                id: row.id,
+                notification_type,
+                hess_id: row.hess_id,
+                summary: notification_type
+                    .summary(&actors.first()?.username, actors_count.saturating_sub(1)),
+                actors,
+                actors_count,
+                occurred_at: row.occurred_at,
+                read_at: row.read_at,
+            })
+        })
+        .collect::<Vec<_>>();
+
+    let next_cursor = match items.last() {
+        Some(last) if has_more => Some(encode_cursor(&NotificationCursor {
+            occurred_at: last.occurred_at,
+            id: last.id,
+        })),
+        _ => None,
+    };
+
+    Ok(NotificationsPage { items, next_cursor })
+}
+
+/// Counts the unread notifications of a user (as shown to them, see `VISIBLE_NOTIFICATIONS_QUERY`).
+///
+/// # Arguments
+///
+/// * `pool` - A database connection pool.
+/// * `recipient_id` - The UUID of the notified user.
+///
+/// # Returns
+///
+/// Returns a `Result` containing the number of unread notifications if successful.
+/// If any error occurs during the database query, returns an `ApiErrorType::InternalServerError` error.
+///
+pub async fn count_unread_notifications(
+    pool: Pool<Postgres>,
+    recipient_id: Uuid,
+) -> Result<i64, ApiErrorType> {
+    let query_string = format!(
+        r#"WITH visible AS ({VISIBLE_NOTIFICATIONS_QUERY})
+        SELECT COUNT(*) FROM visible WHERE read_at IS NULL AND cardinality(actor_ids) > 0
+        "#
+    );
+
+    sqlx::query_scalar::<_, i64>(&query_string)
+        .bind(recipient_id)
+        .fetch_one(&pool)
+        .await
+        .map_err(|_| ApiErrorType::InternalServerError)
+}
+
+/// Marks a notification of a user as read, new events of its group start a new notification.
+///
+/// # Arguments
+///
+/// * `pool` - A database connection pool.
+/// * `recipient_id` - The UUID of the notified user.
+/// * `id` - The UUID of the notification.
+///
+/// # Returns
+///
+/// Returns a `Result<(), ApiErrorType>` indicating success or an `ApiErrorType::InternalServerError` error
+/// if any error occurs during database update.
+/// If the user has no such notification, returns an `ApiErrorType::ResourceNotFound` error.
+///
+pub async fn mark_notification_as_read(
+    pool: Pool<Postgres>,
+    recipient_id: Uuid,
+    id: Uuid,
+) -> Result<(), ApiErrorType> {
+    let query_result = sqlx::query!(
+        "UPDATE notifications SET read_at = COALESCE(read_at, NOW())
+        WHERE id = $1 AND recipient_id = $2",
+        id,
+        recipient_id
+    )
+    .execute(&pool)
+    .await;
+
+    match query_result {
+        Ok(result) => {
+            if result.rows_affected() == 0 {
+                Err(ApiErrorType::ResourceNotFound(ApiResource::Notifications))
+            } else {
+                Ok(())
+            }
+        }
+        Err(_) => Err(ApiErrorType::InternalServerError),
+    }
+}
+
+/// Marks all the notifications of a user as read.
+///
+/// # Arguments
+///
+/// * `pool` - A database connection pool.
+/// * `recipient_id` - The UUID of the notified user.
+///
+/// # Returns
+///
+/// Returns a `Result<(), ApiErrorType>` indicating success or an `ApiErrorType::InternalServerError` error
+/// if any error occurs during database update.
+///
+pub async fn mark_all_notifications_as_read(
+    pool: Pool<Postgres>,
+    recipient_id: Uuid,
+) -> Result<(), ApiErrorType> {
+    sqlx::query!(
+        "UPDATE notifications SET read_at = NOW() WHERE recipient_id = $1 AND read_at IS NULL",
+        recipient_id
+    )
+    .execute(&pool)
+    .await
+    .map(|_| ())
+    .map_err(|_| ApiErrorType::InternalServerError)
+}
@@ -0,0 +1,568 @@
+use sqlx::{Pool, Postgres};
+use uuid::Uuid;
+
+use crate::{
+    models::{HessSearchResult, UserSearchResult, UserSuggestion},
+    policies::{policy_sql_predicate, PolicyAction},
+    search::{HessSearchQuery, UserSearchQuery},
+    utils::response::ApiErrorType,
+};
+#[cfg(feature = "tantivy-search")]
+use crate::models::{Hess, HessIndexDocument, UserIndexDocument};
+
+/// Searches the hesses visible to a viewer.
+///
+/// Results are ordered by relevance (`ts_rank_cd`) then by descending creation date.
+///
+/// # Arguments
+///
+/// * `pool` - A database connection pool.
+/// * `viewer_id` - The UUID of the user searching.
+/// * `query` - The parsed search query.
+/// * `page` - The page number, starting from 1.
+/// * `size` - The maximum number of results in the page.
+///
+/// # Returns
+///
+/// Returns a `Result` containing the matching hesses of the page.
+/// If any error occurs during the database query, returns an `ApiErrorType::InternalServerError` error.
+///
+pub async fn search_hesses(
+    pool: Pool<Postgres>,
+    viewer_id: Uuid,
+    query: &HessSearchQuery,
+    page: i64,
+    size: i64,
+) -> Result<Vec<HessSearchResult>, ApiErrorType> {
+    let websearch = query.to_websearch();
+    let has_text = !websearch.is_empty();
+
+    // The full-text condition is left out when there's only filters, so the GIN index
+    // is used whenever there's something to match
+    let (rank, snippet, text_condition) = if has_text {
+        (
+            "ts_rank_cd(h.search_vector, s.query)",
+            "ts_headline('english', COALESCE(h.content, ''), s.query, \
+            'StartSel=<mark>, StopSel=</mark>, MaxFragments=2, MaxWords=30, MinWords=10')",
+            "AND h.search_vector @@ s.query",
+        )
+    } else {
+        ("0::REAL", "NULL::TEXT", "")
+    };
+
+    let can_view_hess = policy_sql_predicate(PolicyAction::View, "$1", "h.user_id", Some("h.id"));
+
+    let query_string = format!(
+        r#"WITH search AS (
+            SELECT websearch_to_tsquery('english', $2) AS query
+        )
+        SELECT
+            {rank} AS rank,
+            {snippet} AS snippet,
+            h.id,
+            h.user_id,
+            h.content,
+            h.parent_hess_id,
+            h.who_can_reply,
+            h.who_can_like,
+            h.who_can_watch_replies,
+            h.who_can_watch_likes,
+            h.created_at,
+            h.updated_at,
+            h.deleted_at
+        FROM hesses h
+        CROSS JOIN search s
+        WHERE h.deleted_at IS NULL
+            {text_condition}
+            AND (
+                $3::TEXT IS NULL
+                OR h.user_id = (
+                    SELECT u.id FROM users u
+                    WHERE LOWER(u.username) = LOWER($3) AND u.deleted_at IS NULL
+                )
+            )
+            AND NOT EXISTS (
+                SELECT 1 FROM unnest($4::TEXT[]) AS tag
+                WHERE h.content IS NULL
+                    OR h.content !~* ('(^|[^[:alnum:]_])#' || tag || '([^[:alnum:]_]|$)')
+            )
+            AND ($5::TIMESTAMPTZ IS NULL OR h.created_at >= $5)
+            AND ($6::TIMESTAMPTZ IS NULL OR h.created_at < $6)
+            AND (
+                NOT $7
+                OR EXISTS (
+                    SELECT 1 FROM hess_media m
+                    WHERE m.hess_id = h.id AND m.deleted_at IS NULL AND m.processing_status = 'READY'
+                )
+            )
+            AND {can_view_hess}
+        ORDER BY rank DESC, h.created_at DESC, h.id DESC
+        LIMIT $8 OFFSET $9
+        "#
+    );
+
+    let query_result = sqlx::query_as::<_, HessSearchResult>(query_string.as_str())
+        .bind(viewer_id)
+        .bind(websearch)
+        .bind(query.from_username.as_ref())
+        .bind(&query.hashtags)
+        .bind(query.since_timestamp())
+        .bind(query.until_timestamp())
+        .bind(query.has_media)
+        .bind(size)
+        .bind((page - 1) * size)
+        .fetch_all(&pool)
+        .await;
+
+    match query_result {
+        Ok(results) => Ok(results),
+        Err(_) => Err(ApiErrorType::InternalServerError),
+    }
+}
+
+/// Searches users by username and name, with prefix and trigram (typo tolerant) matching.
+///
+/// Soft-deleted and unactivated users are left out, as well as users blocking the searching
+/// user (or blocked by them). Prefix matches, verified users and users followed by the
+/// searching user are boosted.
+///
+/// # Arguments
+///
+/// * `pool` - A database connection pool.
+/// * `viewer_id` - The UUID of the user searching.
+/// * `query` - The normalized search query.
+/// * `page` - The page number, starting from 1.
+/// * `size` - The maximum number of results in the page.
+///
+/// # Returns
+///
+/// Returns a `Result` containing the matching users of the page, best matches first.
+/// If any error occurs during the database query, returns an `ApiErrorType::InternalServerError` error.
+///
+pub async fn search_users(
+    pool: Pool<Postgres>,
+    viewer_id: Uuid,
+    query: &UserSearchQuery,
+    page: i64,
+    size: i64,
+) -> Result<Vec<UserSearchResult>, ApiErrorType> {
+    let query_result = sqlx::query_as!(
+        UserSearchResult,
+        r#"WITH matches AS (
+            SELECT
+                u.id,
+                u.name,
+                u.username,
+                u.bio,
+                u.user_profile_image_id,
+                u.verified,
+                EXISTS (
+                    SELECT 1 FROM followers f
+                    WHERE f.follower_id = $1 AND f.followed_id = u.id AND f.deleted_at IS NULL
+                ) AS followed,
+                GREATEST(
+                    similarity(LOWER(u.username), $2),
+                    word_similarity($2, LOWER(u.name))
+                ) AS similarity,
+                CASE
+                    WHEN LOWER(u.username) LIKE $3 THEN 1.0
+                    WHEN LOWER(u.name) LIKE $3 OR LOWER(u.name) LIKE '% ' || $3 THEN 0.8
+                    ELSE 0.0
+                END AS prefix_boost
+            FROM users u
+            WHERE u.deleted_at IS NULL
+                AND u.activated
+                AND (
+                    LOWER(u.username) LIKE $3
+                    OR LOWER(u.name) LIKE $3
+                    OR LOWER(u.name) LIKE '% ' || $3
+                    OR LOWER(u.username) % $2
+                    OR $2 <% LOWER(u.name)
+                )
+                AND NOT EXISTS (
+                    SELECT 1 FROM blocked_users b
+                    WHERE b.deleted_at IS NULL AND (
+                        (b.blocker_id = u.id AND b.blocked_id = $1)
+                        OR (b.blocker_id = $1 AND b.blocked_id = u.id)
+                    )
+                )
+        )
+        SELECT
+            m.id,
+            m.name,
+            m.username,
+            m.bio,
+            m.user_profile_image_id,
+            m.verified,
+            m.followed AS "followed!",
+            (
+                m.similarity
+                + m.prefix_boost
+                + CASE WHEN m.verified THEN 0.3 ELSE 0.0 END
+                + CASE WHEN m.followed THEN 0.5 ELSE 0.0 END
+            )::REAL AS "score!"
+        FROM matches m
+        ORDER BY 8 DESC, LENGTH(m.username), m.username
+        LIMIT $4 OFFSET $5
+        "#,
+        viewer_id,
+        query.text,
+        query.like_prefix_pattern(),
+        size,
+        (page - 1) * size
+    )
+    .fetch_all(&pool)
+    .await;
+
+    match query_result {
+        Ok(results) => Ok(results),
+        Err(_) => Err(ApiErrorType::InternalServerError),
+    }
+}
+
+/// Suggests users to mention, whose username or name (or one of its words) starts with the query.
+///
+/// Only users the searching user is allowed to mention are suggested (besides the exclusions
+/// of `search_users`), followed then verified users first.
+///
+/// # Arguments
+///
+/// * `pool` - A database connection pool.
+/// * `viewer_id` - The UUID of the user typing.
+/// * `query` - The normalized search query (what's typed after the `@`).
+/// * `size` - The maximum number of suggestions.
+///
+/// # Returns
+///
+/// Returns a `Result` containing the suggested users.
+/// If any error occurs during the database query, returns an `ApiErrorType::InternalServerError` error.
+///
+pub async fn get_user_suggestions(
+    pool: Pool<Postgres>,
+    viewer_id: Uuid,
+    query: &UserSearchQuery,
+    size: i64,
+) -> Result<Vec<UserSuggestion>, ApiErrorType> {
+    let can_mention = policy_sql_predicate(PolicyAction::Mention, "$1", "u.id", None);
+
+    let query_string = format!(
+        r#"SELECT u.id, u.name, u.username, u.user_profile_image_id, u.verified
+        FROM users u
+        WHERE u.deleted_at IS NULL
+            AND u.activated
+            AND u.id <> $1
+            AND (
+                LOWER(u.username) LIKE $2
+                OR LOWER(u.name) LIKE $2
+                OR LOWER(u.name) LIKE '% ' || $2
+            )
+            AND {can_mention}
+        ORDER BY
+            EXISTS (
+                SELECT 1 FROM followers f
+                WHERE f.follower_id = $1 AND f.followed_id = u.id AND f.deleted_at IS NULL
+            ) DESC,
+            u.verified DESC,
+            LENGTH(u.username),
+            u.username
+        LIMIT $3
+        "#
+    );
+
+    let query_result = sqlx::query_as::<_, UserSuggestion>(query_string.as_str())
+        .bind(viewer_id)
+        .bind(query.like_prefix_pattern())
+        .bind(size)
+        .fetch_all(&pool)
+        .await;
+
+    match query_result {
+        Ok(suggestions) => Ok(suggestions),
+        Err(_) => Err(ApiErrorType::InternalServerError),
+    }
+}
+
+/// Keeps the hesses (found by an external search index) matching the filters of a query
+/// and visible to a viewer, in the given order.
+///
+/// # Arguments
+///
+/// * `pool` - A database connection pool.
+/// * `viewer_id` - The UUID of the user searching.
+/// * `query` - The parsed search query, only its filters (not the full-text part) are applied.
+/// * `hess_ids` - The UUIDs of the candidate hesses, best matches first.
+///
+/// # Returns
+///
+/// Returns a `Result` containing the kept hesses, in the order of `hess_ids`.
+/// If any error occurs during the database query, returns an `ApiErrorType::InternalServerError` error.
+///
+#[cfg(feature = "tantivy-search")]
+pub async fn filter_hess_search_candidates(
+    pool: Pool<Postgres>,
+    viewer_id: Uuid,
+    query: &HessSearchQuery,
+    hess_ids: &[Uuid],
+) -> Result<Vec<Hess>, ApiErrorType> {
+    let can_view_hess = policy_sql_predicate(PolicyAction::View, "$1", "h.user_id", Some("h.id"));
+
+    let query_string = format!(
+        r#"SELECT
+            h.id,
+            h.user_id,
+            h.content,
+            h.parent_hess_id,
+            h.who_can_reply,
+            h.who_can_like,
+            h.who_can_watch_replies,
+            h.who_can_watch_likes,
+            h.created_at,
+            h.updated_at,
+            h.deleted_at
+        FROM unnest($2::UUID[]) WITH ORDINALITY AS c (id, position)
+        JOIN hesses h ON h.id = c.id
+        WHERE h.deleted_at IS NULL
+            AND (
+                $3::TEXT IS NULL
+                OR h.user_id = (
+                    SELECT u.id FROM users u
+                    WHERE LOWER(u.username) = LOWER($3) AND u.deleted_at IS NULL
+                )
+            )
+            AND NOT EXISTS (
+                SELECT 1 FROM unnest($4::TEXT[]) AS tag
+                WHERE h.content IS NULL
+                    OR h.content !~* ('(^|[^[:alnum:]_])#' || tag || '([^[:alnum:]_]|$)')
+            )
+            AND ($5::TIMESTAMPTZ IS NULL OR h.created_at >= $5)
+            AND ($6::TIMESTAMPTZ IS NULL OR h.created_at < $6)
+            AND (
+                NOT $7
+                OR EXISTS (
+                    SELECT 1 FROM hess_media m
+                    WHERE m.hess_id = h.id AND m.deleted_at IS NULL AND m.processing_status = 'READY'
+                )
+            )
+            AND {can_view_hess}
+        ORDER BY c.position
+        "#
+    );
+
+    let query_result = sqlx::query_as::<_, Hess>(query_string.as_str())
+        .bind(viewer_id)
+        .bind(hess_ids)
+        .bind(query.from_username.as_ref())
+        .bind(&query.hashtags)
+        .bind(query.since_timestamp())
+        .bind(query.until_timestamp())
+        .bind(query.has_media)
+        .fetch_all(&pool)
+        .await;
+
+    match query_result {
+        Ok(hesses) => Ok(hesses),
+        Err(_) => Err(ApiErrorType::InternalServerError),
+    }
+}
+
+/// Keeps the users (found by an external search index) a viewer can find, in the given order.
+///
+/// The exclusions are the same as `search_users`, the scores of the results are left to 0.
+///
+/// # Arguments
+///
+/// * `pool` - A database connection pool.
+/// * `viewer_id` - The UUID of the user searching.
+/// * `user_ids` - The UUIDs of the candidate users, best matches first.
+///
+/// # Returns
+///
+/// Returns a `Result` containing the kept users, in the order of `user_ids`.
+/// If any error occurs during the database query, returns an `ApiErrorType::InternalServerError` error.
+///
+#[cfg(feature = "tantivy-search")]
+pub async fn filter_user_search_candidates(
+    pool: Pool<Postgres>,
+    viewer_id: Uuid,
+    user_ids: &[Uuid],
+) -> Result<Vec<UserSearchResult>, ApiErrorType> {
+    let query_result = sqlx::query_as!(
+        UserSearchResult,
+        r#"SELECT
+            u.id,
+            u.name,
+            u.username,
+            u.bio,
+            u.user_profile_image_id,
+            u.verified,
+            EXISTS (
+                SELECT 1 FROM followers f
+                WHERE f.follower_id = $1 AND f.followed_id = u.id AND f.deleted_at IS NULL
+            ) AS "followed!",
+            0::REAL AS "score!"
+        FROM unnest($2::UUID[]) WITH ORDINALITY AS c (id, position)
+        JOIN users u ON u.id = c.id
+        WHERE u.deleted_at IS NULL
+            AND u.activated
+            AND NOT EXISTS (
+                SELECT 1 FROM blocked_users b
+                WHERE b.deleted_at IS NULL AND (
+                    (b.blocker_id = u.id AND b.blocked_id = $1)
+                    OR (b.blocker_id = $1 AND b.blocked_id = u.id)
+                )
+            )
+        ORDER BY c.position
+        "#,
+        viewer_id,
+        user_ids
+    )
+    .fetch_all(&pool)
+    .await;
+
+    match query_result {
+        Ok(results) => Ok(results),
+        Err(_) => Err(ApiErrorType::InternalServerError),
+    }
+}
+
+/// Gets the searchable part of a hess, to be (re)indexed by an external search index.
+///
+/// # Arguments
+///
+/// * `pool` - A database connection pool.
+/// * `hess_id` - The UUID of the hess.
+///
+/// # Returns
+///
+/// Returns a `Result` containing the document, or `None` if the hess doesn't exist
+/// or is soft-deleted (and should be removed from the index).
+/// If any error occurs during the database query, returns an `ApiErrorType::InternalServerError` error.
+///
+#[cfg(feature = "tantivy-search")]
+pub async fn get_hess_index_document(
+    pool: Pool<Postgres>,
+    hess_id: Uuid,
+) -> Result<Option<HessIndexDocument>, ApiErrorType> {
+    let query_result = sqlx::query_as!(
+        HessIndexDocument,
+        "SELECT id, content FROM hesses WHERE id = $1 AND deleted_at IS NULL",
+        hess_id
+    )
+    .fetch_optional(&pool)
+    .await;
+
+    match query_result {
+        Ok(document) => Ok(document),
+        Err(_) => Err(ApiErrorType::InternalServerError),
+    }
+}
+
+/// Gets a page of the searchable hesses, ordered by id, to rebuild an external search index.
+///
+/// # Arguments
+///
+/// * `pool` - A database connection pool.
+/// * `after_id` - The id of the last hess of the previous page, `None` for the first page.
+/// * `size` - The maximum number of hesses in the page.
+///
+/// # Returns
+///
+/// Returns a `Result` containing the documents of the page.
+/// If any error occurs during the database query, returns an `ApiErrorType::InternalServerError` error.
+///
+#[cfg(feature = "tantivy-search")]
+pub async fn get_hess_index_documents(
+    pool: Pool<Postgres>,
+    after_id: Option<Uuid>,
+    size: i64,
+) -> Result<Vec<HessIndexDocument>, ApiErrorType> {
+    let query_result = sqlx::query_as!(
+        HessIndexDocument,
+        r#"SELECT id, content FROM hesses
+        WHERE deleted_at IS NULL AND ($1::UUID IS NULL OR id > $1)
+        ORDER BY id
+        LIMIT $2
+        "#,
+        after_id,
+        size
+    )
+    .fetch_all(&pool)
+    .await;
+
+    match query_result {
+        Ok(documents) => Ok(documents),
+        Err(_) => Err(ApiErrorType::InternalServerError),
+    }
+}
+
+/// Gets the searchable part of a user, to be (re)indexed by an external search index.
+///
+/// # Arguments
+///
+/// * `pool` - A database connection pool.
+/// * `user_id` - The UUID of the user.
+///
+/// # Returns
+///
+/// Returns a `Result` containing the document, or `None` if the user doesn't exist, is soft-deleted
+/// or isn't activated (and should be removed from the index).
+/// If any error occurs during the database query, returns an `ApiErrorType::InternalServerError` error.
+///
+#[cfg(feature = "tantivy-search")]
+pub async fn get_user_index_document(
+    pool: Pool<Postgres>,
+    user_id: Uuid,
+) -> Result<Option<UserIndexDocument>, ApiErrorType> {
+    let query_result = sqlx::query_as!(
+        UserIndexDocument,
+        r#"SELECT id, username, name FROM users
+        WHERE id = $1 AND deleted_at IS NULL AND activated
+        "#,
+        user_id
+    )
+    .fetch_optional(&pool)
+    .await;
+
+    match query_result {
+        Ok(document) => Ok(document),
+        Err(_) => Err(ApiErrorType::InternalServerError),
+    }
+}
+
+/// Gets a page of the searchable users, ordered by id, to rebuild an external search index.
+///
+/// # Arguments
+///
+/// * `pool` - A database connection pool.
+/// * `after_id` - The id of the last user of the previous page, `None` for the first page.
+/// * `size` - The maximum number of users in the page.
+///
+/// # Returns
+///
+/// Returns a `Result` containing the documents of the page.
+/// If any error occurs during the database query, returns an `ApiErrorType::InternalServerError` error.
+///
+#[cfg(feature = "tantivy-search")]
+pub async fn get_user_index_documents(
+    pool: Pool<Postgres>,
+    after_id: Option<Uuid>,
+    size: i64,
+) -> Result<Vec<UserIndexDocument>, ApiErrorType> {
+    let query_result = sqlx::query_as!(
+        UserIndexDocument,
+        r#"SELECT id, username, name FROM users
+        WHERE deleted_at IS NULL AND activated AND ($1::UUID IS NULL OR id > $1)
+        ORDER BY id
+        LIMIT $2
+        "#,
+        after_id,
+        size
+    )
+    .fetch_all(&pool)
+    .await;
+
+    match query_result {
+        Ok(documents) => Ok(documents),
+        Err(_) => Err(ApiErrorType::InternalServerError),
+    }
+}
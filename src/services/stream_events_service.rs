@@ -0,0 +1,215 @@
+use chrono::{DateTime, Utc};
+use sqlx::{Pool, Postgres};
+use uuid::Uuid;
+
+use crate::{models::StreamEvent, utils::response::ApiErrorType};
+
+// Stream events are published by the database (see `publish_stream_event` in the migrations),
+// either from triggers or from the functions below, and delivered by `src/stream`.
+
+fn map_query_result(
+    query_result: Result<sqlx::postgres::PgQueryResult, sqlx::Error>,
+) -> Result<u64, ApiErrorType> {
+    match query_result {
+        Ok(result) => Ok(result.rows_affected()),
+        Err(_) => Err(ApiErrorType::InternalServerError),
+    }
+}
+
+/// Retrieves the stream events published after a given one, oldest first.
+///
+/// # Arguments
+///
+/// * `pool` - A database connection pool.
+/// * `user_id` - The UUID of the user the events were sent to, `None` for every user.
+/// * `after_id` - The ID of the last event already delivered.
+/// * `limit` - The maximum number of events retrieved.
+///
+/// # Returns
+///
+/// Returns a `Result` containing the retrieved events if successful.
+/// If any error occurs during the database query, returns an `ApiErrorType::InternalServerError` error.
+///
+pub async fn get_stream_events_after(
+    pool: Pool<Postgres>,
+    user_id: Option<Uuid>,
+    after_id: i64,
+    limit: i64,
+) -> Result<Vec<StreamEvent>, ApiErrorType> {
+    sqlx::query_as!(
+        StreamEvent,
+        r#"SELECT id, user_id, event_type, payload, created_at
+        FROM stream_events
+        WHERE id > $2 AND ($1::UUID IS NULL OR user_id = $1)
+        ORDER BY id
+        LIMIT $3
+        "#,
+        user_id,
+        after_id,
+        limit
+    )
+    .fetch_all(&pool)
+    .await
+    .map_err(|_| ApiErrorType::InternalServerError)
+}
+
+/// Retrieves the ID of the latest stream event of a user.
+///
+/// # Arguments
+///
+/// * `pool` - A database connection pool.
+/// * `user_id` - The UUID of the user the events were sent to.
+///
+/// # Returns
+///
+/// Returns a `Result` containing the ID of the latest event, or 0 if the user has none.
+/// If any error occurs during the database query, returns an `ApiErrorType::InternalServerError` error.
+///
+pub async fn get_latest_stream_event_id(
+    pool: Pool<Postgres>,
+    user_id: Uuid,
+) -> Result<i64, ApiErrorType> {
+    sqlx::query_scalar!(
+        r#"SELECT COALESCE(MAX(id), 0) AS "id!" FROM stream_events WHERE user_id = $1"#,
+        user_id
+    )
+    .fetch_one(&pool)
+    .await
+    .map_err(|_| ApiErrorType::InternalServerError)
+}
+
+/// Deletes the stream events published before a given time, they can't be resumed anymore.
+///
+/// # Arguments
+///
+/// * `pool` - A database connection pool.
+/// * `before` - The publication time before which the events are deleted.
+///
+/// # Returns
+///
+/// Returns a `Result` containing the number of deleted events if successful.
+/// If any error occurs during the database query, returns an `ApiErrorType::InternalServerError` error.
+///
+pub async fn delete_stream_events_before(
+    pool: Pool<Postgres>,
+    before: DateTime<Utc>,
+) -> Result<u64, ApiErrorType> {
+    let query_result = sqlx::query!("DELETE FROM stream_events WHERE created_at < $1", before)
+        .execute(&pool)
+        .await;
+
+    map_query_result(query_result)
+}
+
+/// Hints the followers of the author of a new hess (or reply) watching it that their feed has
+/// new items.
+///
+/// # Arguments
+///
+/// * `pool` - A database connection pool.
+/// * `hess_id` - The UUID of the created hess.
+///
+/// # Returns
+///
+/// Returns a `Result` containing the number of hinted followers if successful.
+/// If any error occurs during the database query, returns an `ApiErrorType::InternalServerError` error.
+///
+pub async fn publish_hess_feed_item_hints(
+    pool: Pool<Postgres>,
+    hess_id: Uuid,
+) -> Result<u64, ApiErrorType> {
+    let query_result = sqlx::query!(
+        r#"SELECT publish_stream_event(
+            f.follower_id,
+            'FEED_ITEM',
+            jsonb_build_object(
+                'itemType',
+                CASE WHEN h.parent_hess_id IS NULL THEN 'NEW_HESS' ELSE 'REPLY' END
+            )
+        )
+        FROM hesses h
+        JOIN followers f ON f.followed_id = h.user_id AND f.deleted_at IS NULL
+        WHERE h.id = $1
+            AND h.deleted_at IS NULL
+            AND CASE WHEN h.parent_hess_id IS NULL THEN f.watch_new_hesses ELSE f.watch_replies END
+        "#,
+        hess_id
+    )
+    .execute(&pool)
+    .await;
+
+    map_query_result(query_result)
+}
+
+/// Hints the followers of a liker watching their likes that their feed has new items.
+///
+/// # Arguments
+///
+/// * `pool` - A database connection pool.
+/// * `like_id` - The UUID of the created like.
+///
+/// # Returns
+///
+/// Returns a `Result` containing the number of hinted followers if successful.
+/// If any error occurs during the database query, returns an `ApiErrorType::InternalServerError` error.
+///
+pub async fn publish_like_feed_item_hints(
+    pool: Pool<Postgres>,
+    like_id: Uuid,
+) -> Result<u64, ApiErrorType> {
+    let query_result = sqlx::query!(
+        r#"SELECT publish_stream_event(
+            f.follower_id,
+            'FEED_ITEM',
+            jsonb_build_object('itemType', 'LIKE')
+        )
+        FROM likes l
+        JOIN followers f ON f.followed_id = l.user_id AND f.deleted_at IS NULL
+        WHERE l.id = $1
+            AND l.deleted_at IS NULL
+            AND f.watch_likes
+        "#,
+        like_id
+    )
+    .execute(&pool)
+    .await;
+
+    map_query_result(query_result)
+}
+
+/// Hints the followers of a new follower watching their follows that their feed has new items.
+///
+/// # Arguments
+///
+/// * `pool` - A database connection pool.
+/// * `follower_row_id` - The UUID of the created followers row.
+///
+/// # Returns
+///
+/// Returns a `Result` containing the number of hinted followers if successful.
+/// If any error occurs during the database query, returns an `ApiErrorType::InternalServerError` error.
+///
+pub async fn publish_follow_feed_item_hints(
+    pool: Pool<Postgres>,
+    follower_row_id: Uuid,
+) -> Result<u64, ApiErrorType> {
+    let query_result = sqlx::query!(
+        r#"SELECT publish_stream_event(
+            f.follower_id,
+            'FEED_ITEM',
+            jsonb_build_object('itemType', 'FOLLOW')
+        )
+        FROM followers fo
+        JOIN followers f ON f.followed_id = fo.follower_id AND f.deleted_at IS NULL
+        WHERE fo.id = $1
+            AND fo.deleted_at IS NULL
+            AND f.watch_follows
+            AND f.follower_id <> fo.followed_id
+        "#,
+        follower_row_id
+    )
+    .execute(&pool)
+    .await;
+
+    map_query_result(query_result)
+}
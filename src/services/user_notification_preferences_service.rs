@@ -0,0 +1,319 @@
+use chrono::{DateTime, Duration, NaiveTime, Utc};
+use sqlx::{Pool, Postgres};
+use uuid::Uuid;
+
+use crate::{
+    models::{DigestFrequency, NotificationChannels, QuietHours, UserNotificationPreferences},
+    models_validators::user_notification_preferences_validator::UpdateUserNotificationPreferencesData,
+    utils::response::{ApiErrorType, ApiResource},
+};
+
+struct UserNotificationPreferencesRow {
+    id: Uuid,
+    user_id: Uuid,
+    like_in_app: bool,
+    like_push: bool,
+    like_email: bool,
+    reply_in_app: bool,
+    reply_push: bool,
+    reply_email: bool,
+    mention_in_app: bool,
+    mention_push: bool,
+    mention_email: bool,
+    follow_in_app: bool,
+    follow_push: bool,
+    follow_email: bool,
+    follow_request_in_app: bool,
+    follow_request_push: bool,
+    follow_request_email: bool,
+    quiet_hours_start: Option<NaiveTime>,
+    quiet_hours_end: Option<NaiveTime>,
+    timezone: String,
+    digest_frequency: DigestFrequency,
+    updated_at: DateTime<Utc>,
+}
+
+impl From<UserNotificationPreferencesRow> for UserNotificationPreferences {
+    fn from(row: UserNotificationPreferencesRow) -> Self {
+        UserNotificationPreferences {
+            id: row.id,
+            user_id: row.user_id,
+            like: NotificationChannels {
+                in_app: row.like_in_app,
+                push: row.like_push,
+                email: row.like_email,
+            },
+            reply: NotificationChannels {
+                in_app: row.reply_in_app,
+                push: row.reply_push,
+                email: row.reply_email,
+            },
+            mention: NotificationChannels {
+                in_app: row.mention_in_app,
+                push: row.mention_push,
+                email: row.mention_email,
+            },
+            follow: NotificationChannels {
+                in_app: row.follow_in_app,
+                push: row.follow_push,
+                email: row.follow_email,
+            },
+            follow_request: NotificationChannels {
+                in_app: row.follow_request_in_app,
+                push: row.follow_request_push,
+                email: row.follow_request_email,
+            },
+            quiet_hours: match (row.quiet_hours_start, row.quiet_hours_end) {
+                (Some(start), Some(end)) => Some(QuietHours { start, end }),
+                _ => None,
+            },
+            timezone: row.timezone,
+            digest_frequency: row.digest_frequency,
+            updated_at: row.updated_at,
+        }
+    }
+}
+
+/// Retrieves the notification preferences of a user from the database.
+///
+/// # Arguments
+///
+/// * `pool` - A database connection pool.
+/// * `user_id` - The UUID of the user owning the notification preferences.
+///
+/// # Returns
+///
+/// Returns a `Result` containing the retrieved `UserNotificationPreferences` if successful.
+/// If no notification preferences are found, returns an `ApiErrorType::ResourceNotFound` error.
+/// If any other error occurs during the database query, returns an `ApiErrorType::InternalServerError` error.
+///
+pub async fn get_user_notification_preferences_by_user_id(
+    pool: Pool<Postgres>,
+    user_id: Uuid,
+) -> Result<UserNotificationPreferences, ApiErrorType> {
+    let query_result = sqlx::query_as!(
+        UserNotificationPreferencesRow,
+        r#"SELECT
+            id,
+            user_id,
+            like_in_app,
+            like_push,
+            like_email,
+            reply_in_app,
+            reply_push,
+            reply_email,
+            mention_in_app,
+            mention_push,
+            mention_email,
+            follow_in_app,
+            follow_push,
+            follow_email,
+            follow_request_in_app,
+            follow_request_push,
+            follow_request_email,
+            quiet_hours_start,
+            quiet_hours_end,
+            timezone,
+            digest_frequency AS "digest_frequency: DigestFrequency",
+            updated_at
+        FROM user_notification_preferences WHERE user_id = $1
+        "#,
+        user_id
+    )
+    .fetch_optional(&pool)
+    .await;
+
+    match query_result {
+        Ok(Some(row)) => Ok(row.into()),
+        Ok(None) => Err(ApiErrorType::ResourceNotFound(
+            ApiResource::UserNotificationPreferences,
+        )),
+        Err(_) => Err(ApiErrorType::InternalServerError),
+    }
+}
+
+/// Updates the notification preferences of a user in the database.
+///
+/// Only the fields present in `update_data` are updated (down to each channel of an event),
+/// quiet hours explicitly set to null are removed.
+///
+/// # Arguments
+///
+/// * `pool` - A database connection pool.
+/// * `user_id` - The UUID of the user owning the notification preferences.
+/// * `update_data` - Data containing the fields to update.
+///
+/// # Returns
+///
+/// Returns a `Result` containing the updated `UserNotificationPreferences` if successful.
+/// If no notification preferences are found for the user, returns an `ApiErrorType::ResourceNotFound` error.
+/// If any other error occurs during the database update, returns an `ApiErrorType::InternalServerError` error.
+///
+pub async fn update_user_notification_preferences(
+    pool: Pool<Postgres>,
+    user_id: Uuid,
+    update_data: UpdateUserNotificationPreferencesData,
+) -> Result<UserNotificationPreferences, ApiErrorType> {
+    let mut channel_fields = vec![];
+
+    for (event, channels) in [
+        ("like", &update_data.like),
+        ("reply", &update_data.reply),
+        ("mention", &update_data.mention),
+        ("follow", &update_data.follow),
+        ("follow_request", &update_data.follow_request),
+    ] {
+        if let Some(channels) = channels {
+            for (channel, value) in [
+                ("in_app", channels.in_app),
+                ("push", channels.push),
+                ("email", channels.email),
+            ] {
+                if let Some(value) = value {
+                    channel_fields.push((format!("{}_{}", event, channel), value));
+                }
+            }
+        }
+    }
+
+    let mut update_set = vec![];
+    let mut counter = 1;
+
+    for (column, _) in &channel_fields {
+        update_set.push(format!("{} = ${}", column, counter));
+        counter += 1;
+    }
+
+    if update_data.quiet_hours.is_some() {
+        update_set.push(format!(
+            "quiet_hours_start = ${}, quiet_hours_end = ${}",
+            counter,
+            counter + 1
+        ));
+        counter += 2;
+    }
+
+    if update_data.timezone.is_some() {
+        update_set.push(format!("timezone = ${}", counter));
+        counter += 1;
+    }
+
+    if update_data.digest_frequency.is_some() {
+        update_set.push(format!("digest_frequency = ${}", counter));
+        counter += 1;
+    }
+
+    if counter != 1 {
+        let query_string = format!(
+            "UPDATE user_notification_preferences SET {} WHERE user_id = ${}",
+            update_set.join(", "),
+            counter
+        );
+
+        let mut query = sqlx::query(query_string.as_str());
+
+        for (_, value) in &channel_fields {
+            query = query.bind(value);
+        }
+
+        if let Some(quiet_hours) = &update_data.quiet_hours {
+            query = query
+                .bind(quiet_hours.map(|quiet_hours| quiet_hours.start))
+                .bind(quiet_hours.map(|quiet_hours| quiet_hours.end));
+        }
+
+        if let Some(timezone) = &update_data.timezone {
+            query = query.bind(timezone);
+        }
+
+        if let Some(digest_frequency) = &update_data.digest_frequency {
+            query = query.bind(digest_frequency);
+        }
+
+        query = query.bind(user_id);
+
+        if query.execute(&pool).await.is_err() {
+            return Err(ApiErrorType::InternalServerError);
+        }
+    }
+
+    get_user_notification_preferences_by_user_id(pool, user_id).await
+}
+
+/// Checks whether a timezone is known to the database, i.e. is listed in `pg_timezone_names`.
+///
+/// # Arguments
+///
+/// * `pool` - A database connection pool.
+/// * `timezone` - The name of the timezone (e.g. `Africa/Cairo`).
+///
+/// # Returns
+///
+/// Returns a `Result` containing whether the timezone is known.
+/// If an error occurs during the database query, returns an `ApiErrorType::InternalServerError` error.
+///
+pub async fn is_known_timezone(pool: Pool<Postgres>, timezone: &str) -> Result<bool, ApiErrorType> {
+    sqlx::query_scalar!(
+        r#"SELECT EXISTS (SELECT 1 FROM pg_timezone_names WHERE name = $1) AS "exists!""#,
+        timezone
+    )
+    .fetch_one(&pool)
+    .await
+    .map_err(|_| ApiErrorType::InternalServerError)
+}
+
+/// Retrieves how long the current quiet hours of a user still last, in their timezone.
+///
+/// # Arguments
+///
+/// * `pool` - A database connection pool.
+/// * `user_id` - The UUID of the user.
+///
+/// # Returns
+///
+/// Returns a `Result` containing how long the quiet hours of the user still last, `None` when
+/// it's not within them (or they have none, or no preferences at all).
+/// If an error occurs during the database query, returns an `ApiErrorType::InternalServerError` error.
+///
+pub async fn get_quiet_hours_left(
+    pool: Pool<Postgres>,
+    user_id: Uuid,
+) -> Result<Option<Duration>, ApiErrorType> {
+    let query_result = sqlx::query!(
+        r#"SELECT
+            quiet_hours_start,
+            quiet_hours_end,
+            (NOW() AT TIME ZONE timezone)::TIME AS "local_time!"
+        FROM user_notification_preferences
+        WHERE user_id = $1
+        "#,
+        user_id
+    )
+    .fetch_optional(&pool)
+    .await
+    .map_err(|_| ApiErrorType::InternalServerError)?;
+
+    Ok(
+        query_result.and_then(|row| match (row.quiet_hours_start, row.quiet_hours_end) {
+            (Some(start), Some(end)) => QuietHours { start, end }.remaining_at(row.local_time),
+            _ => None,
+        }),
+    )
+}
+
+/// Checks whether it's currently within the quiet hours of a user, in their timezone.
+///
+/// # Arguments
+///
+/// * `pool` - A database connection pool.
+/// * `user_id` - The UUID of the user.
+///
+/// # Returns
+///
+/// Returns a `Result` containing whether it's within the quiet hours of the user, `false` when
+/// they have none (or no preferences at all).
+/// If an error occurs during the database query, returns an `ApiErrorType::InternalServerError` error.
+///
+pub async fn is_in_quiet_hours(pool: Pool<Postgres>, user_id: Uuid) -> Result<bool, ApiErrorType> {
+    Ok(get_quiet_hours_left(pool, user_id).await?.is_some())
+}
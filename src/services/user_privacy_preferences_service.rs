@@ -0,0 +1,175 @@
+use sqlx::{Pool, Postgres};
+use uuid::Uuid;
+
+use crate::{
+    models::{UserPrivacyPreferences, WhoCan},
+    models_validators::user_privacy_preferences_validator::UpdateUserPrivacyPreferencesData,
+    services::follow_requests_service::approve_pending_follow_requests,
+    utils::response::{ApiErrorType, ApiResource},
+};
+
+/// Retrieves the privacy preferences of a user from the database.
+///
+/// # Arguments
+///
+/// * `pool` - A database connection pool.
+/// * `user_id` - The UUID of the user owning the privacy preferences.
+///
+/// # Returns
+///
+/// Returns a `Result` containing the retrieved `UserPrivacyPreferences` if successful.
+/// If no privacy preferences are found, returns an `ApiErrorType::ResourceNotFound` error.
+/// If any other error occurs during the database query, returns an `ApiErrorType::InternalServerError` error.
+///
+pub async fn get_user_privacy_preferences_by_user_id(
+    pool: Pool<Postgres>,
+    user_id: Uuid,
+) -> Result<UserPrivacyPreferences, ApiErrorType> {
+    let query_result = sqlx::query_as!(
+        UserPrivacyPreferences,
+        r#"SELECT
+            id,
+            user_id,
+            is_private_profile,
+            who_can_reply AS "who_can_reply: Vec<WhoCan>",
+            who_can_like AS "who_can_like: Vec<WhoCan>",
+            who_can_mention_me AS "who_can_mention_me: Vec<WhoCan>",
+            who_can_watch_new_hesses AS "who_can_watch_new_hesses: Vec<WhoCan>",
+            who_can_watch_replies AS "who_can_watch_replies: Vec<WhoCan>",
+            who_can_watch_follows AS "who_can_watch_follows: Vec<WhoCan>",
+            who_can_watch_likes AS "who_can_watch_likes: Vec<WhoCan>",
+            who_can_message_me AS "who_can_message_me: Vec<WhoCan>",
+            updated_at
+        FROM user_privacy_preferences WHERE user_id = $1
+        "#,
+        user_id
+    )
+    .fetch_optional(&pool)
+    .await;
+
+    match query_result {
+        Ok(Some(user_privacy_preferences)) => Ok(user_privacy_preferences),
+        Ok(None) => Err(ApiErrorType::ResourceNotFound(
+            ApiResource::UserPrivacyPreferences,
+        )),
+        Err(_) => Err(ApiErrorType::InternalServerError),
+    }
+}
+
+/// Updates the privacy preferences of a user in the database.
+///
+/// Only the fields present in `update_data` are updated, a `who_can_*` field explicitly set
+/// to null resets the preference to public (`NULL`) while an array replaces it.
+///
+/// When the profile switches from private to public and `auto_approve_follow_requests` is set,
+/// all the pending follow requests sent to the user are approved in the same transaction.
+///
+/// # Arguments
+///
+/// * `pool` - A database connection pool.
+/// * `user_id` - The UUID of the user owning the privacy preferences.
+/// * `update_data` - Data containing the fields to update.
+/// * `auto_approve_follow_requests` - Whether to approve pending follow requests when the profile becomes public.
+///
+/// # Returns
+///
+/// Returns a `Result` containing the updated `UserPrivacyPreferences` if successful.
+/// If no privacy preferences are found for the user, returns an `ApiErrorType::ResourceNotFound` error.
+/// If any other error occurs during the database update, returns an `ApiErrorType::InternalServerError` error.
+///
+pub async fn update_user_privacy_preferences(
+    pool: Pool<Postgres>,
+    user_id: Uuid,
+    update_data: UpdateUserPrivacyPreferencesData,
+    auto_approve_follow_requests: bool,
+) -> Result<UserPrivacyPreferences, ApiErrorType> {
+    let mut tx = pool
+        .begin()
+        .await
+        .map_err(|_| ApiErrorType::InternalServerError)?;
+
+    let was_private_profile = sqlx::query_scalar!(
+        "SELECT is_private_profile FROM user_privacy_preferences WHERE user_id = $1 FOR UPDATE",
+        user_id
+    )
+    .fetch_optional(&mut *tx)
+    .await;
+
+    let was_private_profile = match was_private_profile {
+        Ok(Some(was_private_profile)) => was_private_profile,
+        Ok(None) => {
+            return Err(ApiErrorType::ResourceNotFound(
+                ApiResource::UserPrivacyPreferences,
+            ))
+        }
+        Err(_) => return Err(ApiErrorType::InternalServerError),
+    };
+
+    let who_can_fields = [
+        ("who_can_reply", &update_data.who_can_reply),
+        ("who_can_like", &update_data.who_can_like),
+        ("who_can_mention_me", &update_data.who_can_mention_me),
+        (
+            "who_can_watch_new_hesses",
+            &update_data.who_can_watch_new_hesses,
+        ),
+        ("who_can_watch_replies", &update_data.who_can_watch_replies),
+        ("who_can_watch_follows", &update_data.who_can_watch_follows),
+        ("who_can_watch_likes", &update_data.who_can_watch_likes),
+        ("who_can_message_me", &update_data.who_can_message_me),
+    ];
+
+    let mut update_set = vec![];
+    let mut counter = 1;
+
+    if update_data.is_private_profile.is_some() {
+        update_set.push(format!("is_private_profile = ${}", counter));
+        counter += 1;
+    }
+
+    for (column, value) in &who_can_fields {
+        if value.is_some() {
+            update_set.push(format!("{} = ${}", column, counter));
+            counter += 1;
+        }
+    }
+
+    if counter != 1 {
+        let query_string = format!(
+            "UPDATE user_privacy_preferences SET {} WHERE user_id = ${}",
+            update_set.join(", "),
+            counter
+        );
+
+        let mut query = sqlx::query(query_string.as_str());
+
+        if let Some(is_private_profile) = &update_data.is_private_profile {
+            query = query.bind(is_private_profile);
+        }
+
+        for (_, value) in &who_can_fields {
+            if let Some(value) = value {
+                query = query.bind(value);
+            }
+        }
+
+        query = query.bind(user_id);
+
+        if query.execute(&mut *tx).await.is_err() {
+            return Err(ApiErrorType::InternalServerError);
+        }
+    }
+
+    if auto_approve_follow_requests
+        && was_private_profile
+        && update_data.is_private_profile == Some(false)
+    {
+        approve_pending_follow_requests(&mut tx, user_id).await?;
+    }
+
+    tx.commit()
+        .await
+        .map_err(|_| ApiErrorType::InternalServerError)?;
+
+    get_user_privacy_preferences_by_user_id(pool, user_id).await
+}
@@ -0,0 +1,260 @@
+use std::collections::BTreeMap;
+
+use chrono::Utc;
+use sqlx::{types::Json, Pool, Postgres};
+use uuid::Uuid;
+
+use crate::{
+    models::{Page, UserProfileImage},
+    utils::{
+        list_query::{ListQuery, ListSpec, SortField},
+        response::{ApiErrorType, ApiResource},
+    },
+};
+
+/// The data of a processed and stored profile image.
+pub struct InsertUserProfileImageData {
+    pub id: Uuid,
+    pub image_url: String,
+    pub thumbnail_urls: BTreeMap<u32, String>,
+    pub content_type: String,
+    pub width: i32,
+    pub height: i32,
+}
+
+/// Retrieves a (non deleted) profile image by its ID from the database.
+///
+/// # Arguments
+///
+/// * `pool` - A database connection pool.
+/// * `id` - The UUID of the profile image to retrieve.
+///
+/// # Returns
+///
+/// Returns a `Result` containing the retrieved `UserProfileImage` if successful.
+/// If no profile image is found, returns an `ApiErrorType::ResourceNotFound` error.
+/// If any other error occurs during the database query, returns an `ApiErrorType::InternalServerError` error.
+///
+pub async fn get_user_profile_image_by_id(
+    pool: Pool<Postgres>,
+    id: Uuid,
+) -> Result<UserProfileImage, ApiErrorType> {
+    let query_result = sqlx::query_as!(
+        UserProfileImage,
+        r#"SELECT id, user_id, image_url,
+            thumbnail_urls AS "thumbnail_urls: Json<BTreeMap<u32, String>>",
+            content_type, width, height, created_at, updated_at, deleted_at
+        FROM user_profile_images WHERE id = $1 AND deleted_at IS NULL
+        "#,
+        id
+    )
+    .fetch_optional(&pool)
+    .await;
+
+    match query_result {
+        Ok(Some(user_profile_image)) => Ok(user_profile_image),
+        Ok(None) => Err(ApiErrorType::ResourceNotFound(
+            ApiResource::UserProfileImages,
+        )),
+        Err(_) => Err(ApiErrorType::InternalServerError),
+    }
+}
+
+/// The sortable fields of the profile images history.
+pub static USER_PROFILE_IMAGES_LIST_SPEC: ListSpec = ListSpec {
+    table: "user_profile_images",
+    sort_fields: &[SortField {
+        name: "createdAt",
+        column: "created_at",
+    }],
+    default_sort: "-createdAt",
+    filter_fields: &[],
+};
+
+/// Retrieves a page of the (non deleted) profile images a user uploaded, the current one included.
+///
+/// # Arguments
+///
+/// * `pool` - A database connection pool.
+/// * `user_id` - The UUID of the user who uploaded the profile images.
+/// * `list_query` - The sorting and pagination, parsed against `USER_PROFILE_IMAGES_LIST_SPEC`.
+///
+/// # Returns
+///
+/// Returns a `Result` containing the page of profile images.
+/// If any error occurs during the database query, returns an `ApiErrorType::InternalServerError` error.
+///
+pub async fn get_user_profile_images_by_user_id(
+    pool: Pool<Postgres>,
+    user_id: Uuid,
+    list_query: ListQuery,
+) -> Result<Page<UserProfileImage>, ApiErrorType> {
+    let total = sqlx::query_scalar!(
+        r#"SELECT COUNT(*) AS "count!" FROM user_profile_images WHERE user_id = $1 AND deleted_at IS NULL"#,
+        user_id
+    )
+    .fetch_one(&pool)
+    .await
+    .map_err(|_| ApiErrorType::InternalServerError)?;
+
+    let query_string = format!(
+        r#"SELECT i.id, i.user_id, i.image_url, i.thumbnail_urls, i.content_type, i.width, i.height,
+            i.created_at, i.updated_at, i.deleted_at
+        FROM user_profile_images i
+        {}
+        WHERE i.user_id = $1 AND i.deleted_at IS NULL AND {}
+        ORDER BY {}
+        LIMIT $3 OFFSET $4
+        "#,
+        list_query.keyset_join_sql("$2"),
+        list_query.keyset_condition_sql("i"),
+        list_query.order_by_sql("i")
+    );
+
+    let query_result = sqlx::query_as::<_, UserProfileImage>(query_string.as_str())
+        .bind(user_id)
+        .bind(list_query.after_id)
+        .bind(list_query.limit())
+        .bind(list_query.offset())
+        .fetch_all(&pool)
+        .await;
+
+    match query_result {
+        Ok(user_profile_images) => {
+            Ok(list_query.into_page(user_profile_images, total, |image| image.id))
+        }
+        Err(_) => Err(ApiErrorType::InternalServerError),
+    }
+}
+
+/// Inserts a new profile image and makes it the current profile image of the user,
+/// in the same transaction.
+///
+/// The previous profile image (if any) is kept in the history of the user.
+///
+/// # Arguments
+///
+/// * `pool` - A database connection pool.
+/// * `user_id` - The UUID of the user uploading the profile image.
+/// * `data` - The data of the processed and stored profile image.
+///
+/// # Returns
+///
+/// Returns a `Result` containing the inserted `UserProfileImage` if successful.
+/// If the user doesn't exist (or is deleted), returns an `ApiErrorType::ResourceNotFound` error.
+/// If any other error occurs during the database transaction, returns an `ApiErrorType::InternalServerError` error.
+///
+pub async fn insert_user_profile_image(
+    pool: Pool<Postgres>,
+    user_id: Uuid,
+    data: InsertUserProfileImageData,
+) -> Result<UserProfileImage, ApiErrorType> {
+    let mut tx = pool
+        .begin()
+        .await
+        .map_err(|_| ApiErrorType::InternalServerError)?;
+
+    let user_profile_image = sqlx::query_as!(
+        UserProfileImage,
+        r#"INSERT INTO user_profile_images (id, user_id, image_url, thumbnail_urls, content_type, width, height)
+        VALUES ($1, $2, $3, $4, $5, $6, $7)
+        RETURNING id, user_id, image_url,
+            thumbnail_urls AS "thumbnail_urls: Json<BTreeMap<u32, String>>",
+            content_type, width, height, created_at, updated_at, deleted_at
+        "#,
+        data.id,
+        user_id,
+        data.image_url,
+        Json(data.thumbnail_urls) as _,
+        data.content_type,
+        data.width,
+        data.height,
+    )
+    .fetch_one(&mut *tx)
+    .await
+    .map_err(|_| ApiErrorType::InternalServerError)?;
+
+    let query_result = sqlx::query!(
+        "UPDATE users SET user_profile_image_id = $1, updated_at = $2 WHERE id = $3 AND deleted_at IS NULL",
+        user_profile_image.id,
+        Utc::now(),
+        user_id,
+    )
+    .execute(&mut *tx)
+    .await;
+
+    match query_result {
+        Ok(result) if result.rows_affected() == 0 => {
+            return Err(ApiErrorType::ResourceNotFound(ApiResource::Users))
+        }
+        Ok(_) => {}
+        Err(_) => return Err(ApiErrorType::InternalServerError),
+    }
+
+    tx.commit()
+        .await
+        .map_err(|_| ApiErrorType::InternalServerError)?;
+
+    Ok(user_profile_image)
+}
+
+/// Soft deletes a profile image of a user, clearing the current profile image of the user
+/// in the same transaction when it's the deleted one.
+///
+/// # Arguments
+///
+/// * `pool` - A database connection pool.
+/// * `user_id` - The UUID of the user who uploaded the profile image.
+/// * `id` - The UUID of the profile image to delete.
+///
+/// # Returns
+///
+/// Returns a `Result<(), ApiErrorType>` indicating success.
+/// If the user has no such (non deleted) profile image, returns an `ApiErrorType::ResourceNotFound` error.
+/// If any other error occurs during the database transaction, returns an `ApiErrorType::InternalServerError` error.
+///
+pub async fn delete_user_profile_image(
+    pool: Pool<Postgres>,
+    user_id: Uuid,
+    id: Uuid,
+) -> Result<(), ApiErrorType> {
+    let current_date = Utc::now();
+
+    let mut tx = pool
+        .begin()
+        .await
+        .map_err(|_| ApiErrorType::InternalServerError)?;
+
+    sqlx::query!(
+        "UPDATE users SET user_profile_image_id = NULL, updated_at = $1 WHERE id = $2 AND user_profile_image_id = $3",
+        current_date,
+        user_id,
+        id,
+    )
+    .execute(&mut *tx)
+    .await
+    .map_err(|_| ApiErrorType::InternalServerError)?;
+
+    let query_result = sqlx::query!(
+        "UPDATE user_profile_images SET deleted_at = $1 WHERE id = $2 AND user_id = $3 AND deleted_at IS NULL",
+        current_date,
+        id,
+        user_id,
+    )
+    .execute(&mut *tx)
+    .await;
+
+    match query_result {
+        Ok(result) if result.rows_affected() == 0 => {
+            return Err(ApiErrorType::ResourceNotFound(
+                ApiResource::UserProfileImages,
+            ))
+        }
+        Ok(_) => {}
+        Err(_) => return Err(ApiErrorType::InternalServerError),
+    }
+
+    tx.commit()
+        .await
+        .map_err(|_| ApiErrorType::InternalServerError)
+}
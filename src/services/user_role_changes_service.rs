@@ -0,0 +1,105 @@
+use sqlx::{Pool, Postgres};
+use uuid::Uuid;
+
+use crate::{
+    models::{UserRole, UserRoleChange},
+    utils::response::ApiErrorType,
+};
+
+/// Changes the role of a user and audits the change, in a single statement.
+///
+/// The role of roots is left untouched (see `ensure_can_change_role`), and so is an unchanged
+/// role, which isn't audited.
+///
+/// # Arguments
+///
+/// * `pool` - A database connection pool.
+/// * `user_id` - The UUID of the user whose role is changed.
+/// * `role` - The new role.
+/// * `changed_by` - The UUID of the root changing the role.
+///
+/// # Returns
+///
+/// Returns a `Result` containing the audited `UserRoleChange`, `None` if the role didn't change.
+/// If any error occurs during the database query, returns an `ApiErrorType::InternalServerError` error.
+///
+pub async fn change_user_role(
+    pool: Pool<Postgres>,
+    user_id: Uuid,
+    role: UserRole,
+    changed_by: Uuid,
+) -> Result<Option<UserRoleChange>, ApiErrorType> {
+    let query_result = sqlx::query_as!(
+        UserRoleChange,
+        r#"WITH old AS (
+            SELECT id, role FROM users
+            WHERE id = $1 AND deleted_at IS NULL AND role <> 'ROOT'
+            FOR UPDATE
+        ), updated AS (
+            UPDATE users u SET role = $2
+            FROM old
+            WHERE u.id = old.id AND old.role <> $2
+            RETURNING old.role AS old_role
+        )
+        INSERT INTO user_role_changes (user_id, changed_by, old_role, new_role)
+        SELECT $1, $3, old_role, $2 FROM updated
+        RETURNING
+            id,
+            user_id,
+            changed_by,
+            old_role AS "old_role: UserRole",
+            new_role AS "new_role: UserRole",
+            created_at
+        "#,
+        user_id,
+        role as UserRole,
+        changed_by
+    )
+    .fetch_optional(&pool)
+    .await;
+
+    match query_result {
+        Ok(change) => Ok(change),
+        Err(_) => Err(ApiErrorType::InternalServerError),
+    }
+}
+
+/// Retrieves the role changes of a user, latest first.
+///
+/// # Arguments
+///
+/// * `pool` - A database connection pool.
+/// * `user_id` - The UUID of the user.
+///
+/// # Returns
+///
+/// Returns a `Result` containing the role changes of the user.
+/// If any error occurs during the database query, returns an `ApiErrorType::InternalServerError` error.
+///
+pub async fn get_user_role_changes(
+    pool: Pool<Postgres>,
+    user_id: Uuid,
+) -> Result<Vec<UserRoleChange>, ApiErrorType> {
+    let query_result = sqlx::query_as!(
+        UserRoleChange,
+        r#"SELECT
+            id,
+            user_id,
+            changed_by,
+            old_role AS "old_role: UserRole",
+            new_role AS "new_role: UserRole",
+            created_at
+        FROM user_role_changes
+        WHERE user_id = $1
+        ORDER BY created_at DESC
+        "#,
+        user_id
+    )
+    .fetch_all(&pool)
+    .await;
+
+    match query_result {
+        Ok(changes) => Ok(changes),
+        Err(_) => Err(ApiErrorType::InternalServerError),
+    }
+}
@@ -3,9 +3,13 @@ use sqlx::{Pool, Postgres};
 use uuid::Uuid;
 
 use crate::{
-    models::{Gender, User, UserRole},
+    models::{Gender, Page, User, UserOverview, UserRole},
     models_validators::user_validator::{InsertUserData, UpdateUserData},
-    utils::response::{ApiErrorType, ApiResource},
+    utils::{
+        list_filter::{FilterField, FilterType},
+        list_query::{ListQuery, ListSpec, SortField},
+        response::{ApiErrorType, ApiResource},
+    },
 };
 
 /// Retrieves a user by their ID from the database.
@@ -62,7 +66,6 @@ pub async fn get_user_by_id(pool: Pool<Postgres>, id: Uuid) -> Result<User, ApiE
 /// * `pool` - A database connection pool.
 /// * `insert_user_data` - Data to insert for the new user.
 /// * `user_profile_image_id` - Optional UUID of the user's profile image.
-/// * `verified_by` - Optional UUID of the user who verified this user.
 ///
 /// # Returns
 ///
@@ -74,14 +77,7 @@ pub async fn insert_user(
     pool: Pool<Postgres>,
     insert_user_data: InsertUserData,
     user_profile_image_id: Option<Uuid>,
-    verified_by: Option<Uuid>,
 ) -> Result<User, ApiErrorType> {
-    let verified_at = if let Some(_) = verified_by {
-        Some(Utc::now())
-    } else {
-        None
-    };
-
     let query_result = sqlx::query_as!(
         User,
         r#"INSERT INTO users (
@@ -92,12 +88,8 @@ pub async fn insert_user(
             email,
             user_profile_image_id,
             username,
-            password,
-            activated,
-            verified,
-            verified_at,
-            verified_by
-        ) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12)
+            password
+        ) VALUES ($1, $2, 'USER', $3, $4, $5, $6, $7)
         RETURNING
             id,
             name,
@@ -118,16 +110,11 @@ pub async fn insert_user(
         "#,
         insert_user_data.name,
         insert_user_data.gender as Gender,
-        insert_user_data.role as UserRole,
         insert_user_data.bio,
         insert_user_data.email,
         user_profile_image_id,
         insert_user_data.username,
-        insert_user_data.password,
-        insert_user_data.activated,
-        insert_user_data.verified,
-        verified_at,
-        verified_by
+        insert_user_data.password
     )
     .fetch_one(&pool)
     .await;
@@ -214,11 +201,6 @@ pub async fn update_user(
         counter += 1;
     }
 
-    if let Some(role) = &update_user_data.role {
-        update_set.push(format!("role = ${}", serde_json::to_string(role).unwrap()));
-        counter += 1;
-    }
-
     if let Some(bio) = &update_user_data.bio {
         update_set.push(format!("bio = ${}", bio));
         counter += 1;
@@ -239,16 +221,6 @@ pub async fn update_user(
         counter += 1;
     }
 
-    if let Some(activated) = &update_user_data.activated {
-        update_set.push(format!("activated = ${}", activated));
-        counter += 1;
-    }
-
-    if let Some(verified) = &update_user_data.verified {
-        update_set.push(format!("verified = ${}", verified));
-        counter += 1;
-    }
-
     if counter == 1 {
         return Ok(());
     }
@@ -269,10 +241,6 @@ pub async fn update_user(
         query = query.bind(gender);
     }
 
-    if let Some(role) = &update_user_data.role {
-        query = query.bind(role);
-    }
-
     if let Some(bio) = &update_user_data.bio {
         query = query.bind(bio);
     }
@@ -289,14 +257,6 @@ pub async fn update_user(
         query = query.bind(password);
     }
 
-    if let Some(activated) = &update_user_data.activated {
-        query = query.bind(activated);
-    }
-
-    if let Some(verified) = &update_user_data.verified {
-        query = query.bind(verified);
-    }
-
     query = query.bind(user_id);
 
     match query.execute(&pool).await {
@@ -310,3 +270,117 @@ pub async fn update_user(
         _ => Err(ApiErrorType::InternalServerError),
     }
 }
+
+/// The sortable and filterable fields of the users (soft-deleted ones included), as listed to admins.
+pub static USERS_LIST_SPEC: ListSpec = ListSpec {
+    table: "users",
+    sort_fields: &[
+        SortField {
+            name: "createdAt",
+            column: "created_at",
+        },
+        SortField {
+            name: "username",
+            column: "username",
+        },
+        SortField {
+            name: "name",
+            column: "name",
+        },
+    ],
+    default_sort: "-createdAt",
+    filter_fields: &[
+        FilterField {
+            name: "role",
+            expression: "u.role::TEXT",
+            filter_type: FilterType::Enum(&["USER", "MANAGER", "ROOT"]),
+        },
+        FilterField {
+            name: "activated",
+            expression: "u.activated",
+            filter_type: FilterType::Bool,
+        },
+        FilterField {
+            name: "verified",
+            expression: "u.verified",
+            filter_type: FilterType::Bool,
+        },
+        FilterField {
+            name: "deleted",
+            expression: "(u.deleted_at IS NOT NULL)",
+            filter_type: FilterType::Bool,
+        },
+        FilterField {
+            name: "createdAt",
+            expression: "u.created_at",
+            filter_type: FilterType::Timestamp,
+        },
+    ],
+};
+
+/// Retrieves a page of users, soft-deleted ones included.
+///
+/// # Arguments
+///
+/// * `pool` - A database connection pool.
+/// * `list_query` - The filters, sorting and pagination, parsed against `USERS_LIST_SPEC`.
+///
+/// # Returns
+///
+/// Returns a `Result` containing the page of users.
+/// If any error occurs during the database query, returns an `ApiErrorType::InternalServerError` error.
+///
+pub async fn get_users(
+    pool: Pool<Postgres>,
+    list_query: ListQuery,
+) -> Result<Page<UserOverview>, ApiErrorType> {
+    let count_query_string = format!(
+        "SELECT COUNT(*) FROM users u WHERE {}",
+        list_query.filters_sql(1)
+    );
+
+    let total = list_query
+        .bind_filters(sqlx::query_as::<_, (i64,)>(count_query_string.as_str()))
+        .fetch_one(&pool)
+        .await
+        .map_err(|_| ApiErrorType::InternalServerError)?
+        .0;
+
+    let query_string = format!(
+        r#"SELECT
+            u.id,
+            u.name,
+            u.username,
+            u.email,
+            u.role,
+            u.activated,
+            u.verified,
+            u.created_at,
+            u.deleted_at
+        FROM users u
+        {}
+        WHERE {} AND {}
+        ORDER BY {}
+        LIMIT $2 OFFSET $3
+        "#,
+        list_query.keyset_join_sql("$1"),
+        list_query.keyset_condition_sql("u"),
+        list_query.filters_sql(4),
+        list_query.order_by_sql("u")
+    );
+
+    let query_result = list_query
+        .bind_filters(
+            sqlx::query_as::<_, UserOverview>(query_string.as_str())
+                .bind(list_query.after_id)
+                .bind(list_query.limit())
+                .bind(list_query.offset()),
+        )
+        .fetch_all(&pool)
+        .await;
+
+    match query_result {
+        Ok(users) => Ok(list_query.into_page(users, total, |user| user.id)),
+        Err(_) => Err(ApiErrorType::InternalServerError),
+    }
+}
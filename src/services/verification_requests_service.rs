@@ -0,0 +1,359 @@
+use sqlx::{Pool, Postgres};
+use uuid::Uuid;
+
+use crate::{
+    models::{Page, VerificationRequest, VerificationRequestStatus},
+    models_validators::verification_request_validator::{
+        InsertVerificationRequestData, ReviewVerificationData,
+    },
+    utils::{
+        list_filter::{FilterField, FilterType},
+        list_query::{ListQuery, ListSpec, SortField},
+        response::{ApiErrorType, ApiResource},
+    },
+};
+
+/// The columns of a verification request (aliased `r`) joined with its user (aliased `u`).
+const VERIFICATION_REQUEST_COLUMNS: &str = r#"
+    r.id,
+    r.user_id,
+    u.username,
+    u.name,
+    r.evidence,
+    r.status,
+    r.reviewer_id,
+    r.review_notes,
+    r.reviewed_at,
+    r.revoked_by,
+    r.revocation_notes,
+    r.revoked_at,
+    r.created_at,
+    r.updated_at"#;
+
+/// Requests a user to be verified.
+///
+/// # Arguments
+///
+/// * `pool` - A database connection pool.
+/// * `user_id` - The UUID of the user.
+/// * `data` - The evidence of the request.
+///
+/// # Returns
+///
+/// Returns a `Result` containing the inserted `VerificationRequest` if successful.
+/// If the user already has a pending request, returns an `ApiErrorType::AlreadyExists` error.
+/// If any other error occurs during the database query, returns an `ApiErrorType::InternalServerError` error.
+///
+pub async fn insert_verification_request(
+    pool: Pool<Postgres>,
+    user_id: Uuid,
+    data: InsertVerificationRequestData,
+) -> Result<VerificationRequest, ApiErrorType> {
+    let query_result = sqlx::query_as::<_, VerificationRequest>(&format!(
+        r#"WITH r AS (
+            INSERT INTO verification_requests (user_id, evidence) VALUES ($1, $2)
+            RETURNING *
+        )
+        SELECT {} FROM r JOIN users u ON u.id = r.user_id
+        "#,
+        VERIFICATION_REQUEST_COLUMNS
+    ))
+    .bind(user_id)
+    .bind(data.evidence)
+    .fetch_one(&pool)
+    .await;
+
+    match query_result {
+        Ok(request) => Ok(request),
+        Err(sqlx::Error::Database(e)) if e.is_unique_violation() => Err(
+            ApiErrorType::AlreadyExists(ApiResource::VerificationRequests),
+        ),
+        Err(_) => Err(ApiErrorType::InternalServerError),
+    }
+}
+
+/// Retrieves a verification request by its ID.
+///
+/// # Arguments
+///
+/// * `pool` - A database connection pool.
+/// * `id` - The UUID of the request.
+///
+/// # Returns
+///
+/// Returns a `Result` containing the `VerificationRequest` if successful.
+/// If no request is found, returns an `ApiErrorType::ResourceNotFound` error.
+/// If any other error occurs during the database query, returns an `ApiErrorType::InternalServerError` error.
+///
+pub async fn get_verification_request_by_id(
+    pool: Pool<Postgres>,
+    id: Uuid,
+) -> Result<VerificationRequest, ApiErrorType> {
+    let query_result = sqlx::query_as::<_, VerificationRequest>(&format!(
+        r#"SELECT {} FROM verification_requests r
+        JOIN users u ON u.id = r.user_id
+        WHERE r.id = $1
+        "#,
+        VERIFICATION_REQUEST_COLUMNS
+    ))
+    .bind(id)
+    .fetch_optional(&pool)
+    .await;
+
+    match query_result {
+        Ok(Some(request)) => Ok(request),
+        Ok(None) => Err(ApiErrorType::ResourceNotFound(
+            ApiResource::VerificationRequests,
+        )),
+        Err(_) => Err(ApiErrorType::InternalServerError),
+    }
+}
+
+/// Retrieves the verification requests of a user, latest first.
+///
+/// # Arguments
+///
+/// * `pool` - A database connection pool.
+/// * `user_id` - The UUID of the user.
+///
+/// # Returns
+///
+/// Returns a `Result` containing the requests of the user.
+/// If any error occurs during the database query, returns an `ApiErrorType::InternalServerError` error.
+///
+pub async fn get_verification_requests_by_user_id(
+    pool: Pool<Postgres>,
+    user_id: Uuid,
+) -> Result<Vec<VerificationRequest>, ApiErrorType> {
+    let query_result = sqlx::query_as::<_, VerificationRequest>(&format!(
+        r#"SELECT {} FROM verification_requests r
+        JOIN users u ON u.id = r.user_id
+        WHERE r.user_id = $1
+        ORDER BY r.created_at DESC
+        "#,
+        VERIFICATION_REQUEST_COLUMNS
+    ))
+    .bind(user_id)
+    .fetch_all(&pool)
+    .await;
+
+    match query_result {
+        Ok(requests) => Ok(requests),
+        Err(_) => Err(ApiErrorType::InternalServerError),
+    }
+}
+
+/// The sortable and filterable fields of the verification requests, as listed to admins.
+pub static VERIFICATION_REQUESTS_LIST_SPEC: ListSpec = ListSpec {
+    table: "verification_requests",
+    sort_fields: &[SortField {
+        name: "createdAt",
+        column: "created_at",
+    }],
+    default_sort: "createdAt",
+    filter_fields: &[
+        FilterField {
+            name: "status",
+            expression: "r.status::TEXT",
+            filter_type: FilterType::Enum(&["PENDING", "APPROVED", "REJECTED", "REVOKED"]),
+        },
+        FilterField {
+            name: "userId",
+            expression: "r.user_id",
+            filter_type: FilterType::Uuid,
+        },
+        FilterField {
+            name: "reviewerId",
+            expression: "r.reviewer_id",
+            filter_type: FilterType::Uuid,
+        },
+        FilterField {
+            name: "createdAt",
+            expression: "r.created_at",
+            filter_type: FilterType::Timestamp,
+        },
+    ],
+};
+
+/// Retrieves a page of verification requests (the review queue, once filtered by status).
+///
+/// # Arguments
+///
+/// * `pool` - A database connection pool.
+/// * `list_query` - The filters, sorting and pagination, parsed against `VERIFICATION_REQUESTS_LIST_SPEC`.
+///
+/// # Returns
+///
+/// Returns a `Result` containing the page of verification requests.
+/// If any error occurs during the database query, returns an `ApiErrorType::InternalServerError` error.
+///
+pub async fn get_verification_requests(
+    pool: Pool<Postgres>,
+    list_query: ListQuery,
+) -> Result<Page<VerificationRequest>, ApiErrorType> {
+    let count_query_string = format!(
+        "SELECT COUNT(*) FROM verification_requests r WHERE {}",
+        list_query.filters_sql(1)
+    );
+
+    let total = list_query
+        .bind_filters(sqlx::query_as::<_, (i64,)>(count_query_string.as_str()))
+        .fetch_one(&pool)
+        .await
+        .map_err(|_| ApiErrorType::InternalServerError)?
+        .0;
+
+    let query_string = format!(
+        r#"SELECT {}
+        FROM verification_requests r
+        JOIN users u ON u.id = r.user_id
+        {}
+        WHERE {} AND {}
+        ORDER BY {}
+        LIMIT $2 OFFSET $3
+        "#,
+        VERIFICATION_REQUEST_COLUMNS,
+        list_query.keyset_join_sql("$1"),
+        list_query.keyset_condition_sql("r"),
+        list_query.filters_sql(4),
+        list_query.order_by_sql("r")
+    );
+
+    let query_result = list_query
+        .bind_filters(
+            sqlx::query_as::<_, VerificationRequest>(query_string.as_str())
+                .bind(list_query.after_id)
+                .bind(list_query.limit())
+                .bind(list_query.offset()),
+        )
+        .fetch_all(&pool)
+        .await;
+
+    match query_result {
+        Ok(requests) => Ok(list_query.into_page(requests, total, |request| request.id)),
+        Err(_) => Err(ApiErrorType::InternalServerError),
+    }
+}
+
+/// Approves or rejects a pending verification request. Approving it verifies the user, by the
+/// reviewer.
+///
+/// # Arguments
+///
+/// * `pool` - A database connection pool.
+/// * `id` - The UUID of the request.
+/// * `reviewer_id` - The UUID of the admin reviewing the request.
+/// * `status` - `Approved` or `Rejected`.
+/// * `data` - The notes of the reviewer.
+///
+/// # Returns
+///
+/// Returns a `Result` containing nothing.
+/// If the request isn't pending, returns an `ApiErrorType::ResourceNotFound` error.
+/// If any other error occurs during the database queries, returns an `ApiErrorType::InternalServerError` error.
+///
+pub async fn review_verification_request(
+    pool: Pool<Postgres>,
+    id: Uuid,
+    reviewer_id: Uuid,
+    status: VerificationRequestStatus,
+    data: ReviewVerificationData,
+) -> Result<(), ApiErrorType> {
+    let mut tx = pool
+        .begin()
+        .await
+        .map_err(|_| ApiErrorType::InternalServerError)?;
+
+    let user_id = sqlx::query_scalar!(
+        r#"UPDATE verification_requests
+        SET status = $3, reviewer_id = $2, review_notes = $4, reviewed_at = NOW()
+        WHERE id = $1 AND status = 'PENDING'
+        RETURNING user_id
+        "#,
+        id,
+        reviewer_id,
+        status as VerificationRequestStatus,
+        data.notes
+    )
+    .fetch_optional(&mut *tx)
+    .await
+    .map_err(|_| ApiErrorType::InternalServerError)?
+    .ok_or(ApiErrorType::ResourceNotFound(
+        ApiResource::VerificationRequests,
+    ))?;
+
+    if status == VerificationRequestStatus::Approved {
+        sqlx::query!(
+            r#"UPDATE users SET verified = TRUE, verified_at = NOW(), verified_by = $2
+            WHERE id = $1
+            "#,
+            user_id,
+            reviewer_id
+        )
+        .execute(&mut *tx)
+        .await
+        .map_err(|_| ApiErrorType::InternalServerError)?;
+    }
+
+    tx.commit()
+        .await
+        .map_err(|_| ApiErrorType::InternalServerError)
+}
+
+/// Revokes the verification of a user, along with the request it was approved from (if any).
+///
+/// # Arguments
+///
+/// * `pool` - A database connection pool.
+/// * `user_id` - The UUID of the verified user.
+/// * `revoked_by` - The UUID of the admin revoking the verification.
+/// * `data` - The notes of the admin.
+///
+/// # Returns
+///
+/// Returns a `Result` containing nothing.
+/// If the user isn't verified, returns an `ApiErrorType::NotVerified` error.
+/// If any other error occurs during the database queries, returns an `ApiErrorType::InternalServerError` error.
+///
+pub async fn revoke_user_verification(
+    pool: Pool<Postgres>,
+    user_id: Uuid,
+    revoked_by: Uuid,
+    data: ReviewVerificationData,
+) -> Result<(), ApiErrorType> {
+    let mut tx = pool
+        .begin()
+        .await
+        .map_err(|_| ApiErrorType::InternalServerError)?;
+
+    let result = sqlx::query!(
+        r#"UPDATE users SET verified = FALSE, verified_at = NULL, verified_by = NULL
+        WHERE id = $1 AND verified
+        "#,
+        user_id
+    )
+    .execute(&mut *tx)
+    .await
+    .map_err(|_| ApiErrorType::InternalServerError)?;
+
+    if result.rows_affected() == 0 {
+        return Err(ApiErrorType::NotVerified);
+    }
+
+    sqlx::query!(
+        r#"UPDATE verification_requests
+        SET status = 'REVOKED', revoked_by = $2, revocation_notes = $3, revoked_at = NOW()
+        WHERE user_id = $1 AND status = 'APPROVED'
+        "#,
+        user_id,
+        revoked_by,
+        data.notes
+    )
+    .execute(&mut *tx)
+    .await
+    .map_err(|_| ApiErrorType::InternalServerError)?;
+
+    tx.commit()
+        .await
+        .map_err(|_| ApiErrorType::InternalServerError)
+}
@@ -0,0 +1,116 @@
+use sqlx::{Pool, Postgres};
+use uuid::Uuid;
+
+use crate::{policies::ViewerRelationship, utils::response::ApiErrorType};
+
+/// Retrieves everything the visibility policy needs to know about a viewer and an author.
+///
+/// # Arguments
+///
+/// * `pool` - A database connection pool.
+/// * `viewer_id` - The UUID of the user trying to perform an action.
+/// * `author_id` - The UUID of the user owning the targeted content (or profile).
+/// * `hess_id` - The UUID of the targeted hess, if any, used to check whether the viewer is mentioned in it.
+///
+/// # Returns
+///
+/// Returns a `Result` containing the `ViewerRelationship` between the viewer and the author.
+/// If any error occurs during the database query, returns an `ApiErrorType::InternalServerError` error.
+///
+pub async fn get_viewer_relationship(
+    pool: Pool<Postgres>,
+    viewer_id: Uuid,
+    author_id: Uuid,
+    hess_id: Option<Uuid>,
+) -> Result<ViewerRelationship, ApiErrorType> {
+    let query_result = sqlx::query!(
+        r#"SELECT
+            EXISTS (
+                SELECT 1 FROM followers f
+                WHERE f.follower_id = $1 AND f.followed_id = $2 AND f.deleted_at IS NULL
+            ) AS "viewer_follows_author!",
+            EXISTS (
+                SELECT 1 FROM followers f
+                WHERE f.follower_id = $2 AND f.followed_id = $1 AND f.deleted_at IS NULL
+            ) AS "author_follows_viewer!",
+            EXISTS (
+                SELECT 1 FROM blocked_users b
+                WHERE b.deleted_at IS NULL AND (
+                    (b.blocker_id = $2 AND b.blocked_id = $1)
+                    OR (b.blocker_id = $1 AND b.blocked_id = $2)
+                )
+            ) AS "is_blocked!",
+            EXISTS (
+                SELECT 1 FROM hess_mentions m
+                WHERE m.hess_id = $3 AND m.user_id = $1 AND m.deleted_at IS NULL
+            ) AS "is_mentioned!",
+            ARRAY(
+                SELECT l.id FROM audience_lists l
+                JOIN audience_list_members m ON m.list_id = l.id
+                WHERE l.owner_id = $2
+                    AND l.deleted_at IS NULL
+                    AND m.user_id = $1
+                    AND m.deleted_at IS NULL
+            ) AS "audience_list_ids!"
+        "#,
+        viewer_id,
+        author_id,
+        hess_id
+    )
+    .fetch_one(&pool)
+    .await;
+
+    match query_result {
+        Ok(row) => Ok(ViewerRelationship {
+            is_author: viewer_id == author_id,
+            viewer_follows_author: row.viewer_follows_author,
+            author_follows_viewer: row.author_follows_viewer,
+            is_blocked: row.is_blocked,
+            is_mentioned: row.is_mentioned,
+            audience_list_ids: row.audience_list_ids,
+        }),
+        Err(_) => Err(ApiErrorType::InternalServerError),
+    }
+}
+
+/// Whether a user muted another one, or any of them blocked the other.
+///
+/// Used to silence the activity of a user towards another one (e.g. push notifications).
+///
+/// # Arguments
+///
+/// * `pool` - A database connection pool.
+/// * `viewer_id` - The UUID of the user the activity is shown to.
+/// * `user_id` - The UUID of the user behind the activity.
+///
+/// # Returns
+///
+/// Returns a `Result` containing whether the activity must be silenced.
+/// If any error occurs during the database query, returns an `ApiErrorType::InternalServerError` error.
+///
+pub async fn is_muted_or_blocked(
+    pool: Pool<Postgres>,
+    viewer_id: Uuid,
+    user_id: Uuid,
+) -> Result<bool, ApiErrorType> {
+    sqlx::query_scalar!(
+        r#"SELECT
+            EXISTS (
+                SELECT 1 FROM muted_users m
+                WHERE m.muter_id = $1 AND m.muted_id = $2 AND m.deleted_at IS NULL
+            )
+            OR EXISTS (
+                SELECT 1 FROM blocked_users b
+                WHERE b.deleted_at IS NULL AND (
+                    (b.blocker_id = $1 AND b.blocked_id = $2)
+                    OR (b.blocker_id = $2 AND b.blocked_id = $1)
+                )
+            ) AS "is_muted_or_blocked!"
+        "#,
+        viewer_id,
+        user_id
+    )
+    .fetch_one(&pool)
+    .await
+    .map_err(|_| ApiErrorType::InternalServerError)
+}
@@ -0,0 +1,81 @@
+use std::path::Path;
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use sha2::{Digest, Sha256};
+
+use crate::utils::response::ApiErrorType;
+
+/// The content-addressed key of a blob: `blobs/{first two hex digits}/{SHA-256 of the content}`.
+pub fn content_key(content: &[u8]) -> String {
+    digest_key(&Sha256::digest(content))
+}
+
+/// The content-addressed key of a blob from the SHA-256 of its content, when it was hashed
+/// while streamed (see `content_key`).
+pub fn digest_key(digest: &[u8]) -> String {
+    let hash = hex::encode(digest);
+
+    format!("blobs/{}/{}", &hash[..2], hash)
+}
+
+/// A storage backend for the uploaded files.
+///
+/// Blobs are content-addressed (see `content_key`), storing the same content twice yields the same
+/// key, so the rows referencing blobs don't own them: when such a row is hard-deleted its blobs are
+/// released, and the storage worker deletes the ones no row references anymore.
+///
+#[async_trait]
+pub trait BlobStore: Send + Sync {
+    /// Stores a blob under the given key, replacing it if it's already stored.
+    async fn put(
+        &self,
+        key: &str,
+        content: Vec<u8>,
+        content_type: &str,
+    ) -> Result<(), ApiErrorType>;
+
+    /// Stores a blob under the given key from a file, replacing it if it's already stored,
+    /// without reading the whole file in memory.
+    async fn put_file(
+        &self,
+        key: &str,
+        path: &Path,
+        content_type: &str,
+    ) -> Result<(), ApiErrorType>;
+
+    /// Retrieves the content of a blob.
+    async fn get(&self, key: &str) -> Result<Vec<u8>, ApiErrorType>;
+
+    /// Retrieves the size of a blob, in bytes.
+    async fn size(&self, key: &str) -> Result<u64, ApiErrorType>;
+
+    /// Retrieves a part of the content of a blob, from the `start` byte to the `end` byte
+    /// (inclusive), which must be within the blob.
+    async fn get_range(&self, key: &str, start: u64, end: u64) -> Result<Vec<u8>, ApiErrorType>;
+
+    /// Deletes a blob, doing nothing if it doesn't exist.
+    async fn delete(&self, key: &str) -> Result<(), ApiErrorType>;
+
+    /// A URL anyone can download the blob from (with the given content type) until `expires_at`,
+    /// to share private media without proxying it through the API.
+    fn signed_url(&self, key: &str, content_type: &str, expires_at: DateTime<Utc>) -> String;
+
+    /// Stores a blob under its content-addressed key.
+    ///
+    /// # Returns
+    ///
+    /// Returns a `Result` containing the key of the blob if successful.
+    ///
+    async fn put_content(
+        &self,
+        content: Vec<u8>,
+        content_type: &str,
+    ) -> Result<String, ApiErrorType> {
+        let key = content_key(&content);
+
+        self.put(&key, content, content_type).await?;
+
+        Ok(key)
+    }
+}
@@ -0,0 +1,150 @@
+use chrono::{DateTime, Utc};
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+
+/// Signs and verifies the time-limited URLs served by the API itself (`/blobs/{key}`),
+/// with an HMAC-SHA256 of the key, the content type and the expiration.
+pub struct BlobUrlSigner {
+    secret: Vec<u8>,
+    /// The URL the keys are appended to, e.g. `http://localhost:6060/api/v1/blobs`.
+    base_url: String,
+}
+
+impl BlobUrlSigner {
+    pub fn new(secret: &str, public_base_url: &str) -> Self {
+        BlobUrlSigner {
+            secret: secret.as_bytes().to_vec(),
+            base_url: format!("{}/api/v1/blobs", public_base_url.trim_end_matches('/')),
+        }
+    }
+
+    fn mac(&self, key: &str, content_type: &str, expires: i64) -> Hmac<Sha256> {
+        let mut mac =
+            Hmac::<Sha256>::new_from_slice(&self.secret).expect("HMAC accepts keys of any size");
+
+        mac.update(format!("{key}\n{content_type}\n{expires}").as_bytes());
+
+        mac
+    }
+
+    /// The signed URL of a blob, valid until `expires_at`.
+    pub fn sign(&self, key: &str, content_type: &str, expires_at: DateTime<Utc>) -> String {
+        let expires = expires_at.timestamp();
+        let signature = hex::encode(self.mac(key, content_type, expires).finalize().into_bytes());
+
+        let mut url = reqwest::Url::parse(&format!("{}/{}", self.base_url, key))
+            .expect("The public base URL must be a valid URL");
+
+        url.query_pairs_mut()
+            .append_pair("contentType", content_type)
+            .append_pair("expires", &expires.to_string())
+            .append_pair("signature", &signature);
+
+        url.to_string()
+    }
+
+    /// Checks the signature of a URL and that it hasn't expired.
+    pub fn verify(&self, key: &str, content_type: &str, expires: i64, signature: &str) -> bool {
+        let signature = match hex::decode(signature) {
+            Ok(signature) => signature,
+            Err(_) => return false,
+        };
+
+        expires > Utc::now().timestamp()
+            && self
+                .mac(key, content_type, expires)
+                .verify_slice(&signature)
+                .is_ok()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::Duration;
+    use reqwest::Url;
+
+    use super::*;
+
+    const KEY: &str = "ab/cd/abcd";
+    const CONTENT_TYPE: &str = "image/png";
+
+    fn signer() -> BlobUrlSigner {
+        BlobUrlSigner::new("storage-secret", "http://localhost:6060/")
+    }
+
+    /// The `contentType`, `expires` and `signature` query fields of a signed URL.
+    fn query(url: &str) -> (String, i64, String) {
+        let url = Url::parse(url).unwrap();
+        let field = |name: &str| {
+            url.query_pairs()
+                .find(|(field, _)| field == name)
+                .unwrap()
+                .1
+                .to_string()
+        };
+
+        (
+            field("contentType"),
+            field("expires").parse().unwrap(),
+            field("signature"),
+        )
+    }
+
+    #[test]
+    fn signed_urls_point_to_the_blob() {
+        let url = signer().sign(KEY, CONTENT_TYPE, Utc::now() + Duration::minutes(15));
+
+        assert!(url.starts_with(
+            "http://localhost:6060/api/v1/blobs/ab/cd/abcd?contentType=image%2Fpng&expires="
+        ));
+    }
+
+    #[test]
+    fn valid_signatures_are_accepted() {
+        let expires_at = Utc::now() + Duration::minutes(15);
+        let (content_type, expires, signature) =
+            query(&signer().sign(KEY, CONTENT_TYPE, expires_at));
+
+        assert_eq!(content_type, CONTENT_TYPE);
+        assert_eq!(expires, expires_at.timestamp());
+        assert!(signer().verify(KEY, &content_type, expires, &signature));
+    }
+
+    #[test]
+    fn expired_urls_are_rejected() {
+        let (content_type, expires, signature) =
+            query(&signer().sign(KEY, CONTENT_TYPE, Utc::now() - Duration::seconds(1)));
+
+        assert!(!signer().verify(KEY, &content_type, expires, &signature));
+    }
+
+    #[test]
+    fn tampered_urls_are_rejected() {
+        let (content_type, expires, signature) =
+            query(&signer().sign(KEY, CONTENT_TYPE, Utc::now() + Duration::minutes(15)));
+        let mut flipped_signature = signature.clone().into_bytes();
+        flipped_signature[0] = if flipped_signature[0] == b'0' {
+            b'1'
+        } else {
+            b'0'
+        };
+        let flipped_signature = String::from_utf8(flipped_signature).unwrap();
+
+        assert!(!signer().verify("ab/cd/other", &content_type, expires, &signature));
+        assert!(!signer().verify(KEY, "text/html", expires, &signature));
+        assert!(!signer().verify(KEY, &content_type, expires + 3600, &signature));
+        assert!(!signer().verify(KEY, &content_type, expires, &flipped_signature));
+        assert!(!signer().verify(KEY, &content_type, expires, &signature[..32]));
+        assert!(!signer().verify(KEY, &content_type, expires, "not hex"));
+        assert!(!signer().verify(KEY, &content_type, expires, ""));
+    }
+
+    #[test]
+    fn signatures_depend_on_the_secret() {
+        let (content_type, expires, signature) =
+            query(&signer().sign(KEY, CONTENT_TYPE, Utc::now() + Duration::minutes(15)));
+        let other_signer = BlobUrlSigner::new("other-secret", "http://localhost:6060");
+
+        assert!(!other_signer.verify(KEY, &content_type, expires, &signature));
+    }
+}
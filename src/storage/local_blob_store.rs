@@ -0,0 +1,141 @@
+use std::{
+    io::{self, SeekFrom},
+    path::{Component, Path, PathBuf},
+    sync::Arc,
+};
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use tokio::io::{AsyncReadExt, AsyncSeekExt};
+use uuid::Uuid;
+
+use crate::utils::response::ApiErrorType;
+
+use super::{BlobStore, BlobUrlSigner};
+
+/// Stores the blobs as files in a local directory, the signed URLs being served by the API.
+pub struct LocalBlobStore {
+    root: PathBuf,
+    url_signer: Arc<BlobUrlSigner>,
+}
+
+impl LocalBlobStore {
+    pub fn new(root: &str, url_signer: Arc<BlobUrlSigner>) -> Self {
+        LocalBlobStore {
+            root: PathBuf::from(root),
+            url_signer,
+        }
+    }
+
+    /// The path of a blob, refusing keys escaping the root directory.
+    fn path(&self, key: &str) -> Result<PathBuf, ApiErrorType> {
+        let relative = Path::new(key);
+
+        if key.is_empty()
+            || !relative
+                .components()
+                .all(|component| matches!(component, Component::Normal(_)))
+        {
+            return Err(ApiErrorType::InternalServerError);
+        }
+
+        Ok(self.root.join(relative))
+    }
+
+    /// The path of a blob along with a temporary path next to it, creating their directory.
+    ///
+    /// Blobs are written aside then renamed (see `commit`), so they're never read half written.
+    ///
+    async fn temporary_path(&self, key: &str) -> Result<(PathBuf, PathBuf), ApiErrorType> {
+        let path = self.path(key)?;
+
+        if let Some(parent) = path.parent() {
+            tokio::fs::create_dir_all(parent)
+                .await
+                .map_err(|_| ApiErrorType::InternalServerError)?;
+        }
+
+        let temporary_path = path.with_file_name(format!(".{}.tmp", Uuid::new_v4()));
+
+        Ok((path, temporary_path))
+    }
+
+    /// Moves a blob written to its temporary path in place, or removes it when it couldn't be
+    /// written.
+    async fn commit(
+        &self,
+        path: &Path,
+        temporary_path: &Path,
+        written: bool,
+    ) -> Result<(), ApiErrorType> {
+        if !written || tokio::fs::rename(temporary_path, path).await.is_err() {
+            let _ = tokio::fs::remove_file(temporary_path).await;
+
+            return Err(ApiErrorType::InternalServerError);
+        }
+
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl BlobStore for LocalBlobStore {
+    async fn put(&self, key: &str, content: Vec<u8>, _: &str) -> Result<(), ApiErrorType> {
+        let (path, temporary_path) = self.temporary_path(key).await?;
+
+        let written = tokio::fs::write(&temporary_path, content).await;
+
+        self.commit(&path, &temporary_path, written.is_ok()).await
+    }
+
+    async fn put_file(&self, key: &str, source: &Path, _: &str) -> Result<(), ApiErrorType> {
+        let (path, temporary_path) = self.temporary_path(key).await?;
+
+        let copied = tokio::fs::copy(source, &temporary_path).await;
+
+        self.commit(&path, &temporary_path, copied.is_ok()).await
+    }
+
+    async fn get(&self, key: &str) -> Result<Vec<u8>, ApiErrorType> {
+        tokio::fs::read(self.path(key)?)
+            .await
+            .map_err(|_| ApiErrorType::InternalServerError)
+    }
+
+    async fn size(&self, key: &str) -> Result<u64, ApiErrorType> {
+        tokio::fs::metadata(self.path(key)?)
+            .await
+            .map(|metadata| metadata.len())
+            .map_err(|_| ApiErrorType::InternalServerError)
+    }
+
+    async fn get_range(&self, key: &str, start: u64, end: u64) -> Result<Vec<u8>, ApiErrorType> {
+        let mut file = tokio::fs::File::open(self.path(key)?)
+            .await
+            .map_err(|_| ApiErrorType::InternalServerError)?;
+
+        file.seek(SeekFrom::Start(start))
+            .await
+            .map_err(|_| ApiErrorType::InternalServerError)?;
+
+        let mut content = vec![0; (end - start + 1) as usize];
+
+        file.read_exact(&mut content)
+            .await
+            .map_err(|_| ApiErrorType::InternalServerError)?;
+
+        Ok(content)
+    }
+
+    async fn delete(&self, key: &str) -> Result<(), ApiErrorType> {
+        match tokio::fs::remove_file(self.path(key)?).await {
+            Ok(()) => Ok(()),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(()),
+            Err(_) => Err(ApiErrorType::InternalServerError),
+        }
+    }
+
+    fn signed_url(&self, key: &str, content_type: &str, expires_at: DateTime<Utc>) -> String {
+        self.url_signer.sign(key, content_type, expires_at)
+    }
+}
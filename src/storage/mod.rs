@@ -0,0 +1,34 @@
+mod blob_store;
+mod blob_url_signer;
+mod local_blob_store;
+mod s3_blob_store;
+
+use std::sync::Arc;
+
+use crate::utils::{StorageBackend, StorageConfig};
+
+pub use blob_store::{digest_key, BlobStore};
+pub use blob_url_signer::BlobUrlSigner;
+pub use local_blob_store::LocalBlobStore;
+pub use s3_blob_store::S3BlobStore;
+
+/// Opens the storage backend chosen in the configuration.
+///
+/// Panics if S3 is chosen but isn't configured.
+///
+pub fn open_blob_store(
+    storage_config: &StorageConfig,
+    url_signer: Arc<BlobUrlSigner>,
+) -> Arc<dyn BlobStore> {
+    match storage_config.backend {
+        StorageBackend::Local => {
+            Arc::new(LocalBlobStore::new(&storage_config.local_path, url_signer))
+        }
+        StorageBackend::S3 => Arc::new(S3BlobStore::new(
+            storage_config
+                .s3
+                .clone()
+                .expect("The S3 storage backend requires the S3 configuration"),
+        )),
+    }
+}
@@ -0,0 +1,7 @@
+mod stream_hub;
+mod stream_listener;
+mod stream_subscription;
+
+pub use stream_hub::StreamHub;
+pub use stream_listener::spawn_stream_listener;
+pub use stream_subscription::StreamSubscription;
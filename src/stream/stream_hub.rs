@@ -0,0 +1,34 @@
+use std::sync::Arc;
+
+use tokio::sync::broadcast;
+
+use crate::models::StreamEvent;
+
+/// How many events a slow connection can fall behind before missing some (it then catches up
+/// from the database, see `StreamSubscription`).
+const CHANNEL_CAPACITY: usize = 1024;
+
+/// Dispatches the stream events received by the listener to the connections of this instance.
+///
+/// Every connection receives every event and keeps its own user's ones.
+pub struct StreamHub {
+    sender: broadcast::Sender<Arc<StreamEvent>>,
+}
+
+impl Default for StreamHub {
+    fn default() -> Self {
+        let (sender, _) = broadcast::channel(CHANNEL_CAPACITY);
+        StreamHub { sender }
+    }
+}
+
+impl StreamHub {
+    pub fn subscribe(&self) -> broadcast::Receiver<Arc<StreamEvent>> {
+        self.sender.subscribe()
+    }
+
+    /// Dispatches an event, it's dropped when there's no connection.
+    pub fn publish(&self, event: StreamEvent) {
+        let _ = self.sender.send(Arc::new(event));
+    }
+}
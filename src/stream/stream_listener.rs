@@ -0,0 +1,115 @@
+use std::{sync::Arc, time::Duration};
+
+use chrono::Utc;
+use sqlx::{postgres::PgListener, Pool, Postgres};
+
+use crate::{
+    models::StreamEvent,
+    services::stream_events_service::{delete_stream_events_before, get_stream_events_after},
+    utils::StreamConfig,
+};
+
+use super::StreamHub;
+
+/// The channel the stream events are announced on (see `publish_stream_event`).
+const CHANNEL: &str = "stream_events";
+const RECONNECT_DELAY: Duration = Duration::from_secs(1);
+const RECOVERY_LIMIT: i64 = 10_000;
+const PRUNE_INTERVAL: Duration = Duration::from_secs(60 * 60);
+
+/// Replays the events published while the listener was disconnected.
+async fn recover(pool: &Pool<Postgres>, hub: &StreamHub, last_event_id: &mut Option<i64>) {
+    let after_id = match last_event_id {
+        Some(last_event_id) => *last_event_id,
+        None => return,
+    };
+
+    match get_stream_events_after(pool.clone(), None, after_id, RECOVERY_LIMIT).await {
+        Ok(events) => {
+            for event in events {
+                *last_event_id = Some(event.id);
+                hub.publish(event);
+            }
+        }
+        Err(_) => log::error!("Couldn't recover the missed stream events"),
+    }
+}
+
+async fn listen(pool: Pool<Postgres>, hub: Arc<StreamHub>) {
+    let mut last_event_id = None::<i64>;
+
+    loop {
+        let mut listener = match PgListener::connect_with(&pool).await {
+            Ok(listener) => listener,
+            Err(_) => {
+                log::error!("Couldn't connect the stream events listener");
+                tokio::time::sleep(RECONNECT_DELAY).await;
+                continue;
+            }
+        };
+
+        if listener.listen(CHANNEL).await.is_err() {
+            log::error!("Couldn't listen to the stream events");
+            tokio::time::sleep(RECONNECT_DELAY).await;
+            continue;
+        }
+
+        recover(&pool, &hub, &mut last_event_id).await;
+
+        loop {
+            match listener.try_recv().await {
+                Ok(Some(notification)) => {
+                    match serde_json::from_str::<StreamEvent>(notification.payload()) {
+                        Ok(event) => {
+                            last_event_id = Some(last_event_id.unwrap_or(0).max(event.id));
+                            hub.publish(event);
+                        }
+                        Err(_) => log::warn!("Dropping a malformed stream event"),
+                    }
+                }
+                // The connection was lost, what's published meanwhile is recovered once listening again
+                Ok(None) => {
+                    log::warn!("The stream events listener was disconnected");
+                    break;
+                }
+                Err(_) => {
+                    log::error!("The stream events listener failed");
+                    break;
+                }
+            }
+        }
+
+        tokio::time::sleep(RECONNECT_DELAY).await;
+    }
+}
+
+async fn prune(pool: Pool<Postgres>, stream_config: Arc<StreamConfig>) {
+    let mut interval = tokio::time::interval(PRUNE_INTERVAL);
+
+    loop {
+        interval.tick().await;
+
+        let before = Utc::now() - chrono::Duration::hours(stream_config.events_retention_hours);
+
+        match delete_stream_events_before(pool.clone(), before).await {
+            Ok(0) => {}
+            Ok(count) => log::info!("Pruned {} stream event(s)", count),
+            Err(_) => log::error!("Couldn't prune the stream events"),
+        }
+    }
+}
+
+/// Spawns the background tasks delivering the stream events announced by the database (by any
+/// instance) to the hub, and pruning the events too old to be resumed.
+///
+/// Events published while the listener is disconnected are replayed from the database once
+/// it's reconnected.
+///
+pub fn spawn_stream_listener(
+    pool: Pool<Postgres>,
+    hub: Arc<StreamHub>,
+    stream_config: Arc<StreamConfig>,
+) {
+    tokio::spawn(listen(pool.clone(), hub));
+    tokio::spawn(prune(pool, stream_config));
+}
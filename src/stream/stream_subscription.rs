@@ -0,0 +1,124 @@
+use std::{collections::VecDeque, sync::Arc};
+
+use sqlx::{Pool, Postgres};
+use tokio::sync::broadcast::{self, error::RecvError};
+use uuid::Uuid;
+
+use crate::{
+    models::StreamEvent,
+    services::stream_events_service::{get_latest_stream_event_id, get_stream_events_after},
+    utils::response::ApiErrorType,
+};
+
+use super::StreamHub;
+
+/// The maximum number of stored events replayed when resuming, or when catching up after
+/// falling behind.
+const CATCH_UP_LIMIT: i64 = 500;
+
+/// How many delivered event IDs are remembered, to skip the events both caught up and received.
+const DELIVERED_IDS_CAPACITY: usize = 2 * CATCH_UP_LIMIT as usize;
+
+/// The stream events of a user, for a single connection.
+///
+/// When resuming from a `last_event_id`, the events published since then are replayed from the
+/// database first, the live events already replayed are skipped. Otherwise, the latest event
+/// when subscribing is where falling behind catches up from.
+pub struct StreamSubscription {
+    pool: Pool<Postgres>,
+    user_id: Uuid,
+    receiver: broadcast::Receiver<Arc<StreamEvent>>,
+    pending: VecDeque<Arc<StreamEvent>>,
+    delivered_ids: VecDeque<i64>,
+    last_event_id: i64,
+}
+
+impl StreamSubscription {
+    /// Subscribes to the events of a user.
+    ///
+    /// # Arguments
+    ///
+    /// * `pool` - A database connection pool.
+    /// * `hub` - The stream hub of this instance.
+    /// * `user_id` - The UUID of the connected user.
+    /// * `last_event_id` - The ID of the last event the client got, if resuming.
+    ///
+    /// # Returns
+    ///
+    /// Returns a `Result` containing the subscription if successful.
+    /// If the events to replay (or the latest one) can't be retrieved, returns an `ApiErrorType::InternalServerError` error.
+    ///
+    pub async fn new(
+        pool: Pool<Postgres>,
+        hub: &StreamHub,
+        user_id: Uuid,
+        last_event_id: Option<i64>,
+    ) -> Result<Self, ApiErrorType> {
+        // Subscribed before replaying, so that nothing is published in between
+        let receiver = hub.subscribe();
+
+        let last_event_id = match last_event_id {
+            Some(last_event_id) => last_event_id,
+            None => get_latest_stream_event_id(pool.clone(), user_id).await?,
+        };
+
+        let mut subscription = StreamSubscription {
+            pool,
+            user_id,
+            receiver,
+            pending: VecDeque::new(),
+            delivered_ids: VecDeque::new(),
+            last_event_id,
+        };
+
+        subscription.catch_up().await?;
+
+        Ok(subscription)
+    }
+
+    /// Queues the stored events published after the last delivered one.
+    async fn catch_up(&mut self) -> Result<(), ApiErrorType> {
+        let events = get_stream_events_after(
+            self.pool.clone(),
+            Some(self.user_id),
+            self.last_event_id,
+            CATCH_UP_LIMIT,
+        )
+        .await?;
+
+        self.pending.extend(events.into_iter().map(Arc::new));
+
+        Ok(())
+    }
+
+    /// Waits for the next event of the user, `None` once the hub is gone.
+    pub async fn next(&mut self) -> Option<Arc<StreamEvent>> {
+        loop {
+            if let Some(event) = self.pending.pop_front() {
+                if self.delivered_ids.contains(&event.id) {
+                    continue;
+                }
+
+                if self.delivered_ids.len() == DELIVERED_IDS_CAPACITY {
+                    self.delivered_ids.pop_front();
+                }
+                self.delivered_ids.push_back(event.id);
+                self.last_event_id = self.last_event_id.max(event.id);
+
+                return Some(event);
+            }
+
+            match self.receiver.recv().await {
+                Ok(event) if event.user_id == self.user_id => self.pending.push_back(event),
+                Ok(_) => {}
+                Err(RecvError::Lagged(skipped)) => {
+                    log::warn!("A stream connection fell behind by {} events", skipped);
+                    if self.catch_up().await.is_err() {
+                        log::error!("Couldn't catch up the stream events of {}", self.user_id);
+                    }
+                }
+                Err(RecvError::Closed) => return None,
+            }
+        }
+    }
+}
@@ -0,0 +1,23 @@
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use serde::{de::DeserializeOwned, Serialize};
+
+/// Encodes a pagination cursor into an opaque (URL safe) string.
+///
+/// The cursor is serialized as JSON then base64 encoded, clients must treat it as opaque
+/// and only pass it back as is.
+///
+pub fn encode_cursor<T: Serialize>(cursor: &T) -> String {
+    URL_SAFE_NO_PAD.encode(serde_json::to_vec(cursor).unwrap_or_default())
+}
+
+/// Decodes an opaque pagination cursor previously produced by `encode_cursor`.
+///
+/// # Returns
+///
+/// Returns `None` if the cursor isn't valid base64 or doesn't hold the expected data.
+///
+pub fn decode_cursor<T: DeserializeOwned>(cursor: &str) -> Option<T> {
+    let bytes = URL_SAFE_NO_PAD.decode(cursor).ok()?;
+
+    serde_json::from_slice(&bytes).ok()
+}
@@ -0,0 +1,35 @@
+use std::str::FromStr;
+
+/// How emails are delivered.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EmailBackend {
+    /// An SMTP relay.
+    Smtp,
+
+    /// Nothing is delivered, the emails are only recorded and logged (development and tests).
+    Recording,
+}
+
+impl FromStr for EmailBackend {
+    type Err = ();
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.to_lowercase().as_str() {
+            "smtp" => Ok(EmailBackend::Smtp),
+            "recording" => Ok(EmailBackend::Recording),
+            _ => Err(()),
+        }
+    }
+}
+
+pub struct EmailConfig {
+    pub backend: EmailBackend,
+    /// The address the emails are sent from (e.g. `HessHub <no-reply@hesshub.com>`).
+    pub from: String,
+    /// The host of the SMTP relay (SMTP backend only).
+    pub smtp_host: Option<String>,
+    /// Defaults to the submission port (587, with STARTTLS).
+    pub smtp_port: Option<u16>,
+    pub smtp_username: Option<String>,
+    pub smtp_password: Option<String>,
+}
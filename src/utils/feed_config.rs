@@ -0,0 +1,45 @@
+use std::str::FromStr;
+
+/// How home feeds are assembled.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FeedStrategy {
+    /// Fan-out on read, feeds are assembled from the followed users' activity on every request.
+    Read,
+
+    /// Fan-out on write, events are materialized into `feed_items` by the feed worker.
+    Write,
+
+    /// Fan-out on write, except for high-follower authors which are merged at read time.
+    Hybrid,
+}
+
+impl FromStr for FeedStrategy {
+    type Err = ();
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.to_lowercase().as_str() {
+            "read" => Ok(FeedStrategy::Read),
+            "write" => Ok(FeedStrategy::Write),
+            "hybrid" => Ok(FeedStrategy::Hybrid),
+            _ => Err(()),
+        }
+    }
+}
+
+pub struct FeedConfig {
+    pub strategy: FeedStrategy,
+    /// Authors with at least this many followers aren't fanned out (hybrid strategy only).
+    pub fan_out_follower_threshold: i64,
+    /// How many recent events of each type are copied to a feed when a follow is created.
+    pub backfill_size: i64,
+}
+
+impl FeedConfig {
+    /// The follower count from which authors are no longer fanned out, `None` if there's no limit.
+    pub fn fan_out_limit(&self) -> Option<i64> {
+        match self.strategy {
+            FeedStrategy::Hybrid => Some(self.fan_out_follower_threshold),
+            FeedStrategy::Read | FeedStrategy::Write => None,
+        }
+    }
+}
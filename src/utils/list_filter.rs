@@ -0,0 +1,525 @@
+use chrono::{DateTime, NaiveDate, Utc};
+use serde_derive::Serialize;
+use sqlx::{postgres::PgArguments, query::QueryAs, Postgres};
+use uuid::Uuid;
+
+use super::response::{ApiErrorType, ValidationError};
+
+/// The type of the values of a filterable field, which decides the allowed operators.
+pub enum FilterType {
+    /// `true` or `false`, with `eq` and `ne`.
+    Bool,
+    /// A `YYYY-MM-DD` date (midnight UTC) or an RFC 3339 timestamp, with every operator.
+    Timestamp,
+    /// A UUID, with `eq` and `ne`.
+    Uuid,
+    /// One of the given (case sensitive) values, with `eq` and `ne`.
+    Enum(&'static [&'static str]),
+}
+
+/// The expected format of a filter value, reported when a value can't be parsed.
+#[derive(Debug, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum FilterValueFormat {
+    Bool,
+    Date,
+    Uuid,
+}
+
+/// A field a list can be filtered by.
+pub struct FilterField {
+    /// The name of the field in the `filter` query field (camelCase, as in the JSON responses).
+    pub name: &'static str,
+    /// The SQL expression compared to the value, written against the aliases of the list query.
+    pub expression: &'static str,
+    pub filter_type: FilterType,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FilterOperator {
+    Eq,
+    Ne,
+    Gt,
+    Gte,
+    Lt,
+    Lte,
+}
+
+const ALL_OPERATORS: &[FilterOperator] = &[
+    FilterOperator::Eq,
+    FilterOperator::Ne,
+    FilterOperator::Gt,
+    FilterOperator::Gte,
+    FilterOperator::Lt,
+    FilterOperator::Lte,
+];
+
+const EQUALITY_OPERATORS: &[FilterOperator] = &[FilterOperator::Eq, FilterOperator::Ne];
+
+impl FilterOperator {
+    fn name(self) -> &'static str {
+        match self {
+            FilterOperator::Eq => "eq",
+            FilterOperator::Ne => "ne",
+            FilterOperator::Gt => "gt",
+            FilterOperator::Gte => "gte",
+            FilterOperator::Lt => "lt",
+            FilterOperator::Lte => "lte",
+        }
+    }
+
+    fn to_sql(self) -> &'static str {
+        match self {
+            FilterOperator::Eq => "=",
+            FilterOperator::Ne => "<>",
+            FilterOperator::Gt => ">",
+            FilterOperator::Gte => ">=",
+            FilterOperator::Lt => "<",
+            FilterOperator::Lte => "<=",
+        }
+    }
+}
+
+impl FilterType {
+    fn operators(&self) -> &'static [FilterOperator] {
+        match self {
+            FilterType::Timestamp => ALL_OPERATORS,
+            FilterType::Bool | FilterType::Uuid | FilterType::Enum(_) => EQUALITY_OPERATORS,
+        }
+    }
+}
+
+/// A typed filter value, bound as a query parameter (never interpolated).
+#[derive(Debug)]
+pub enum FilterValue {
+    Bool(bool),
+    Timestamp(DateTime<Utc>),
+    Uuid(Uuid),
+    Text(String),
+}
+
+/// A parsed `field:operator:value` predicate.
+#[derive(Debug)]
+pub struct Filter {
+    pub expression: &'static str,
+    pub operator: FilterOperator,
+    pub value: FilterValue,
+}
+
+fn parse_value(
+    field: &FilterField,
+    value: &str,
+    errors: &mut Vec<ValidationError>,
+) -> Option<FilterValue> {
+    let invalid_value = |expected_format| ValidationError::InvalidFilterValue {
+        field_name: field.name.to_string(),
+        passed_value: value.to_string(),
+        expected_format,
+    };
+
+    let parsed = match &field.filter_type {
+        FilterType::Bool => value
+            .parse::<bool>()
+            .map(FilterValue::Bool)
+            .map_err(|_| invalid_value(FilterValueFormat::Bool)),
+        FilterType::Timestamp => DateTime::parse_from_rfc3339(value)
+            .map(|timestamp| timestamp.with_timezone(&Utc))
+            .or_else(|_| {
+                NaiveDate::parse_from_str(value, "%Y-%m-%d")
+                    .map(|date| date.and_hms_opt(0, 0, 0).unwrap().and_utc())
+            })
+            .map(FilterValue::Timestamp)
+            .map_err(|_| invalid_value(FilterValueFormat::Date)),
+        FilterType::Uuid => Uuid::parse_str(value)
+            .map(FilterValue::Uuid)
+            .map_err(|_| invalid_value(FilterValueFormat::Uuid)),
+        FilterType::Enum(values) => match values.contains(&value) {
+            true => Ok(FilterValue::Text(value.to_string())),
+            false => Err(ValidationError::IncorrectEnumValue {
+                field_name: field.name.to_string(),
+                passed_value: value.to_string(),
+                expected_values: values.iter().map(|value| value.to_string()).collect(),
+            }),
+        },
+    };
+
+    parsed.map_err(|error| errors.push(error)).ok()
+}
+
+/// Parses the `filter` query field of a list request against the filterable fields of the list.
+///
+/// The syntax is comma separated `field:operator:value` predicates, all of which must match
+/// (e.g. `role:eq:MANAGER,createdAt:gte:2026-01-01`). The operators are `eq`, `ne`, `gt`, `gte`,
+/// `lt` and `lte`, depending on the type of the field (see `FilterType`).
+///
+/// # Returns
+///
+/// Returns the parsed filters, or an `ApiErrorType::QueryValidationErrors` error holding
+/// every invalid predicate.
+///
+pub fn parse_filters(fields: &[FilterField], filter: &str) -> Result<Vec<Filter>, ApiErrorType> {
+    let mut filters = Vec::new();
+    let mut errors = Vec::new();
+
+    for predicate in filter.split(',') {
+        // Timestamps hold colons too, only the first two separate the parts
+        let mut parts = predicate.splitn(3, ':');
+
+        let (name, operator, value) = match (parts.next(), parts.next(), parts.next()) {
+            (Some(name), Some(operator), Some(value)) if !value.is_empty() => {
+                (name, operator, value)
+            }
+            _ => {
+                errors.push(ValidationError::InvalidFilterSyntax {
+                    passed_value: predicate.to_string(),
+                });
+                continue;
+            }
+        };
+
+        let field = match fields.iter().find(|field| field.name == name) {
+            Some(field) => field,
+            None => {
+                errors.push(ValidationError::NonExistantFilterField {
+                    field_name: name.to_string(),
+                });
+                continue;
+            }
+        };
+
+        let allowed_operators = field.filter_type.operators();
+
+        let operator = match allowed_operators
+            .iter()
+            .find(|allowed| allowed.name() == operator)
+        {
+            Some(operator) => *operator,
+            None => {
+                errors.push(ValidationError::InvalidFilterOperator {
+                    field_name: field.name.to_string(),
+                    passed_operator: operator.to_string(),
+                    expected_operators: allowed_operators
+                        .iter()
+                        .map(|allowed| allowed.name().to_string())
+                        .collect(),
+                });
+                continue;
+            }
+        };
+
+        if let Some(value) = parse_value(field, value, &mut errors) {
+            filters.push(Filter {
+                expression: field.expression,
+                operator,
+                value,
+            });
+        }
+    }
+
+    if errors.is_empty() {
+        Ok(filters)
+    } else {
+        Err(ApiErrorType::QueryValidationErrors(errors))
+    }
+}
+
+/// The conditions of the filters (joined with `AND`, `TRUE` when there's none), their values
+/// being bound to the parameters starting from `$first_param`.
+pub fn filters_sql(filters: &[Filter], first_param: usize) -> String {
+    filters
+        .iter()
+        .enumerate()
+        .map(|(index, filter)| {
+            format!(
+                "{} {} ${}",
+                filter.expression,
+                filter.operator.to_sql(),
+                first_param + index
+            )
+        })
+        .chain(std::iter::once("TRUE".to_string()))
+        .collect::<Vec<_>>()
+        .join(" AND ")
+}
+
+/// Binds the values of the filters, in the order of `filters_sql`.
+pub fn bind_filters<'q, O>(
+    filters: &'q [Filter],
+    mut query: QueryAs<'q, Postgres, O, PgArguments>,
+) -> QueryAs<'q, Postgres, O, PgArguments> {
+    for filter in filters {
+        query = match &filter.value {
+            FilterValue::Bool(value) => query.bind(value),
+            FilterValue::Timestamp(value) => query.bind(value),
+            FilterValue::Uuid(value) => query.bind(value),
+            FilterValue::Text(value) => query.bind(value),
+        };
+    }
+
+    query
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FIELDS: &[FilterField] = &[
+        FilterField {
+            name: "activated",
+            expression: "u.activated",
+            filter_type: FilterType::Bool,
+        },
+        FilterField {
+            name: "createdAt",
+            expression: "u.created_at",
+            filter_type: FilterType::Timestamp,
+        },
+        FilterField {
+            name: "userId",
+            expression: "h.user_id",
+            filter_type: FilterType::Uuid,
+        },
+        FilterField {
+            name: "role",
+            expression: "u.role::TEXT",
+            filter_type: FilterType::Enum(&["USER", "MANAGER", "ROOT"]),
+        },
+    ];
+
+    fn errors(filter: &str) -> Vec<ValidationError> {
+        match parse_filters(FIELDS, filter) {
+            Err(ApiErrorType::QueryValidationErrors(errors)) => errors,
+            result => panic!("{:?} parsed as {:?}", filter, result),
+        }
+    }
+
+    fn timestamp(timestamp: &str) -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339(timestamp)
+            .unwrap()
+            .with_timezone(&Utc)
+    }
+
+    #[test]
+    fn valid_filters_are_parsed() {
+        let user_id = Uuid::new_v4();
+        let filters = parse_filters(
+            FIELDS,
+            &format!(
+                "activated:eq:true,createdAt:gte:2026-01-31,createdAt:lt:2026-02-01T12:30:00+02:00,\
+                 userId:ne:{user_id},role:eq:MANAGER"
+            ),
+        )
+        .unwrap();
+
+        assert_eq!(filters.len(), 5);
+        assert!(matches!(
+            filters[0],
+            Filter {
+                expression: "u.activated",
+                operator: FilterOperator::Eq,
+                value: FilterValue::Bool(true),
+            }
+        ));
+        assert!(matches!(
+            filters[1],
+            Filter {
+                expression: "u.created_at",
+                operator: FilterOperator::Gte,
+                value: FilterValue::Timestamp(value),
+            } if value == timestamp("2026-01-31T00:00:00Z")
+        ));
+        assert!(matches!(
+            filters[2],
+            Filter {
+                operator: FilterOperator::Lt,
+                value: FilterValue::Timestamp(value),
+                ..
+            } if value == timestamp("2026-02-01T10:30:00Z")
+        ));
+        assert!(matches!(
+            filters[3],
+            Filter {
+                expression: "h.user_id",
+                operator: FilterOperator::Ne,
+                value: FilterValue::Uuid(value),
+            } if value == user_id
+        ));
+        assert!(matches!(
+            &filters[4],
+            Filter {
+                expression: "u.role::TEXT",
+                value: FilterValue::Text(value),
+                ..
+            } if value == "MANAGER"
+        ));
+    }
+
+    #[test]
+    fn repeated_fields_are_all_applied() {
+        let filters = parse_filters(
+            FIELDS,
+            "createdAt:gte:2026-01-01,createdAt:lt:2026-02-01,role:ne:USER,role:ne:ROOT",
+        )
+        .unwrap();
+
+        assert_eq!(filters.len(), 4);
+        assert_eq!(
+            filters_sql(&filters, 1),
+            "u.created_at >= $1 AND u.created_at < $2 AND u.role::TEXT <> $3 \
+             AND u.role::TEXT <> $4 AND TRUE"
+        );
+    }
+
+    #[test]
+    fn non_whitelisted_fields_are_rejected() {
+        for (filter, name) in [
+            ("password:eq:secret", "password"),
+            ("u.activated:eq:true", "u.activated"),
+            ("Activated:eq:true", "Activated"),
+            ("1=1;--:eq:true", "1=1;--"),
+        ] {
+            assert!(
+                matches!(
+                    &errors(filter)[..],
+                    [ValidationError::NonExistantFilterField { field_name }] if field_name == name
+                ),
+                "{:?}",
+                filter
+            );
+        }
+    }
+
+    #[test]
+    fn unknown_and_disallowed_operators_are_rejected() {
+        let cases = [
+            ("createdAt:like:2026-01-01", "createdAt", "like", 6),
+            ("createdAt:=:2026-01-01", "createdAt", "=", 6),
+            ("createdAt:EQ:2026-01-01", "createdAt", "EQ", 6),
+            ("activated:gt:true", "activated", "gt", 2),
+            (
+                "userId:lte:00000000-0000-0000-0000-000000000001",
+                "userId",
+                "lte",
+                2,
+            ),
+            ("role:gte:USER", "role", "gte", 2),
+        ];
+
+        for (filter, name, operator, expected_count) in cases {
+            assert!(
+                matches!(
+                    &errors(filter)[..],
+                    [ValidationError::InvalidFilterOperator {
+                        field_name,
+                        passed_operator,
+                        expected_operators,
+                    }] if field_name == name
+                        && passed_operator == operator
+                        && expected_operators.len() == expected_count
+                ),
+                "{:?}",
+                filter
+            );
+        }
+    }
+
+    #[test]
+    fn malformed_values_are_rejected() {
+        let cases = [
+            ("activated:eq:yes", FilterValueFormat::Bool),
+            ("activated:eq:TRUE", FilterValueFormat::Bool),
+            ("activated:eq:1", FilterValueFormat::Bool),
+            ("createdAt:gte:2026-02-30", FilterValueFormat::Date),
+            ("createdAt:gte:31/01/2026", FilterValueFormat::Date),
+            (
+                "createdAt:gte:2026-01-31T25:00:00Z",
+                FilterValueFormat::Date,
+            ),
+            ("createdAt:gte:2026-01-31 00:00:00", FilterValueFormat::Date),
+            ("createdAt:gte:yesterday", FilterValueFormat::Date),
+            ("userId:eq:42", FilterValueFormat::Uuid),
+        ];
+
+        for (filter, format) in cases {
+            let value = filter.splitn(3, ':').last().unwrap();
+
+            assert!(
+                matches!(
+                    &errors(filter)[..],
+                    [ValidationError::InvalidFilterValue {
+                        passed_value,
+                        expected_format,
+                        ..
+                    }] if passed_value == value
+                        && *expected_format == format
+                ),
+                "{:?}",
+                filter
+            );
+        }
+
+        assert!(matches!(
+            &errors("role:eq:ADMIN")[..],
+            [ValidationError::IncorrectEnumValue { passed_value, .. }] if passed_value == "ADMIN"
+        ));
+    }
+
+    #[test]
+    fn invalid_syntax_is_rejected() {
+        for filter in ["", "activated", "activated:eq", "activated:eq:", "a,,b"] {
+            assert!(
+                errors(filter)
+                    .iter()
+                    .any(|error| matches!(error, ValidationError::InvalidFilterSyntax { .. })),
+                "{:?}",
+                filter
+            );
+        }
+    }
+
+    #[test]
+    fn every_invalid_predicate_is_reported() {
+        let errors = errors("password:eq:x,activated:gt:true,activated:eq:maybe,role:eq:USER");
+
+        assert!(matches!(
+            &errors[..],
+            [
+                ValidationError::NonExistantFilterField { .. },
+                ValidationError::InvalidFilterOperator { .. },
+                ValidationError::InvalidFilterValue { .. },
+            ]
+        ));
+    }
+
+    #[test]
+    fn values_are_bound_and_never_spliced_into_the_sql() {
+        let filters = parse_filters(
+            FIELDS,
+            "role:eq:ROOT,createdAt:gte:2026-01-31T00:00:00+00:00,activated:ne:false",
+        )
+        .unwrap();
+        let sql = filters_sql(&filters, 3);
+
+        assert_eq!(
+            sql,
+            "u.role::TEXT = $3 AND u.created_at >= $4 AND u.activated <> $5 AND TRUE"
+        );
+        for value in ["ROOT", "2026", "false"] {
+            assert!(!sql.contains(value), "{:?} is in {:?}", value, sql);
+        }
+
+        assert!(matches!(
+            &errors("role:eq:USER' OR '1'='1")[..],
+            [ValidationError::IncorrectEnumValue { .. }]
+        ));
+        assert!(matches!(
+            &errors("userId:eq:' OR 1=1 --")[..],
+            [ValidationError::InvalidFilterValue { .. }]
+        ));
+    }
+
+    #[test]
+    fn no_filters_match_everything() {
+        assert_eq!(filters_sql(&[], 1), "TRUE");
+    }
+}
@@ -0,0 +1,504 @@
+use std::{collections::HashMap, num::NonZeroU64};
+
+use serde_derive::{Deserialize, Serialize};
+use sqlx::{postgres::PgArguments, query::QueryAs, Postgres};
+use uuid::Uuid;
+
+use crate::models::Page;
+
+use super::{
+    cursor::{decode_cursor, encode_cursor},
+    list_filter::{bind_filters, filters_sql, parse_filters, Filter, FilterField},
+    response::ApiErrorType,
+};
+
+const DEFAULT_PAGE_SIZE: u64 = 20;
+const MAX_PAGE_SIZE: u64 = 100;
+
+/// A field a list can be sorted by.
+pub struct SortField {
+    /// The name of the field in the `sort` query field (camelCase, as in the JSON responses).
+    pub name: &'static str,
+    /// The (non nullable) column the field is stored in.
+    pub column: &'static str,
+}
+
+/// What a list endpoint allows to sort and filter by, as well as its default order.
+///
+/// Columns never come from the request: only the fields listed here can be used, which is
+/// what makes the generated SQL safe to interpolate (filter values are bound as parameters).
+///
+pub struct ListSpec {
+    /// The table the listed rows come from, used to look up the row a cursor points to.
+    pub table: &'static str,
+    pub sort_fields: &'static [SortField],
+    /// The order used when there's no `sort` query field (same syntax).
+    pub default_sort: &'static str,
+    pub filter_fields: &'static [FilterField],
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SortDirection {
+    Ascending,
+    Descending,
+}
+
+impl SortDirection {
+    fn to_sql(self) -> &'static str {
+        match self {
+            SortDirection::Ascending => "ASC",
+            SortDirection::Descending => "DESC",
+        }
+    }
+
+    fn comparison_to_sql(self) -> &'static str {
+        match self {
+            SortDirection::Ascending => ">",
+            SortDirection::Descending => "<",
+        }
+    }
+}
+
+/// The opaque `cursor` query field, pointing to the last row of the previous page.
+#[derive(Debug, Serialize, Deserialize)]
+struct ListCursor {
+    id: Uuid,
+}
+
+/// The filtering, sorting and pagination of a list request, parsed against a `ListSpec`.
+///
+/// - `filter=role:eq:MANAGER,createdAt:gte:2026-01-01`: see `parse_filters`.
+/// - `sort=-createdAt,name`: comma separated fields, descending when prefixed with `-`.
+///   The rows are always ordered by id last, so the order is stable.
+/// - `size`: the page size, defaults to 20 and capped at 100.
+/// - `cursor`: the `nextCursor` of the previous page (keyset pagination).
+/// - `page`: the page number starting from 1 (offset pagination), ignored when there's a cursor.
+///
+#[derive(Debug)]
+pub struct ListQuery {
+    pub filters: Vec<Filter>,
+    pub sort: Vec<(&'static str, SortDirection)>,
+    pub page: i64,
+    pub size: i64,
+    pub after_id: Option<Uuid>,
+    table: &'static str,
+}
+
+fn parse_sort(
+    spec: &ListSpec,
+    sort: &str,
+) -> Result<Vec<(&'static str, SortDirection)>, ApiErrorType> {
+    let mut parsed: Vec<(&'static str, SortDirection)> = Vec::new();
+
+    for part in sort.split(',') {
+        let (direction, name) = match part.strip_prefix('-') {
+            Some(name) => (SortDirection::Descending, name),
+            None => (SortDirection::Ascending, part),
+        };
+
+        if name.is_empty() || !name.chars().all(char::is_alphanumeric) {
+            return Err(ApiErrorType::InvalidSortingQuerySyntax);
+        }
+
+        let field = spec
+            .sort_fields
+            .iter()
+            .find(|field| field.name == name)
+            .ok_or_else(|| ApiErrorType::NonExistantSortingQueryField(name.to_string()))?;
+
+        if parsed.iter().any(|(column, _)| *column == field.column) {
+            return Err(ApiErrorType::InvalidSortingQuerySyntax);
+        }
+
+        parsed.push((field.column, direction));
+    }
+
+    Ok(parsed)
+}
+
+impl ListQuery {
+    /// Parses the `filter`, `sort`, `page`, `size` and `cursor` query fields of a list request.
+    ///
+    /// # Returns
+    ///
+    /// Returns the parsed query, or an `ApiErrorType::QueryValidationErrors`,
+    /// `ApiErrorType::InvalidSortingQuerySyntax`,
+    /// `ApiErrorType::NonExistantSortingQueryField`, `ApiErrorType::InvalidPaginationPageQueryField`,
+    /// `ApiErrorType::InvalidPaginationSizeQueryField` or `ApiErrorType::InvalidPaginationCursorQueryField`
+    /// error.
+    ///
+    pub fn parse(
+        spec: &ListSpec,
+        query: &HashMap<String, String>,
+    ) -> Result<ListQuery, ApiErrorType> {
+        let filters = match query.get("filter") {
+            Some(filter) => parse_filters(spec.filter_fields, filter)?,
+            None => Vec::new(),
+        };
+
+        let sort = parse_sort(
+            spec,
+            query
+                .get("sort")
+                .map(String::as_str)
+                .unwrap_or(spec.default_sort),
+        )?;
+
+        let page = match query.get("page") {
+            Some(page) => page
+                .parse::<NonZeroU64>()
+                .map_err(|err| ApiErrorType::InvalidPaginationPageQueryField(*err.kind()))?
+                .get(),
+            None => 1,
+        };
+
+        let size = match query.get("size") {
+            Some(size) => size
+                .parse::<NonZeroU64>()
+                .map_err(|err| ApiErrorType::InvalidPaginationSizeQueryField(*err.kind()))?
+                .get()
+                .min(MAX_PAGE_SIZE),
+            None => DEFAULT_PAGE_SIZE,
+        };
+
+        let after_id = match query.get("cursor") {
+            Some(cursor) => Some(
+                decode_cursor::<ListCursor>(cursor)
+                    .ok_or(ApiErrorType::InvalidPaginationCursorQueryField)?
+                    .id,
+            ),
+            None => None,
+        };
+
+        Ok(ListQuery {
+            filters,
+            sort,
+            page: page.min(i64::MAX as u64 / MAX_PAGE_SIZE) as i64,
+            size: size as i64,
+            after_id,
+            table: spec.table,
+        })
+    }
+
+    /// The conditions of the filters, bound to the parameters starting from `$first_param`
+    /// (see `bind_filters`).
+    pub fn filters_sql(&self, first_param: usize) -> String {
+        filters_sql(&self.filters, first_param)
+    }
+
+    /// Binds the values of the filters, they must be the last parameters of the query.
+    pub fn bind_filters<'q, O>(
+        &'q self,
+        query: QueryAs<'q, Postgres, O, PgArguments>,
+    ) -> QueryAs<'q, Postgres, O, PgArguments> {
+        bind_filters(&self.filters, query)
+    }
+
+    /// The `ORDER BY` clause (without the keywords) for the rows aliased `alias`.
+    pub fn order_by_sql(&self, alias: &str) -> String {
+        self.sort
+            .iter()
+            .map(|(column, direction)| format!("{alias}.{column} {}", direction.to_sql()))
+            .chain(std::iter::once(format!("{alias}.id ASC")))
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+
+    /// The join bringing in the row the cursor points to (aliased `cursor_row`),
+    /// empty when there's no cursor.
+    ///
+    /// `id_param` is the placeholder the cursor id (`after_id`) is bound to, e.g. `$2`.
+    ///
+    pub fn keyset_join_sql(&self, id_param: &str) -> String {
+        match self.after_id {
+            Some(_) => format!(
+                "CROSS JOIN (SELECT * FROM {} WHERE id = {id_param}::UUID) cursor_row",
+                self.table
+            ),
+            None => String::new(),
+        }
+    }
+
+    /// The condition keeping the rows (aliased `alias`) after the cursor row in the sort order,
+    /// `TRUE` when there's no cursor.
+    pub fn keyset_condition_sql(&self, alias: &str) -> String {
+        if self.after_id.is_none() {
+            return "TRUE".to_string();
+        }
+
+        // Built from the last column: (a > c.a) OR (a = c.a AND (<the same for the next columns>))
+        self.sort.iter().rev().fold(
+            format!("{alias}.id > cursor_row.id"),
+            |next, (column, direction)| {
+                format!(
+                    "({alias}.{column} {} cursor_row.{column} OR ({alias}.{column} = cursor_row.{column} AND {next}))",
+                    direction.comparison_to_sql()
+                )
+            },
+        )
+    }
+
+    /// The `LIMIT` of the query, one more row than the page size to know whether there's a next page.
+    pub fn limit(&self) -> i64 {
+        self.size + 1
+    }
+
+    /// The `OFFSET` of the query, always 0 with a cursor.
+    pub fn offset(&self) -> i64 {
+        match self.after_id {
+            Some(_) => 0,
+            None => (self.page - 1) * self.size,
+        }
+    }
+
+    /// Wraps the rows fetched with `limit` into a page, with the cursor of the next page if any.
+    pub fn into_page<T>(self, mut rows: Vec<T>, total: i64, id: impl Fn(&T) -> Uuid) -> Page<T> {
+        let has_next_page = rows.len() as i64 > self.size;
+
+        rows.truncate(self.size as usize);
+
+        let next_cursor = match rows.last() {
+            Some(last) if has_next_page => Some(encode_cursor(&ListCursor { id: id(last) })),
+            _ => None,
+        };
+
+        Page {
+            items: rows,
+            next_cursor,
+            total,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::num::IntErrorKind;
+
+    use serde_json::json;
+
+    use super::super::list_filter::FilterType;
+    use super::*;
+
+    static SPEC: ListSpec = ListSpec {
+        table: "users",
+        sort_fields: &[
+            SortField {
+                name: "createdAt",
+                column: "created_at",
+            },
+            SortField {
+                name: "username",
+                column: "username",
+            },
+        ],
+        default_sort: "-createdAt",
+        filter_fields: &[FilterField {
+            name: "activated",
+            expression: "u.activated",
+            filter_type: FilterType::Bool,
+        }],
+    };
+
+    fn parse(fields: &[(&str, &str)]) -> Result<ListQuery, ApiErrorType> {
+        let query = fields
+            .iter()
+            .map(|(name, value)| (name.to_string(), value.to_string()))
+            .collect();
+
+        ListQuery::parse(&SPEC, &query)
+    }
+
+    #[test]
+    fn sort_fields_are_parsed_in_order() {
+        assert_eq!(
+            parse(&[("sort", "-createdAt,username")]).unwrap().sort,
+            [
+                ("created_at", SortDirection::Descending),
+                ("username", SortDirection::Ascending)
+            ]
+        );
+    }
+
+    #[test]
+    fn default_sort_is_used_without_a_sort_field() {
+        let query = parse(&[]).unwrap();
+
+        assert_eq!(query.sort, [("created_at", SortDirection::Descending)]);
+        assert_eq!(query.order_by_sql("u"), "u.created_at DESC, u.id ASC");
+    }
+
+    #[test]
+    fn non_whitelisted_sort_fields_are_rejected() {
+        for (sort, field) in [
+            ("password", "password"),
+            ("-email", "email"),
+            ("createdAt,id", "id"),
+            ("CreatedAt", "CreatedAt"),
+        ] {
+            assert!(
+                matches!(
+                    parse(&[("sort", sort)]),
+                    Err(ApiErrorType::NonExistantSortingQueryField(name)) if name == field
+                ),
+                "{:?}",
+                sort
+            );
+        }
+    }
+
+    #[test]
+    fn invalid_sort_syntax_is_rejected() {
+        for sort in [
+            "",
+            ",",
+            "-",
+            "--createdAt",
+            "createdAt,",
+            "+createdAt",
+            "created_at",
+            "createdAt DESC",
+            "createdAt;DROP TABLE users",
+            "createdAt,-createdAt",
+        ] {
+            assert!(
+                matches!(
+                    parse(&[("sort", sort)]),
+                    Err(ApiErrorType::InvalidSortingQuerySyntax)
+                ),
+                "{:?}",
+                sort
+            );
+        }
+    }
+
+    #[test]
+    fn page_and_size_default_to_the_first_page() {
+        let query = parse(&[]).unwrap();
+
+        assert_eq!((query.page, query.size), (1, DEFAULT_PAGE_SIZE as i64));
+        assert_eq!((query.limit(), query.offset()), (21, 0));
+
+        let query = parse(&[("page", "3"), ("size", "10")]).unwrap();
+
+        assert_eq!((query.limit(), query.offset()), (11, 20));
+    }
+
+    #[test]
+    fn size_is_capped_at_the_maximum() {
+        for size in ["100", "101", "1000000"] {
+            assert_eq!(parse(&[("size", size)]).unwrap().size, MAX_PAGE_SIZE as i64);
+        }
+    }
+
+    #[test]
+    fn invalid_page_and_size_are_rejected() {
+        let cases = [
+            ("0", IntErrorKind::Zero),
+            ("-1", IntErrorKind::InvalidDigit),
+            ("ten", IntErrorKind::InvalidDigit),
+            ("", IntErrorKind::Empty),
+            ("99999999999999999999", IntErrorKind::PosOverflow),
+        ];
+
+        for (value, kind) in cases {
+            assert!(
+                matches!(
+                    parse(&[("page", value)]),
+                    Err(ApiErrorType::InvalidPaginationPageQueryField(error)) if error == kind
+                ),
+                "page {:?}",
+                value
+            );
+            assert!(
+                matches!(
+                    parse(&[("size", value)]),
+                    Err(ApiErrorType::InvalidPaginationSizeQueryField(error)) if error == kind
+                ),
+                "size {:?}",
+                value
+            );
+        }
+    }
+
+    #[test]
+    fn huge_pages_do_not_overflow_the_offset() {
+        let query = parse(&[("page", &u64::MAX.to_string()), ("size", "100")]).unwrap();
+
+        assert!(query.offset() > 0);
+    }
+
+    #[test]
+    fn invalid_cursors_are_rejected() {
+        let cursor = encode_cursor(&ListCursor { id: Uuid::new_v4() });
+
+        for cursor in [
+            "".to_string(),
+            "not a cursor!".to_string(),
+            cursor[..cursor.len() - 4].to_string(),
+            format!("{}=", cursor),
+            encode_cursor(&json!({ "id": "not-a-uuid" })),
+            encode_cursor(&json!({ "after": Uuid::new_v4() })),
+            encode_cursor(&json!([])),
+        ] {
+            assert!(
+                matches!(
+                    parse(&[("cursor", &cursor)]),
+                    Err(ApiErrorType::InvalidPaginationCursorQueryField)
+                ),
+                "{:?}",
+                cursor
+            );
+        }
+    }
+
+    #[test]
+    fn cursors_round_trip_into_keyset_clauses() {
+        let ids: Vec<Uuid> = (0..3).map(|_| Uuid::new_v4()).collect();
+        let page = parse(&[("size", "2")])
+            .unwrap()
+            .into_page(ids.clone(), 3, |id| *id);
+
+        assert_eq!(page.items, ids[..2]);
+
+        let cursor = page.next_cursor.unwrap();
+        let query = parse(&[
+            ("size", "2"),
+            ("page", "5"),
+            ("sort", "-createdAt,username"),
+            ("cursor", &cursor),
+        ])
+        .unwrap();
+
+        assert_eq!(query.after_id, Some(ids[1]));
+        assert_eq!(query.offset(), 0);
+        assert_eq!(
+            query.keyset_join_sql("$2"),
+            "CROSS JOIN (SELECT * FROM users WHERE id = $2::UUID) cursor_row"
+        );
+        assert_eq!(
+            query.keyset_condition_sql("u"),
+            "(u.created_at < cursor_row.created_at OR (u.created_at = cursor_row.created_at AND \
+             (u.username > cursor_row.username OR (u.username = cursor_row.username AND \
+             u.id > cursor_row.id))))"
+        );
+    }
+
+    #[test]
+    fn last_pages_have_no_cursor() {
+        let ids: Vec<Uuid> = (0..2).map(|_| Uuid::new_v4()).collect();
+        let page = parse(&[("size", "2")])
+            .unwrap()
+            .into_page(ids.clone(), 2, |id| *id);
+
+        assert_eq!(page.items, ids);
+        assert!(page.next_cursor.is_none());
+    }
+
+    #[test]
+    fn no_cursor_means_no_keyset_clauses() {
+        let query = parse(&[]).unwrap();
+
+        assert_eq!(query.keyset_join_sql("$2"), "");
+        assert_eq!(query.keyset_condition_sql("u"), "TRUE");
+    }
+}
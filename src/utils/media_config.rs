@@ -0,0 +1,55 @@
+use crate::models::MediaType;
+
+pub struct MediaConfig {
+    /// The maximum size of an uploaded profile image, in bytes.
+    pub profile_image_max_size_bytes: u64,
+
+    /// The maximum size of a photo attached to a hess, in bytes.
+    pub hess_photo_max_size_bytes: u64,
+    /// The maximum size of a video attached to a hess, in bytes.
+    pub hess_video_max_size_bytes: u64,
+    /// The maximum size of an audio attached to a hess, in bytes.
+    pub hess_audio_max_size_bytes: u64,
+
+    /// The maximum number of photos attached to a hess.
+    pub hess_max_photos: i64,
+    /// The maximum number of videos attached to a hess.
+    pub hess_max_videos: i64,
+    /// The maximum number of audios attached to a hess.
+    pub hess_max_audios: i64,
+
+    /// The maximum size of the captions of a video or an audio attached to a hess, in bytes.
+    pub hess_media_captions_max_size_bytes: u64,
+}
+
+impl MediaConfig {
+    /// The maximum size of a media of the given type attached to a hess, in bytes.
+    pub fn hess_media_max_size_bytes(&self, media_type: &MediaType) -> u64 {
+        match media_type {
+            MediaType::Photo => self.hess_photo_max_size_bytes,
+            MediaType::Video => self.hess_video_max_size_bytes,
+            MediaType::Audio => self.hess_audio_max_size_bytes,
+        }
+    }
+
+    /// The maximum number of media of the given type attached to a hess.
+    pub fn hess_max_media(&self, media_type: &MediaType) -> i64 {
+        match media_type {
+            MediaType::Photo => self.hess_max_photos,
+            MediaType::Video => self.hess_max_videos,
+            MediaType::Audio => self.hess_max_audios,
+        }
+    }
+
+    /// The maximum size of a multipart body uploading media to a hess, enough for the
+    /// maximum number of media of every type at their maximum size.
+    pub fn hess_media_form_max_size_bytes(&self) -> u64 {
+        [MediaType::Photo, MediaType::Video, MediaType::Audio]
+            .iter()
+            .map(|media_type| {
+                self.hess_media_max_size_bytes(media_type)
+                    * self.hess_max_media(media_type).max(0) as u64
+            })
+            .sum()
+    }
+}
@@ -1,8 +1,27 @@
+pub mod cursor;
+pub mod email_config;
+pub mod feed_config;
 pub mod jwt;
+pub mod list_filter;
+pub mod list_query;
+pub mod media_config;
+pub mod privacy_config;
+pub mod push_config;
 pub mod rejection_handler;
 pub mod response;
+pub mod search_config;
+pub mod storage_config;
+pub mod stream_config;
 pub mod tokens_config;
 pub mod validator;
 
+pub use email_config::{EmailBackend, EmailConfig};
+pub use feed_config::{FeedConfig, FeedStrategy};
+pub use media_config::MediaConfig;
+pub use privacy_config::PrivacyConfig;
+pub use push_config::{PushBackend, PushConfig};
 pub use rejection_handler::handle_rejection;
+pub use search_config::{SearchBackend, SearchConfig};
+pub use storage_config::{S3Config, StorageBackend, StorageConfig};
+pub use stream_config::StreamConfig;
 pub use tokens_config::TokensConfig;
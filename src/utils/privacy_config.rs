@@ -0,0 +1,3 @@
+pub struct PrivacyConfig {
+    pub auto_approve_follow_requests_on_public_profile: bool,
+}
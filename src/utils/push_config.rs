@@ -0,0 +1,29 @@
+use std::str::FromStr;
+
+/// How push notifications are delivered.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PushBackend {
+    /// Firebase Cloud Messaging.
+    Fcm,
+
+    /// Nothing is delivered, the pushes are only recorded and logged (development and tests).
+    Recording,
+}
+
+impl FromStr for PushBackend {
+    type Err = ();
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.to_lowercase().as_str() {
+            "fcm" => Ok(PushBackend::Fcm),
+            "recording" => Ok(PushBackend::Recording),
+            _ => Err(()),
+        }
+    }
+}
+
+pub struct PushConfig {
+    pub backend: PushBackend,
+    /// The server key of the Firebase project (FCM backend only).
+    pub fcm_server_key: Option<String>,
+}
@@ -2,9 +2,15 @@ use std::num::IntErrorKind;
 
 use serde_derive::{Deserialize, Serialize};
 use serde_json::json;
+use uuid::Uuid;
 use warp::hyper::StatusCode;
 
-use super::validator::{FieldLength, FieldType};
+use crate::models::MediaType;
+
+use super::{
+    list_filter::FilterValueFormat,
+    validator::{FieldLength, FieldType},
+};
 
 #[derive(Debug, Serialize, Clone, Copy, Deserialize)]
 #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
@@ -14,6 +20,20 @@ pub enum ApiResource {
     UserConfirmationToken,
     Followers,
     BlockedUsers,
+    AudienceLists,
+    AudienceListMembers,
+    UserPrivacyPreferences,
+    MutedUsers,
+    UserProfileImages,
+    Hesses,
+    HessMedia,
+    HessMediaCaptions,
+    DeviceTokens,
+    Notifications,
+    UserNotificationPreferences,
+    Conversations,
+    Messages,
+    VerificationRequests,
 }
 
 #[derive(Debug, Serialize)]
@@ -43,6 +63,41 @@ pub enum ValidationError {
     InvalidTelephoneFormat {
         passed_value: String,
     },
+    InvalidTimeFormat {
+        field_name: String,
+        passed_value: String,
+    },
+    InvalidTimezone {
+        passed_value: String,
+    },
+    InvalidUuid {
+        field_name: String,
+        passed_value: String,
+    },
+    InvalidAudienceListReference {
+        field_name: String,
+        passed_value: String,
+    },
+    NonOwnedAudienceList {
+        field_name: String,
+        list_id: Uuid,
+    },
+    InvalidFilterSyntax {
+        passed_value: String,
+    },
+    NonExistantFilterField {
+        field_name: String,
+    },
+    InvalidFilterOperator {
+        field_name: String,
+        passed_operator: String,
+        expected_operators: Vec<String>,
+    },
+    InvalidFilterValue {
+        field_name: String,
+        passed_value: String,
+        expected_format: FilterValueFormat,
+    },
 }
 
 #[derive(Debug)]
@@ -53,6 +108,7 @@ pub enum ApiErrorType {
 
     // Authentication & Authorization
     Unauthorized,
+    InvalidSignedUrl,
     NotLoggedIn,
     InvalidJwtToken,
     InvalidCredentials,
@@ -66,22 +122,45 @@ pub enum ApiErrorType {
     BodyValidationErrors(Vec<ValidationError>),
 
     // Query Validation
+    QueryValidationErrors(Vec<ValidationError>),
     InvalidSortingQuerySyntax,
     NonExistantSortingQueryField(String),
     InvalidPaginationPageQueryField(IntErrorKind),
     InvalidPaginationSizeQueryField(IntErrorKind),
+    InvalidPaginationCursorQueryField,
+    InvalidFeedModeQueryField(String),
+    InvalidSearchQueryField(String),
+    InvalidLastEventIdQueryField(String),
 
     // Parameters Validation
     InvalidIdParam(ApiResource),
+    InvalidLanguageParam(String),
 
     // Already existing
     AlreadyExists(ApiResource),
 
+    // Direct Messages
+    NotAGroupConversation,
+
+    // Verification
+    AlreadyVerified,
+    NotVerified,
+
     // File Uploading
     NotAnImage,
     UnnamedMultipartFile,
     EmptyFile,
     NoImage,
+    FileTooLarge,
+    InvalidMultipartBody,
+    UnsupportedMediaType,
+    NoMedia,
+    TooManyMedia(MediaType),
+    InvalidCaptions,
+    CaptionsNotSupported(MediaType),
+
+    // File Downloading
+    RangeNotSatisfiable(u64),
 }
 
 fn int_error_kind_to_str(kind: &IntErrorKind) -> Option<String> {
@@ -98,7 +177,7 @@ fn int_error_kind_to_str(kind: &IntErrorKind) -> Option<String> {
 impl ApiErrorType {
     pub fn status_code(&self) -> StatusCode {
         match self {
-            ApiErrorType::Unauthorized => StatusCode::FORBIDDEN,
+            ApiErrorType::Unauthorized | ApiErrorType::InvalidSignedUrl => StatusCode::FORBIDDEN,
             ApiErrorType::UserConfirmationTokenExpired
             | ApiErrorType::PasswordResetTokenExpired
             | ApiErrorType::InvalidCredentials
@@ -108,15 +187,32 @@ impl ApiErrorType {
                 StatusCode::NOT_FOUND
             }
             ApiErrorType::NoImage
+            | ApiErrorType::NoMedia
+            | ApiErrorType::TooManyMedia(_)
             | ApiErrorType::EmptyFile
+            | ApiErrorType::InvalidMultipartBody
             | ApiErrorType::NotAnImage
             | ApiErrorType::UnnamedMultipartFile
             | ApiErrorType::InvalidIdParam(_)
+            | ApiErrorType::InvalidLanguageParam(_)
+            | ApiErrorType::InvalidCaptions
+            | ApiErrorType::CaptionsNotSupported(_)
+            | ApiErrorType::NotAGroupConversation
+            | ApiErrorType::AlreadyVerified
+            | ApiErrorType::NotVerified
             | ApiErrorType::BodyValidationErrors(_)
+            | ApiErrorType::QueryValidationErrors(_)
             | ApiErrorType::InvalidPaginationPageQueryField(_)
             | ApiErrorType::InvalidPaginationSizeQueryField(_)
+            | ApiErrorType::InvalidPaginationCursorQueryField
+            | ApiErrorType::InvalidFeedModeQueryField(_)
+            | ApiErrorType::InvalidSearchQueryField(_)
+            | ApiErrorType::InvalidLastEventIdQueryField(_)
             | ApiErrorType::InvalidSortingQuerySyntax
             | ApiErrorType::NonExistantSortingQueryField(_) => StatusCode::BAD_REQUEST,
+            ApiErrorType::FileTooLarge => StatusCode::PAYLOAD_TOO_LARGE,
+            ApiErrorType::UnsupportedMediaType => StatusCode::UNSUPPORTED_MEDIA_TYPE,
+            ApiErrorType::RangeNotSatisfiable(_) => StatusCode::RANGE_NOT_SATISFIABLE,
             ApiErrorType::AlreadyExists(_) => StatusCode::CONFLICT,
             ApiErrorType::InternalServerError => StatusCode::INTERNAL_SERVER_ERROR,
         }
@@ -127,20 +223,35 @@ impl ApiErrorType {
             ApiErrorType::UserConfirmationTokenExpired
             | ApiErrorType::PasswordResetTokenExpired
             | ApiErrorType::NoImage
+            | ApiErrorType::NoMedia
+            | ApiErrorType::UnsupportedMediaType
+            | ApiErrorType::InvalidCaptions
+            | ApiErrorType::NotAGroupConversation
+            | ApiErrorType::AlreadyVerified
+            | ApiErrorType::NotVerified
             | ApiErrorType::UnnamedMultipartFile
             | ApiErrorType::EmptyFile
             | ApiErrorType::NotAnImage
+            | ApiErrorType::FileTooLarge
+            | ApiErrorType::InvalidMultipartBody
             | ApiErrorType::RouteNotFound
             | ApiErrorType::Unauthorized
+            | ApiErrorType::InvalidSignedUrl
             | ApiErrorType::NotLoggedIn
             | ApiErrorType::InvalidJwtToken
             | ApiErrorType::InvalidCredentials
             | ApiErrorType::InternalServerError
+            | ApiErrorType::InvalidPaginationCursorQueryField
             | ApiErrorType::InvalidSortingQuerySyntax => None,
             ApiErrorType::InvalidIdParam(details) => Some(json!(details)),
+            ApiErrorType::InvalidLanguageParam(details) => Some(json!(details)),
             ApiErrorType::ResourceNotFound(details) => Some(json!(details)),
             ApiErrorType::BodyValidationErrors(details) => Some(json!(details)),
+            ApiErrorType::QueryValidationErrors(details) => Some(json!(details)),
             ApiErrorType::NonExistantSortingQueryField(details) => Some(json!(details)),
+            ApiErrorType::InvalidFeedModeQueryField(details) => Some(json!(details)),
+            ApiErrorType::InvalidSearchQueryField(details) => Some(json!(details)),
+            ApiErrorType::InvalidLastEventIdQueryField(details) => Some(json!(details)),
             ApiErrorType::InvalidPaginationPageQueryField(details) => {
                 Some(json!(int_error_kind_to_str(details)))
             }
@@ -148,6 +259,9 @@ impl ApiErrorType {
                 Some(json!(int_error_kind_to_str(details)))
             }
             ApiErrorType::AlreadyExists(details) => Some(json!(details)),
+            ApiErrorType::TooManyMedia(details) => Some(json!(details)),
+            ApiErrorType::CaptionsNotSupported(details) => Some(json!(details)),
+            ApiErrorType::RangeNotSatisfiable(details) => Some(json!(details)),
         }
     }
 
@@ -156,8 +270,10 @@ impl ApiErrorType {
             ApiErrorType::ResourceNotFound(_) => "RESOURCE_NOT_FOUND",
             ApiErrorType::RouteNotFound => "ROUTE_NOT_FOUND",
             ApiErrorType::Unauthorized => "UNAUTHORIZED",
+            ApiErrorType::InvalidSignedUrl => "INVALID_SIGNED_URL",
 
             ApiErrorType::BodyValidationErrors(_) => "BODY_VALIDATION_ERRORS",
+            ApiErrorType::QueryValidationErrors(_) => "QUERY_VALIDATION_ERRORS",
             ApiErrorType::InvalidSortingQuerySyntax => "INVALID_SORTING_QUERY_SYNTAX",
             ApiErrorType::NonExistantSortingQueryField(_) => "INVALID_SORTING_QUERY_FIELD",
             ApiErrorType::InvalidPaginationPageQueryField(_) => {
@@ -166,6 +282,12 @@ impl ApiErrorType {
             ApiErrorType::InvalidPaginationSizeQueryField(_) => {
                 "INVALID_PAGINATION_SIZE_QUERY_FIELD"
             }
+            ApiErrorType::InvalidPaginationCursorQueryField => {
+                "INVALID_PAGINATION_CURSOR_QUERY_FIELD"
+            }
+            ApiErrorType::InvalidFeedModeQueryField(_) => "INVALID_FEED_MODE_QUERY_FIELD",
+            ApiErrorType::InvalidSearchQueryField(_) => "INVALID_SEARCH_QUERY_FIELD",
+            ApiErrorType::InvalidLastEventIdQueryField(_) => "INVALID_LAST_EVENT_ID_QUERY_FIELD",
 
             ApiErrorType::NotLoggedIn => "NOT_LOGGED_IN",
             ApiErrorType::InvalidJwtToken => "INVALID_JWT_TOKEN",
@@ -173,12 +295,26 @@ impl ApiErrorType {
 
             ApiErrorType::AlreadyExists(_) => "ALREADY_EXISTS",
 
+            ApiErrorType::NotAGroupConversation => "NOT_A_GROUP_CONVERSATION",
+
+            ApiErrorType::AlreadyVerified => "ALREADY_VERIFIED",
+            ApiErrorType::NotVerified => "NOT_VERIFIED",
+
             ApiErrorType::InternalServerError => "INTERNAL_SERVER_ERROR",
             ApiErrorType::InvalidIdParam(_) => "INVALID_ID_PARAM",
+            ApiErrorType::InvalidLanguageParam(_) => "INVALID_LANGUAGE_PARAM",
             ApiErrorType::UnnamedMultipartFile => "UNNAMED_MULTIPART_FILE",
             ApiErrorType::NotAnImage => "NOT_AN_IMAGE",
             ApiErrorType::EmptyFile => "EMPTY_FILE",
             ApiErrorType::NoImage => "NO_IMAGE",
+            ApiErrorType::FileTooLarge => "FILE_TOO_LARGE",
+            ApiErrorType::InvalidMultipartBody => "INVALID_MULTIPART_BODY",
+            ApiErrorType::UnsupportedMediaType => "UNSUPPORTED_MEDIA_TYPE",
+            ApiErrorType::NoMedia => "NO_MEDIA",
+            ApiErrorType::TooManyMedia(_) => "TOO_MANY_MEDIA",
+            ApiErrorType::InvalidCaptions => "INVALID_CAPTIONS",
+            ApiErrorType::CaptionsNotSupported(_) => "CAPTIONS_NOT_SUPPORTED",
+            ApiErrorType::RangeNotSatisfiable(_) => "RANGE_NOT_SATISFIABLE",
             ApiErrorType::PasswordResetTokenExpired => "PASSWORD_RESET_TOKEN_EXPIRED",
             ApiErrorType::UserConfirmationTokenExpired => "USER_CONFIRMATION_TOKEN_EXPIRED",
         }
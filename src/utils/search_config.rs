@@ -0,0 +1,31 @@
+use std::str::FromStr;
+
+/// Where searches are run.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SearchBackend {
+    /// The Postgres full-text and trigram indexes, always up to date.
+    Postgres,
+
+    /// An embedded Tantivy index on disk, kept in sync by the search worker
+    /// (requires the `tantivy-search` feature).
+    Tantivy,
+}
+
+impl FromStr for SearchBackend {
+    type Err = ();
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.to_lowercase().as_str() {
+            "postgres" => Ok(SearchBackend::Postgres),
+            "tantivy" => Ok(SearchBackend::Tantivy),
+            _ => Err(()),
+        }
+    }
+}
+
+pub struct SearchConfig {
+    pub backend: SearchBackend,
+    /// The directory of the on-disk index (Tantivy backend only).
+    #[cfg(feature = "tantivy-search")]
+    pub index_path: String,
+}
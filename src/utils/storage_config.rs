@@ -0,0 +1,48 @@
+use std::str::FromStr;
+
+/// Where uploaded files are stored.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StorageBackend {
+    /// A directory on the local disk (development and tests).
+    Local,
+
+    /// An S3-compatible object storage (AWS S3, MinIO...).
+    S3,
+}
+
+impl FromStr for StorageBackend {
+    type Err = ();
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.to_lowercase().as_str() {
+            "local" => Ok(StorageBackend::Local),
+            "s3" => Ok(StorageBackend::S3),
+            _ => Err(()),
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct S3Config {
+    /// The URL of the S3 API, e.g. `https://s3.eu-west-1.amazonaws.com` or `http://localhost:9000`
+    /// (objects are addressed path-style, `{endpoint}/{bucket}/{key}`).
+    pub endpoint: String,
+    pub region: String,
+    pub bucket: String,
+    pub access_key_id: String,
+    pub secret_access_key: String,
+}
+
+pub struct StorageConfig {
+    pub backend: StorageBackend,
+    /// The directory the blobs are stored in (local backend only).
+    pub local_path: String,
+    /// The S3 settings (S3 backend only).
+    pub s3: Option<S3Config>,
+    /// The public URL of the API, which the signed URLs of the local backend point to.
+    pub public_base_url: String,
+    /// The secret the URLs of the local backend are signed with.
+    pub signing_secret: String,
+    /// How long signed URLs stay valid.
+    pub signed_url_expire_in_minutes: i64,
+}
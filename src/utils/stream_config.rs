@@ -0,0 +1,6 @@
+pub struct StreamConfig {
+    /// How often an idle connection gets a heartbeat (a ping frame or an SSE comment).
+    pub heartbeat_interval_seconds: u64,
+    /// How long events are kept for clients resuming from their last event.
+    pub events_retention_hours: i64,
+}
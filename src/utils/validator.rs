@@ -1,6 +1,8 @@
+use chrono::NaiveTime;
 use regex::Regex;
 use serde_derive::Serialize;
 use std::collections::HashMap;
+use uuid::Uuid;
 
 use super::response::ValidationError;
 
@@ -427,6 +429,243 @@ pub fn validate_array_field(
     }
 }
 
+/// Validates a JSON field that is expected to contain an object.
+///
+/// This function validates a specific field in a JSON-like data structure. It checks
+/// whether the field is an object, its own fields are then validated by the caller (usually
+/// with the other helpers, naming them after the object, e.g. `like.push`). It supports
+/// scenarios where the field is optional or nullable.
+///
+/// # Parameters
+///
+/// - `value`: A reference to an `Option` containing a reference to a `serde_json::Value`.
+///   This represents the value of the field that needs to be validated.
+///
+/// - `name`: A string slice (`&str`) representing the name of the field being validated.
+///
+/// - `errors`: A mutable reference to a vector (`&mut Vec<ValidationError>`) that will
+///   store any validation errors encountered during the validation process.
+///
+/// - `optional`: A boolean indicating whether the field is optional (if `true`) or
+///   required (if `false`).
+///
+/// - `nullable`: A boolean indicating whether the field can be `null` (if `true`) or
+///   not (if `false`).
+///
+/// # Returns
+///
+/// This function returns a nested `Option<HashMap<String, serde_json::Value>>` to represent
+/// different outcomes:
+///
+/// - `Some(Some(...))`: If the validation is successful and the field contains an object,
+///   it returns `Some` wrapping `Some` with a map of the object's fields.
+///
+/// - `Some(None)`: If the validation is successful but the field is `null` (and `nullable` is `true`),
+///   it returns `Some` wrapping `None`.
+///
+/// - `None`: If the validation encounters an error or the field is missing. If the field is optional,
+///   it may return `None` to indicate that the field is not present without indicating an error.
+///
+pub fn validate_object_field(
+    value: &Option<&serde_json::Value>,
+    name: &str,
+    errors: &mut Vec<ValidationError>,
+    optional: bool,
+    nullable: bool,
+) -> Option<Option<HashMap<String, serde_json::Value>>> {
+    match value {
+        Some(serde_json::Value::Object(value)) => Some(Some(
+            value
+                .iter()
+                .map(|(key, value)| (key.to_owned(), value.to_owned()))
+                .collect(),
+        )),
+        Some(serde_json::Value::Null) if nullable => Some(None),
+        None => {
+            if !optional {
+                errors.push(ValidationError::RequiredFieldMissing {
+                    field_name: name.to_string(),
+                });
+            }
+            None
+        }
+        _ => {
+            errors.push(ValidationError::InvalidFieldDataType {
+                field_name: name.to_string(),
+                expected_type: FieldType::Object,
+            });
+            None
+        }
+    }
+}
+
+/// Validates a JSON field that is expected to contain a time of day, formatted as `HH:MM`
+/// (seconds are accepted as well, `HH:MM:SS`).
+///
+/// # Parameters
+///
+/// - `value`: A reference to an `Option` containing a reference to a `serde_json::Value`.
+///   This represents the value of the field that needs to be validated.
+///
+/// - `name`: A string slice (`&str`) representing the name of the field being validated.
+///
+/// - `errors`: A mutable reference to a vector (`&mut Vec<ValidationError>`) that will
+///   store any validation errors encountered during the validation process.
+///
+/// - `optional`: A boolean indicating whether the field is optional (if `true`) or
+///   required (if `false`).
+///
+/// # Returns
+///
+/// - `Some(value)`: If the validation is successful, the parsed `NaiveTime`.
+///
+/// - `None`: If the validation encounters an error or the field is missing.
+///
+/// # Errors
+///
+/// If validation fails, the function appends one of the following `ValidationError` variants
+/// to the `errors` vector:
+///
+/// - `RequiredFieldMissing`: If a required field is missing.
+///
+/// - `InvalidFieldDataType`: If the field does not contain a string value.
+///
+/// - `InvalidTimeFormat`: If the string isn't a valid time of day.
+///
+pub fn validate_time_field(
+    value: &Option<&serde_json::Value>,
+    name: &str,
+    errors: &mut Vec<ValidationError>,
+    optional: bool,
+) -> Option<NaiveTime> {
+    match value {
+        Some(serde_json::Value::String(value)) => {
+            match NaiveTime::parse_from_str(value, "%H:%M")
+                .or_else(|_| NaiveTime::parse_from_str(value, "%H:%M:%S"))
+            {
+                Ok(time) => Some(time),
+                Err(_) => {
+                    errors.push(ValidationError::InvalidTimeFormat {
+                        field_name: name.to_string(),
+                        passed_value: value.to_string(),
+                    });
+                    None
+                }
+            }
+        }
+        None => {
+            if !optional {
+                errors.push(ValidationError::RequiredFieldMissing {
+                    field_name: name.to_string(),
+                });
+            }
+            None
+        }
+        _ => {
+            errors.push(ValidationError::InvalidFieldDataType {
+                field_name: name.to_string(),
+                expected_type: FieldType::String,
+            });
+            None
+        }
+    }
+}
+
+/// Validates a JSON field that is expected to contain an array of UUIDs (e.g. referenced users).
+///
+/// Duplicated UUIDs are only kept once, the length constraints apply to the distinct ones.
+///
+/// # Parameters
+///
+/// - `value`: A reference to an `Option` containing a reference to a `serde_json::Value`.
+///   This represents the value of the field that needs to be validated.
+///
+/// - `name`: A string slice (`&str`) representing the name of the field being validated.
+///
+/// - `field_length`: A `FieldLength` struct defining the minimum and/or maximum number
+///   of UUIDs in the array.
+///
+/// - `errors`: A mutable reference to a vector (`&mut Vec<ValidationError>`) that will
+///   store any validation errors encountered during the validation process.
+///
+/// - `optional`: A boolean indicating whether the field is optional (if `true`) or
+///   required (if `false`).
+///
+/// # Returns
+///
+/// - `Some(value)`: If the validation is successful, the parsed UUIDs in their original order.
+///
+/// - `None`: If the validation encounters an error or the field is missing.
+///
+/// # Errors
+///
+/// If validation fails, the function appends one of the following `ValidationError` variants
+/// to the `errors` vector:
+///
+/// - `RequiredFieldMissing`: If a required field is missing.
+///
+/// - `InvalidFieldDataType`: If the field isn't an array of strings.
+///
+/// - `InvalidUuid`: If an element isn't a valid UUID (named after its index, e.g. `userIds[1]`).
+///
+/// - `InvalidFieldContentLength`: If the number of UUIDs is outside the specified range.
+///
+pub fn validate_uuid_array_field(
+    value: &Option<&serde_json::Value>,
+    name: &str,
+    field_length: FieldLength,
+    errors: &mut Vec<ValidationError>,
+    optional: bool,
+) -> Option<Vec<Uuid>> {
+    let values = validate_array_field(value, name, errors, optional, false)??;
+
+    let mut uuids = Vec::<Uuid>::new();
+    let mut is_valid = true;
+
+    for (index, value) in values.iter().enumerate() {
+        match value {
+            serde_json::Value::String(value) => match Uuid::parse_str(value) {
+                Ok(uuid) => {
+                    if !uuids.contains(&uuid) {
+                        uuids.push(uuid);
+                    }
+                }
+                Err(_) => {
+                    errors.push(ValidationError::InvalidUuid {
+                        field_name: format!("{}[{}]", name, index),
+                        passed_value: value.to_string(),
+                    });
+                    is_valid = false;
+                }
+            },
+            _ => {
+                errors.push(ValidationError::InvalidFieldDataType {
+                    field_name: format!("{}[{}]", name, index),
+                    expected_type: FieldType::String,
+                });
+                is_valid = false;
+            }
+        }
+    }
+
+    if !is_valid {
+        return None;
+    }
+
+    if field_length.min.is_some_and(|min| uuids.len() < min)
+        || field_length.max.is_some_and(|max| uuids.len() > max)
+    {
+        errors.push(ValidationError::InvalidFieldContentLength {
+            field_name: name.to_string(),
+            passed_length: uuids.len(),
+            expected_length: field_length,
+        });
+        return None;
+    }
+
+    Some(uuids)
+}
+
 /// Validates a JSON field that is expected to contain a string.
 ///
 /// This function validates a specific field in a JSON-like data structure. It checks whether